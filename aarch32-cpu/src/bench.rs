@@ -0,0 +1,303 @@
+//! A cycle-counting micro-benchmark harness
+//!
+//! Runs a closure a number of times with interrupts masked, counting cycles
+//! (and optionally some other PMU events) around each run. The cost of the
+//! measurement itself is measured first, and subtracted from every result.
+//!
+//! ```rust,ignore
+//! let mut pmu = unsafe { aarch32_cpu::pmu::Pmu::new() };
+//! let config = bench::Config {
+//!     iterations: 100,
+//!     cache: bench::CachePolicy::Warm,
+//!     events: &[pmu::Event::L1DCacheRefill],
+//! };
+//! let report = bench::run(&mut pmu, &config, || my_function()).unwrap();
+//! println!("cycles: {:?}", report.cycles);
+//! ```
+//!
+//! On QEMU the cycle counter is only approximate.
+
+use crate::pmu::{self, Counter, Event, Pmu};
+use crate::register::{self, BpIAll, Iciallu};
+
+/// The maximum number of PMU events we can count at once
+pub const MAX_EVENTS: usize = 4;
+
+/// Ways this API can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// Asked for more than [`MAX_EVENTS`] events
+    TooManyEvents,
+    /// Asked for zero iterations
+    NoIterations,
+    /// The PMU could not count the events
+    Pmu(pmu::Error),
+}
+
+impl From<pmu::Error> for Error {
+    fn from(value: pmu::Error) -> Self {
+        Error::Pmu(value)
+    }
+}
+
+/// What to do with the caches before each run
+#[derive(Debug, Clone, Copy, Default)]
+pub enum CachePolicy {
+    /// Leave the caches alone
+    #[default]
+    Unchanged,
+    /// Run the closure once, without measuring it, before the timed runs
+    Warm,
+    /// Clean and invalidate the L1 caches before every run
+    ///
+    /// The function given cleans and invalidates the L1 data cache. That
+    /// depends on the cache geometry, so pick the [`crate::cache`] helper that
+    /// matches your processor, like
+    /// `cache::clean_and_invalidate_l1_data_cache::<2, 5, 8>`. The instruction
+    /// cache and branch predictor are invalidated for you.
+    Cold(fn()),
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for CachePolicy {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            CachePolicy::Unchanged => defmt::write!(f, "Unchanged"),
+            CachePolicy::Warm => defmt::write!(f, "Warm"),
+            CachePolicy::Cold(_) => defmt::write!(f, "Cold"),
+        }
+    }
+}
+
+/// Settings for a benchmark
+#[derive(Debug, Clone)]
+pub struct Config<'a> {
+    /// How many times to run the closure
+    pub iterations: u32,
+    /// What to do with the caches
+    pub cache: CachePolicy,
+    /// Which PMU events to count, in addition to cycles
+    pub events: &'a [Event],
+}
+
+/// Summary statistics for one counter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// The smallest value seen
+    pub min: u64,
+    /// The mean value
+    pub mean: u64,
+    /// The largest value seen
+    pub max: u64,
+}
+
+/// The results of a benchmark
+///
+/// All values have had the measurement overhead subtracted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Report {
+    /// How many times the closure was run
+    pub iterations: u32,
+    /// Processor cycles taken
+    pub cycles: Stats,
+    /// The events counted, in the order they were given in the [`Config`]
+    pub events: [Option<(Event, Stats)>; MAX_EVENTS],
+}
+
+/// Run a benchmark
+///
+/// The PMU is started if it wasn't already, and event counters are
+/// allocated for the duration of the benchmark.
+pub fn run<F>(pmu: &mut Pmu, config: &Config, mut f: F) -> Result<Report, Error>
+where
+    F: FnMut(),
+{
+    if config.events.len() > MAX_EVENTS {
+        return Err(Error::TooManyEvents);
+    }
+    if config.iterations == 0 {
+        return Err(Error::NoIterations);
+    }
+
+    let mut counters: [Option<Counter>; MAX_EVENTS] = [None, None, None, None];
+    for (slot, event) in counters.iter_mut().zip(config.events) {
+        match pmu.allocate(*event) {
+            Ok(counter) => *slot = Some(counter),
+            Err(e) => {
+                free_all(pmu, &mut counters);
+                return Err(e.into());
+            }
+        }
+    }
+    pmu.enable_cycle_counter(false);
+    pmu.start();
+
+    // Measure the cost of measuring nothing
+    let mut overhead = [u64::MAX; MAX_EVENTS + 1];
+    for _ in 0..config.iterations.min(16) {
+        let sample = measure(pmu, &counters, &mut || {});
+        for (overhead, value) in overhead.iter_mut().zip(sample) {
+            *overhead = (*overhead).min(value);
+        }
+    }
+
+    if let CachePolicy::Warm = config.cache {
+        f();
+    }
+
+    let mut accumulators = [Accumulator::new(); MAX_EVENTS + 1];
+    for _ in 0..config.iterations {
+        if let CachePolicy::Cold(flush_l1_data_cache) = config.cache {
+            flush_l1_data_cache();
+            flush_l1_instruction_cache();
+        }
+        let sample = measure(pmu, &counters, &mut f);
+        for ((acc, value), overhead) in accumulators.iter_mut().zip(sample).zip(overhead) {
+            acc.add(value.saturating_sub(overhead));
+        }
+    }
+
+    let mut events = [None; MAX_EVENTS];
+    for ((slot, event), acc) in events.iter_mut().zip(config.events).zip(&accumulators[1..]) {
+        *slot = Some((*event, acc.stats()));
+    }
+
+    free_all(pmu, &mut counters);
+
+    Ok(Report {
+        iterations: config.iterations,
+        cycles: accumulators[0].stats(),
+        events,
+    })
+}
+
+/// Run the closure once with interrupts masked, and return the cycles and
+/// events it took
+///
+/// Not inlined, so the real closure and the empty closure used to measure the
+/// overhead are called in exactly the same way.
+#[inline(never)]
+fn measure(
+    pmu: &Pmu,
+    counters: &[Option<Counter>; MAX_EVENTS],
+    f: &mut dyn FnMut(),
+) -> [u64; MAX_EVENTS + 1] {
+    let irqs_enabled = !register::Cpsr::read().i();
+    crate::interrupt::disable();
+
+    let mut start = [0u64; MAX_EVENTS + 1];
+    for (value, counter) in start[1..].iter_mut().zip(counters) {
+        if let Some(counter) = counter {
+            *value = pmu.read(counter);
+        }
+    }
+    start[0] = pmu.read_cycles();
+
+    f();
+
+    let mut end = [0u64; MAX_EVENTS + 1];
+    end[0] = pmu.read_cycles();
+    for (value, counter) in end[1..].iter_mut().zip(counters) {
+        if let Some(counter) = counter {
+            *value = pmu.read(counter);
+        }
+    }
+
+    if irqs_enabled {
+        // Safety: We're only turning them back on if they were on previously
+        unsafe {
+            crate::interrupt::enable();
+        }
+    }
+
+    let mut delta = [0u64; MAX_EVENTS + 1];
+    for ((delta, start), end) in delta.iter_mut().zip(start).zip(end) {
+        *delta = end.wrapping_sub(start);
+    }
+    delta
+}
+
+fn free_all(pmu: &mut Pmu, counters: &mut [Option<Counter>; MAX_EVENTS]) {
+    for counter in counters.iter_mut() {
+        if let Some(counter) = counter.take() {
+            pmu.free(counter);
+        }
+    }
+}
+
+/// Invalidate the instruction cache and branch predictor
+fn flush_l1_instruction_cache() {
+    #[cfg(target_arch = "arm")]
+    crate::asm::dsb();
+    Iciallu::write();
+    BpIAll::write();
+    #[cfg(target_arch = "arm")]
+    {
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+}
+
+/// Collects min, max and total
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    min: u64,
+    max: u64,
+    total: u64,
+    count: u64,
+}
+
+impl Accumulator {
+    const fn new() -> Accumulator {
+        Accumulator {
+            min: u64::MAX,
+            max: 0,
+            total: 0,
+            count: 0,
+        }
+    }
+
+    fn add(&mut self, value: u64) {
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.total = self.total.saturating_add(value);
+        self.count += 1;
+    }
+
+    fn stats(&self) -> Stats {
+        if self.count == 0 {
+            return Stats::default();
+        }
+        Stats {
+            min: self.min,
+            mean: self.total / self.count,
+            max: self.max,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn accumulator() {
+        let mut acc = Accumulator::new();
+        assert_eq!(acc.stats(), Stats::default());
+        for value in [10, 30, 20] {
+            acc.add(value);
+        }
+        assert_eq!(
+            acc.stats(),
+            Stats {
+                min: 10,
+                mean: 20,
+                max: 30
+            }
+        );
+    }
+}
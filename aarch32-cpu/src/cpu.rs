@@ -0,0 +1,509 @@
+//! Identifying the processor, and which errata apply to it
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::cpu::{self, Erratum};
+//!
+//! let (part, revision) = cpu::identify();
+//! println!("Running on {} {}", part, revision);
+//! if cpu::has_erratum(Erratum::A9_794072) {
+//!     // apply the workaround
+//! }
+//! ```
+
+use crate::register::Midr;
+
+/// The implementer code for Arm Limited
+pub const IMPLEMENTER_ARM: u8 = 0x41;
+
+/// A processor part, as identified by MIDR
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Part {
+    /// Arm ARM920T
+    Arm920,
+    /// Arm ARM926EJ-S
+    Arm926,
+    /// Arm ARM946E-S
+    Arm946,
+    /// Arm ARM966E-S
+    Arm966,
+    /// Arm ARM1136J(F)-S
+    Arm1136,
+    /// Arm ARM1156T2(F)-S
+    Arm1156,
+    /// Arm ARM1176JZ(F)-S
+    Arm1176,
+    /// Arm ARM11 MPCore
+    Arm11MpCore,
+    /// Arm Cortex-A5
+    CortexA5,
+    /// Arm Cortex-A7
+    CortexA7,
+    /// Arm Cortex-A8
+    CortexA8,
+    /// Arm Cortex-A9
+    CortexA9,
+    /// Arm Cortex-A12
+    CortexA12,
+    /// Arm Cortex-A15
+    CortexA15,
+    /// Arm Cortex-A17
+    CortexA17,
+    /// Arm Cortex-A32
+    CortexA32,
+    /// Arm Cortex-A35
+    CortexA35,
+    /// Arm Cortex-A53
+    CortexA53,
+    /// Arm Cortex-A57
+    CortexA57,
+    /// Arm Cortex-A72
+    CortexA72,
+    /// Arm Cortex-R4
+    CortexR4,
+    /// Arm Cortex-R5
+    CortexR5,
+    /// Arm Cortex-R7
+    CortexR7,
+    /// Arm Cortex-R8
+    CortexR8,
+    /// Arm Cortex-R52
+    CortexR52,
+    /// Arm Cortex-R52+
+    CortexR52Plus,
+    /// Some other processor
+    Unknown {
+        /// The MIDR implementer code
+        implementer: u8,
+        /// The MIDR part number
+        part_number: u16,
+    },
+}
+
+impl Part {
+    /// Work out the part from the MIDR implementer code and part number
+    pub const fn from_ids(implementer: u8, part_number: u16) -> Part {
+        if implementer != IMPLEMENTER_ARM {
+            return Part::Unknown {
+                implementer,
+                part_number,
+            };
+        }
+        match part_number {
+            0x920 => Part::Arm920,
+            0x926 => Part::Arm926,
+            0x946 => Part::Arm946,
+            0x966 => Part::Arm966,
+            0xB36 => Part::Arm1136,
+            0xB56 => Part::Arm1156,
+            0xB76 => Part::Arm1176,
+            0xB02 => Part::Arm11MpCore,
+            0xC05 => Part::CortexA5,
+            0xC07 => Part::CortexA7,
+            0xC08 => Part::CortexA8,
+            0xC09 => Part::CortexA9,
+            0xC0D => Part::CortexA12,
+            0xC0F => Part::CortexA15,
+            0xC0E => Part::CortexA17,
+            0xD01 => Part::CortexA32,
+            0xD04 => Part::CortexA35,
+            0xD03 => Part::CortexA53,
+            0xD07 => Part::CortexA57,
+            0xD08 => Part::CortexA72,
+            0xC14 => Part::CortexR4,
+            0xC15 => Part::CortexR5,
+            0xC17 => Part::CortexR7,
+            0xC18 => Part::CortexR8,
+            0xD13 => Part::CortexR52,
+            0xD16 => Part::CortexR52Plus,
+            _ => Part::Unknown {
+                implementer,
+                part_number,
+            },
+        }
+    }
+
+    /// The marketing name for this part
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Part::Arm920 => "ARM920T",
+            Part::Arm926 => "ARM926EJ-S",
+            Part::Arm946 => "ARM946E-S",
+            Part::Arm966 => "ARM966E-S",
+            Part::Arm1136 => "ARM1136",
+            Part::Arm1156 => "ARM1156T2",
+            Part::Arm1176 => "ARM1176JZ",
+            Part::Arm11MpCore => "ARM11 MPCore",
+            Part::CortexA5 => "Cortex-A5",
+            Part::CortexA7 => "Cortex-A7",
+            Part::CortexA8 => "Cortex-A8",
+            Part::CortexA9 => "Cortex-A9",
+            Part::CortexA12 => "Cortex-A12",
+            Part::CortexA15 => "Cortex-A15",
+            Part::CortexA17 => "Cortex-A17",
+            Part::CortexA32 => "Cortex-A32",
+            Part::CortexA35 => "Cortex-A35",
+            Part::CortexA53 => "Cortex-A53",
+            Part::CortexA57 => "Cortex-A57",
+            Part::CortexA72 => "Cortex-A72",
+            Part::CortexR4 => "Cortex-R4",
+            Part::CortexR5 => "Cortex-R5",
+            Part::CortexR7 => "Cortex-R7",
+            Part::CortexR8 => "Cortex-R8",
+            Part::CortexR52 => "Cortex-R52",
+            Part::CortexR52Plus => "Cortex-R52+",
+            Part::Unknown { .. } => "Unknown",
+        }
+    }
+}
+
+impl core::fmt::Display for Part {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Part::Unknown {
+                implementer,
+                part_number,
+            } => write!(f, "Unknown (0x{:02x}/0x{:03x})", implementer, part_number),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+/// A processor revision, as written in Arm documentation (e.g. `r2p1`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Revision {
+    /// The major revision (the `rN` part), from MIDR.Variant
+    pub variant: u8,
+    /// The minor revision (the `pN` part), from MIDR.Revision
+    pub patch: u8,
+}
+
+impl Revision {
+    /// Create a revision `r<variant>p<patch>`
+    pub const fn new(variant: u8, patch: u8) -> Revision {
+        Revision { variant, patch }
+    }
+}
+
+impl core::fmt::Display for Revision {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "r{}p{}", self.variant, self.patch)
+    }
+}
+
+/// Decode a MIDR value into a part and revision
+pub const fn decode(midr: Midr) -> (Part, Revision) {
+    let part = Part::from_ids(midr.implementer(), midr.part_no().value());
+    let revision = Revision::new(midr.variant().value(), midr.rev().value());
+    (part, revision)
+}
+
+/// Read MIDR, and work out what processor we are running on
+pub fn identify() -> (Part, Revision) {
+    decode(Midr::read())
+}
+
+/// A known processor erratum
+///
+/// The names are the part, followed by the erratum number from Arm's
+/// errata notice for that part.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Erratum {
+    /// ARM1136, ARM1156 and ARM1176: Invalidation of the Instruction Cache
+    /// operation can fail
+    Arm1136_411920,
+    /// Cortex-A7: Cache maintenance by set/way operations can execute out of order
+    A7_814220,
+    /// Cortex-A8: Stale prediction on replaced interworking branch
+    A8_430973,
+    /// Cortex-A8: Possible data corruption with PLD instructions
+    A8_458693,
+    /// Cortex-A8: Write-allocate in the L2 cache may cause data corruption
+    A8_460075,
+    /// Cortex-A9: LoUIS bit field in CLIDR register is incorrect
+    A9_643719,
+    /// Cortex-A9: TLBIASIDIS and TLBIMVAIS might not invalidate all entries
+    A9_720789,
+    /// Cortex-A9: DMB operation may be faulty
+    A9_742230,
+    /// Cortex-A9: Concurrent writes to the same shared cache line may corrupt data
+    A9_742231,
+    /// Cortex-A9: Faulty hazard checking in the Store Buffer
+    A9_743622,
+    /// Cortex-A9: Interrupted ICIALLUIS may prevent completion of broadcast operations
+    A9_751472,
+    /// Cortex-A9: Possible faulty MMU translations following an ASID switch
+    A9_754322,
+    /// Cortex-A9: No automatic Store Buffer drain
+    A9_754327,
+    /// Cortex-A9: Data cache line maintenance by MVA may not succeed on SMP
+    A9_764369,
+    /// Cortex-A9: Data cache clean by MVA may cause a deadlock
+    A9_775420,
+    /// Cortex-A9: A short loop including a DMB can stall other cores
+    A9_794072,
+    /// Cortex-A15: Incorrect execution from the loop buffer
+    A15_773022,
+    /// Cortex-A15: TLBI-IS and DSB may not ensure completion of invalidation
+    A15_798181,
+    /// Cortex-A17: DMB ST may fail to create a barrier for shared memory
+    A17_852421,
+}
+
+/// An entry in the errata table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ErratumEntry {
+    /// The erratum
+    pub erratum: Erratum,
+    /// The affected part
+    pub part: Part,
+    /// The first affected revision
+    pub first: Revision,
+    /// The last affected revision
+    pub last: Revision,
+}
+
+impl ErratumEntry {
+    const fn new(erratum: Erratum, part: Part, first: Revision, last: Revision) -> ErratumEntry {
+        ErratumEntry {
+            erratum,
+            part,
+            first,
+            last,
+        }
+    }
+
+    /// Does this entry cover the given part and revision?
+    pub fn matches(&self, part: Part, revision: Revision) -> bool {
+        self.part == part && revision >= self.first && revision <= self.last
+    }
+}
+
+/// Covers every revision
+const ALL: (Revision, Revision) = (Revision::new(0, 0), Revision::new(0xF, 0xF));
+
+/// The errata we know about
+///
+/// An erratum may have more than one entry, if it affects non-contiguous
+/// revisions.
+pub static ERRATA: &[ErratumEntry] = &[
+    ErratumEntry::new(
+        Erratum::Arm1136_411920,
+        Part::Arm1136,
+        Revision::new(0, 0),
+        Revision::new(1, 3),
+    ),
+    ErratumEntry::new(Erratum::Arm1136_411920, Part::Arm1156, ALL.0, ALL.1),
+    ErratumEntry::new(Erratum::Arm1136_411920, Part::Arm1176, ALL.0, ALL.1),
+    ErratumEntry::new(Erratum::A7_814220, Part::CortexA7, ALL.0, ALL.1),
+    ErratumEntry::new(
+        Erratum::A8_430973,
+        Part::CortexA8,
+        Revision::new(1, 0),
+        Revision::new(1, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A8_458693,
+        Part::CortexA8,
+        Revision::new(2, 0),
+        Revision::new(2, 0),
+    ),
+    ErratumEntry::new(
+        Erratum::A8_460075,
+        Part::CortexA8,
+        Revision::new(2, 0),
+        Revision::new(2, 0),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_643719,
+        Part::CortexA9,
+        Revision::new(0, 0),
+        Revision::new(0, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_720789,
+        Part::CortexA9,
+        Revision::new(0, 0),
+        Revision::new(1, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_742230,
+        Part::CortexA9,
+        Revision::new(1, 0),
+        Revision::new(2, 2),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_742231,
+        Part::CortexA9,
+        Revision::new(2, 0),
+        Revision::new(2, 2),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_743622,
+        Part::CortexA9,
+        Revision::new(2, 0),
+        Revision::new(2, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_751472,
+        Part::CortexA9,
+        Revision::new(0, 0),
+        Revision::new(2, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_754322,
+        Part::CortexA9,
+        Revision::new(2, 0),
+        Revision::new(3, 0xF),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_754327,
+        Part::CortexA9,
+        Revision::new(0, 0),
+        Revision::new(1, 0xF),
+    ),
+    ErratumEntry::new(Erratum::A9_764369, Part::CortexA9, ALL.0, ALL.1),
+    ErratumEntry::new(
+        Erratum::A9_775420,
+        Part::CortexA9,
+        Revision::new(2, 2),
+        Revision::new(2, 2),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_775420,
+        Part::CortexA9,
+        Revision::new(2, 6),
+        Revision::new(2, 6),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_775420,
+        Part::CortexA9,
+        Revision::new(2, 8),
+        Revision::new(2, 8),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_775420,
+        Part::CortexA9,
+        Revision::new(2, 10),
+        Revision::new(2, 10),
+    ),
+    ErratumEntry::new(
+        Erratum::A9_775420,
+        Part::CortexA9,
+        Revision::new(3, 0),
+        Revision::new(3, 0),
+    ),
+    ErratumEntry::new(Erratum::A9_794072, Part::CortexA9, ALL.0, ALL.1),
+    ErratumEntry::new(
+        Erratum::A15_773022,
+        Part::CortexA15,
+        Revision::new(0, 0),
+        Revision::new(0, 4),
+    ),
+    ErratumEntry::new(
+        Erratum::A15_798181,
+        Part::CortexA15,
+        Revision::new(0, 0),
+        Revision::new(3, 2),
+    ),
+    ErratumEntry::new(
+        Erratum::A17_852421,
+        Part::CortexA17,
+        Revision::new(1, 0),
+        Revision::new(1, 2),
+    ),
+];
+
+/// Does the given erratum affect the given part and revision?
+pub fn is_affected(erratum: Erratum, part: Part, revision: Revision) -> bool {
+    ERRATA
+        .iter()
+        .any(|entry| entry.erratum == erratum && entry.matches(part, revision))
+}
+
+/// List the errata which affect the given part and revision
+pub fn errata(part: Part, revision: Revision) -> impl Iterator<Item = Erratum> {
+    ERRATA
+        .iter()
+        .filter(move |entry| entry.matches(part, revision))
+        .map(|entry| entry.erratum)
+}
+
+/// Does the given erratum affect the processor we are running on?
+pub fn has_erratum(erratum: Erratum) -> bool {
+    let (part, revision) = identify();
+    is_affected(erratum, part, revision)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cortex_a9_r2p10() {
+        let (part, revision) = decode(Midr::new_with_raw_value(0x412F_C09A));
+        assert_eq!(part, Part::CortexA9);
+        assert_eq!(revision, Revision::new(2, 10));
+        assert!(is_affected(Erratum::A9_775420, part, revision));
+        assert!(is_affected(Erratum::A9_794072, part, revision));
+        assert!(!is_affected(Erratum::A9_742231, part, revision));
+        assert!(!is_affected(Erratum::A15_798181, part, revision));
+        assert_eq!(errata(part, revision).count(), 6);
+    }
+
+    #[test]
+    fn unknown_parts() {
+        let (part, revision) = decode(Midr::new_with_raw_value(0x4106_9265));
+        assert_eq!(part, Part::Arm926);
+        assert_eq!(revision, Revision::new(0, 5));
+        assert_eq!(
+            Part::from_ids(0x51, 0x00F),
+            Part::Unknown {
+                implementer: 0x51,
+                part_number: 0x00F
+            }
+        );
+        assert_eq!(errata(Part::CortexR5, revision).count(), 0);
+    }
+
+    #[test]
+    fn arm11_icache_invalidation() {
+        let erratum = Erratum::Arm1136_411920;
+        assert!(is_affected(erratum, Part::Arm1136, Revision::new(1, 3)));
+        assert!(!is_affected(erratum, Part::Arm1136, Revision::new(1, 4)));
+        assert!(is_affected(erratum, Part::Arm1156, Revision::new(0, 4)));
+        assert!(is_affected(erratum, Part::Arm1176, Revision::new(0, 7)));
+        assert!(!is_affected(
+            erratum,
+            Part::Arm11MpCore,
+            Revision::new(0, 0)
+        ));
+    }
+
+    #[test]
+    fn a9_tlbi_asid_broadcast() {
+        let erratum = Erratum::A9_720789;
+        assert!(is_affected(erratum, Part::CortexA9, Revision::new(1, 0xF)));
+        assert!(!is_affected(erratum, Part::CortexA9, Revision::new(2, 0)));
+    }
+
+    #[test]
+    fn a17_dmb_st() {
+        let erratum = Erratum::A17_852421;
+        assert!(!is_affected(
+            erratum,
+            Part::CortexA17,
+            Revision::new(0, 0xF)
+        ));
+        assert!(is_affected(erratum, Part::CortexA17, Revision::new(1, 0)));
+        assert!(is_affected(erratum, Part::CortexA17, Revision::new(1, 2)));
+        assert!(!is_affected(erratum, Part::CortexA17, Revision::new(1, 3)));
+    }
+}
@@ -0,0 +1,130 @@
+//! Finding out which Exception Level and Security State we are running in
+//!
+//! Some drivers come in one flavour per Exception Level (like
+//! [`El1PhysicalTimer`](crate::generic_timer::El1PhysicalTimer) and
+//! [`El2PhysicalTimer`](crate::generic_timer::El2PhysicalTimer)). Use
+//! [`current`] to work out which one you need.
+
+use crate::register::{cpsr::ProcessorMode, Cpsr};
+
+/// An Exception Level
+///
+/// In the Armv7 Architecture Reference Manual these are called Privilege
+/// Levels (PL0, PL1 and PL2). Monitor mode is reported as EL3, and all the
+/// other privileged modes apart from Hyp mode are reported as EL1.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ExceptionLevel {
+    /// EL0, or PL0 - User mode
+    El0,
+    /// EL1, or PL1 - System, Supervisor, IRQ, FIQ, Abort and Undefined modes
+    El1,
+    /// EL2, or PL2 - Hyp mode
+    El2,
+    /// EL3 - Monitor mode
+    El3,
+}
+
+impl ExceptionLevel {
+    /// Get the Exception Level a processor mode runs at
+    pub const fn from_mode(mode: ProcessorMode) -> ExceptionLevel {
+        match mode {
+            ProcessorMode::Usr => ExceptionLevel::El0,
+            ProcessorMode::Fiq
+            | ProcessorMode::Irq
+            | ProcessorMode::Svc
+            | ProcessorMode::Abt
+            | ProcessorMode::Und
+            | ProcessorMode::Sys => ExceptionLevel::El1,
+            ProcessorMode::Hyp => ExceptionLevel::El2,
+            ProcessorMode::Mon => ExceptionLevel::El3,
+        }
+    }
+}
+
+/// A Security State
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SecurityState {
+    /// Secure state
+    ///
+    /// Processors without the Security Extensions (like all Armv7-R and
+    /// Armv8-R processors) only have one Security State, and we report it as
+    /// Secure.
+    Secure,
+    /// Non-secure state
+    NonSecure,
+    /// We couldn't tell
+    ///
+    /// SCR can only be read from Secure PL1, and trying to read it from
+    /// Non-secure PL1 causes an Undefined Instruction exception, so a PL1
+    /// mode other than Monitor mode on a processor with the Security
+    /// Extensions cannot find out which state it is in. Neither can User mode
+    /// on such a processor.
+    Unknown,
+}
+
+/// Get the current Exception Level and Security State
+///
+/// The Exception Level comes from the mode in CPSR. The Security State is
+/// Secure in Monitor mode and Non-secure in Hyp mode. In other modes it is
+/// Secure if the processor does not implement the Security Extensions, and
+/// otherwise [`SecurityState::Unknown`].
+pub fn current() -> (ExceptionLevel, SecurityState) {
+    let level = match Cpsr::read().mode() {
+        Ok(mode) => ExceptionLevel::from_mode(mode),
+        // Reserved modes never appear in CPSR on real hardware. This only
+        // happens when testing on the host, where CPSR reads as zero.
+        Err(_) => ExceptionLevel::El1,
+    };
+    let security = match level {
+        ExceptionLevel::El3 => SecurityState::Secure,
+        ExceptionLevel::El2 => SecurityState::NonSecure,
+        ExceptionLevel::El1 if !has_security_extensions() => SecurityState::Secure,
+        ExceptionLevel::El0 if !may_have_security_extensions() => SecurityState::Secure,
+        ExceptionLevel::El1 | ExceptionLevel::El0 => SecurityState::Unknown,
+    };
+    (level, security)
+}
+
+/// Can this architecture have the Security Extensions?
+const fn may_have_security_extensions() -> bool {
+    cfg!(any(arm_architecture = "v6", arm_architecture = "v7-a"))
+}
+
+/// Does this processor implement the Security Extensions?
+///
+/// Only call this at PL1 or higher, as ID_PFR1 is not accessible from User
+/// mode.
+fn has_security_extensions() -> bool {
+    if !may_have_security_extensions() {
+        return false;
+    }
+    (crate::register::IdPfr1::read().0 >> 4) & 0xF != 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn levels() {
+        assert_eq!(
+            ExceptionLevel::from_mode(ProcessorMode::Usr),
+            ExceptionLevel::El0
+        );
+        assert_eq!(
+            ExceptionLevel::from_mode(ProcessorMode::Sys),
+            ExceptionLevel::El1
+        );
+        assert_eq!(
+            ExceptionLevel::from_mode(ProcessorMode::Hyp),
+            ExceptionLevel::El2
+        );
+        assert!(ExceptionLevel::El2 > ExceptionLevel::El1);
+        // On the host CPSR reads as zero and there are no Security Extensions
+        assert_eq!(current(), (ExceptionLevel::El1, SecurityState::Secure));
+    }
+}
@@ -0,0 +1,435 @@
+//! CPU feature discovery, using the ID registers
+//!
+//! ```rust,ignore
+//! let features = aarch32_cpu::features::CpuFeatures::detect();
+//! if features.lpae {
+//!     // use the long-descriptor translation table format
+//! }
+//! println!("{}", features);
+//! ```
+//!
+//! The decoding is done by a `const fn`, so you can also decode register
+//! values you've collected from elsewhere, using [`CpuFeatures::decode`].
+
+use crate::register;
+
+/// The raw values of the ID registers we decode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdRegisters {
+    /// ID_PFR0 (*Processor Feature Register 0*)
+    pub pfr0: u32,
+    /// ID_PFR1 (*Processor Feature Register 1*)
+    pub pfr1: u32,
+    /// ID_DFR0 (*Debug Feature Register 0*)
+    pub dfr0: u32,
+    /// ID_MMFR0 (*Memory Model Feature Register 0*)
+    pub mmfr0: u32,
+    /// ID_MMFR3 (*Memory Model Feature Register 3*)
+    pub mmfr3: u32,
+    /// ID_ISAR0 (*Instruction Set Attribute Register 0*)
+    pub isar0: u32,
+    /// MVFR0 (*Media and VFP Feature Register 0*)
+    pub mvfr0: u32,
+    /// MVFR1 (*Media and VFP Feature Register 1*)
+    pub mvfr1: u32,
+}
+
+impl IdRegisters {
+    /// Read the ID registers from this processor
+    ///
+    /// Must be called at PL1 or higher.
+    ///
+    /// MVFR0 and MVFR1 are only read when this crate is compiled for a target
+    /// with VFP enabled, and when CPACR allows access to CP10. Otherwise they
+    /// are reported as zero, which means no floating-point unit.
+    pub fn read() -> IdRegisters {
+        let (mvfr0, mvfr1) = read_mvfr();
+        IdRegisters {
+            pfr0: register::IdPfr0::read().0,
+            pfr1: register::IdPfr1::read().0,
+            dfr0: register::IdDfr0::read().0,
+            mmfr0: register::IdMmfr0::read().0,
+            mmfr3: register::IdMmfr3::read().0,
+            isar0: register::IdIsar0::read().0,
+            mvfr0,
+            mvfr1,
+        }
+    }
+}
+
+#[cfg(all(target_arch = "arm", target_feature = "vfp2"))]
+fn read_mvfr() -> (u32, u32) {
+    use register::cpacr::CpAccess;
+    if matches!(
+        register::Cpacr::read().cp10(),
+        CpAccess::Denied | CpAccess::Reserved
+    ) {
+        return (0, 0);
+    }
+    let mvfr0: u32;
+    let mvfr1: u32;
+    unsafe {
+        core::arch::asm!(
+            "vmrs {0}, mvfr0",
+            "vmrs {1}, mvfr1",
+            out(reg) mvfr0,
+            out(reg) mvfr1,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+    (mvfr0, mvfr1)
+}
+
+#[cfg(not(all(target_arch = "arm", target_feature = "vfp2")))]
+fn read_mvfr() -> (u32, u32) {
+    (0, 0)
+}
+
+/// Which memory system architecture the processor implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MemorySystem {
+    /// No MMU or MPU
+    None,
+    /// Armv6 Virtual Memory System Architecture (MMU)
+    Vmsav6,
+    /// Armv7 Virtual Memory System Architecture (MMU)
+    Vmsav7,
+    /// Armv6 Protected Memory System Architecture (MPU)
+    Pmsav6,
+    /// Armv7 Protected Memory System Architecture (MPU)
+    Pmsav7,
+    /// Armv8 Protected Memory System Architecture (MPU)
+    Pmsav8,
+    /// Something implementation defined
+    ImplementationDefined,
+}
+
+/// Which version of the Performance Monitors the processor implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PmuVersion {
+    /// Not reported in ID_DFR0
+    ///
+    /// Some Armv7 cores (e.g. Cortex-A9 and Cortex-R5) have a PMU but report
+    /// this.
+    NotReported,
+    /// PMUv1
+    V1,
+    /// PMUv2
+    V2,
+    /// PMUv3
+    V3,
+    /// PMUv3 for Armv8.1
+    V3p1,
+    /// PMUv3 for Armv8.4
+    V3p4,
+    /// PMUv3 for Armv8.5
+    V3p5,
+    /// There is no architected PMU
+    None,
+    /// Some value we don't understand
+    Other(u8),
+}
+
+/// Which version of the Debug architecture the processor implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DebugVersion {
+    /// No debug support
+    None,
+    /// Armv6 debug
+    V6,
+    /// Armv6.1 debug
+    V6p1,
+    /// Armv7 debug
+    V7,
+    /// Armv7.1 debug
+    V7p1,
+    /// Armv8 debug
+    V8,
+    /// Armv8 debug, with the Virtualization Host Extensions
+    V8Vhe,
+    /// Armv8.2 debug
+    V8p2,
+    /// Armv8.4 debug
+    V8p4,
+    /// Some value we don't understand
+    Other(u8),
+}
+
+/// Which version of VFP the processor implements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FpuVersion {
+    /// No floating-point unit (or we couldn't read MVFR0)
+    None,
+    /// VFPv2
+    Vfpv2,
+    /// VFPv3
+    Vfpv3,
+    /// VFPv4 (VFPv3 plus fused multiply-accumulate)
+    Vfpv4,
+}
+
+/// Which cache and TLB maintenance operations are broadcast to other cores
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MaintenanceBroadcast {
+    /// Maintenance operations only affect the local core
+    None,
+    /// Cache maintenance is broadcast, but TLB maintenance is not
+    CacheOnly,
+    /// Both cache and TLB maintenance are broadcast
+    CacheAndTlb,
+}
+
+/// The features of a processor, decoded from the ID registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuFeatures {
+    /// Supports Thumb-2 (the T32 instruction set)
+    pub thumb2: bool,
+    /// Has `SDIV` and `UDIV` in the A32 instruction set
+    pub divide_arm: bool,
+    /// Has `SDIV` and `UDIV` in the T32 instruction set
+    pub divide_thumb: bool,
+    /// Supports the Security Extensions (TrustZone)
+    pub security_extensions: bool,
+    /// Supports the Virtualization Extensions (Hyp mode / EL2)
+    pub virtualization_extensions: bool,
+    /// Has the Generic Timer
+    pub generic_timer: bool,
+    /// Has the GIC CPU interface system registers
+    pub gic_system_registers: bool,
+    /// The memory system
+    pub memory_system: MemorySystem,
+    /// Supports the Large Physical Address Extension
+    pub lpae: bool,
+    /// Which maintenance operations are broadcast
+    pub maintenance_broadcast: MaintenanceBroadcast,
+    /// The Performance Monitors version
+    pub pmu: PmuVersion,
+    /// The Debug architecture version
+    pub debug: DebugVersion,
+    /// The VFP version
+    pub fpu: FpuVersion,
+    /// How many 64-bit floating-point registers there are (0, 16 or 32)
+    pub fp_registers: u8,
+    /// Supports Advanced SIMD (NEON)
+    pub neon: bool,
+}
+
+impl CpuFeatures {
+    /// Read the ID registers and decode them
+    ///
+    /// Must be called at PL1 or higher. See [`IdRegisters::read`] for notes
+    /// on floating-point support.
+    pub fn detect() -> CpuFeatures {
+        Self::decode(&IdRegisters::read())
+    }
+
+    /// Decode some ID register values
+    pub const fn decode(regs: &IdRegisters) -> CpuFeatures {
+        let divide = field(regs.isar0, 24);
+        let vmsa = field(regs.mmfr0, 0);
+        let pmsa = field(regs.mmfr0, 4);
+        let memory_system = match (vmsa, pmsa) {
+            (3..=5, _) => MemorySystem::Vmsav7,
+            (2, _) => MemorySystem::Vmsav6,
+            (_, 4) => MemorySystem::Pmsav8,
+            (_, 3) => MemorySystem::Pmsav7,
+            (_, 2) => MemorySystem::Pmsav6,
+            (0, 0) => MemorySystem::None,
+            _ => MemorySystem::ImplementationDefined,
+        };
+        let maintenance_broadcast = match field(regs.mmfr3, 12) {
+            0 => MaintenanceBroadcast::None,
+            1 => MaintenanceBroadcast::CacheOnly,
+            _ => MaintenanceBroadcast::CacheAndTlb,
+        };
+        let pmu = match field(regs.dfr0, 24) {
+            0 => PmuVersion::NotReported,
+            1 => PmuVersion::V1,
+            2 => PmuVersion::V2,
+            3 => PmuVersion::V3,
+            4 => PmuVersion::V3p1,
+            5 => PmuVersion::V3p4,
+            6 => PmuVersion::V3p5,
+            0xF => PmuVersion::None,
+            n => PmuVersion::Other(n),
+        };
+        // Prefer the CP14 interface, but Cortex-R cores only report the
+        // memory-mapped one
+        let debug = match field(regs.dfr0, 0) {
+            0 => field(regs.dfr0, 8),
+            n => n,
+        };
+        let debug = match debug {
+            0 => DebugVersion::None,
+            2 => DebugVersion::V6,
+            3 => DebugVersion::V6p1,
+            4 => DebugVersion::V7,
+            5 => DebugVersion::V7p1,
+            6 => DebugVersion::V8,
+            7 => DebugVersion::V8Vhe,
+            8 => DebugVersion::V8p2,
+            9 => DebugVersion::V8p4,
+            n => DebugVersion::Other(n),
+        };
+        let single = field(regs.mvfr0, 4);
+        let double = field(regs.mvfr0, 8);
+        let fpu = if single == 0 && double == 0 {
+            FpuVersion::None
+        } else if single < 2 && double < 2 {
+            FpuVersion::Vfpv2
+        } else if field(regs.mvfr1, 28) != 0 {
+            FpuVersion::Vfpv4
+        } else {
+            FpuVersion::Vfpv3
+        };
+        let fp_registers = match field(regs.mvfr0, 0) {
+            1 => 16,
+            2 => 32,
+            _ => 0,
+        };
+        CpuFeatures {
+            thumb2: field(regs.pfr0, 4) == 3,
+            divide_arm: divide >= 2,
+            divide_thumb: divide >= 1,
+            security_extensions: field(regs.pfr1, 4) != 0,
+            virtualization_extensions: field(regs.pfr1, 12) != 0,
+            generic_timer: field(regs.pfr1, 16) != 0,
+            gic_system_registers: field(regs.pfr1, 28) != 0,
+            memory_system,
+            lpae: vmsa >= 5,
+            maintenance_broadcast,
+            pmu,
+            debug,
+            fpu,
+            fp_registers,
+            neon: field(regs.mvfr1, 8) != 0 || field(regs.mvfr1, 12) != 0,
+        }
+    }
+}
+
+impl core::fmt::Display for CpuFeatures {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Features\t:")?;
+        let flags = [
+            (self.thumb2, "thumb2"),
+            (self.divide_arm, "idiva"),
+            (self.divide_thumb, "idivt"),
+            (self.fpu == FpuVersion::Vfpv2, "vfpv2"),
+            (self.fpu == FpuVersion::Vfpv3, "vfpv3"),
+            (self.fpu == FpuVersion::Vfpv4, "vfpv4"),
+            (self.fp_registers == 32, "vfpd32"),
+            (self.neon, "neon"),
+            (self.lpae, "lpae"),
+            (self.security_extensions, "security"),
+            (self.virtualization_extensions, "virt"),
+            (self.generic_timer, "gentimer"),
+            (self.gic_system_registers, "gicsysreg"),
+        ];
+        for (_, name) in flags.iter().filter(|(present, _)| *present) {
+            write!(f, " {}", name)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "Memory system\t: {:?}", self.memory_system)?;
+        writeln!(f, "Broadcast\t: {:?}", self.maintenance_broadcast)?;
+        writeln!(f, "PMU\t\t: {:?}", self.pmu)?;
+        write!(f, "Debug\t\t: {:?}", self.debug)
+    }
+}
+
+/// Get the 4-bit ID field starting at bit `lsb`
+const fn field(value: u32, lsb: u32) -> u8 {
+    ((value >> lsb) & 0xF) as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Text([u8; 256], usize);
+
+    impl core::fmt::Write for Text {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            let end = self.1 + s.len();
+            self.0[self.1..end].copy_from_slice(s.as_bytes());
+            self.1 = end;
+            Ok(())
+        }
+    }
+
+    // Values from the Cortex-A9 Technical Reference Manual
+    const CORTEX_A9: IdRegisters = IdRegisters {
+        pfr0: 0x0000_1231,
+        pfr1: 0x0000_0011,
+        dfr0: 0x0001_0444,
+        mmfr0: 0x0010_0103,
+        mmfr3: 0x0010_2111,
+        isar0: 0x0010_1111,
+        mvfr0: 0x1011_1222,
+        mvfr1: 0x0111_1111,
+    };
+
+    // Values from the Cortex-A15 Technical Reference Manual
+    const CORTEX_A15: IdRegisters = IdRegisters {
+        pfr0: 0x0000_1131,
+        pfr1: 0x0001_1131,
+        dfr0: 0x0201_0555,
+        mmfr0: 0x1020_1105,
+        mmfr3: 0x1021_2111,
+        isar0: 0x0210_1110,
+        mvfr0: 0x1011_1222,
+        mvfr1: 0x1111_1111,
+    };
+
+    #[test]
+    fn cortex_a9() {
+        const FEATURES: CpuFeatures = CpuFeatures::decode(&CORTEX_A9);
+        // Decoded at compile time
+        const {
+            assert!(FEATURES.thumb2);
+            assert!(!FEATURES.divide_arm && !FEATURES.divide_thumb);
+            assert!(FEATURES.security_extensions);
+            assert!(!FEATURES.virtualization_extensions);
+            assert!(!FEATURES.lpae);
+            assert!(FEATURES.neon);
+        }
+        assert_eq!(FEATURES.memory_system, MemorySystem::Vmsav7);
+        assert_eq!(
+            FEATURES.maintenance_broadcast,
+            MaintenanceBroadcast::CacheAndTlb
+        );
+        assert_eq!(FEATURES.debug, DebugVersion::V7);
+        assert_eq!(FEATURES.fpu, FpuVersion::Vfpv3);
+    }
+
+    #[test]
+    fn cortex_a15() {
+        let features = CpuFeatures::decode(&CORTEX_A15);
+        assert!(features.divide_arm && features.divide_thumb);
+        assert!(features.virtualization_extensions);
+        assert!(features.generic_timer);
+        assert!(features.lpae);
+        assert_eq!(features.pmu, PmuVersion::V2);
+        assert_eq!(features.debug, DebugVersion::V7p1);
+        assert_eq!(features.fpu, FpuVersion::Vfpv4);
+        assert_eq!(features.fp_registers, 32);
+        let mut text = Text([0; 256], 0);
+        core::fmt::write(&mut text, format_args!("{}", features)).unwrap();
+        assert!(text.0[..text.1].starts_with(
+            b"Features\t: thumb2 idiva idivt vfpv4 vfpd32 neon lpae security virt gentimer\n"
+        ));
+    }
+}
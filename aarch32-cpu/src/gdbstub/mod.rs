@@ -0,0 +1,1116 @@
+//! A GDB Remote Serial Protocol stub, using debug-monitor exceptions
+//!
+//! This lets you attach GDB to a running Armv7-R or Armv7-A system over a
+//! serial port (or any other byte stream), without a JTAG probe.
+//!
+//! Breakpoints are `BKPT` instructions (software breakpoints) or Breakpoint
+//! Register Pairs (hardware breakpoints), both of which cause a Prefetch
+//! Abort. The `aarch32-rt` crate provides a Prefetch Abort trampoline called
+//! `_asm_debug_monitor_handler` which saves the interrupted context as a
+//! [`Registers`] structure, and passes it to your
+//! `_debug_monitor_handler` function. That function should call
+//! [`GdbStub::handle_exception`].
+//!
+//! ```rust,ignore
+//! static STUB: Mutex<RefCell<Option<GdbStub<Uart>>>> = ...;
+//!
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _debug_monitor_handler(regs: &mut Registers) {
+//!     critical_section::with(|cs| {
+//!         let mut stub = STUB.borrow_ref_mut(cs);
+//!         let stub = stub.as_mut().unwrap();
+//!         unsafe { stub.handle_exception(regs) }.unwrap();
+//!     });
+//! }
+//! ```
+//!
+//! Call [`breakpoint`] early on to give GDB a chance to attach.
+//!
+//! Single-stepping is done by decoding the current instruction and planting
+//! a temporary software breakpoint at wherever it will go next, so stepping
+//! code in read-only memory is not supported.
+
+mod step;
+
+use crate::register::{BpIAll, Dbgbcr, Dbgbvr, Dbgdidr, Dbgdscrext, Dbgoslar, Dccmvau, Iciallu};
+
+use step::{Memory, Target};
+
+/// Something we can talk to GDB over, like a UART
+pub trait Connection {
+    /// The error returned when the connection fails
+    type Error;
+
+    /// Read one byte, blocking until it arrives
+    fn read_byte(&mut self) -> Result<u8, Self::Error>;
+
+    /// Write one byte
+    fn write_byte(&mut self, byte: u8) -> Result<(), Self::Error>;
+
+    /// Wait until all written bytes have been sent
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// The saved state of the interrupted code
+///
+/// The layout matches the frame saved by `_asm_debug_monitor_handler` in
+/// `aarch32-rt`.
+#[repr(C)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Registers {
+    /// R0 to R12
+    pub r: [u32; 13],
+    /// The Stack Pointer
+    pub sp: u32,
+    /// The Link Register
+    pub lr: u32,
+    /// The address of the instruction to execute on return
+    pub pc: u32,
+    /// The saved Program Status Register
+    pub cpsr: u32,
+}
+
+/// Execute a `BKPT` instruction, to hand control to GDB
+///
+/// GDB will see this as a `SIGTRAP`, and resuming will continue execution
+/// after the `BKPT` instruction.
+#[inline(always)]
+pub fn breakpoint() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("bkpt #0");
+    }
+}
+
+/// The maximum number of software breakpoints we keep track of
+pub const MAX_SW_BREAKPOINTS: usize = 32;
+
+/// The size of our packet buffers
+const PACKET_SIZE: usize = 1024;
+
+/// The signal we report for breakpoints and steps
+const SIGTRAP: u8 = 5;
+
+/// The signal we report for any other Prefetch Abort
+const SIGSEGV: u8 = 11;
+
+/// The A32 `BKPT #0` instruction
+const ARM_BKPT: u32 = 0xE120_0070;
+
+/// The T32 `BKPT #0` instruction
+const THUMB_BKPT: u16 = 0xBE00;
+
+/// The CPSR T bit
+const CPSR_T: u32 = 1 << 5;
+
+/// GDB register number for the CPSR
+const REGNUM_CPSR: u32 = 25;
+
+/// Describes the registers in our `g` packet
+const TARGET_XML: &[u8] = br#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+<architecture>arm</architecture>
+<feature name="org.gnu.gdb.arm.core">
+<reg name="r0" bitsize="32"/>
+<reg name="r1" bitsize="32"/>
+<reg name="r2" bitsize="32"/>
+<reg name="r3" bitsize="32"/>
+<reg name="r4" bitsize="32"/>
+<reg name="r5" bitsize="32"/>
+<reg name="r6" bitsize="32"/>
+<reg name="r7" bitsize="32"/>
+<reg name="r8" bitsize="32"/>
+<reg name="r9" bitsize="32"/>
+<reg name="r10" bitsize="32"/>
+<reg name="r11" bitsize="32"/>
+<reg name="r12" bitsize="32"/>
+<reg name="sp" bitsize="32" type="data_ptr"/>
+<reg name="lr" bitsize="32"/>
+<reg name="pc" bitsize="32" type="code_ptr"/>
+<reg name="cpsr" bitsize="32" regnum="25"/>
+</feature>
+</target>
+"#;
+
+/// A breakpoint made by replacing an instruction with `BKPT`
+#[derive(Debug, Clone, Copy)]
+struct SwBreakpoint {
+    /// Where the breakpoint is
+    addr: u32,
+    /// How many bytes we replaced (2 for T32, 4 for A32)
+    len: u8,
+    /// The bytes we replaced
+    saved: [u8; 4],
+}
+
+impl SwBreakpoint {
+    /// Replace the instruction at `addr` with a `BKPT`
+    ///
+    /// # Safety
+    ///
+    /// The address must be valid, writable, and contain an instruction of
+    /// the given length.
+    unsafe fn insert(mem: &mut impl MemoryMut, addr: u32, thumb: bool) -> SwBreakpoint {
+        let len: u8 = if thumb { 2 } else { 4 };
+        let mut saved = [0u8; 4];
+        for (i, byte) in saved.iter_mut().take(len as usize).enumerate() {
+            *byte = mem.read_u8(addr + i as u32);
+        }
+        let bkpt = if thumb {
+            let mut bytes = [0u8; 4];
+            bytes[0..2].copy_from_slice(&THUMB_BKPT.to_ne_bytes());
+            bytes
+        } else {
+            ARM_BKPT.to_ne_bytes()
+        };
+        for (i, byte) in bkpt.iter().take(len as usize).enumerate() {
+            mem.write_u8(addr + i as u32, *byte);
+        }
+        mem.sync_caches(addr, len as usize);
+        SwBreakpoint { addr, len, saved }
+    }
+
+    /// Put back the instruction we replaced
+    ///
+    /// # Safety
+    ///
+    /// Must only be called once, on a breakpoint created with [`Self::insert`].
+    unsafe fn remove(&self, mem: &mut impl MemoryMut) {
+        for (i, byte) in self.saved.iter().take(self.len as usize).enumerate() {
+            mem.write_u8(self.addr + i as u32, *byte);
+        }
+        mem.sync_caches(self.addr, self.len as usize);
+    }
+}
+
+/// Memory that GDB can write to, as well as read
+trait MemoryMut: Memory {
+    /// Write one byte
+    fn write_u8(&mut self, addr: u32, value: u8);
+
+    /// Make sure modified instructions are seen by the instruction fetch
+    fn sync_caches(&mut self, addr: u32, len: usize);
+}
+
+/// Read and write target memory
+struct TargetMemory;
+
+impl Memory for TargetMemory {
+    fn read_u8(&self, addr: u32) -> u8 {
+        unsafe { read_byte(addr) }
+    }
+
+    fn read_u16(&self, addr: u32) -> u16 {
+        unsafe { core::ptr::read_volatile(addr as usize as *const u16) }
+    }
+
+    fn read_u32(&self, addr: u32) -> u32 {
+        unsafe { core::ptr::read_volatile(addr as usize as *const u32) }
+    }
+}
+
+impl MemoryMut for TargetMemory {
+    fn write_u8(&mut self, addr: u32, value: u8) {
+        unsafe { write_byte(addr, value) }
+    }
+
+    fn sync_caches(&mut self, addr: u32, len: usize) {
+        sync_caches(addr, len);
+    }
+}
+
+/// Read a byte of target memory
+///
+/// # Safety
+///
+/// The address must be valid for reading
+unsafe fn read_byte(addr: u32) -> u8 {
+    unsafe { core::ptr::read_volatile(addr as usize as *const u8) }
+}
+
+/// Write a byte of target memory
+///
+/// # Safety
+///
+/// The address must be valid for writing
+unsafe fn write_byte(addr: u32, value: u8) {
+    unsafe { core::ptr::write_volatile(addr as usize as *mut u8, value) }
+}
+
+/// Make sure modified instructions are seen by the instruction fetch
+fn sync_caches(addr: u32, len: usize) {
+    // The smallest cache line on any Armv7 processor is 16 bytes
+    let end = addr.wrapping_add(len as u32);
+    let mut line = addr & !15;
+    while line < end {
+        unsafe {
+            Dccmvau::write(Dccmvau::new(line));
+        }
+        line += 16;
+    }
+    #[cfg(target_arch = "arm")]
+    crate::asm::dsb();
+    Iciallu::write();
+    BpIAll::write();
+    #[cfg(target_arch = "arm")]
+    {
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+}
+
+/// Did the current Prefetch Abort come from a debug event?
+fn is_debug_event() -> bool {
+    #[cfg(target_arch = "arm")]
+    {
+        use crate::register::SysRegRead;
+        is_debug_fault(<crate::register::Ifsr as SysRegRead>::read_raw())
+    }
+    #[cfg(not(target_arch = "arm"))]
+    {
+        true
+    }
+}
+
+/// Does this IFSR value report a debug event?
+///
+/// IFSR.LPAE says whether the fault status is in the Short-descriptor format
+/// (FS is `0b00010` for a debug event) or the Long-descriptor format (STATUS
+/// is `0b100010`).
+fn is_debug_fault(ifsr: u32) -> bool {
+    if ifsr & (1 << 9) != 0 {
+        ifsr & 0x3F == 0b10_0010
+    } else {
+        ifsr & 0x40F == 0b0_0010
+    }
+}
+
+/// Is there a `BKPT` instruction at the given address?
+fn is_bkpt(addr: u32, thumb: bool) -> Option<u32> {
+    let mem = TargetMemory;
+    if thumb {
+        (mem.read_u16(addr) & 0xFF00 == THUMB_BKPT).then_some(2)
+    } else {
+        (mem.read_u32(addr) & 0x0FF0_00F0 == ARM_BKPT & 0x0FF0_00F0).then_some(4)
+    }
+}
+
+/// Builds a packet to send to GDB
+struct Response<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl Response<'_> {
+    fn push(&mut self, byte: u8) {
+        if let Some(slot) = self.buffer.get_mut(self.len) {
+            *slot = byte;
+            self.len += 1;
+        }
+    }
+
+    fn push_str(&mut self, s: &[u8]) {
+        for byte in s {
+            self.push(*byte);
+        }
+    }
+
+    fn push_hex(&mut self, byte: u8) {
+        self.push(HEX_DIGITS[usize::from(byte >> 4)]);
+        self.push(HEX_DIGITS[usize::from(byte & 0xF)]);
+    }
+
+    /// Push a register value, in target byte order
+    fn push_reg(&mut self, value: u32) {
+        for byte in value.to_ne_bytes() {
+            self.push_hex(byte);
+        }
+    }
+
+    fn space(&self) -> usize {
+        self.buffer.len() - self.len
+    }
+}
+
+const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+fn hex_digit(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Parse a big-endian hex number, like an address or a length
+fn parse_hex(s: &[u8]) -> Option<u32> {
+    if s.is_empty() || s.len() > 8 {
+        return None;
+    }
+    s.iter()
+        .try_fold(0u32, |acc, c| Some((acc << 4) | u32::from(hex_digit(*c)?)))
+}
+
+/// Parse a register value, in target byte order
+fn parse_reg(s: &[u8]) -> Option<u32> {
+    if s.len() != 8 {
+        return None;
+    }
+    let mut bytes = [0u8; 4];
+    for (byte, pair) in bytes.iter_mut().zip(s.chunks(2)) {
+        *byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+    }
+    Some(u32::from_ne_bytes(bytes))
+}
+
+/// Split `s` in two at the first `sep`
+fn split(s: &[u8], sep: u8) -> Option<(&[u8], &[u8])> {
+    let idx = s.iter().position(|c| *c == sep)?;
+    Some((&s[..idx], &s[idx + 1..]))
+}
+
+/// Parse `addr,len`
+fn parse_addr_len(s: &[u8]) -> Option<(u32, u32)> {
+    let (addr, len) = split(s, b',')?;
+    Some((parse_hex(addr)?, parse_hex(len)?))
+}
+
+/// What to do after handling a packet
+enum Action {
+    /// Send the response
+    Reply,
+    /// Resume execution without replying
+    Resume,
+    /// GDB has gone away - send the response (if any) and resume execution
+    Detach,
+}
+
+/// The breakpoints we have inserted
+struct Breakpoints {
+    sw: [Option<SwBreakpoint>; MAX_SW_BREAKPOINTS],
+    hw: [Option<u32>; 16],
+    num_hw: u8,
+    step: Option<SwBreakpoint>,
+}
+
+/// A GDB Remote Serial Protocol stub
+pub struct GdbStub<C> {
+    conn: C,
+    request: [u8; PACKET_SIZE],
+    response: [u8; PACKET_SIZE],
+    breakpoints: Breakpoints,
+    resumed: bool,
+}
+
+impl<C> GdbStub<C>
+where
+    C: Connection,
+{
+    /// Create a new GDB stub, talking over the given connection
+    pub fn new(conn: C) -> GdbStub<C> {
+        GdbStub {
+            conn,
+            request: [0; PACKET_SIZE],
+            response: [0; PACKET_SIZE],
+            breakpoints: Breakpoints {
+                sw: [None; MAX_SW_BREAKPOINTS],
+                hw: [None; 16],
+                num_hw: Dbgdidr::read().num_breakpoints(),
+                step: None,
+            },
+            resumed: false,
+        }
+    }
+
+    /// Enable Monitor debug-mode, so that hardware breakpoints work
+    ///
+    /// `BKPT` instructions work without this.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that the Prefetch Abort handler calls
+    /// [`GdbStub::handle_exception`] for debug events.
+    pub unsafe fn enable_debug_monitor(&mut self) {
+        Dbgoslar::unlock();
+        unsafe {
+            Dbgdscrext::modify(|r| r.set_mdbgen(true));
+        }
+        #[cfg(target_arch = "arm")]
+        crate::asm::isb();
+    }
+
+    /// Give back the connection
+    pub fn into_inner(self) -> C {
+        self.conn
+    }
+
+    /// Talk to GDB until it tells us to resume execution
+    ///
+    /// Call this from your Prefetch Abort handler.
+    ///
+    /// # Safety
+    ///
+    /// GDB is able to read and write any memory, and to change any register
+    /// of the interrupted code. The `regs` must be the state saved by the
+    /// exception entry code, which will be restored on exit.
+    pub unsafe fn handle_exception(&mut self, regs: &mut Registers) -> Result<(), C::Error> {
+        if let Some(bp) = self.breakpoints.step.take() {
+            unsafe { bp.remove(&mut TargetMemory) };
+        }
+
+        let signal = if is_debug_event() { SIGTRAP } else { SIGSEGV };
+
+        if self.resumed {
+            // GDB is waiting for us to say why we stopped
+            let mut response = Response {
+                buffer: &mut self.response,
+                len: 0,
+            };
+            stop_reply(&mut response, signal);
+            let len = response.len;
+            self.send_packet(len)?;
+        }
+
+        loop {
+            let len = self.read_packet()?;
+            let mut response = Response {
+                buffer: &mut self.response,
+                len: 0,
+            };
+            let action = unsafe {
+                handle_packet(
+                    &mut TargetMemory,
+                    &self.request[..len],
+                    &mut response,
+                    &mut self.breakpoints,
+                    regs,
+                    signal,
+                )
+            };
+            let len = response.len;
+            match action {
+                Action::Reply => {
+                    self.send_packet(len)?;
+                }
+                Action::Resume => {
+                    self.resumed = true;
+                    break;
+                }
+                Action::Detach => {
+                    if len != 0 {
+                        self.send_packet(len)?;
+                    }
+                    self.resumed = false;
+                    break;
+                }
+            }
+        }
+
+        // Step over any BKPT compiled into the program. Our own breakpoints
+        // are removed by GDB before it resumes from them.
+        let thumb = regs.cpsr & CPSR_T != 0;
+        if signal == SIGTRAP && !self.breakpoints.is_ours(regs.pc) {
+            if let Some(len) = is_bkpt(regs.pc, thumb) {
+                regs.pc = regs.pc.wrapping_add(len);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read a packet into the request buffer, and acknowledge it
+    fn read_packet(&mut self) -> Result<usize, C::Error> {
+        loop {
+            // Skip anything before the start of the packet, including acks
+            // and Ctrl-C (we're already stopped)
+            while self.conn.read_byte()? != b'$' {}
+            let mut len = 0;
+            let mut checksum = 0u8;
+            let mut overflow = false;
+            loop {
+                let byte = self.conn.read_byte()?;
+                if byte == b'#' {
+                    break;
+                }
+                checksum = checksum.wrapping_add(byte);
+                match self.request.get_mut(len) {
+                    Some(slot) => {
+                        *slot = byte;
+                        len += 1;
+                    }
+                    None => overflow = true,
+                }
+            }
+            let hi = hex_digit(self.conn.read_byte()?);
+            let lo = hex_digit(self.conn.read_byte()?);
+            let expected = hi.zip(lo).map(|(hi, lo)| (hi << 4) | lo);
+            if expected == Some(checksum) && !overflow {
+                self.conn.write_byte(b'+')?;
+                self.conn.flush()?;
+                return Ok(len);
+            }
+            self.conn.write_byte(b'-')?;
+            self.conn.flush()?;
+        }
+    }
+
+    /// Send the start of the response buffer as a packet, until GDB acknowledges it
+    fn send_packet(&mut self, len: usize) -> Result<(), C::Error> {
+        loop {
+            self.conn.write_byte(b'$')?;
+            let mut checksum = 0u8;
+            for byte in &self.response[..len] {
+                checksum = checksum.wrapping_add(*byte);
+                self.conn.write_byte(*byte)?;
+            }
+            self.conn.write_byte(b'#')?;
+            self.conn
+                .write_byte(HEX_DIGITS[usize::from(checksum >> 4)])?;
+            self.conn
+                .write_byte(HEX_DIGITS[usize::from(checksum & 0xF)])?;
+            self.conn.flush()?;
+            match self.conn.read_byte()? {
+                b'-' => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Handle one packet from GDB, writing any reply into `r`
+///
+/// # Safety
+///
+/// See [`GdbStub::handle_exception`].
+unsafe fn handle_packet(
+    mem: &mut impl MemoryMut,
+    request: &[u8],
+    r: &mut Response,
+    breakpoints: &mut Breakpoints,
+    regs: &mut Registers,
+    signal: u8,
+) -> Action {
+    let Some((&cmd, args)) = request.split_first() else {
+        return Action::Reply;
+    };
+    match cmd {
+        b'?' => {
+            stop_reply(r, signal);
+        }
+        b'g' => {
+            for value in regs.r {
+                r.push_reg(value);
+            }
+            r.push_reg(regs.sp);
+            r.push_reg(regs.lr);
+            r.push_reg(regs.pc);
+            r.push_reg(regs.cpsr);
+        }
+        b'G' => {
+            let mut values = [0u32; 17];
+            let mut ok = args.len() == values.len() * 8;
+            for (value, chunk) in values.iter_mut().zip(args.chunks(8)) {
+                match parse_reg(chunk) {
+                    Some(v) => *value = v,
+                    None => ok = false,
+                }
+            }
+            if ok {
+                regs.r.copy_from_slice(&values[0..13]);
+                regs.sp = values[13];
+                regs.lr = values[14];
+                regs.pc = values[15];
+                regs.cpsr = values[16];
+                r.push_str(b"OK");
+            } else {
+                r.push_str(b"E01");
+            }
+        }
+        b'p' => match parse_hex(args).and_then(|n| register_mut(regs, n)) {
+            Some(value) => r.push_reg(*value),
+            None => r.push_str(b"xxxxxxxx"),
+        },
+        b'P' => {
+            let result = split(args, b'=').and_then(|(n, value)| {
+                let slot = register_mut(regs, parse_hex(n)?)?;
+                *slot = parse_reg(value)?;
+                Some(())
+            });
+            r.push_str(if result.is_some() { b"OK" } else { b"E01" });
+        }
+        b'm' => match parse_addr_len(args) {
+            Some((addr, len)) => {
+                let len = (len as usize).min(r.space() / 2);
+                for i in 0..len {
+                    r.push_hex(mem.read_u8(addr.wrapping_add(i as u32)));
+                }
+            }
+            None => r.push_str(b"E01"),
+        },
+        b'M' => {
+            let result = split(args, b':').and_then(|(addr_len, data)| {
+                let (addr, len) = parse_addr_len(addr_len)?;
+                if data.len() != len as usize * 2 {
+                    return None;
+                }
+                for (i, pair) in data.chunks(2).enumerate() {
+                    let byte = (hex_digit(pair[0])? << 4) | hex_digit(pair[1])?;
+                    mem.write_u8(addr.wrapping_add(i as u32), byte);
+                }
+                mem.sync_caches(addr, len as usize);
+                Some(())
+            });
+            r.push_str(if result.is_some() { b"OK" } else { b"E01" });
+        }
+        b'X' => {
+            let result = split(args, b':').and_then(|(addr_len, data)| {
+                let (addr, len) = parse_addr_len(addr_len)?;
+                let mut written = 0;
+                let mut escaped = false;
+                for &c in data {
+                    if c == 0x7D && !escaped {
+                        escaped = true;
+                        continue;
+                    }
+                    let byte = if escaped { c ^ 0x20 } else { c };
+                    escaped = false;
+                    mem.write_u8(addr.wrapping_add(written), byte);
+                    written += 1;
+                }
+                if written != len {
+                    return None;
+                }
+                mem.sync_caches(addr, len as usize);
+                Some(())
+            });
+            r.push_str(if result.is_some() { b"OK" } else { b"E01" });
+        }
+        b'c' | b's' => {
+            if let Some(addr) = parse_hex(args) {
+                regs.pc = addr;
+            }
+            if cmd == b's' {
+                let Target { addr, thumb } = step::next_pc(regs, mem);
+                if !breakpoints.is_ours(addr) {
+                    breakpoints.step = Some(unsafe { SwBreakpoint::insert(mem, addr, thumb) });
+                }
+            }
+            return Action::Resume;
+        }
+        b'Z' | b'z' => {
+            let insert = cmd == b'Z';
+            let result = split(args, b',').and_then(|(kind, rest)| {
+                let (addr, size) = parse_addr_len(rest)?;
+                match kind {
+                    b"0" => breakpoints.sw_breakpoint(mem, insert, addr, size),
+                    b"1" => breakpoints.hw_breakpoint(insert, addr, size),
+                    _ => None,
+                }
+            });
+            match (result, kind_supported(args)) {
+                (Some(()), _) => r.push_str(b"OK"),
+                (None, true) => r.push_str(b"E01"),
+                // an empty reply means "not supported"
+                (None, false) => {}
+            }
+        }
+        b'D' => {
+            breakpoints.remove_all(mem);
+            r.push_str(b"OK");
+            return Action::Detach;
+        }
+        b'k' => {
+            breakpoints.remove_all(mem);
+            return Action::Detach;
+        }
+        b'H' => r.push_str(b"OK"),
+        b'q' => {
+            if args.starts_with(b"Supported") {
+                r.push_str(b"PacketSize=");
+                for byte in (PACKET_SIZE as u16).to_be_bytes() {
+                    r.push_hex(byte);
+                }
+                r.push_str(b";qXfer:features:read+");
+            } else if args == b"Attached" {
+                r.push(b'1');
+            } else if let Some(rest) = args.strip_prefix(b"Xfer:features:read:target.xml:") {
+                match parse_addr_len(rest) {
+                    Some((offset, len)) => {
+                        let offset = (offset as usize).min(TARGET_XML.len());
+                        let len = (len as usize).min(r.space() - 1);
+                        let chunk = &TARGET_XML[offset..];
+                        if chunk.len() > len {
+                            r.push(b'm');
+                            r.push_str(&chunk[..len]);
+                        } else {
+                            r.push(b'l');
+                            r.push_str(chunk);
+                        }
+                    }
+                    None => r.push_str(b"E01"),
+                }
+            }
+        }
+        _ => {
+            // Unsupported commands get an empty response
+        }
+    }
+    Action::Reply
+}
+
+/// Write a stop reply packet
+fn stop_reply(r: &mut Response, signal: u8) {
+    r.push(b'S');
+    r.push_hex(signal);
+}
+
+impl Breakpoints {
+    /// Is there a software, hardware or step breakpoint at this address?
+    fn is_ours(&self, addr: u32) -> bool {
+        self.sw
+            .iter()
+            .chain(core::iter::once(&self.step))
+            .flatten()
+            .any(|bp| bp.addr == addr)
+            || self.hw.iter().flatten().any(|a| *a == addr)
+    }
+
+    /// Insert or remove a software breakpoint
+    fn sw_breakpoint(
+        &mut self,
+        mem: &mut impl MemoryMut,
+        insert: bool,
+        addr: u32,
+        kind: u32,
+    ) -> Option<()> {
+        let existing = self
+            .sw
+            .iter()
+            .position(|bp| bp.is_some_and(|bp| bp.addr == addr));
+        if insert {
+            if existing.is_some() {
+                return Some(());
+            }
+            let thumb = match kind {
+                2 | 3 => true,
+                4 => false,
+                _ => return None,
+            };
+            let slot = self.sw.iter_mut().find(|bp| bp.is_none())?;
+            *slot = Some(unsafe { SwBreakpoint::insert(mem, addr, thumb) });
+        } else if let Some(idx) = existing {
+            if let Some(bp) = self.sw[idx].take() {
+                unsafe { bp.remove(mem) };
+            }
+        }
+        Some(())
+    }
+
+    /// Insert or remove a hardware breakpoint
+    fn hw_breakpoint(&mut self, insert: bool, addr: u32, kind: u32) -> Option<()> {
+        let slots = &mut self.hw[..usize::from(self.num_hw)];
+        if insert {
+            let bas = match (kind, addr & 2) {
+                (2 | 3, 0) => 0b0011,
+                (2 | 3, _) => 0b1100,
+                (4, _) => 0b1111,
+                _ => return None,
+            };
+            let idx = slots.iter().position(|a| a.is_none())?;
+            slots[idx] = Some(addr);
+            let bcr = Dbgbcr::new_with_raw_value(0)
+                .with_bt(crate::register::BreakpointType::UnlinkedAddressMatch)
+                .with_bas(arbitrary_int::u4::new(bas))
+                .with_pmc(arbitrary_int::u2::new(0b11))
+                .with_e(true);
+            unsafe {
+                Dbgbvr::write_indexed(idx as u8, Dbgbvr(addr & !3));
+                Dbgbcr::write_indexed(idx as u8, bcr);
+            }
+        } else {
+            let idx = slots.iter().position(|a| *a == Some(addr))?;
+            slots[idx] = None;
+            unsafe {
+                Dbgbcr::write_indexed(idx as u8, Dbgbcr::new_with_raw_value(0));
+            }
+        }
+        #[cfg(target_arch = "arm")]
+        crate::asm::isb();
+        Some(())
+    }
+
+    /// Remove every breakpoint, because GDB is going away
+    fn remove_all(&mut self, mem: &mut impl MemoryMut) {
+        for bp in self.sw.iter_mut() {
+            if let Some(bp) = bp.take() {
+                unsafe { bp.remove(mem) };
+            }
+        }
+        for (idx, bp) in self.hw.iter_mut().enumerate() {
+            if bp.take().is_some() {
+                unsafe {
+                    Dbgbcr::write_indexed(idx as u8, Dbgbcr::new_with_raw_value(0));
+                }
+            }
+        }
+    }
+}
+
+/// Is this a `Z`/`z` packet for a breakpoint type we support?
+fn kind_supported(args: &[u8]) -> bool {
+    args.starts_with(b"0,") || args.starts_with(b"1,")
+}
+
+/// Find the register with the given GDB register number
+fn register_mut(regs: &mut Registers, n: u32) -> Option<&mut u32> {
+    match n {
+        0..=12 => Some(&mut regs.r[n as usize]),
+        13 => Some(&mut regs.sp),
+        14 => Some(&mut regs.lr),
+        15 => Some(&mut regs.pc),
+        REGNUM_CPSR => Some(&mut regs.cpsr),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::vec::Vec;
+
+    /// A connection that replays bytes from GDB and records what we send
+    #[derive(Default)]
+    struct FakeConnection {
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+    }
+
+    impl Connection for FakeConnection {
+        type Error = ();
+
+        fn read_byte(&mut self) -> Result<u8, ()> {
+            self.input.pop_front().ok_or(())
+        }
+
+        fn write_byte(&mut self, byte: u8) -> Result<(), ()> {
+            self.output.push(byte);
+            Ok(())
+        }
+    }
+
+    fn stub(input: &[u8]) -> GdbStub<FakeConnection> {
+        crate::sim::reset();
+        GdbStub::new(FakeConnection {
+            input: input.iter().copied().collect(),
+            output: Vec::new(),
+        })
+    }
+
+    /// Target memory starting at [`FakeMemory::BASE`]
+    struct FakeMemory {
+        bytes: [u8; 16],
+        synced: Option<(u32, usize)>,
+    }
+
+    impl FakeMemory {
+        const BASE: u32 = 0x8000;
+
+        fn new() -> FakeMemory {
+            let mut bytes = [0u8; 16];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = i as u8;
+            }
+            FakeMemory {
+                bytes,
+                synced: None,
+            }
+        }
+
+        fn offset(&self, addr: u32) -> usize {
+            (addr - Self::BASE) as usize
+        }
+    }
+
+    impl Memory for FakeMemory {
+        fn read_u8(&self, addr: u32) -> u8 {
+            self.bytes[self.offset(addr)]
+        }
+
+        fn read_u16(&self, addr: u32) -> u16 {
+            let o = self.offset(addr);
+            u16::from_ne_bytes([self.bytes[o], self.bytes[o + 1]])
+        }
+
+        fn read_u32(&self, addr: u32) -> u32 {
+            let o = self.offset(addr);
+            u32::from_ne_bytes(self.bytes[o..o + 4].try_into().unwrap())
+        }
+    }
+
+    impl MemoryMut for FakeMemory {
+        fn write_u8(&mut self, addr: u32, value: u8) {
+            let o = self.offset(addr);
+            self.bytes[o] = value;
+        }
+
+        fn sync_caches(&mut self, addr: u32, len: usize) {
+            self.synced = Some((addr, len));
+        }
+    }
+
+    fn breakpoints() -> Breakpoints {
+        Breakpoints {
+            sw: [None; MAX_SW_BREAKPOINTS],
+            hw: [None; 16],
+            num_hw: 0,
+            step: None,
+        }
+    }
+
+    fn regs(pc: u32) -> Registers {
+        Registers {
+            r: [0; 13],
+            sp: 0,
+            lr: 0,
+            pc,
+            cpsr: 0x1F,
+        }
+    }
+
+    /// Handle one request, giving the action and the response
+    fn handle(
+        mem: &mut FakeMemory,
+        breakpoints: &mut Breakpoints,
+        regs: &mut Registers,
+        request: &[u8],
+    ) -> (Action, Vec<u8>) {
+        let mut buffer = [0u8; PACKET_SIZE];
+        let mut r = Response {
+            buffer: &mut buffer,
+            len: 0,
+        };
+        let action = unsafe { handle_packet(mem, request, &mut r, breakpoints, regs, SIGTRAP) };
+        let len = r.len;
+        (action, buffer[..len].to_vec())
+    }
+
+    #[test]
+    fn packet_framing() {
+        // noise and an ack before the packet, then a bad checksum, then a
+        // good one
+        let mut stub = stub(b"+\x03$g#00$g#67");
+        let len = stub.read_packet().unwrap();
+        assert_eq!(&stub.request[..len], b"g");
+        assert_eq!(stub.conn.output, b"-+");
+        assert!(stub.conn.input.is_empty());
+    }
+
+    #[test]
+    fn oversized_packet_is_rejected() {
+        let mut input = Vec::from(*b"$");
+        input.resize(PACKET_SIZE + 2, b'0');
+        // the sum of (PACKET_SIZE + 1) '0' characters
+        let checksum = (PACKET_SIZE as u32 + 1).wrapping_mul(u32::from(b'0')) as u8;
+        input.push(b'#');
+        input.push(HEX_DIGITS[usize::from(checksum >> 4)]);
+        input.push(HEX_DIGITS[usize::from(checksum & 0xF)]);
+        let mut stub = stub(&input);
+        assert_eq!(stub.read_packet(), Err(()));
+        assert_eq!(stub.conn.output, b"-");
+    }
+
+    #[test]
+    fn packet_sending() {
+        // GDB asks for a retransmit once
+        let mut stub = stub(b"-+");
+        stub.response[..2].copy_from_slice(b"OK");
+        stub.send_packet(2).unwrap();
+        assert_eq!(stub.conn.output, b"$OK#9a$OK#9a");
+    }
+
+    #[test]
+    fn read_registers() {
+        let mut regs = regs(0x8000);
+        regs.sp = 0x2000_0000;
+        regs.r[0] = 0x1234_5678;
+        let (action, response) =
+            handle(&mut FakeMemory::new(), &mut breakpoints(), &mut regs, b"g");
+        assert!(matches!(action, Action::Reply));
+        assert_eq!(response.len(), 17 * 8);
+        assert_eq!(&response[0..8], b"78563412");
+        assert_eq!(&response[13 * 8..14 * 8], b"00000020");
+        assert_eq!(&response[15 * 8..16 * 8], b"00800000");
+        assert_eq!(&response[16 * 8..], b"1f000000");
+    }
+
+    #[test]
+    fn read_and_write_memory() {
+        let mut mem = FakeMemory::new();
+        let mut bps = breakpoints();
+        let mut regs = regs(FakeMemory::BASE);
+        let (_, response) = handle(&mut mem, &mut bps, &mut regs, b"m8002,3");
+        assert_eq!(response, b"020304");
+        let (_, response) = handle(&mut mem, &mut bps, &mut regs, b"M8004,2:aabb");
+        assert_eq!(response, b"OK");
+        assert_eq!(mem.bytes[4..6], [0xAA, 0xBB]);
+        assert_eq!(mem.synced, Some((0x8004, 2)));
+    }
+
+    #[test]
+    fn software_breakpoints() {
+        let mut mem = FakeMemory::new();
+        let mut bps = breakpoints();
+        let mut regs = regs(FakeMemory::BASE);
+        let (_, response) = handle(&mut mem, &mut bps, &mut regs, b"Z0,8004,4");
+        assert_eq!(response, b"OK");
+        assert_eq!(mem.bytes[4..8], ARM_BKPT.to_ne_bytes());
+        assert_eq!(mem.synced, Some((0x8004, 4)));
+        assert!(bps.is_ours(0x8004));
+
+        let (_, response) = handle(&mut mem, &mut bps, &mut regs, b"Z0,8008,2");
+        assert_eq!(response, b"OK");
+        assert_eq!(mem.bytes[8..10], THUMB_BKPT.to_ne_bytes());
+
+        let (_, response) = handle(&mut mem, &mut bps, &mut regs, b"z0,8004,4");
+        assert_eq!(response, b"OK");
+        assert_eq!(mem.bytes[4..8], [4, 5, 6, 7]);
+        assert!(!bps.is_ours(0x8004));
+
+        // detaching takes out the rest
+        let (action, _) = handle(&mut mem, &mut bps, &mut regs, b"D");
+        assert!(matches!(action, Action::Detach));
+        assert_eq!(mem.bytes, FakeMemory::new().bytes);
+    }
+
+    #[test]
+    fn debug_fault_status() {
+        // Short-descriptor: FS is split between bit 10 and bits 3:0
+        assert!(is_debug_fault(0b0_0010));
+        assert!(!is_debug_fault(1 << 10 | 0b0_0010));
+        assert!(!is_debug_fault(0b0_0101));
+        // Long-descriptor: STATUS is bits 5:0
+        assert!(is_debug_fault(1 << 9 | 0b10_0010));
+        assert!(!is_debug_fault(1 << 9 | 0b00_0010));
+        assert!(!is_debug_fault(1 << 9 | 0b00_0101));
+    }
+
+    #[test]
+    fn hex_parsing() {
+        assert_eq!(parse_hex(b"1f"), Some(0x1F));
+        assert_eq!(parse_hex(b"DEADBEEF"), Some(0xDEAD_BEEF));
+        assert_eq!(parse_hex(b""), None);
+        assert_eq!(parse_hex(b"123456789"), None);
+        assert_eq!(parse_addr_len(b"8000,4"), Some((0x8000, 4)));
+        assert_eq!(
+            parse_reg(b"78563412"),
+            Some(u32::from_ne_bytes([0x78, 0x56, 0x34, 0x12]))
+        );
+    }
+}
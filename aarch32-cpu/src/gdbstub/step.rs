@@ -0,0 +1,515 @@
+//! Works out where the processor will go next, so we can single-step in software
+//!
+//! Armv7 processors ignore instruction address mismatch breakpoints at PL1
+//! when in Monitor debug-mode, so the only way to single-step privileged code
+//! is to decode the instruction at the PC and plant a temporary breakpoint
+//! wherever it will go to next.
+
+use super::Registers;
+
+/// Something we can read instructions and data from
+pub(crate) trait Memory {
+    /// Read one byte
+    fn read_u8(&self, addr: u32) -> u8;
+
+    /// Read a 16-bit value in target byte order
+    fn read_u16(&self, addr: u32) -> u16;
+
+    /// Read a 32-bit value in target byte order
+    fn read_u32(&self, addr: u32) -> u32;
+}
+
+/// A place the processor will execute next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Target {
+    /// The address of the instruction
+    pub addr: u32,
+    /// Is the instruction executed in Thumb state?
+    pub thumb: bool,
+}
+
+impl Target {
+    fn arm(addr: u32) -> Target {
+        Target {
+            addr: addr & !3,
+            thumb: false,
+        }
+    }
+
+    fn thumb(addr: u32) -> Target {
+        Target {
+            addr: addr & !1,
+            thumb: true,
+        }
+    }
+
+    /// A branch to `addr` which switches state based on bit 0
+    fn interworking(addr: u32) -> Target {
+        if addr & 1 != 0 {
+            Target::thumb(addr)
+        } else {
+            Target::arm(addr)
+        }
+    }
+}
+
+/// The CPSR T bit
+const CPSR_T: u32 = 1 << 5;
+
+/// Work out where the instruction at the PC will go to next.
+///
+/// Condition codes (and the IT state) are evaluated against the current
+/// flags, so there is only ever one answer.
+pub(crate) fn next_pc(regs: &Registers, mem: &impl Memory) -> Target {
+    if regs.cpsr & CPSR_T != 0 {
+        next_pc_thumb(regs, mem)
+    } else {
+        next_pc_arm(regs, mem)
+    }
+}
+
+/// Read a register, as seen by an instruction at the PC
+///
+/// In Arm state, the PC reads as the instruction address plus 8. In Thumb
+/// state, it reads as the instruction address plus 4.
+fn reg(regs: &Registers, n: u32) -> u32 {
+    match n {
+        0..=12 => regs.r[n as usize],
+        13 => regs.sp,
+        14 => regs.lr,
+        _ if regs.cpsr & CPSR_T != 0 => regs.pc.wrapping_add(4),
+        _ => regs.pc.wrapping_add(8),
+    }
+}
+
+/// Sign-extend the bottom `bits` bits of `value`
+fn sign_extend(value: u32, bits: u32) -> u32 {
+    let shift = 32 - bits;
+    (((value << shift) as i32) >> shift) as u32
+}
+
+/// Does the given condition code pass, given the flags in the CPSR?
+fn condition_passed(cond: u32, cpsr: u32) -> bool {
+    let n = cpsr & (1 << 31) != 0;
+    let z = cpsr & (1 << 30) != 0;
+    let c = cpsr & (1 << 29) != 0;
+    let v = cpsr & (1 << 28) != 0;
+    let result = match cond >> 1 {
+        0b000 => z,
+        0b001 => c,
+        0b010 => n,
+        0b011 => v,
+        0b100 => c && !z,
+        0b101 => n == v,
+        0b110 => !z && (n == v),
+        _ => true,
+    };
+    if cond & 1 != 0 && cond != 0b1111 {
+        !result
+    } else {
+        result
+    }
+}
+
+/// Apply an immediate shift, as used by data-processing and load/store instructions
+fn shift_imm(value: u32, shift_type: u32, amount: u32, cpsr: u32) -> u32 {
+    match (shift_type, amount) {
+        (0b00, n) => value << n,
+        (0b01, 0) => 0,
+        (0b01, n) => value >> n,
+        (0b10, 0) => ((value as i32) >> 31) as u32,
+        (0b10, n) => ((value as i32) >> n) as u32,
+        (_, 0) => {
+            // RRX
+            let carry = (cpsr >> 29) & 1;
+            (carry << 31) | (value >> 1)
+        }
+        (_, n) => value.rotate_right(n),
+    }
+}
+
+/// Calculate the address used by an LDM/POP instruction to load the PC
+///
+/// The PC is always the highest numbered register, so it is loaded from the
+/// highest address.
+fn ldm_pc_address(base: u32, count: u32, increment: bool, before: bool) -> u32 {
+    match (increment, before) {
+        (true, false) => base.wrapping_add(4 * (count - 1)),
+        (true, true) => base.wrapping_add(4 * count),
+        (false, false) => base,
+        (false, true) => base.wrapping_sub(4),
+    }
+}
+
+fn next_pc_arm(regs: &Registers, mem: &impl Memory) -> Target {
+    let insn = mem.read_u32(regs.pc);
+    let next = Target::arm(regs.pc.wrapping_add(4));
+    let cond = insn >> 28;
+
+    if cond == 0b1111 {
+        // Unconditional instruction space - we only care about BLX (immediate)
+        if insn & 0x0E00_0000 == 0x0A00_0000 {
+            let offset = sign_extend((insn & 0x00FF_FFFF) << 2, 26) | ((insn >> 23) & 0b10);
+            return Target::thumb(reg(regs, 15).wrapping_add(offset));
+        }
+        return next;
+    }
+
+    if !condition_passed(cond, regs.cpsr) {
+        return next;
+    }
+
+    let rn = (insn >> 16) & 0xF;
+    let rd = (insn >> 12) & 0xF;
+
+    let target = if insn & 0x0E00_0000 == 0x0A00_0000 {
+        // B, BL
+        let offset = sign_extend((insn & 0x00FF_FFFF) << 2, 26);
+        Some(Target::arm(reg(regs, 15).wrapping_add(offset)))
+    } else if insn & 0x0FFF_FFD0 == 0x012F_FF10 {
+        // BX, BLX (register)
+        Some(Target::interworking(reg(regs, insn & 0xF)))
+    } else if insn & 0x0E10_8000 == 0x0810_8000 {
+        // LDM with the PC in the register list
+        let count = (insn & 0xFFFF).count_ones();
+        let increment = insn & (1 << 23) != 0;
+        let before = insn & (1 << 24) != 0;
+        let addr = ldm_pc_address(reg(regs, rn), count, increment, before);
+        Some(Target::interworking(mem.read_u32(addr)))
+    } else if insn & 0x0C50_0000 == 0x0410_0000 && rd == 15 {
+        // LDR to the PC
+        let register_offset = insn & (1 << 25) != 0;
+        if register_offset && insn & (1 << 4) != 0 {
+            // Media instructions live here
+            None
+        } else {
+            let offset = if register_offset {
+                let amount = (insn >> 7) & 0x1F;
+                let shift_type = (insn >> 5) & 0b11;
+                shift_imm(reg(regs, insn & 0xF), shift_type, amount, regs.cpsr)
+            } else {
+                insn & 0xFFF
+            };
+            let base = reg(regs, rn);
+            let addr = if insn & (1 << 24) == 0 {
+                // post-indexed
+                base
+            } else if insn & (1 << 23) != 0 {
+                base.wrapping_add(offset)
+            } else {
+                base.wrapping_sub(offset)
+            };
+            Some(Target::interworking(mem.read_u32(addr)))
+        }
+    } else if insn & 0x0C00_0000 == 0 && rd == 15 && insn & (1 << 20) == 0 {
+        // MOV, ADD, or SUB, writing to the PC (without setting flags)
+        let immediate = insn & (1 << 25) != 0;
+        if !immediate && insn & (1 << 4) != 0 {
+            // register-shifted register, multiplies, and extra load/stores
+            None
+        } else {
+            let operand = if immediate {
+                (insn & 0xFF).rotate_right(2 * ((insn >> 8) & 0xF))
+            } else {
+                let amount = (insn >> 7) & 0x1F;
+                let shift_type = (insn >> 5) & 0b11;
+                shift_imm(reg(regs, insn & 0xF), shift_type, amount, regs.cpsr)
+            };
+            match (insn >> 21) & 0xF {
+                0b1101 => Some(Target::interworking(operand)),
+                0b0100 => Some(Target::interworking(reg(regs, rn).wrapping_add(operand))),
+                0b0010 => Some(Target::interworking(reg(regs, rn).wrapping_sub(operand))),
+                _ => None,
+            }
+        }
+    } else {
+        None
+    };
+
+    target.unwrap_or(next)
+}
+
+fn next_pc_thumb(regs: &Registers, mem: &impl Memory) -> Target {
+    let hw1 = u32::from(mem.read_u16(regs.pc));
+    let is_32bit = (hw1 >> 11) >= 0b11101;
+    let next = Target::thumb(regs.pc.wrapping_add(if is_32bit { 4 } else { 2 }));
+
+    // ITSTATE is split across CPSR[26:25] and CPSR[15:10]
+    let it_state = ((regs.cpsr >> 25) & 0b11) | (((regs.cpsr >> 10) & 0x3F) << 2);
+    if it_state & 0xF != 0 && !condition_passed(it_state >> 4, regs.cpsr) {
+        return next;
+    }
+
+    let pc = reg(regs, 15);
+
+    let target = if !is_32bit {
+        if hw1 & 0xF000 == 0xD000 {
+            // B<c> (T1) - 0b1110 is UDF and 0b1111 is SVC
+            let cond = (hw1 >> 8) & 0xF;
+            if cond < 0b1110 && condition_passed(cond, regs.cpsr) {
+                let offset = sign_extend((hw1 & 0xFF) << 1, 9);
+                Some(Target::thumb(pc.wrapping_add(offset)))
+            } else {
+                None
+            }
+        } else if hw1 & 0xF800 == 0xE000 {
+            // B (T2)
+            let offset = sign_extend((hw1 & 0x7FF) << 1, 12);
+            Some(Target::thumb(pc.wrapping_add(offset)))
+        } else if hw1 & 0xF500 == 0xB100 {
+            // CBZ, CBNZ
+            let nonzero = hw1 & (1 << 11) != 0;
+            let offset = ((hw1 >> 3) & 0x40) | ((hw1 >> 2) & 0x3E);
+            let value = reg(regs, hw1 & 0b111);
+            if (value != 0) == nonzero {
+                Some(Target::thumb(pc.wrapping_add(offset)))
+            } else {
+                None
+            }
+        } else if hw1 & 0xFF00 == 0x4700 {
+            // BX, BLX (register)
+            Some(Target::interworking(reg(regs, (hw1 >> 3) & 0xF)))
+        } else if hw1 & 0xFF00 == 0xBD00 {
+            // POP, including the PC
+            let count = (hw1 & 0xFF).count_ones() + 1;
+            let addr = ldm_pc_address(regs.sp, count, true, false);
+            Some(Target::interworking(mem.read_u32(addr)))
+        } else if hw1 & 0xFF87 == 0x4687 {
+            // MOV PC, Rm
+            Some(Target::thumb(reg(regs, (hw1 >> 3) & 0xF)))
+        } else if hw1 & 0xFF87 == 0x4487 {
+            // ADD PC, Rm
+            Some(Target::thumb(pc.wrapping_add(reg(regs, (hw1 >> 3) & 0xF))))
+        } else {
+            None
+        }
+    } else {
+        let hw2 = u32::from(mem.read_u16(regs.pc.wrapping_add(2)));
+        let rn = hw1 & 0xF;
+        if hw1 & 0xF800 == 0xF000 && hw2 & 0x8000 != 0 {
+            // Branches and miscellaneous control
+            let s = (hw1 >> 10) & 1;
+            let j1 = (hw2 >> 13) & 1;
+            let j2 = (hw2 >> 11) & 1;
+            let imm11 = hw2 & 0x7FF;
+            match hw2 & 0x5000 {
+                0x0000 => {
+                    // B<c> (T3) - conditions 0b111x are other instructions
+                    let cond = (hw1 >> 6) & 0xF;
+                    if cond < 0b1110 && condition_passed(cond, regs.cpsr) {
+                        let imm6 = hw1 & 0x3F;
+                        let offset = sign_extend(
+                            (s << 20) | (j2 << 19) | (j1 << 18) | (imm6 << 12) | (imm11 << 1),
+                            21,
+                        );
+                        Some(Target::thumb(pc.wrapping_add(offset)))
+                    } else {
+                        None
+                    }
+                }
+                op => {
+                    // B (T4), BL, BLX (immediate)
+                    let i1 = !(j1 ^ s) & 1;
+                    let i2 = !(j2 ^ s) & 1;
+                    let imm10 = hw1 & 0x3FF;
+                    let offset = sign_extend(
+                        (s << 24) | (i1 << 23) | (i2 << 22) | (imm10 << 12) | (imm11 << 1),
+                        25,
+                    );
+                    if op == 0x4000 {
+                        Some(Target::arm((pc & !3).wrapping_add(offset)))
+                    } else {
+                        Some(Target::thumb(pc.wrapping_add(offset)))
+                    }
+                }
+            }
+        } else if (hw1 & 0xFFD0 == 0xE890 || hw1 & 0xFFD0 == 0xE910) && hw2 & 0x8000 != 0 {
+            // LDM.W, LDMDB including the PC
+            let count = hw2.count_ones();
+            let increment = hw1 & 0xFFD0 == 0xE890;
+            let addr = ldm_pc_address(reg(regs, rn), count, increment, !increment);
+            Some(Target::interworking(mem.read_u32(addr)))
+        } else if hw1 & 0xFF70 == 0xF850 && rn == 15 && hw2 >> 12 == 15 {
+            // LDR PC, [PC, #imm] (literal)
+            let base = pc & !3;
+            let imm12 = hw2 & 0xFFF;
+            let addr = if hw1 & (1 << 7) != 0 {
+                base.wrapping_add(imm12)
+            } else {
+                base.wrapping_sub(imm12)
+            };
+            Some(Target::interworking(mem.read_u32(addr)))
+        } else if hw1 & 0xFFF0 == 0xF8D0 && hw2 >> 12 == 15 {
+            // LDR.W PC, [Rn, #imm12] (T3)
+            let addr = reg(regs, rn).wrapping_add(hw2 & 0xFFF);
+            Some(Target::interworking(mem.read_u32(addr)))
+        } else if hw1 & 0xFFF0 == 0xF850 && hw2 >> 12 == 15 {
+            let base = reg(regs, rn);
+            if hw2 & 0x0800 != 0 {
+                // LDR PC, [Rn, #+/-imm8] with pre/post-indexing (T4)
+                let imm8 = hw2 & 0xFF;
+                let addr = if hw2 & (1 << 10) == 0 {
+                    base
+                } else if hw2 & (1 << 9) != 0 {
+                    base.wrapping_add(imm8)
+                } else {
+                    base.wrapping_sub(imm8)
+                };
+                Some(Target::interworking(mem.read_u32(addr)))
+            } else if hw2 & 0x0FC0 == 0 {
+                // LDR PC, [Rn, Rm, LSL #imm2] (T2)
+                let offset = reg(regs, hw2 & 0xF) << ((hw2 >> 4) & 0b11);
+                let addr = base.wrapping_add(offset);
+                Some(Target::interworking(mem.read_u32(addr)))
+            } else {
+                None
+            }
+        } else if hw1 & 0xFFF0 == 0xE8D0 && hw2 & 0xFFE0 == 0xF000 {
+            // TBB, TBH
+            let base = reg(regs, rn);
+            let index = reg(regs, hw2 & 0xF);
+            let halfwords = if hw2 & (1 << 4) != 0 {
+                u32::from(mem.read_u16(base.wrapping_add(index << 1)))
+            } else {
+                u32::from(mem.read_u8(base.wrapping_add(index)))
+            };
+            Some(Target::thumb(pc.wrapping_add(halfwords << 1)))
+        } else {
+            None
+        }
+    };
+
+    target.unwrap_or(next)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A little-endian test image, loaded at address 0x1000
+    struct TestMemory<'a>(&'a [u8]);
+
+    impl TestMemory<'_> {
+        const BASE: u32 = 0x1000;
+
+        fn offset(&self, addr: u32) -> usize {
+            (addr - Self::BASE) as usize
+        }
+    }
+
+    impl Memory for TestMemory<'_> {
+        fn read_u8(&self, addr: u32) -> u8 {
+            self.0[self.offset(addr)]
+        }
+
+        fn read_u16(&self, addr: u32) -> u16 {
+            let o = self.offset(addr);
+            u16::from_le_bytes([self.0[o], self.0[o + 1]])
+        }
+
+        fn read_u32(&self, addr: u32) -> u32 {
+            let o = self.offset(addr);
+            u32::from_le_bytes([self.0[o], self.0[o + 1], self.0[o + 2], self.0[o + 3]])
+        }
+    }
+
+    fn regs(pc: u32, cpsr: u32) -> Registers {
+        Registers {
+            r: [0; 13],
+            sp: 0,
+            lr: 0,
+            pc,
+            cpsr,
+        }
+    }
+
+    const ARM_SYS: u32 = 0x1F;
+    const THUMB_SYS: u32 = 0x1F | CPSR_T;
+    const Z_FLAG: u32 = 1 << 30;
+
+    #[test]
+    fn arm_branch() {
+        // b . + 0x10
+        let image = 0xEA00_0002u32.to_le_bytes();
+        let result = next_pc(&regs(0x1000, ARM_SYS), &TestMemory(&image));
+        assert_eq!(result, Target::arm(0x1010));
+    }
+
+    #[test]
+    fn arm_conditional_branch() {
+        // beq .
+        let image = 0x0AFF_FFFEu32.to_le_bytes();
+        let taken = next_pc(&regs(0x1000, ARM_SYS | Z_FLAG), &TestMemory(&image));
+        assert_eq!(taken, Target::arm(0x1000));
+        let not_taken = next_pc(&regs(0x1000, ARM_SYS), &TestMemory(&image));
+        assert_eq!(not_taken, Target::arm(0x1004));
+    }
+
+    #[test]
+    fn arm_bx_lr() {
+        // bx lr
+        let image = 0xE12F_FF1Eu32.to_le_bytes();
+        let mut r = regs(0x1000, ARM_SYS);
+        r.lr = 0x2001;
+        let result = next_pc(&r, &TestMemory(&image));
+        assert_eq!(result, Target::thumb(0x2000));
+    }
+
+    #[test]
+    fn arm_pop_pc() {
+        // pop {r4, pc} ; followed by the stacked values
+        let mut image = [0u8; 12];
+        image[0..4].copy_from_slice(&0xE8BD_8010u32.to_le_bytes());
+        image[4..8].copy_from_slice(&0x1111_1111u32.to_le_bytes());
+        image[8..12].copy_from_slice(&0x0000_3000u32.to_le_bytes());
+        let mut r = regs(0x1000, ARM_SYS);
+        r.sp = 0x1004;
+        let result = next_pc(&r, &TestMemory(&image));
+        assert_eq!(result, Target::arm(0x3000));
+    }
+
+    #[test]
+    fn thumb_bl() {
+        // bl . + 0x100
+        let mut image = [0u8; 4];
+        image[0..2].copy_from_slice(&0xF000u16.to_le_bytes());
+        image[2..4].copy_from_slice(&0xF87Eu16.to_le_bytes());
+        let result = next_pc(&regs(0x1000, THUMB_SYS), &TestMemory(&image));
+        assert_eq!(result, Target::thumb(0x1100));
+    }
+
+    #[test]
+    fn thumb_cbz() {
+        // cbz r0, . + 0x14
+        let image = 0xB140u16.to_le_bytes();
+        let taken = next_pc(&regs(0x1000, THUMB_SYS), &TestMemory(&image));
+        assert_eq!(taken, Target::thumb(0x1014));
+        let mut r = regs(0x1000, THUMB_SYS);
+        r.r[0] = 1;
+        let not_taken = next_pc(&r, &TestMemory(&image));
+        assert_eq!(not_taken, Target::thumb(0x1002));
+    }
+
+    #[test]
+    fn thumb_it_block_skipped() {
+        // bx lr, inside an ITE EQ block with Z clear, so it is skipped
+        let image = 0x4770u16.to_le_bytes();
+        // ITSTATE = 0b0000_1000 (EQ, last instruction)
+        let cpsr = THUMB_SYS | (0b10 << 10);
+        let result = next_pc(&regs(0x1000, cpsr), &TestMemory(&image));
+        assert_eq!(result, Target::thumb(0x1002));
+    }
+
+    #[test]
+    fn thumb_tbb() {
+        // tbb [pc, r0] ; followed by the table
+        let mut image = [0u8; 8];
+        image[0..2].copy_from_slice(&0xE8DFu16.to_le_bytes());
+        image[2..4].copy_from_slice(&0xF000u16.to_le_bytes());
+        image[4..8].copy_from_slice(&[2, 3, 4, 5]);
+        let mut r = regs(0x1000, THUMB_SYS);
+        r.r[0] = 2;
+        let result = next_pc(&r, &TestMemory(&image));
+        assert_eq!(result, Target::thumb(0x100C));
+    }
+}
@@ -123,6 +123,127 @@ pub fn read_virtual_timer() -> u64 {
     crate::register::CntVct::read().0
 }
 
+/// Implements [`GenericTimer`] for an enum by calling the timer it holds
+macro_rules! dispatch_timer {
+    ($name:ident { $($variant:ident),* }) => {
+        impl GenericTimer for $name {
+            fn frequency_hz(&self) -> u32 {
+                match self { $($name::$variant(t) => t.frequency_hz()),* }
+            }
+
+            fn counter(&self) -> u64 {
+                match self { $($name::$variant(t) => t.counter()),* }
+            }
+
+            fn counter_compare(&self) -> u64 {
+                match self { $($name::$variant(t) => t.counter_compare()),* }
+            }
+
+            fn counter_compare_set(&mut self, value: u64) {
+                match self { $($name::$variant(t) => t.counter_compare_set(value)),* }
+            }
+
+            fn countdown(&self) -> u32 {
+                match self { $($name::$variant(t) => t.countdown()),* }
+            }
+
+            fn countdown_set(&mut self, duration_ticks: u32) {
+                match self { $($name::$variant(t) => t.countdown_set(duration_ticks)),* }
+            }
+
+            fn enabled(&self) -> bool {
+                match self { $($name::$variant(t) => t.enabled()),* }
+            }
+
+            fn enable(&self, enabled: bool) {
+                match self { $($name::$variant(t) => t.enable(enabled)),* }
+            }
+
+            fn interrupt_masked(&self) -> bool {
+                match self { $($name::$variant(t) => t.interrupt_masked()),* }
+            }
+
+            fn interrupt_mask(&mut self, mask: bool) {
+                match self { $($name::$variant(t) => t.interrupt_mask(mask)),* }
+            }
+
+            fn interrupt_status(&self) -> bool {
+                match self { $($name::$variant(t) => t.interrupt_status()),* }
+            }
+        }
+    };
+}
+
+/// A Physical Timer handle for whichever Exception Level we are running at
+///
+/// See [`best_physical_timer`].
+pub enum PhysicalTimer {
+    /// We are running at EL0
+    El0(El0PhysicalTimer),
+    /// We are running at EL1 (or EL3)
+    El1(El1PhysicalTimer),
+    /// We are running at EL2
+    El2(El2PhysicalTimer),
+}
+
+dispatch_timer!(PhysicalTimer { El0, El1, El2 });
+
+/// A Virtual Timer handle for whichever Exception Level we are running at
+///
+/// See [`best_virtual_timer`].
+pub enum VirtualTimer {
+    /// We are running at EL0
+    El0(El0VirtualTimer),
+    /// We are running at EL1 (or EL3)
+    El1(El1VirtualTimer),
+    /// We are running at EL2
+    El2(El2VirtualTimer),
+}
+
+dispatch_timer!(VirtualTimer { El0, El1, El2 });
+
+/// Create a Physical Timer handle suitable for the current Exception Level
+///
+/// Uses [`el::current`](crate::el::current) to pick between the EL0, EL1 and
+/// EL2 handles. Monitor mode gets the EL1 handle.
+///
+/// # Safety
+///
+/// Only create one Physical Timer handle (at any EL) at any given time, as
+/// they access shared mutable state within the processor and do
+/// read-modify-writes on that state.
+pub unsafe fn best_physical_timer() -> PhysicalTimer {
+    use crate::el::ExceptionLevel;
+    match crate::el::current().0 {
+        ExceptionLevel::El0 => PhysicalTimer::El0(unsafe { El0PhysicalTimer::new() }),
+        ExceptionLevel::El1 | ExceptionLevel::El3 => {
+            PhysicalTimer::El1(unsafe { El1PhysicalTimer::new() })
+        }
+        ExceptionLevel::El2 => PhysicalTimer::El2(unsafe { El2PhysicalTimer::new() }),
+    }
+}
+
+/// Create a Virtual Timer handle suitable for the current Exception Level
+///
+/// Uses [`el::current`](crate::el::current) to pick between the EL0, EL1 and
+/// EL2 handles. Monitor mode gets the EL1 handle.
+///
+/// # Safety
+///
+/// Only create one Virtual Timer handle (at any EL) at any given time, as
+/// they access shared mutable state within the processor and do
+/// read-modify-writes on that state.
+pub unsafe fn best_virtual_timer() -> VirtualTimer {
+    use crate::el::ExceptionLevel;
+    match crate::el::current().0 {
+        ExceptionLevel::El0 => VirtualTimer::El0(unsafe { El0VirtualTimer::new() }),
+        ExceptionLevel::El1 | ExceptionLevel::El3 => {
+            VirtualTimer::El1(unsafe { El1VirtualTimer::new() })
+        }
+        ExceptionLevel::El2 => VirtualTimer::El2(unsafe { El2VirtualTimer::new() }),
+    }
+}
+
 /// Describes the configuration for an Edvent
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EventConfig {
@@ -179,3 +300,62 @@ pub enum EventRate {
     /// Fire every 32768 ticks
     _32768 = 15,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::register::{CntPct, Cntfrq, CntpCtl, CntpCval, CntpTval};
+    use crate::sim::{self, Encoding};
+
+    /// Make the simulated physical timer count up, and fire at CNTP_CVAL
+    ///
+    /// Time only moves on when CNTP_CTL is read, by 100 ticks each time.
+    fn simulate_physical_timer() {
+        sim::reset();
+        sim::set(Encoding::of::<Cntfrq>(), 1_000_000);
+        sim::on_write(Encoding::of::<CntpTval>(), |regs, ticks| {
+            let now = regs.get(Encoding::of64::<CntPct>());
+            regs.set(Encoding::of64::<CntpCval>(), now + ticks);
+        });
+        sim::on_read(Encoding::of::<CntpCtl>(), |regs| {
+            let now = regs.get(Encoding::of64::<CntPct>()) + 100;
+            regs.set(Encoding::of64::<CntPct>(), now);
+            let ctl = regs.get(Encoding::of::<CntpCtl>()) & 0b011;
+            let fired = ctl & 1 != 0 && now >= regs.get(Encoding::of64::<CntpCval>());
+            ctl | u64::from(fired) << 2
+        });
+    }
+
+    #[test]
+    fn physical_timer_delay() {
+        simulate_physical_timer();
+        let mut timer = unsafe { best_physical_timer() };
+        // CPSR reads as zero on the host, which counts as EL1
+        assert!(matches!(timer, PhysicalTimer::El1(_)));
+        assert_eq!(timer.frequency_hz(), 1_000_000);
+        assert!(!timer.enabled());
+
+        timer.delay_us(1500);
+        // The countdown was set after three reads of CNTP_CTL
+        assert_eq!(sim::get(Encoding::of64::<CntpCval>()), 300 + 1500);
+        // It fired at 1800 ticks, then turning it off read CNTP_CTL once more
+        assert_eq!(read_physical_timer(), 1900);
+        assert!(!timer.enabled());
+        assert!(!timer.interrupt_status());
+    }
+
+    #[test]
+    fn physical_timer_interrupt() {
+        simulate_physical_timer();
+        let mut timer = unsafe { El1PhysicalTimer::new() };
+        timer.counter_compare_set(450);
+        // each of these reads CNTP_CTL, so they take us to 400 ticks
+        timer.interrupt_mask(true);
+        timer.enable(true);
+        assert!(timer.interrupt_masked());
+        assert!(!timer.interrupt_status());
+        // and now we're at 500
+        assert!(timer.interrupt_status());
+        assert_eq!(timer.counter_compare(), 450);
+    }
+}
@@ -0,0 +1,460 @@
+//! A driver for the GICv2 Generic Interrupt Controller
+//!
+//! Cortex-A processors like the Cortex-A7, A9 and A15 have a memory-mapped
+//! GICv2, made of a Distributor (GICD) shared by all the cores and a CPU
+//! interface (GICC) for each core. On those processors, the GIC is at a fixed
+//! offset from the private peripheral base address in CBAR, so
+//! [`Gicv2::from_cbar`] can find it for you. Otherwise, give
+//! [`Gicv2::new`] the addresses from your SoC's documentation.
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::gicv2::{Gicv2, Layout, Trigger};
+//! use aarch32_cpu::interrupt::InterruptController;
+//!
+//! let mut gic = unsafe { Gicv2::from_cbar(Layout::CortexA9) };
+//! gic.enable_distributor(true);
+//! gic.set_priority(TIMER_IRQ, 0x80);
+//! gic.set_targets(TIMER_IRQ, 0b1);
+//! gic.set_trigger(TIMER_IRQ, Trigger::Level);
+//! gic.set_enabled(TIMER_IRQ, true);
+//! gic.set_priority_mask(0xFF);
+//! gic.enable_cpu_interface(true);
+//!
+//! // then, in the IRQ handler
+//! gic.handle_pending(|id| handle(id));
+//! ```
+//!
+//! QEMU's `vexpress-a9` machine has a GICv2 with the [`Layout::CortexA9`]
+//! layout, and its `virt` machine has one at `0x0800_0000` (GICD) and
+//! `0x0801_0000` (GICC).
+
+use arbitrary_int::u4;
+
+use crate::gicv3::InterruptGroup;
+use crate::interrupt::InterruptController;
+use crate::register::ImpCbar;
+
+/// GICD_CTLR (*Distributor Control Register*)
+const GICD_CTLR: usize = 0x000;
+/// GICD_TYPER (*Interrupt Controller Type Register*)
+const GICD_TYPER: usize = 0x004;
+/// GICD_IGROUPRn (*Interrupt Group Registers*)
+const GICD_IGROUPR: usize = 0x080;
+/// GICD_ISENABLERn (*Interrupt Set-Enable Registers*)
+const GICD_ISENABLER: usize = 0x100;
+/// GICD_ICENABLERn (*Interrupt Clear-Enable Registers*)
+const GICD_ICENABLER: usize = 0x180;
+/// GICD_IPRIORITYRn (*Interrupt Priority Registers*)
+const GICD_IPRIORITYR: usize = 0x400;
+/// GICD_ITARGETSRn (*Interrupt Processor Targets Registers*)
+const GICD_ITARGETSR: usize = 0x800;
+/// GICD_ICFGRn (*Interrupt Configuration Registers*)
+const GICD_ICFGR: usize = 0xC00;
+/// GICD_SGIR (*Software Generated Interrupt Register*)
+const GICD_SGIR: usize = 0xF00;
+
+/// GICC_CTLR (*CPU Interface Control Register*)
+const GICC_CTLR: usize = 0x00;
+/// GICC_PMR (*Interrupt Priority Mask Register*)
+const GICC_PMR: usize = 0x04;
+/// GICC_IAR (*Interrupt Acknowledge Register*)
+const GICC_IAR: usize = 0x0C;
+/// GICC_EOIR (*End of Interrupt Register*)
+const GICC_EOIR: usize = 0x10;
+/// GICC_RPR (*Running Priority Register*)
+const GICC_RPR: usize = 0x14;
+/// GICC_HPPIR (*Highest Priority Pending Interrupt Register*)
+const GICC_HPPIR: usize = 0x18;
+
+/// The bits of GICC_IAR that hold the interrupt ID
+pub const INTID_MASK: u32 = 0x3FF;
+
+/// The first of the interrupt IDs that are never real interrupts
+///
+/// 1023 means there was no pending interrupt.
+pub const SPURIOUS_INTID_START: u32 = 1020;
+
+/// The priority GICC_RPR reports when no interrupt is active
+pub const IDLE_PRIORITY: u8 = 0xFF;
+
+/// Is this interrupt ID one of the spurious ones?
+///
+/// The source CPU bits that GICC_IAR gives for SGIs are ignored.
+pub const fn is_spurious(id: u32) -> bool {
+    (id & INTID_MASK) >= SPURIOUS_INTID_START
+}
+
+/// Where the GIC is, relative to the private peripheral base address
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Layout {
+    /// The Cortex-A5 and Cortex-A9, which put the CPU interface at `+0x0100`
+    /// and the Distributor at `+0x1000`
+    CortexA9,
+    /// The Cortex-A7 and Cortex-A15, which put the Distributor at `+0x1000`
+    /// and the CPU interface at `+0x2000`
+    CortexA15,
+}
+
+impl Layout {
+    /// The offset of the Distributor from the peripheral base address
+    pub const fn distributor_offset(self) -> usize {
+        0x1000
+    }
+
+    /// The offset of the CPU interface from the peripheral base address
+    pub const fn cpu_interface_offset(self) -> usize {
+        match self {
+            Layout::CortexA9 => 0x0100,
+            Layout::CortexA15 => 0x2000,
+        }
+    }
+
+    /// The bits of CBAR that hold the peripheral base address
+    ///
+    /// The Cortex-A9 aligns its private peripherals to 8 KiB, and the
+    /// Cortex-A15 to 32 KiB. The bits below that are used for other things.
+    pub const fn periphbase_mask(self) -> u32 {
+        match self {
+            Layout::CortexA9 => 0xFFFF_E000,
+            Layout::CortexA15 => 0xFFFF_8000,
+        }
+    }
+}
+
+/// How an interrupt is triggered
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trigger {
+    /// Pending while the interrupt line is asserted
+    Level,
+    /// Pending when the interrupt line goes from low to high
+    Edge,
+}
+
+/// Which CPUs a Software Generated Interrupt goes to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SgiTarget {
+    /// The CPUs whose bits are set in this mask
+    List(u8),
+    /// Every CPU except this one
+    AllOther,
+    /// Only this CPU
+    Current,
+}
+
+/// A GICv2 Distributor, and the CPU interface of the current core
+#[derive(Debug)]
+pub struct Gicv2 {
+    gicd: *mut u32,
+    gicc: *mut u32,
+}
+
+// Safety: the GIC's registers can be accessed from any core
+unsafe impl Send for Gicv2 {}
+
+impl Gicv2 {
+    /// Create a driver for the GIC at these addresses
+    ///
+    /// # Safety
+    ///
+    /// The addresses must be those of a GICv2 Distributor and CPU interface,
+    /// mapped as Device memory, and nothing else may use the GIC while this
+    /// driver exists.
+    pub const unsafe fn new(gicd: *mut u32, gicc: *mut u32) -> Gicv2 {
+        Gicv2 { gicd, gicc }
+    }
+
+    /// Create a driver for the GIC in a Cortex-A processor's private
+    /// peripherals
+    ///
+    /// # Safety
+    ///
+    /// This must be a processor with the given [`Layout`], and nothing else
+    /// may use the GIC while this driver exists.
+    pub unsafe fn from_cbar(layout: Layout) -> Gicv2 {
+        let base = (ImpCbar::read().0 & layout.periphbase_mask()) as usize;
+        // Safety: as per outer function
+        unsafe {
+            Gicv2::new(
+                (base + layout.distributor_offset()) as *mut u32,
+                (base + layout.cpu_interface_offset()) as *mut u32,
+            )
+        }
+    }
+
+    /// The number of interrupt IDs the Distributor supports, including SGIs
+    /// and PPIs
+    pub fn num_interrupts(&self) -> u32 {
+        let lines = self.read_gicd(GICD_TYPER) & 0x1F;
+        (32 * (lines + 1)).min(SPURIOUS_INTID_START)
+    }
+
+    /// Enable or disable the Distributor
+    ///
+    /// This only sets bit 0 of GICD_CTLR, which enables the interrupts of the
+    /// Security state we are running in. The Secure world's view of bit 1 is
+    /// left clear, as the Non-secure world enables its own interrupts.
+    pub fn enable_distributor(&mut self, enable: bool) {
+        self.write_gicd(GICD_CTLR, enable as u32);
+    }
+
+    /// Enable or disable an interrupt
+    pub fn set_enabled(&mut self, id: u32, enable: bool) {
+        let offset = if enable {
+            GICD_ISENABLER
+        } else {
+            GICD_ICENABLER
+        };
+        self.write_gicd(offset + word(id), bit(id));
+    }
+
+    /// Is this interrupt enabled?
+    pub fn is_enabled(&self, id: u32) -> bool {
+        self.read_gicd(GICD_ISENABLER + word(id)) & bit(id) != 0
+    }
+
+    /// Set the priority of an interrupt, where lower values are more urgent
+    pub fn set_priority(&mut self, id: u32, priority: u8) {
+        self.write_gicd_byte(GICD_IPRIORITYR + id as usize, priority);
+    }
+
+    /// Get the priority of an interrupt
+    pub fn priority(&self, id: u32) -> u8 {
+        self.read_gicd_byte(GICD_IPRIORITYR + id as usize)
+    }
+
+    /// Choose which CPUs a Shared Peripheral Interrupt is sent to
+    ///
+    /// Bit `n` of `cpus` selects CPU `n`. SGIs and PPIs always go to the core
+    /// they belong to, so this does nothing for them.
+    pub fn set_targets(&mut self, id: u32, cpus: u8) {
+        self.write_gicd_byte(GICD_ITARGETSR + id as usize, cpus);
+    }
+
+    /// Choose whether an interrupt is level-sensitive or edge-triggered
+    pub fn set_trigger(&mut self, id: u32, trigger: Trigger) {
+        let offset = GICD_ICFGR + 4 * (id as usize / 16);
+        let shift = 2 * (id % 16) + 1;
+        let value = self.read_gicd(offset) & !(1 << shift);
+        let edge = (trigger == Trigger::Edge) as u32;
+        self.write_gicd(offset, value | edge << shift);
+    }
+
+    /// Put an interrupt in a group
+    ///
+    /// Only the Secure world can do this, on a GIC with the Security
+    /// Extensions.
+    pub fn set_group(&mut self, id: u32, group: InterruptGroup) {
+        let offset = GICD_IGROUPR + word(id);
+        let value = self.read_gicd(offset) & !bit(id);
+        let group1 = if group == InterruptGroup::Group1 {
+            bit(id)
+        } else {
+            0
+        };
+        self.write_gicd(offset, value | group1);
+    }
+
+    /// Send a Software Generated Interrupt
+    pub fn send_sgi(&mut self, id: u4, target: SgiTarget) {
+        let (filter, cpus) = match target {
+            SgiTarget::List(cpus) => (0, cpus),
+            SgiTarget::AllOther => (1, 0),
+            SgiTarget::Current => (2, 0),
+        };
+        self.write_gicd(
+            GICD_SGIR,
+            filter << 24 | (cpus as u32) << 16 | id.value() as u32,
+        );
+    }
+
+    /// Enable or disable this core's CPU interface
+    ///
+    /// This only sets EnableGrp1, because the other bits of GICC_CTLR are
+    /// reserved in the Non-secure view.
+    pub fn enable_cpu_interface(&mut self, enable: bool) {
+        self.write_gicc(GICC_CTLR, if enable { 1 } else { 0 });
+    }
+
+    /// Only signal interrupts with a higher priority (a lower value) than this
+    pub fn set_priority_mask(&mut self, priority: u8) {
+        self.write_gicc(GICC_PMR, priority as u32);
+    }
+
+    /// Get the current priority mask
+    pub fn priority_mask(&self) -> u8 {
+        self.read_gicc(GICC_PMR) as u8
+    }
+
+    /// Get the priority of the highest priority active interrupt
+    ///
+    /// Gives `None` if no interrupt is active.
+    pub fn running_priority(&self) -> Option<u8> {
+        match self.read_gicc(GICC_RPR) as u8 {
+            IDLE_PRIORITY => None,
+            priority => Some(priority),
+        }
+    }
+
+    /// Acknowledge the highest priority pending interrupt
+    ///
+    /// Gives the interrupt ID, or `None` if the interrupt was spurious. For an
+    /// SGI, the ID also holds the number of the CPU that sent it in bits 10 to
+    /// 12; mask it with [`INTID_MASK`] to get the SGI number. Pass the whole
+    /// ID to [`Gicv2::end_of_interrupt`] when you are done with it.
+    pub fn acknowledge(&mut self) -> Option<u32> {
+        let id = self.read_gicc(GICC_IAR) & 0x1FFF;
+        (!is_spurious(id)).then_some(id)
+    }
+
+    /// Get the highest priority pending interrupt, without acknowledging it
+    pub fn highest_pending(&self) -> Option<u32> {
+        let id = self.read_gicc(GICC_HPPIR) & 0x1FFF;
+        (!is_spurious(id)).then_some(id)
+    }
+
+    /// Tell the GIC that we have finished with an interrupt we acknowledged
+    pub fn end_of_interrupt(&mut self, id: u32) {
+        self.write_gicc(GICC_EOIR, id);
+    }
+
+    fn read_gicd(&self, offset: usize) -> u32 {
+        // Safety: we were given the address of a Distributor
+        unsafe { self.gicd.byte_add(offset).read_volatile() }
+    }
+
+    fn write_gicd(&mut self, offset: usize, value: u32) {
+        // Safety: we were given the address of a Distributor
+        unsafe { self.gicd.byte_add(offset).write_volatile(value) }
+    }
+
+    fn read_gicd_byte(&self, offset: usize) -> u8 {
+        // Safety: we were given the address of a Distributor, whose priority
+        // and target registers can be accessed a byte at a time
+        unsafe { self.gicd.cast::<u8>().add(offset).read_volatile() }
+    }
+
+    fn write_gicd_byte(&mut self, offset: usize, value: u8) {
+        // Safety: we were given the address of a Distributor, whose priority
+        // and target registers can be accessed a byte at a time
+        unsafe { self.gicd.cast::<u8>().add(offset).write_volatile(value) }
+    }
+
+    fn read_gicc(&self, offset: usize) -> u32 {
+        // Safety: we were given the address of a CPU interface
+        unsafe { self.gicc.byte_add(offset).read_volatile() }
+    }
+
+    fn write_gicc(&mut self, offset: usize, value: u32) {
+        // Safety: we were given the address of a CPU interface
+        unsafe { self.gicc.byte_add(offset).write_volatile(value) }
+    }
+}
+
+impl InterruptController for Gicv2 {
+    fn acknowledge(&mut self) -> Option<u32> {
+        Gicv2::acknowledge(self)
+    }
+
+    fn end_of_interrupt(&mut self, id: u32) {
+        Gicv2::end_of_interrupt(self, id)
+    }
+}
+
+/// The offset of the 32-bit register holding this interrupt's bit
+const fn word(id: u32) -> usize {
+    4 * (id as usize / 32)
+}
+
+/// This interrupt's bit, in a 32-bit register
+const fn bit(id: u32) -> u32 {
+    1 << (id % 32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sim::{self, Encoding};
+
+    /// Fake Distributor and CPU interface registers
+    struct Fake {
+        gicd: [u32; 0x1000 / 4],
+        gicc: [u32; 0x100 / 4],
+    }
+
+    impl Fake {
+        fn new() -> Fake {
+            Fake {
+                gicd: [0; 0x1000 / 4],
+                gicc: [0; 0x100 / 4],
+            }
+        }
+
+        fn gic(&mut self) -> Gicv2 {
+            unsafe { Gicv2::new(self.gicd.as_mut_ptr(), self.gicc.as_mut_ptr()) }
+        }
+    }
+
+    #[test]
+    fn distributor() {
+        let mut fake = Fake::new();
+        fake.gicd[GICD_TYPER / 4] = 2;
+        let mut gic = fake.gic();
+        assert_eq!(gic.num_interrupts(), 96);
+        gic.enable_distributor(true);
+        gic.set_enabled(34, true);
+        gic.set_priority(34, 0xA0);
+        gic.set_targets(34, 0b10);
+        gic.set_trigger(34, Trigger::Edge);
+        gic.set_group(34, InterruptGroup::Group1);
+        gic.send_sgi(u4::new(5), SgiTarget::List(0b1100));
+        assert_eq!(gic.priority(34), 0xA0);
+
+        assert_eq!(fake.gicd[GICD_CTLR / 4], 1);
+        assert_eq!(fake.gicd[GICD_ISENABLER / 4 + 1], 1 << 2);
+        assert_eq!(fake.gicd[GICD_IPRIORITYR / 4 + 8], 0xA0 << 16);
+        assert_eq!(fake.gicd[GICD_ITARGETSR / 4 + 8], 0b10 << 16);
+        assert_eq!(fake.gicd[GICD_ICFGR / 4 + 2], 1 << 5);
+        assert_eq!(fake.gicd[GICD_IGROUPR / 4 + 1], 1 << 2);
+        assert_eq!(fake.gicd[GICD_SGIR / 4], 0b1100 << 16 | 5);
+    }
+
+    #[test]
+    fn cpu_interface() {
+        let mut fake = Fake::new();
+        // SGI 3, from CPU 1
+        fake.gicc[GICC_IAR / 4] = 1 << 10 | 3;
+        fake.gicc[GICC_RPR / 4] = 0xFF;
+        let mut gic = fake.gic();
+        gic.enable_cpu_interface(true);
+        assert_eq!(fake.gicc[GICC_CTLR / 4], 1);
+        gic.set_priority_mask(0xF0);
+        assert_eq!(gic.priority_mask(), 0xF0);
+        assert_eq!(gic.running_priority(), None);
+        let id = gic.acknowledge().unwrap();
+        assert_eq!(id & INTID_MASK, 3);
+        gic.end_of_interrupt(id);
+        assert_eq!(fake.gicc[GICC_EOIR / 4], 1 << 10 | 3);
+
+        fake.gicc[GICC_IAR / 4] = 1023;
+        let mut gic = fake.gic();
+        assert_eq!(gic.acknowledge(), None);
+        assert!(is_spurious(1022));
+        assert!(!is_spurious(1019));
+    }
+
+    #[test]
+    fn cbar() {
+        sim::reset();
+        // Like the Allwinner A20, which isn't aligned to 1 MiB
+        sim::set(Encoding::of::<ImpCbar>(), 0x01C8_0000);
+        let gic = unsafe { Gicv2::from_cbar(Layout::CortexA15) };
+        assert_eq!(gic.gicd as usize, 0x01C8_1000);
+        assert_eq!(gic.gicc as usize, 0x01C8_2000);
+        let gic = unsafe { Gicv2::from_cbar(Layout::CortexA9) };
+        assert_eq!(gic.gicd as usize, 0x01C8_1000);
+        assert_eq!(gic.gicc as usize, 0x01C8_0100);
+    }
+}
@@ -0,0 +1,297 @@
+//! A driver for the GICv3 CPU interface
+//!
+//! Each core talks to its GICv3 CPU interface through the ICC system
+//! registers, so this needs no MMIO and no extra dependencies. Setting up the
+//! Distributor and Redistributors is still up to you.
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::gicv3::{CpuInterface, InterruptGroup};
+//!
+//! CpuInterface::enable_system_registers();
+//! CpuInterface::set_priority_mask(0xFF);
+//! CpuInterface::enable_group(InterruptGroup::Group1, true);
+//!
+//! // then, in the IRQ handler
+//! while let Some(intid) = CpuInterface::acknowledge(InterruptGroup::Group1) {
+//!     handle(intid);
+//!     CpuInterface::end_of_interrupt(InterruptGroup::Group1, intid);
+//! }
+//! ```
+
+use arbitrary_int::{u24, u4};
+
+use crate::interrupt::InterruptController;
+use crate::register::{
+    IccAsgi1r, IccDir, IccEoir0, IccEoir1, IccHppir0, IccHppir1, IccIar0, IccIar1, IccIgrpen0,
+    IccIgrpen1, IccPmr, IccRpr, IccSgi0r, IccSgi1r, IccSre,
+};
+
+/// The first of the special INTIDs, which are never real interrupts
+///
+/// INTID 1023 means there was no pending interrupt.
+pub const SPECIAL_INTID_START: u32 = 1020;
+
+/// The priority ICC_RPR reports when no interrupt is active
+pub const IDLE_PRIORITY: u8 = 0xFF;
+
+/// The two groups a GICv3 interrupt can belong to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum InterruptGroup {
+    /// Group 0, usually signalled as FIQ
+    Group0,
+    /// Group 1, usually signalled as IRQ
+    Group1,
+}
+
+/// Which PEs a Software Generated Interrupt goes to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SgiTarget {
+    /// Every PE except this one
+    AllOther,
+    /// The PEs with these affinity values
+    List {
+        /// The Affinity 3 value of the target PEs
+        affinity3: u8,
+        /// The Affinity 2 value of the target PEs
+        affinity2: u8,
+        /// The Affinity 1 value of the target PEs
+        affinity1: u8,
+        /// Which block of 16 Affinity 0 values `target_list` covers, from
+        /// `range_selector * 16` to `range_selector * 16 + 15`
+        ///
+        /// Anything other than 0 needs ICC_CTLR.RSS to be set.
+        range_selector: u4,
+        /// One bit for each Affinity 0 value in the range to send to
+        target_list: u16,
+    },
+}
+
+/// The Security state an SGI is sent to
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SgiGroup {
+    /// Group 0
+    Group0,
+    /// Group 1, for the current Security state
+    Group1,
+    /// Group 1, for the other Security state
+    Group1Alias,
+}
+
+/// The GICv3 CPU interface of the current core
+#[derive(Debug)]
+pub struct CpuInterface;
+
+impl CpuInterface {
+    /// Use the system register interface
+    ///
+    /// Do this before any of the other functions. It also turns on IRQ and
+    /// FIQ bypass disable, so that interrupts only reach us through the GIC.
+    pub fn enable_system_registers() {
+        let sre = IccSre::read().with_sre(true).with_dib(true).with_dfb(true);
+        // Safety: we only select the system register interface, which this
+        // module needs
+        unsafe { IccSre::write(sre) }
+    }
+
+    /// Enable or disable signalling of interrupts in this group
+    pub fn enable_group(group: InterruptGroup, enable: bool) {
+        // Safety: this only masks or unmasks a group at the CPU interface
+        unsafe {
+            match group {
+                InterruptGroup::Group0 => {
+                    IccIgrpen0::write(IccIgrpen0::new_with_raw_value(0).with_enable(enable))
+                }
+                InterruptGroup::Group1 => {
+                    IccIgrpen1::write(IccIgrpen1::new_with_raw_value(0).with_enable(enable))
+                }
+            }
+        }
+    }
+
+    /// Only signal interrupts with a higher priority (a lower value) than this
+    pub fn set_priority_mask(priority: u8) {
+        // Safety: this only masks interrupts at the CPU interface
+        unsafe { IccPmr::write(IccPmr::new_with_raw_value(0).with_priority(priority)) }
+    }
+
+    /// Get the current priority mask
+    pub fn priority_mask() -> u8 {
+        IccPmr::read().priority()
+    }
+
+    /// Get the priority of the highest priority active interrupt
+    ///
+    /// Gives `None` if no interrupt is active.
+    pub fn running_priority() -> Option<u8> {
+        match IccRpr::read().priority() {
+            IDLE_PRIORITY => None,
+            priority => Some(priority),
+        }
+    }
+
+    /// Acknowledge the highest priority pending interrupt in this group
+    ///
+    /// Gives the INTID of the interrupt, or `None` if there wasn't one. You
+    /// must pass the INTID to [`CpuInterface::end_of_interrupt`] when you are
+    /// done with it.
+    pub fn acknowledge(group: InterruptGroup) -> Option<u32> {
+        let intid = match group {
+            InterruptGroup::Group0 => IccIar0::read().intid(),
+            InterruptGroup::Group1 => IccIar1::read().intid(),
+        };
+        real_intid(intid)
+    }
+
+    /// Get the highest priority pending interrupt in this group, without
+    /// acknowledging it
+    pub fn highest_pending(group: InterruptGroup) -> Option<u32> {
+        let intid = match group {
+            InterruptGroup::Group0 => IccHppir0::read().intid(),
+            InterruptGroup::Group1 => IccHppir1::read().intid(),
+        };
+        real_intid(intid)
+    }
+
+    /// Tell the GIC that we have finished with an interrupt we acknowledged
+    ///
+    /// If ICC_CTLR.EOImode is set, this only drops the running priority, and
+    /// you must also call [`CpuInterface::deactivate`].
+    pub fn end_of_interrupt(group: InterruptGroup, intid: u32) {
+        let intid = u24::new(intid);
+        // Safety: completing an interrupt can't break memory safety
+        unsafe {
+            match group {
+                InterruptGroup::Group0 => {
+                    IccEoir0::write(IccEoir0::new_with_raw_value(0).with_intid(intid))
+                }
+                InterruptGroup::Group1 => {
+                    IccEoir1::write(IccEoir1::new_with_raw_value(0).with_intid(intid))
+                }
+            }
+        }
+    }
+
+    /// Deactivate an interrupt, when ICC_CTLR.EOImode is set
+    pub fn deactivate(intid: u32) {
+        // Safety: deactivating an interrupt can't break memory safety
+        unsafe { IccDir::write(IccDir::new_with_raw_value(0).with_intid(u24::new(intid))) }
+    }
+
+    /// Send a Software Generated Interrupt
+    pub fn send_sgi(intid: u4, target: SgiTarget, group: SgiGroup) {
+        let raw = match target {
+            SgiTarget::AllOther => IccSgi1r::new_with_raw_value(0).with_irm(true),
+            SgiTarget::List {
+                affinity3,
+                affinity2,
+                affinity1,
+                range_selector,
+                target_list,
+            } => IccSgi1r::new_with_raw_value(0)
+                .with_aff3(affinity3)
+                .with_aff2(affinity2)
+                .with_aff1(affinity1)
+                .with_rs(range_selector)
+                .with_target_list(target_list),
+        }
+        .with_intid(intid)
+        .raw_value();
+        // Safety: sending an SGI can't break memory safety
+        unsafe {
+            match group {
+                SgiGroup::Group0 => IccSgi0r::write(IccSgi0r::new_with_raw_value(raw)),
+                SgiGroup::Group1 => IccSgi1r::write(IccSgi1r::new_with_raw_value(raw)),
+                SgiGroup::Group1Alias => IccAsgi1r::write(IccAsgi1r::new_with_raw_value(raw)),
+            }
+        }
+    }
+}
+
+/// Handles Group 1 interrupts, which are the ones signalled as IRQ
+impl InterruptController for CpuInterface {
+    fn acknowledge(&mut self) -> Option<u32> {
+        CpuInterface::acknowledge(InterruptGroup::Group1)
+    }
+
+    fn end_of_interrupt(&mut self, id: u32) {
+        CpuInterface::end_of_interrupt(InterruptGroup::Group1, id)
+    }
+}
+
+/// Turn a raw INTID into `None` if it is one of the special ones
+fn real_intid(intid: u24) -> Option<u32> {
+    let intid = intid.value();
+    (intid < SPECIAL_INTID_START).then_some(intid)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::register::SysRegRead;
+    use crate::sim::{self, Access, Encoding};
+
+    #[test]
+    fn acknowledge_and_complete() {
+        sim::reset();
+        sim::set(Encoding::of::<IccIar1>(), 42);
+        assert_eq!(CpuInterface::acknowledge(InterruptGroup::Group1), Some(42));
+        CpuInterface::end_of_interrupt(InterruptGroup::Group1, 42);
+        assert_eq!(sim::get(Encoding::of::<IccEoir1>()), 42);
+
+        sim::set(Encoding::of::<IccIar0>(), 1023);
+        assert_eq!(CpuInterface::acknowledge(InterruptGroup::Group0), None);
+        sim::set(Encoding::of::<IccHppir1>(), 1022);
+        assert_eq!(CpuInterface::highest_pending(InterruptGroup::Group1), None);
+    }
+
+    #[test]
+    fn priorities() {
+        sim::reset();
+        CpuInterface::set_priority_mask(0xF0);
+        assert_eq!(CpuInterface::priority_mask(), 0xF0);
+        sim::set(Encoding::of::<IccRpr>(), 0xFF);
+        assert_eq!(CpuInterface::running_priority(), None);
+        sim::set(Encoding::of::<IccRpr>(), 0x80);
+        assert_eq!(CpuInterface::running_priority(), Some(0x80));
+    }
+
+    #[test]
+    fn enable() {
+        sim::reset();
+        CpuInterface::enable_system_registers();
+        assert_eq!(IccSre::read().raw_value(), 0b111);
+        CpuInterface::enable_group(InterruptGroup::Group1, true);
+        assert_eq!(<IccIgrpen1 as SysRegRead>::read_raw(), 1);
+        assert_eq!(sim::get(Encoding::of::<IccIgrpen0>()), 0);
+    }
+
+    #[test]
+    fn sgis() {
+        sim::reset();
+        CpuInterface::send_sgi(
+            u4::new(3),
+            SgiTarget::List {
+                affinity3: 0,
+                affinity2: 0,
+                affinity1: 1,
+                range_selector: u4::new(2),
+                target_list: 0b101,
+            },
+            SgiGroup::Group1,
+        );
+        CpuInterface::send_sgi(u4::new(7), SgiTarget::AllOther, SgiGroup::Group0);
+        assert_eq!(
+            sim::take_log(),
+            [
+                Access::Write(
+                    Encoding::of64::<IccSgi1r>(),
+                    2 << 44 | 0x0300_0000 | 1 << 16 | 0b101
+                ),
+                Access::Write(Encoding::of64::<IccSgi0r>(), 1 << 40 | 0x0700_0000),
+            ]
+        );
+    }
+}
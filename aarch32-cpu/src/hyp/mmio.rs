@@ -0,0 +1,482 @@
+//! Trap-and-emulate support for memory-mapped devices
+//!
+//! When a guest touches an address with nothing behind it at stage 2 (a
+//! page missing from the stage 2 tables on Armv7-A, or an address outside the
+//! EL2 MPU regions on Armv8-R), it takes a Data Abort to Hyp mode. For most
+//! single-register loads and stores, HSR tells us which register was involved
+//! and how wide the access was, so we can do the access on the guest's behalf
+//! against a model of the device, and then let the guest carry on from the
+//! next instruction.
+//!
+//! Register your device models with an [`MmioBus`], and hand it each Data
+//! Abort:
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::hyp::mmio::{self, AccessWidth, MmioBus, MmioDevice};
+//! use aarch32_cpu::hyp::{Exit, Vcpu};
+//!
+//! struct Uart;
+//!
+//! impl MmioDevice for Uart {
+//!     fn read(&mut self, _offset: u32, _width: AccessWidth) -> u32 {
+//!         0
+//!     }
+//!
+//!     fn write(&mut self, offset: u32, _width: AccessWidth, value: u32) {
+//!         if offset == 0 {
+//!             print_char(value as u8);
+//!         }
+//!     }
+//! }
+//!
+//! let mut uart = Uart;
+//! let mut bus: MmioBus<4> = MmioBus::new();
+//! bus.register(0x1C09_0000, 0x1000, &mut uart).unwrap();
+//!
+//! let mut vcpu = Vcpu::new(GUEST_ENTRY);
+//! loop {
+//!     if let Exit::Trap(hsr) = unsafe { vcpu.run() } {
+//!         bus.handle(hsr, mmio::fault_address(), &mut vcpu).unwrap();
+//!     }
+//! }
+//! ```
+
+use crate::register::hyp::hsr::{ExceptionClass, InstructionLength, Iss};
+use crate::register::hyp::{Hdfar, Hsr};
+
+/// Errors that can occur when emulating a guest's access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MmioError {
+    /// The trap was not a Data Abort from a lower Exception level
+    #[error("trap is not a data abort from the guest")]
+    NotDataAbort,
+    /// HSR doesn't describe the instruction that faulted
+    ///
+    /// This happens with loads and stores of more than one register, and
+    /// those that write back to their base register. They have to be
+    /// emulated by decoding the instruction.
+    #[error("data abort has no instruction syndrome")]
+    NoSyndrome,
+    /// HSR gave an access size that AArch32 doesn't have
+    #[error("data abort has an invalid access size")]
+    InvalidWidth,
+    /// The instruction loads or stores the PC
+    ///
+    /// A load into the PC is a branch, which we can't emulate by writing a
+    /// register and moving on to the next instruction.
+    #[error("data abort loads or stores the PC")]
+    PcAccess,
+    /// No device has been registered at the given address
+    #[error("no device at address {0:#x}")]
+    NoDevice(u64),
+    /// The device would overlap one that is already registered
+    #[error("device overlaps an existing device")]
+    Overlap,
+    /// The bus has no room for another device
+    #[error("no room for another device")]
+    Full,
+    /// The device would run past the end of the address space
+    #[error("device runs past the end of the address space")]
+    AddressOverflow,
+}
+
+/// How many bytes a guest's load or store moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AccessWidth {
+    /// One byte, like `LDRB` or `STRB`
+    Byte,
+    /// Two bytes, like `LDRH` or `STRH`
+    Halfword,
+    /// Four bytes, like `LDR` or `STR`
+    Word,
+}
+
+impl AccessWidth {
+    /// The number of bytes accessed
+    pub const fn bytes(self) -> u32 {
+        match self {
+            AccessWidth::Byte => 1,
+            AccessWidth::Halfword => 2,
+            AccessWidth::Word => 4,
+        }
+    }
+
+    /// A mask covering the bits that are accessed
+    pub const fn mask(self) -> u32 {
+        match self {
+            AccessWidth::Byte => 0xFF,
+            AccessWidth::Halfword => 0xFFFF,
+            AccessWidth::Word => 0xFFFF_FFFF,
+        }
+    }
+
+    /// Sign-extend a value of this width to 32 bits
+    pub const fn sign_extend(self, value: u32) -> u32 {
+        let shift = 32 - self.bytes() * 8;
+        (((value << shift) as i32) >> shift) as u32
+    }
+}
+
+/// A guest's load or store, decoded from a Data Abort
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MmioAccess {
+    /// The address the guest accessed
+    ///
+    /// This is an IPA, so it is what the guest thinks is a physical address.
+    pub address: u64,
+    /// How many bytes were accessed
+    pub width: AccessWidth,
+    /// The register being loaded or stored (0 to 14)
+    pub register: u8,
+    /// Whether a load should be sign-extended, like `LDRSB` or `LDRSH`
+    pub sign_extend: bool,
+    /// The value being stored, or `None` if this is a load
+    ///
+    /// The value has been masked to the width of the access.
+    pub write: Option<u32>,
+    /// The length of the instruction that faulted
+    pub instruction_length: InstructionLength,
+}
+
+impl MmioAccess {
+    /// Decode a Data Abort taken from a guest
+    ///
+    /// `address` should come from [`fault_address`], and `guest` is used to
+    /// get the value being stored.
+    pub fn decode<G>(hsr: Hsr, address: u64, guest: &G) -> Result<MmioAccess, MmioError>
+    where
+        G: GuestContext + ?Sized,
+    {
+        let Ok(ExceptionClass::DataAbortFromLower) = hsr.ec() else {
+            return Err(MmioError::NotDataAbort);
+        };
+        let Some(Iss::DataAbortFromLower(iss)) = hsr.get_iss() else {
+            return Err(MmioError::NotDataAbort);
+        };
+        if !iss.isv() {
+            return Err(MmioError::NoSyndrome);
+        }
+        let width = match iss.sas().value() {
+            0b00 => AccessWidth::Byte,
+            0b01 => AccessWidth::Halfword,
+            0b10 => AccessWidth::Word,
+            _ => return Err(MmioError::InvalidWidth),
+        };
+        let register = iss.srt().value();
+        if register == 15 {
+            return Err(MmioError::PcAccess);
+        }
+        let write = if iss.wnr() {
+            Some(guest.register(register) & width.mask())
+        } else {
+            None
+        };
+        Ok(MmioAccess {
+            address,
+            width,
+            register,
+            sign_extend: iss.sae(),
+            write,
+            instruction_length: hsr.il(),
+        })
+    }
+
+    /// Is this a store?
+    pub const fn is_write(&self) -> bool {
+        self.write.is_some()
+    }
+}
+
+/// Access to the registers of a guest that has trapped to Hyp mode
+pub trait GuestContext {
+    /// Read register `n` (0 to 14) as the guest sees it
+    ///
+    /// SP and LR, and R8 to R12 in FIQ mode, are the ones belonging to the
+    /// mode the guest was in when it trapped.
+    fn register(&self, n: u8) -> u32;
+
+    /// Write register `n` (0 to 14) as the guest sees it
+    fn set_register(&mut self, n: u8, value: u32);
+
+    /// Move the guest on past the instruction that trapped
+    ///
+    /// As well as moving the PC on, this must advance the IT state in the
+    /// guest's SPSR, in case the instruction was inside a Thumb IT block.
+    fn skip_instruction(&mut self, length: InstructionLength);
+}
+
+/// A model of a memory-mapped device
+///
+/// Offsets are from the base address the device was registered at.
+pub trait MmioDevice {
+    /// Handle a load from the device, returning the value read
+    ///
+    /// Only the bottom `width` bytes of the result are used.
+    fn read(&mut self, offset: u32, width: AccessWidth) -> u32;
+
+    /// Handle a store to the device
+    fn write(&mut self, offset: u32, width: AccessWidth, value: u32);
+}
+
+/// A device registered with an [`MmioBus`]
+struct Mapping<'a> {
+    base: u64,
+    size: u32,
+    device: &'a mut dyn MmioDevice,
+}
+
+impl Mapping<'_> {
+    fn offset_of(&self, address: u64) -> Option<u32> {
+        address
+            .checked_sub(self.base)
+            .filter(|offset| *offset < u64::from(self.size))
+            .map(|offset| offset as u32)
+    }
+}
+
+/// Sends a guest's loads and stores to up to `N` device models
+pub struct MmioBus<'a, const N: usize> {
+    mappings: [Option<Mapping<'a>>; N],
+}
+
+impl<'a, const N: usize> MmioBus<'a, N> {
+    /// Create an empty bus
+    pub const fn new() -> Self {
+        MmioBus {
+            mappings: [const { None }; N],
+        }
+    }
+
+    /// Put `device` on the bus, covering `size` bytes from `base`
+    pub fn register(
+        &mut self,
+        base: u64,
+        size: u32,
+        device: &'a mut dyn MmioDevice,
+    ) -> Result<(), MmioError> {
+        let end = base
+            .checked_add(u64::from(size))
+            .ok_or(MmioError::AddressOverflow)?;
+        if self
+            .mappings
+            .iter()
+            .flatten()
+            .any(|mapping| base < mapping.base + u64::from(mapping.size) && mapping.base < end)
+        {
+            return Err(MmioError::Overlap);
+        }
+        let slot = self
+            .mappings
+            .iter_mut()
+            .find(|mapping| mapping.is_none())
+            .ok_or(MmioError::Full)?;
+        *slot = Some(Mapping { base, size, device });
+        Ok(())
+    }
+
+    /// Emulate the load or store that caused a Data Abort
+    ///
+    /// The access is decoded with [`MmioAccess::decode`] and passed to
+    /// whichever device covers the address. For a load, the value read is
+    /// put in the guest's register. The guest is then moved on to the next
+    /// instruction.
+    ///
+    /// If this returns an error, the guest has not been changed.
+    pub fn handle<G>(
+        &mut self,
+        hsr: Hsr,
+        address: u64,
+        guest: &mut G,
+    ) -> Result<MmioAccess, MmioError>
+    where
+        G: GuestContext + ?Sized,
+    {
+        let access = MmioAccess::decode(hsr, address, guest)?;
+        let (mapping, offset) = self
+            .mappings
+            .iter_mut()
+            .flatten()
+            .find_map(|mapping| {
+                let offset = mapping.offset_of(address)?;
+                Some((mapping, offset))
+            })
+            .ok_or(MmioError::NoDevice(address))?;
+        match access.write {
+            Some(value) => mapping.device.write(offset, access.width, value),
+            None => {
+                let mut value = mapping.device.read(offset, access.width) & access.width.mask();
+                if access.sign_extend {
+                    value = access.width.sign_extend(value);
+                }
+                guest.set_register(access.register, value);
+            }
+        }
+        guest.skip_instruction(access.instruction_length);
+        Ok(access)
+    }
+}
+
+impl<const N: usize> Default for MmioBus<'_, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Get the address that caused the last Data Abort taken to Hyp mode
+///
+/// On Armv8-R there is no stage 2 translation, so this is just HDFAR. On
+/// Armv7-A, HDFAR holds the guest's virtual address, so we take the page
+/// from HPFAR (which holds the IPA) and the offset within the page from
+/// HDFAR. HPFAR is only valid for faults at stage 2.
+pub fn fault_address() -> u64 {
+    let hdfar = Hdfar::read().0;
+    #[cfg(arm_architecture = "v8-r")]
+    {
+        u64::from(hdfar)
+    }
+    #[cfg(not(arm_architecture = "v8-r"))]
+    {
+        let hpfar = crate::register::hyp::Hpfar::read().0;
+        (u64::from(hpfar >> 4) << 12) | u64::from(hdfar & 0xFFF)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Guest {
+        r: [u32; 15],
+        pc: u32,
+    }
+
+    impl GuestContext for Guest {
+        fn register(&self, n: u8) -> u32 {
+            self.r[usize::from(n)]
+        }
+
+        fn set_register(&mut self, n: u8, value: u32) {
+            self.r[usize::from(n)] = value;
+        }
+
+        fn skip_instruction(&mut self, length: InstructionLength) {
+            self.pc += match length {
+                InstructionLength::SixteenBit => 2,
+                InstructionLength::ThirtyTwoBit => 4,
+            };
+        }
+    }
+
+    #[derive(Default)]
+    struct Device {
+        last_write: Option<(u32, AccessWidth, u32)>,
+    }
+
+    impl MmioDevice for Device {
+        fn read(&mut self, offset: u32, _width: AccessWidth) -> u32 {
+            0xFFFF_FF00 | offset
+        }
+
+        fn write(&mut self, offset: u32, width: AccessWidth, value: u32) {
+            self.last_write = Some((offset, width, value));
+        }
+    }
+
+    /// A Data Abort from a lower EL with a valid instruction syndrome
+    fn data_abort(thumb: bool, sas: u32, sse: bool, srt: u32, wnr: bool) -> Hsr {
+        Hsr::new_with_raw_value(
+            (0b10_0100 << 26)
+                | (u32::from(!thumb) << 25)
+                | (1 << 24)
+                | (sas << 22)
+                | (u32::from(sse) << 21)
+                | (srt << 16)
+                | (u32::from(wnr) << 6)
+                | 0b000110,
+        )
+    }
+
+    #[test]
+    fn load_and_store() {
+        let mut device = Device::default();
+        let mut bus: MmioBus<2> = MmioBus::new();
+        bus.register(0x1000_0000, 0x1000, &mut device).unwrap();
+        let mut guest = Guest {
+            r: [0; 15],
+            pc: 0x100,
+        };
+
+        // LDRSB r3, [..] in Thumb state, from offset 0x80
+        let access = bus
+            .handle(
+                data_abort(true, 0b00, true, 3, false),
+                0x1000_0080,
+                &mut guest,
+            )
+            .unwrap();
+        assert_eq!(access.width, AccessWidth::Byte);
+        assert!(!access.is_write());
+        assert_eq!(guest.r[3], 0xFFFF_FF80);
+        assert_eq!(guest.pc, 0x102);
+
+        // STRH r5, [..] in Arm state
+        guest.r[5] = 0x1234_5678;
+        let access = bus
+            .handle(
+                data_abort(false, 0b01, false, 5, true),
+                0x1000_0010,
+                &mut guest,
+            )
+            .unwrap();
+        assert_eq!(access.write, Some(0x5678));
+        assert_eq!(guest.pc, 0x106);
+
+        // Nothing lives here, so the guest doesn't move
+        assert_eq!(
+            bus.handle(
+                data_abort(false, 0b10, false, 0, false),
+                0x2000_0000,
+                &mut guest
+            ),
+            Err(MmioError::NoDevice(0x2000_0000))
+        );
+        assert_eq!(guest.pc, 0x106);
+
+        // LDR pc, [..] is a branch, so we leave it alone
+        assert_eq!(
+            bus.handle(
+                data_abort(false, 0b10, false, 15, false),
+                0x1000_0000,
+                &mut guest
+            ),
+            Err(MmioError::PcAccess)
+        );
+        assert_eq!(guest.pc, 0x106);
+
+        assert_eq!(
+            device.last_write,
+            Some((0x10, AccessWidth::Halfword, 0x5678))
+        );
+    }
+
+    #[test]
+    fn register_checks_overlap() {
+        let mut a = Device::default();
+        let mut b = Device::default();
+        let mut c = Device::default();
+        let mut d = Device::default();
+        let mut e = Device::default();
+        let mut bus: MmioBus<2> = MmioBus::new();
+        bus.register(0x1000, 0x100, &mut a).unwrap();
+        assert_eq!(bus.register(0x10FF, 0x100, &mut b), Err(MmioError::Overlap));
+        assert_eq!(
+            bus.register(u64::MAX - 0xFF, 0x100, &mut e),
+            Err(MmioError::AddressOverflow)
+        );
+        bus.register(0x1100, 0x100, &mut c).unwrap();
+        assert_eq!(bus.register(0x2000, 0x100, &mut d), Err(MmioError::Full));
+    }
+}
@@ -0,0 +1,16 @@
+//! Support for writing hypervisors that run in Hyp mode (EL2)
+//!
+//! The registers used to control guests live in [`crate::register::hyp`].
+//! This module adds the machinery for switching between the hypervisor and a
+//! guest, for emulating the devices a guest can see, and for giving it
+//! interrupts.
+
+pub mod mmio;
+pub mod timer;
+pub mod vgic;
+
+#[cfg(any(test, arm_architecture = "v8-r"))]
+pub mod vcpu;
+
+#[cfg(any(test, arm_architecture = "v8-r"))]
+pub use vcpu::{Exit, Vcpu};
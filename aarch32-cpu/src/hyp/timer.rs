@@ -0,0 +1,284 @@
+//! Giving each guest its own virtual timer
+//!
+//! A guest uses the EL1 virtual timer, which counts CNTVCT - the physical
+//! count minus CNTVOFF. A [`VirtualTimerContext`] holds one guest's timer
+//! registers and its CNTVOFF, and swaps them in and out around each world
+//! switch. While the guest is descheduled its offset grows, so the guest's
+//! time stands still rather than jumping forwards when it next runs.
+//!
+//! The hardware timer is disabled while its guest is descheduled, so an
+//! expiry then raises nothing. Before running the guest, ask
+//! [`VirtualTimerContext::forward`] whether its timer has expired, and inject
+//! the interrupt it gives you.
+//!
+//! The guest always sees CNTV_CTL as it set it, so once its timer has fired,
+//! the timer's PPI stays asserted until the guest re-arms, disables or masks
+//! it. Keep the PPI disabled at your GIC while
+//! [`VirtualTimerContext::masked`] says so, and tell the timer when the guest
+//! completes its interrupt:
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::hyp::timer::{VirtualTimerContext, VIRTUAL_TIMER_INTID};
+//!
+//! let mut timer = VirtualTimerContext::new();
+//! loop {
+//!     if let Some(irq) = timer.forward() {
+//!         vgic.inject(irq).unwrap();
+//!     }
+//!     gic.set_enabled(VIRTUAL_TIMER_INTID, !timer.masked());
+//!     timer.restore();
+//!     run_guest();
+//!     timer.save();
+//!     vgic.handle_maintenance(|intid| {
+//!         if intid == timer.interrupt.intid {
+//!             timer.end_of_interrupt();
+//!         }
+//!     });
+//! }
+//! ```
+//!
+//! On Armv8-R, `hyp::Vcpu` does the `restore` and `save` for you.
+
+use super::vgic::VirtualInterrupt;
+use crate::register::{
+    CntPct, CntVoff, Cnthctl, Cntkctl, CntvCtl, CntvCval, SysRegRead, SysRegWrite,
+};
+
+/// The INTID of the virtual timer's interrupt (PPI 11)
+pub const VIRTUAL_TIMER_INTID: u32 = 27;
+
+/// One guest's virtual timer
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VirtualTimerContext {
+    /// CNTV_CTL (*Virtual Timer Control Register*), as the guest set it
+    pub ctl: CntvCtl,
+    /// CNTV_CVAL (*Virtual Timer CompareValue Register*)
+    pub cval: u64,
+    /// CNTKCTL (*Counter-timer Kernel Control Register*)
+    pub cntkctl: u32,
+    /// CNTVOFF (*Virtual Counter-timer Offset Register*)
+    ///
+    /// The guest's count is the physical count minus this.
+    pub offset: u64,
+    /// Trap the guest's accesses to the physical counter and timer
+    ///
+    /// This is on by default, so that the guest can only see its own
+    /// virtual time.
+    pub trap_physical: bool,
+    /// The interrupt to give the guest when its timer fires
+    ///
+    /// This asks for a maintenance interrupt when the guest completes it, so
+    /// that you can call [`VirtualTimerContext::end_of_interrupt`].
+    pub interrupt: VirtualInterrupt,
+    /// The physical count when we last saved the timer
+    saved_at: u64,
+    /// We have handed out an interrupt that the guest hasn't completed yet
+    forwarded: bool,
+}
+
+impl VirtualTimerContext {
+    /// Create a disabled timer, whose count starts at zero now
+    pub fn new() -> VirtualTimerContext {
+        let now = CntPct::read().0;
+        VirtualTimerContext {
+            ctl: CntvCtl::new_with_raw_value(0),
+            cval: 0,
+            cntkctl: 0,
+            offset: now,
+            trap_physical: true,
+            interrupt: VirtualInterrupt {
+                notify_eoi: true,
+                ..VirtualInterrupt::new(VIRTUAL_TIMER_INTID)
+            },
+            saved_at: now,
+            forwarded: false,
+        }
+    }
+
+    /// The guest's virtual count, as of when the timer was last saved
+    pub fn count(&self) -> u64 {
+        self.saved_at.wrapping_sub(self.offset)
+    }
+
+    /// Has the guest's timer fired?
+    ///
+    /// This only looks at the saved state, so call it while the timer is not
+    /// loaded.
+    pub fn expired(&self) -> bool {
+        self.ctl.enable() && !self.ctl.imask() && self.count() >= self.cval
+    }
+
+    /// Get the interrupt to inject if the guest's timer has fired
+    ///
+    /// An expiry is only forwarded once. It is forwarded again if it is still
+    /// there after the guest has completed the interrupt, and the next one is
+    /// forwarded after the guest has re-armed, disabled or masked its timer.
+    pub fn forward(&mut self) -> Option<VirtualInterrupt> {
+        if !self.expired() {
+            self.forwarded = false;
+            return None;
+        }
+        if self.forwarded {
+            return None;
+        }
+        self.forwarded = true;
+        Some(self.interrupt)
+    }
+
+    /// Should the timer's PPI be kept disabled while the guest runs?
+    ///
+    /// This is true while the guest is dealing with an expiry we have
+    /// forwarded, so that the timer doesn't keep interrupting us until the
+    /// guest re-arms it or completes the interrupt. Ask before calling
+    /// [`VirtualTimerContext::restore`].
+    pub fn masked(&self) -> bool {
+        self.forwarded && self.expired()
+    }
+
+    /// The guest has completed the timer's interrupt
+    pub fn end_of_interrupt(&mut self) {
+        self.forwarded = false;
+    }
+
+    /// Load the guest's timer into the processor
+    ///
+    /// The time the guest spent descheduled is added to its offset.
+    pub fn restore(&mut self) {
+        let now = CntPct::read().0;
+        self.offset = self.offset.wrapping_add(now.wrapping_sub(self.saved_at));
+
+        CntVoff::write(CntVoff(self.offset));
+        CntvCval::write(CntvCval(self.cval));
+        // Safety: CNTKCTL only controls what the guest's EL0 can see
+        unsafe {
+            <Cntkctl as SysRegWrite>::write_raw(self.cntkctl);
+        }
+        Cnthctl::write(
+            Cnthctl::read()
+                .with_pl1pcen(!self.trap_physical)
+                .with_pl1pcten(!self.trap_physical),
+        );
+        CntvCtl::write(self.ctl);
+    }
+
+    /// Save the guest's timer, and stop it
+    ///
+    /// The timer stays disabled until [`VirtualTimerContext::restore`] is
+    /// called, for this guest or another one.
+    pub fn save(&mut self) {
+        self.ctl = CntvCtl::read();
+        self.cval = CntvCval::read().0;
+        self.cntkctl = <Cntkctl as SysRegRead>::read_raw();
+        CntvCtl::write(CntvCtl::new_with_raw_value(0));
+        self.saved_at = CntPct::read().0;
+    }
+}
+
+impl Default for VirtualTimerContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sim::{self, Encoding};
+
+    #[test]
+    fn time_stops_while_descheduled() {
+        sim::reset();
+        sim::set(Encoding::of64::<CntPct>(), 1000);
+        let mut timer = VirtualTimerContext::new();
+        timer.ctl = CntvCtl::new_with_raw_value(0).with_enable(true);
+        timer.cval = 150;
+
+        timer.restore();
+        assert_eq!(sim::get(Encoding::of64::<CntVoff>()), 1000);
+        assert_eq!(sim::get(Encoding::of::<Cnthctl>()), 0);
+        assert_eq!(sim::get(Encoding::of::<CntvCtl>()), 1);
+
+        // The guest runs for 100 ticks
+        sim::set(Encoding::of64::<CntPct>(), 1100);
+        timer.save();
+        assert_eq!(timer.count(), 100);
+        assert_eq!(sim::get(Encoding::of::<CntvCtl>()), 0);
+        assert_eq!(timer.forward(), None);
+
+        // ...and is descheduled for 500
+        sim::set(Encoding::of64::<CntPct>(), 1600);
+        timer.restore();
+        assert_eq!(sim::get(Encoding::of64::<CntVoff>()), 1500);
+
+        // ...and runs for 50 more, when its timer fires
+        sim::set(Encoding::of64::<CntPct>(), 1650);
+        timer.save();
+        assert_eq!(timer.count(), 150);
+        assert_eq!(
+            timer.forward().map(|irq| irq.intid),
+            Some(VIRTUAL_TIMER_INTID)
+        );
+        assert_eq!(timer.forward(), None);
+
+        // The guest still sees CNTV_CTL as it set it, and we keep the PPI
+        // disabled instead
+        assert!(timer.masked());
+        timer.restore();
+        assert_eq!(sim::get(Encoding::of::<CntvCtl>()), 1);
+        timer.save();
+
+        // The guest re-arms its timer
+        timer.cval = 1000;
+        assert_eq!(timer.forward(), None);
+        assert!(!timer.masked());
+        timer.restore();
+        assert_eq!(sim::get(Encoding::of::<CntvCtl>()), 1);
+    }
+
+    #[test]
+    fn guest_masks_forwarded_timer() {
+        sim::reset();
+        let mut timer = VirtualTimerContext::new();
+        timer.ctl = CntvCtl::new_with_raw_value(0).with_enable(true);
+        timer.cval = 10;
+        timer.restore();
+        sim::set(Encoding::of64::<CntPct>(), 20);
+        timer.save();
+        assert!(timer.forward().is_some());
+        assert!(timer.masked());
+
+        // The guest masks its timer while we have the PPI disabled, and that
+        // sticks
+        timer.restore();
+        sim::set(Encoding::of::<CntvCtl>(), 3);
+        timer.save();
+        assert!(timer.ctl.imask());
+        assert_eq!(timer.forward(), None);
+        assert!(!timer.masked());
+        timer.restore();
+        assert_eq!(sim::get(Encoding::of::<CntvCtl>()), 3);
+        timer.save();
+
+        // ...then unmasks it without re-arming, so it fires again
+        timer.restore();
+        sim::set(Encoding::of::<CntvCtl>(), 1);
+        timer.save();
+        assert!(!timer.ctl.imask());
+        assert!(timer.forward().is_some());
+    }
+
+    #[test]
+    fn forwarded_again_after_completion() {
+        sim::reset();
+        let mut timer = VirtualTimerContext::new();
+        timer.ctl = CntvCtl::new_with_raw_value(0).with_enable(true);
+        assert!(timer.forward().is_some());
+        assert_eq!(timer.forward(), None);
+
+        // The guest completes the interrupt without re-arming its timer
+        timer.end_of_interrupt();
+        assert!(!timer.masked());
+        assert!(timer.forward().is_some());
+    }
+}
@@ -0,0 +1,856 @@
+//! Code for running a guest at EL1 on Armv8-R
+//!
+//! A [`Vcpu`] holds the state a guest sees when it runs at EL1 - its core
+//! registers, the banked registers of its exception modes, its EL1 system
+//! registers (including the EL1 MPU regions) and its virtual timer.
+//!
+//! [`Vcpu::run`] loads that state into the processor, enters the guest with
+//! an `eret`, and returns the next time the guest traps to Hyp mode, saving
+//! the guest's state as it goes.
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::hyp::{Exit, Vcpu};
+//! use aarch32_cpu::register::hyp::hsr::ExceptionClass;
+//!
+//! let mut vcpu = Vcpu::new(GUEST_ENTRY);
+//! loop {
+//!     match unsafe { vcpu.run() } {
+//!         Exit::Trap(hsr) if hsr.ec() == Ok(ExceptionClass::Hvc) => {
+//!             // HVC leaves ELR_hyp pointing at the next instruction
+//!             vcpu.regs.r[0] = handle_hypercall(vcpu.regs.r[0]);
+//!         }
+//!         Exit::Trap(hsr) => panic!("Guest trapped: {:?}", hsr),
+//!         Exit::Irq | Exit::Fiq => handle_interrupt(&mut vcpu),
+//!     }
+//! }
+//! ```
+
+use super::mmio::GuestContext;
+use super::timer::VirtualTimerContext;
+use crate::register::banked::*;
+use crate::register::cpsr::ProcessorMode;
+use crate::register::hyp::hsr::InstructionLength;
+use crate::register::hyp::{Hcr, Hsr, Hvbar};
+use crate::register::{
+    Contextidr, Cpacr, Cpsr, Dfar, Ifar, Mair0, Mair1, Mpuir, Prbar, Prlar, Prselr, Sctlr,
+    SysRegRead, SysRegWrite, Tpidrprw, Tpidruro, Tpidrurw, Vbar, Vmpidr, Vpidr, Vsctlr,
+};
+#[cfg(armv5te_or_higher)]
+use crate::register::{Dfsr, Ifsr};
+
+/// The most EL1 MPU regions we save and restore
+///
+/// The Cortex-R52 has up to 24.
+pub const MAX_MPU_REGIONS: usize = 24;
+
+/// `_aarch32_cpu_vcpu_enter` returns this when the guest traps to Hyp mode
+const EXIT_TRAP: u32 = 0;
+
+/// `_aarch32_cpu_vcpu_enter` returns this when an IRQ interrupts the guest
+const EXIT_IRQ: u32 = 1;
+
+/// `_aarch32_cpu_vcpu_enter` returns this when an FIQ interrupts the guest
+const EXIT_FIQ: u32 = 2;
+
+/// Why [`Vcpu::run`] returned
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Exit {
+    /// The guest trapped to Hyp mode, and HSR says why
+    ///
+    /// This covers HVC instructions as well as the traps enabled in
+    /// [`Vcpu::hcr`].
+    Trap(Hsr),
+    /// An IRQ arrived while the guest was running
+    ///
+    /// You only get this if `HCR.IMO` is set.
+    Irq,
+    /// An FIQ arrived while the guest was running
+    ///
+    /// You only get this if `HCR.FMO` is set.
+    Fiq,
+}
+
+/// The core registers of a guest
+///
+/// The layout is fixed, because the assembly code that enters and leaves the
+/// guest loads and stores this structure.
+#[repr(C)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct GuestRegisters {
+    /// R0 to R12
+    pub r: [u32; 13],
+    /// SP_usr (*Stack Pointer for User and System mode*)
+    pub sp: u32,
+    /// LR_usr (*Link Register for User and System mode*)
+    pub lr: u32,
+    /// Where the guest resumes, which goes in ELR_hyp
+    ///
+    /// After a trap this is the preferred return address. For most traps that
+    /// is the instruction that trapped, but for an HVC it is the next one.
+    pub pc: u32,
+    /// The guest's CPSR, which goes in SPSR_hyp
+    pub cpsr: u32,
+    /// D0 to D15
+    ///
+    /// These are only saved and restored on `eabihf` targets.
+    pub d: [u64; 16],
+    /// FPSCR (*Floating-Point Status and Control Register*)
+    ///
+    /// This is only saved and restored on `eabihf` targets.
+    pub fpscr: u32,
+}
+
+/// The banked registers of a guest's exception modes
+///
+/// The SPSRs are kept as raw values - use [`Cpsr::new_with_raw_value`] to
+/// decode them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BankedRegisters {
+    /// SP_svc (*Stack Pointer for Supervisor mode*)
+    pub sp_svc: u32,
+    /// LR_svc (*Link Register for Supervisor mode*)
+    pub lr_svc: u32,
+    /// SPSR_svc (*Saved Program Status Register for Supervisor mode*)
+    pub spsr_svc: u32,
+    /// SP_abt (*Stack Pointer for Abort mode*)
+    pub sp_abt: u32,
+    /// LR_abt (*Link Register for Abort mode*)
+    pub lr_abt: u32,
+    /// SPSR_abt (*Saved Program Status Register for Abort mode*)
+    pub spsr_abt: u32,
+    /// SP_und (*Stack Pointer for Undefined mode*)
+    pub sp_und: u32,
+    /// LR_und (*Link Register for Undefined mode*)
+    pub lr_und: u32,
+    /// SPSR_und (*Saved Program Status Register for Undefined mode*)
+    pub spsr_und: u32,
+    /// SP_irq (*Stack Pointer for IRQ mode*)
+    pub sp_irq: u32,
+    /// LR_irq (*Link Register for IRQ mode*)
+    pub lr_irq: u32,
+    /// SPSR_irq (*Saved Program Status Register for IRQ mode*)
+    pub spsr_irq: u32,
+    /// R8_fiq to R12_fiq
+    pub r_fiq: [u32; 5],
+    /// SP_fiq (*Stack Pointer for FIQ mode*)
+    pub sp_fiq: u32,
+    /// LR_fiq (*Link Register for FIQ mode*)
+    pub lr_fiq: u32,
+    /// SPSR_fiq (*Saved Program Status Register for FIQ mode*)
+    pub spsr_fiq: u32,
+}
+
+/// An EL1 MPU region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MpuRegion {
+    /// PRBAR (*Protection Region Base Address Register*)
+    pub prbar: u32,
+    /// PRLAR (*Protection Region Limit Address Register*)
+    pub prlar: u32,
+}
+
+/// A guest's EL1 system registers
+///
+/// This includes VSCTLR, VMPIDR and VPIDR, which live at EL2 but only affect
+/// the guest. Register values are kept raw.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct El1Registers {
+    /// SCTLR (*System Control Register*)
+    pub sctlr: u32,
+    /// CPACR (*Coprocessor Access Control Register*)
+    pub cpacr: u32,
+    /// VBAR (*Vector Base Address Register*)
+    pub vbar: u32,
+    /// CONTEXTIDR (*Context ID Register*)
+    pub contextidr: u32,
+    /// TPIDRPRW (*PL1 Software Thread ID Register*)
+    pub tpidrprw: u32,
+    /// TPIDRURW (*User Read/Write Thread ID Register*)
+    pub tpidrurw: u32,
+    /// TPIDRURO (*User Read-Only Thread ID Register*)
+    pub tpidruro: u32,
+    /// DFAR (*Data Fault Address Register*)
+    pub dfar: u32,
+    /// DFSR (*Data Fault Status Register*)
+    pub dfsr: u32,
+    /// IFAR (*Instruction Fault Address Register*)
+    pub ifar: u32,
+    /// IFSR (*Instruction Fault Status Register*)
+    pub ifsr: u32,
+    /// MAIR0 and MAIR1 (*Memory Attribute Indirection Registers*)
+    pub mair: [u32; 2],
+    /// PRSELR (*Protection Region Selection Register*)
+    pub prselr: u32,
+    /// The EL1 MPU regions, up to the number given in MPUIR
+    pub regions: [MpuRegion; MAX_MPU_REGIONS],
+    /// VSCTLR (*Virtualization System Control Register*), which holds the VMID
+    pub vsctlr: u32,
+    /// VMPIDR (*Virtualization Multiprocessor ID Register*)
+    ///
+    /// The guest sees this when it reads MPIDR.
+    pub vmpidr: u32,
+    /// VPIDR (*Virtualization Processor ID Register*)
+    ///
+    /// The guest sees this when it reads MIDR.
+    pub vpidr: u32,
+}
+
+/// A virtual CPU, for running a guest at EL1
+///
+/// Only one `Vcpu` can be running on a core at a time, but you can switch
+/// between as many as you like by calling [`Vcpu::run`] on each in turn.
+#[derive(Debug, Clone)]
+pub struct Vcpu {
+    /// The guest's core registers
+    pub regs: GuestRegisters,
+    /// The guest's banked registers
+    pub banked: BankedRegisters,
+    /// The guest's EL1 system registers
+    pub el1: El1Registers,
+    /// The guest's virtual timer
+    pub timer: VirtualTimerContext,
+    /// The HCR value to use while the guest runs
+    ///
+    /// This says which of the guest's actions trap to Hyp mode, and whether
+    /// interrupts go to the guest or to us.
+    pub hcr: Hcr,
+}
+
+impl Vcpu {
+    /// Create a vCPU that starts at `entry` in Supervisor mode
+    ///
+    /// The guest starts with interrupts masked, and in Thumb state if bit 0
+    /// of `entry` is set. Its EL1 system registers start with whatever values
+    /// they have now - which will be their reset values if nothing has touched
+    /// them yet. Its virtual timer starts disabled, with a count of zero.
+    ///
+    /// HCR is set so that IRQs, FIQs and asynchronous aborts come to us
+    /// rather than to the guest, and nothing else traps.
+    pub fn new(entry: u32) -> Vcpu {
+        let cpsr = Cpsr::new_with_raw_value(0)
+            .with_mode(ProcessorMode::Svc)
+            .with_a(true)
+            .with_i(true)
+            .with_f(true)
+            .with_t(entry & 1 != 0);
+        let mut vcpu = Vcpu {
+            regs: GuestRegisters {
+                pc: entry & !1,
+                cpsr: cpsr.raw_value(),
+                ..GuestRegisters::default()
+            },
+            banked: BankedRegisters::default(),
+            el1: El1Registers::default(),
+            timer: VirtualTimerContext::new(),
+            hcr: Hcr::new_with_raw_value(0)
+                .with_amo(true)
+                .with_imo(true)
+                .with_fmo(true),
+        };
+        vcpu.el1.save();
+        vcpu
+    }
+
+    /// Run the guest until it next traps to Hyp mode
+    ///
+    /// We load the guest's state, set HCR, and point HVBAR at a vector table
+    /// of our own while the guest runs. When the guest traps, we put HVBAR
+    /// back, save the guest's state into `self`, and tell you why it stopped.
+    ///
+    /// IRQs are masked while we are switching, and are unmasked again when we
+    /// return if they were unmasked when we were called.
+    ///
+    /// HCR is left with the guest's value, and the EL1 system registers are
+    /// left with the guest's values.
+    ///
+    /// # Safety
+    ///
+    /// Must be called from Hyp mode. `regs.cpsr` must describe a mode at EL1
+    /// or EL0, and the guest must be unable to touch our memory (for example,
+    /// because HCR.VM is set and the EL2 MPU keeps it out).
+    pub unsafe fn run(&mut self) -> Exit {
+        let old_cpsr = Cpsr::read();
+        crate::interrupt::disable();
+        let old_hvbar = Hvbar::read();
+
+        unsafe {
+            self.banked.restore();
+            self.el1.restore();
+        }
+        self.timer.restore();
+        Hcr::write(self.hcr);
+
+        let reason = self.enter();
+
+        unsafe {
+            Hvbar::write(old_hvbar);
+        }
+        self.banked.save();
+        self.el1.save();
+        self.timer.save();
+        let hsr = Hsr::read();
+
+        if !old_cpsr.i() {
+            // Safety: They were unmasked when we started
+            unsafe {
+                crate::interrupt::enable();
+            }
+        }
+
+        match reason {
+            EXIT_IRQ => Exit::Irq,
+            EXIT_FIQ => Exit::Fiq,
+            _ => Exit::Trap(hsr),
+        }
+    }
+
+    /// Jump into the guest and come back when it exits
+    #[cfg(arm_architecture = "v8-r")]
+    fn enter(&mut self) -> u32 {
+        extern "C" {
+            fn _aarch32_cpu_vcpu_vectors();
+            fn _aarch32_cpu_vcpu_enter(regs: *mut GuestRegisters) -> u32;
+        }
+        unsafe {
+            Hvbar::write(Hvbar(
+                _aarch32_cpu_vcpu_vectors as *const () as usize as u32,
+            ));
+            crate::asm::isb();
+            _aarch32_cpu_vcpu_enter(&mut self.regs)
+        }
+    }
+
+    /// Pretend the guest trapped straight away, for testing on the host
+    #[cfg(not(arm_architecture = "v8-r"))]
+    fn enter(&mut self) -> u32 {
+        EXIT_TRAP
+    }
+
+    /// The mode the guest is in
+    fn mode(&self) -> Option<ProcessorMode> {
+        Cpsr::new_with_raw_value(self.regs.cpsr).mode().ok()
+    }
+
+    /// The SP and LR of the mode the guest is in
+    fn sp_lr(&self) -> (u32, u32) {
+        let banked = &self.banked;
+        match self.mode() {
+            Some(ProcessorMode::Fiq) => (banked.sp_fiq, banked.lr_fiq),
+            Some(ProcessorMode::Irq) => (banked.sp_irq, banked.lr_irq),
+            Some(ProcessorMode::Svc) => (banked.sp_svc, banked.lr_svc),
+            Some(ProcessorMode::Abt) => (banked.sp_abt, banked.lr_abt),
+            Some(ProcessorMode::Und) => (banked.sp_und, banked.lr_und),
+            _ => (self.regs.sp, self.regs.lr),
+        }
+    }
+
+    /// The SP and LR of the mode the guest is in, for writing
+    fn sp_lr_mut(&mut self) -> (&mut u32, &mut u32) {
+        let mode = self.mode();
+        let banked = &mut self.banked;
+        match mode {
+            Some(ProcessorMode::Fiq) => (&mut banked.sp_fiq, &mut banked.lr_fiq),
+            Some(ProcessorMode::Irq) => (&mut banked.sp_irq, &mut banked.lr_irq),
+            Some(ProcessorMode::Svc) => (&mut banked.sp_svc, &mut banked.lr_svc),
+            Some(ProcessorMode::Abt) => (&mut banked.sp_abt, &mut banked.lr_abt),
+            Some(ProcessorMode::Und) => (&mut banked.sp_und, &mut banked.lr_und),
+            _ => (&mut self.regs.sp, &mut self.regs.lr),
+        }
+    }
+}
+
+/// Registers are the ones the guest sees in the mode it is in, and R15 is
+/// where the guest will resume.
+impl GuestContext for Vcpu {
+    fn register(&self, n: u8) -> u32 {
+        match n {
+            8..=12 if self.mode() == Some(ProcessorMode::Fiq) => {
+                self.banked.r_fiq[usize::from(n - 8)]
+            }
+            0..=12 => self.regs.r[usize::from(n)],
+            13 => self.sp_lr().0,
+            14 => self.sp_lr().1,
+            _ => self.regs.pc,
+        }
+    }
+
+    fn set_register(&mut self, n: u8, value: u32) {
+        match n {
+            8..=12 if self.mode() == Some(ProcessorMode::Fiq) => {
+                self.banked.r_fiq[usize::from(n - 8)] = value;
+            }
+            0..=12 => self.regs.r[usize::from(n)] = value,
+            13 => *self.sp_lr_mut().0 = value,
+            14 => *self.sp_lr_mut().1 = value,
+            _ => self.regs.pc = value,
+        }
+    }
+
+    fn skip_instruction(&mut self, length: InstructionLength) {
+        self.regs.pc = self.regs.pc.wrapping_add(match length {
+            InstructionLength::SixteenBit => 2,
+            InstructionLength::ThirtyTwoBit => 4,
+        });
+        self.regs.cpsr = it_advance(self.regs.cpsr);
+    }
+}
+
+/// Move the IT state in a CPSR on past one instruction
+///
+/// This follows the `ITAdvance()` pseudocode in the Arm ARM. IT[1:0] are
+/// in CPSR bits 26:25, and IT[7:2] are in CPSR bits 15:10. Outside an IT
+/// block they are all zero, and stay that way.
+fn it_advance(cpsr: u32) -> u32 {
+    let it = (cpsr >> 25 & 0b11) | (cpsr >> 8 & 0b1111_1100);
+    let it = if it & 0b111 == 0 {
+        0
+    } else {
+        (it & 0b1110_0000) | (it << 1 & 0b1_1111)
+    };
+    (cpsr & !(0b11 << 25 | 0b11_1111 << 10)) | (it & 0b11) << 25 | (it >> 2) << 10
+}
+
+impl BankedRegisters {
+    fn save(&mut self) {
+        self.sp_svc = SpSvc::read();
+        self.lr_svc = LrSvc::read();
+        self.spsr_svc = SpsrSvc::read().raw_value();
+        self.sp_abt = SpAbt::read();
+        self.lr_abt = LrAbt::read();
+        self.spsr_abt = SpsrAbt::read().raw_value();
+        self.sp_und = SpUnd::read();
+        self.lr_und = LrUnd::read();
+        self.spsr_und = SpsrUnd::read().raw_value();
+        self.sp_irq = SpIrq::read();
+        self.lr_irq = LrIrq::read();
+        self.spsr_irq = SpsrIrq::read().raw_value();
+        self.r_fiq = read_fiq_gprs();
+        self.sp_fiq = SpFiq::read();
+        self.lr_fiq = LrFiq::read();
+        self.spsr_fiq = SpsrFiq::read().raw_value();
+    }
+
+    unsafe fn restore(&self) {
+        unsafe {
+            SpSvc::write(self.sp_svc);
+            LrSvc::write(self.lr_svc);
+            SpsrSvc::write(Cpsr::new_with_raw_value(self.spsr_svc));
+            SpAbt::write(self.sp_abt);
+            LrAbt::write(self.lr_abt);
+            SpsrAbt::write(Cpsr::new_with_raw_value(self.spsr_abt));
+            SpUnd::write(self.sp_und);
+            LrUnd::write(self.lr_und);
+            SpsrUnd::write(Cpsr::new_with_raw_value(self.spsr_und));
+            SpIrq::write(self.sp_irq);
+            LrIrq::write(self.lr_irq);
+            SpsrIrq::write(Cpsr::new_with_raw_value(self.spsr_irq));
+            write_fiq_gprs(&self.r_fiq);
+            SpFiq::write(self.sp_fiq);
+            LrFiq::write(self.lr_fiq);
+            SpsrFiq::write(Cpsr::new_with_raw_value(self.spsr_fiq));
+        }
+    }
+}
+
+impl El1Registers {
+    fn save(&mut self) {
+        self.sctlr = <Sctlr as SysRegRead>::read_raw();
+        self.cpacr = <Cpacr as SysRegRead>::read_raw();
+        self.vbar = <Vbar as SysRegRead>::read_raw();
+        self.contextidr = <Contextidr as SysRegRead>::read_raw();
+        self.tpidrprw = <Tpidrprw as SysRegRead>::read_raw();
+        self.tpidrurw = <Tpidrurw as SysRegRead>::read_raw();
+        self.tpidruro = <Tpidruro as SysRegRead>::read_raw();
+        self.dfar = <Dfar as SysRegRead>::read_raw();
+        self.ifar = <Ifar as SysRegRead>::read_raw();
+        #[cfg(armv5te_or_higher)]
+        {
+            self.dfsr = <Dfsr as SysRegRead>::read_raw();
+            self.ifsr = <Ifsr as SysRegRead>::read_raw();
+        }
+        self.mair = [
+            <Mair0 as SysRegRead>::read_raw(),
+            <Mair1 as SysRegRead>::read_raw(),
+        ];
+        self.prselr = <Prselr as SysRegRead>::read_raw();
+        for (idx, region) in self.regions.iter_mut().enumerate().take(mpu_regions()) {
+            select_region(idx as u32);
+            *region = MpuRegion {
+                prbar: <Prbar as SysRegRead>::read_raw(),
+                prlar: <Prlar as SysRegRead>::read_raw(),
+            };
+        }
+        self.vsctlr = <Vsctlr as SysRegRead>::read_raw();
+        self.vmpidr = <Vmpidr as SysRegRead>::read_raw();
+        self.vpidr = <Vpidr as SysRegRead>::read_raw();
+    }
+
+    unsafe fn restore(&self) {
+        for (idx, region) in self.regions.iter().enumerate().take(mpu_regions()) {
+            select_region(idx as u32);
+            unsafe {
+                <Prbar as SysRegWrite>::write_raw(region.prbar);
+                <Prlar as SysRegWrite>::write_raw(region.prlar);
+            }
+        }
+        unsafe {
+            <Prselr as SysRegWrite>::write_raw(self.prselr);
+            <Sctlr as SysRegWrite>::write_raw(self.sctlr);
+            <Cpacr as SysRegWrite>::write_raw(self.cpacr);
+            <Vbar as SysRegWrite>::write_raw(self.vbar);
+            <Contextidr as SysRegWrite>::write_raw(self.contextidr);
+            <Tpidrprw as SysRegWrite>::write_raw(self.tpidrprw);
+            <Tpidrurw as SysRegWrite>::write_raw(self.tpidrurw);
+            <Tpidruro as SysRegWrite>::write_raw(self.tpidruro);
+            <Dfar as SysRegWrite>::write_raw(self.dfar);
+            <Ifar as SysRegWrite>::write_raw(self.ifar);
+            #[cfg(armv5te_or_higher)]
+            {
+                <Dfsr as SysRegWrite>::write_raw(self.dfsr);
+                <Ifsr as SysRegWrite>::write_raw(self.ifsr);
+            }
+            <Mair0 as SysRegWrite>::write_raw(self.mair[0]);
+            <Mair1 as SysRegWrite>::write_raw(self.mair[1]);
+            <Vsctlr as SysRegWrite>::write_raw(self.vsctlr);
+            <Vmpidr as SysRegWrite>::write_raw(self.vmpidr);
+            <Vpidr as SysRegWrite>::write_raw(self.vpidr);
+        }
+    }
+}
+
+/// How many EL1 MPU regions we save and restore
+fn mpu_regions() -> usize {
+    usize::from(Mpuir::read().dregions()).min(MAX_MPU_REGIONS)
+}
+
+/// Select an EL1 MPU region, so it appears in PRBAR and PRLAR
+fn select_region(idx: u32) {
+    unsafe {
+        <Prselr as SysRegWrite>::write_raw(idx);
+    }
+    #[cfg(target_arch = "arm")]
+    crate::asm::isb();
+}
+
+/// Read R8_fiq to R12_fiq
+fn read_fiq_gprs() -> [u32; 5] {
+    #[allow(unused_mut)]
+    let mut r = [0; 5];
+    #[cfg(arm_architecture = "v8-r")]
+    unsafe {
+        core::arch::asm!(
+            ".arch_extension virt",
+            "mrs {0}, r8_fiq",
+            "mrs {1}, r9_fiq",
+            "mrs {2}, r10_fiq",
+            "mrs {3}, r11_fiq",
+            "mrs {4}, r12_fiq",
+            out(reg) r[0],
+            out(reg) r[1],
+            out(reg) r[2],
+            out(reg) r[3],
+            out(reg) r[4],
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+    r
+}
+
+/// Write R8_fiq to R12_fiq
+///
+/// # Safety
+///
+/// FIQ mode will use these values when it next runs.
+unsafe fn write_fiq_gprs(_r: &[u32; 5]) {
+    #[cfg(arm_architecture = "v8-r")]
+    unsafe {
+        core::arch::asm!(
+            ".arch_extension virt",
+            "msr r8_fiq, {0}",
+            "msr r9_fiq, {1}",
+            "msr r10_fiq, {2}",
+            "msr r11_fiq, {3}",
+            "msr r12_fiq, {4}",
+            in(reg) _r[0],
+            in(reg) _r[1],
+            in(reg) _r[2],
+            in(reg) _r[3],
+            in(reg) _r[4],
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+}
+
+/// Saves the host's D8-D15 and FPSCR, and loads the guest's D0-D15 and FPSCR
+///
+/// R0 holds the `GuestRegisters` pointer, and R1 and R2 are free to use.
+#[cfg(all(arm_architecture = "v8-r", target_abi = "eabihf"))]
+macro_rules! fpu_enter {
+    () => {
+        r#"
+        vmrs    r1, fpscr                 // save the host's FPSCR
+        push    {{ r1, r2 }}              // (r2 is padding, to keep SP 8-byte aligned)
+        vpush   {{ d8-d15 }}              // save the host's callee-saved FPU registers
+        add     r1, r0, #{d_offset}       //
+        vldm    r1, {{ d0-d15 }}          // load the guest's FPU registers
+        ldr     r1, [r0, #{fpscr_offset}] //
+        vmsr    fpscr, r1                 // load the guest's FPSCR
+        "#
+    };
+}
+
+/// Saves the guest's D0-D15 and FPSCR, and restores the host's D8-D15 and FPSCR
+///
+/// R1 holds the `GuestRegisters` pointer, and R2 and R3 are free to use.
+#[cfg(all(arm_architecture = "v8-r", target_abi = "eabihf"))]
+macro_rules! fpu_exit {
+    () => {
+        r#"
+        add     r2, r1, #{d_offset}       //
+        vstm    r2, {{ d0-d15 }}          // save the guest's FPU registers
+        vmrs    r2, fpscr                 //
+        str     r2, [r1, #{fpscr_offset}] // save the guest's FPSCR
+        vpop    {{ d8-d15 }}              // restore the host's callee-saved FPU registers
+        pop     {{ r2, r3 }}              //
+        vmsr    fpscr, r2                 // restore the host's FPSCR
+        "#
+    };
+}
+
+/// How many bytes `fpu_enter!` pushes
+#[cfg(all(arm_architecture = "v8-r", target_abi = "eabihf"))]
+const FPU_FRAME_SIZE: usize = 72;
+
+#[cfg(all(arm_architecture = "v8-r", not(target_abi = "eabihf")))]
+macro_rules! fpu_enter {
+    () => {
+        ""
+    };
+}
+
+#[cfg(all(arm_architecture = "v8-r", not(target_abi = "eabihf")))]
+macro_rules! fpu_exit {
+    () => {
+        ""
+    };
+}
+
+#[cfg(all(arm_architecture = "v8-r", not(target_abi = "eabihf")))]
+const FPU_FRAME_SIZE: usize = 0;
+
+// Entering and leaving the guest.
+//
+// `_aarch32_cpu_vcpu_enter` is called like
+// `extern "C" fn _aarch32_cpu_vcpu_enter(regs: *mut GuestRegisters) -> u32;`.
+// It saves the host's callee-saved registers on the Hyp mode stack, loads the
+// guest's core registers, and does an `eret`.
+//
+// The guest can't change SP_hyp, so when it next traps to Hyp mode through
+// `_aarch32_cpu_vcpu_vectors` we find everything where we left it. The exit
+// path saves the guest's core registers, restores the host's, and returns to
+// whoever called `_aarch32_cpu_vcpu_enter`, with the reason for the exit in R0.
+#[cfg(arm_architecture = "v8-r")]
+core::arch::global_asm!(
+    r#"
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3
+    .arch_extension virt
+
+    .section .text._aarch32_cpu_vcpu_vectors
+    .arm
+    .balign 32
+    .global _aarch32_cpu_vcpu_vectors
+    .type _aarch32_cpu_vcpu_vectors, %function
+    _aarch32_cpu_vcpu_vectors:
+        // We should never take these, as they come from Hyp mode itself
+        b       .                         // Reset
+        b       .                         // Undefined Instruction
+        b       .                         // Hypervisor Call
+        b       .                         // Prefetch Abort
+        b       .                         // Data Abort
+        // These come from the guest
+        b       1f                        // Hyp Trap
+        b       2f                        // IRQ
+        b       3f                        // FIQ
+    1:
+        push    {{ r0, r1 }}              // make room to work
+        mov     r0, #{exit_trap}          //
+        b       _aarch32_cpu_vcpu_exit
+    2:
+        push    {{ r0, r1 }}              // make room to work
+        mov     r0, #{exit_irq}           //
+        b       _aarch32_cpu_vcpu_exit
+    3:
+        push    {{ r0, r1 }}              // make room to work
+        mov     r0, #{exit_fiq}           //
+        b       _aarch32_cpu_vcpu_exit
+    .size _aarch32_cpu_vcpu_vectors, . - _aarch32_cpu_vcpu_vectors
+
+    .section .text._aarch32_cpu_vcpu_enter
+    .arm
+    .global _aarch32_cpu_vcpu_enter
+    .type _aarch32_cpu_vcpu_enter, %function
+    _aarch32_cpu_vcpu_enter:
+        push    {{ r0, r4-r11, lr }}      // save the host's registers, and the GuestRegisters pointer
+    "#,
+    fpu_enter!(),
+    r#"
+        ldr     r1, [r0, #{sp_offset}]    //
+        msr     sp_usr, r1                // load the guest's SP
+        ldr     lr, [r0, #{lr_offset}]    // load the guest's LR (Hyp mode shares LR_usr)
+        ldr     r1, [r0, #{pc_offset}]    //
+        msr     elr_hyp, r1               // where the guest resumes
+        ldr     r1, [r0, #{cpsr_offset}]  //
+        msr     spsr_hyp, r1              // the mode and state the guest resumes in
+        ldm     r0, {{ r0-r12 }}          // load the guest's R0-R12
+        eret                              // into the guest
+    .size _aarch32_cpu_vcpu_enter, . - _aarch32_cpu_vcpu_enter
+
+    .section .text._aarch32_cpu_vcpu_exit
+    .arm
+    .type _aarch32_cpu_vcpu_exit, %function
+    _aarch32_cpu_vcpu_exit:
+        // The guest's R0 and R1 are on the stack, R0 holds the exit reason,
+        // and everything else still belongs to the guest.
+        ldr     r1, [sp, #{regs_ptr_offset}] // r1 = GuestRegisters pointer
+        add     r1, r1, #8                //
+        stm     r1, {{ r2-r12 }}          // save the guest's R2-R12
+        sub     r1, r1, #8                //
+        pop     {{ r2, r3 }}              //
+        stm     r1, {{ r2, r3 }}          // save the guest's R0 and R1
+        mrs     r2, sp_usr                //
+        str     r2, [r1, #{sp_offset}]    // save the guest's SP
+        str     lr, [r1, #{lr_offset}]    // save the guest's LR
+        mrs     r2, elr_hyp               //
+        str     r2, [r1, #{pc_offset}]    // save where the guest stopped
+        mrs     r2, spsr_hyp              //
+        str     r2, [r1, #{cpsr_offset}]  // save the guest's CPSR
+    "#,
+    fpu_exit!(),
+    r#"
+        pop     {{ r1, r4-r11, pc }}      // restore the host's registers, and return the reason in R0
+    .size _aarch32_cpu_vcpu_exit, . - _aarch32_cpu_vcpu_exit
+    "#,
+    exit_trap = const EXIT_TRAP,
+    exit_irq = const EXIT_IRQ,
+    exit_fiq = const EXIT_FIQ,
+    regs_ptr_offset = const 8 + FPU_FRAME_SIZE,
+    sp_offset = const core::mem::offset_of!(GuestRegisters, sp),
+    lr_offset = const core::mem::offset_of!(GuestRegisters, lr),
+    pc_offset = const core::mem::offset_of!(GuestRegisters, pc),
+    cpsr_offset = const core::mem::offset_of!(GuestRegisters, cpsr),
+    d_offset = const core::mem::offset_of!(GuestRegisters, d),
+    fpscr_offset = const core::mem::offset_of!(GuestRegisters, fpscr),
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::register::hyp::hsr::ExceptionClass;
+    use crate::register::CntvCval;
+    use crate::sim::{self, Encoding};
+
+    #[test]
+    fn run_swaps_state() {
+        sim::reset();
+        // HVC, with a 32-bit instruction
+        sim::set(Encoding::of::<Hsr>(), 0x4A00_0000);
+        sim::set(Encoding::of::<Hvbar>(), 0x1000);
+        // Two EL1 MPU regions, selected by PRSELR
+        sim::set(Encoding::of::<Mpuir>(), 2 << 8);
+        sim::add_bank(
+            Encoding::of::<Prselr>(),
+            &[Encoding::of::<Prbar>(), Encoding::of::<Prlar>()],
+        );
+
+        let mut vcpu = Vcpu::new(0x0800_0001);
+        assert_eq!(vcpu.regs.pc, 0x0800_0000);
+        let cpsr = Cpsr::new_with_raw_value(vcpu.regs.cpsr);
+        assert_eq!(cpsr.mode(), Ok(ProcessorMode::Svc));
+        assert!(cpsr.t());
+
+        vcpu.el1.sctlr = 0x30C5_0838;
+        vcpu.el1.vmpidr = 0x8000_0001;
+        vcpu.el1.regions[1] = MpuRegion {
+            prbar: 0x2000_0002,
+            prlar: 0x2000_FFC1,
+        };
+        vcpu.timer.cval = 0x1_0000_0000;
+        let el1 = vcpu.el1.clone();
+
+        match unsafe { vcpu.run() } {
+            Exit::Trap(hsr) => assert_eq!(hsr.ec(), Ok(ExceptionClass::Hvc)),
+            exit => panic!("unexpected exit {:?}", exit),
+        }
+
+        assert_eq!(sim::get(Encoding::of::<Sctlr>()), 0x30C5_0838);
+        assert_eq!(sim::get(Encoding::of::<Vmpidr>()), 0x8000_0001);
+        assert_eq!(sim::get_banked(Encoding::of::<Prlar>(), 1), 0x2000_FFC1);
+        assert_eq!(sim::get(Encoding::of64::<CntvCval>()), 0x1_0000_0000);
+        assert_eq!(
+            sim::get(Encoding::of::<Hcr>()),
+            u64::from(vcpu.hcr.raw_value())
+        );
+        // We put HVBAR back, and saved the same EL1 state we loaded
+        assert_eq!(sim::get(Encoding::of::<Hvbar>()), 0x1000);
+        assert_eq!(vcpu.el1, el1);
+    }
+
+    #[test]
+    fn registers_follow_mode() {
+        sim::reset();
+        let mut vcpu = Vcpu::new(0x0800_0000);
+        vcpu.regs.r[8] = 8;
+        vcpu.regs.sp = 0x100;
+        vcpu.banked.r_fiq[0] = 0x88;
+        vcpu.banked.sp_svc = 0x200;
+        vcpu.banked.sp_fiq = 0x300;
+
+        // We start in Supervisor mode
+        assert_eq!(vcpu.register(8), 8);
+        assert_eq!(vcpu.register(13), 0x200);
+        vcpu.set_register(14, 0x0800_0100);
+        assert_eq!(vcpu.banked.lr_svc, 0x0800_0100);
+
+        vcpu.regs.cpsr = Cpsr::new_with_raw_value(vcpu.regs.cpsr)
+            .with_mode(ProcessorMode::Fiq)
+            .raw_value();
+        assert_eq!(vcpu.register(8), 0x88);
+        assert_eq!(vcpu.register(13), 0x300);
+
+        vcpu.skip_instruction(InstructionLength::ThirtyTwoBit);
+        assert_eq!(vcpu.register(15), 0x0800_0004);
+    }
+
+    #[test]
+    fn skip_advances_it_state() {
+        sim::reset();
+        /// Pull IT[7:0] out of a CPSR
+        fn it(cpsr: u32) -> u32 {
+            (cpsr >> 25 & 0b11) | (cpsr >> 10 & 0b11_1111) << 2
+        }
+
+        // In Thumb state, with three instructions left in an IT block
+        let mut vcpu = Vcpu::new(0x0800_0001);
+        vcpu.regs.cpsr |= 0b10 << 25 | 0b10_0010 << 10;
+        assert_eq!(it(vcpu.regs.cpsr), 0b1000_1010);
+
+        vcpu.skip_instruction(InstructionLength::SixteenBit);
+        assert_eq!(it(vcpu.regs.cpsr), 0b1001_0100);
+        vcpu.skip_instruction(InstructionLength::ThirtyTwoBit);
+        assert_eq!(it(vcpu.regs.cpsr), 0b1000_1000);
+        vcpu.skip_instruction(InstructionLength::SixteenBit);
+        assert_eq!(it(vcpu.regs.cpsr), 0);
+        assert_eq!(vcpu.regs.pc, 0x0800_0008);
+
+        // The rest of the CPSR is left alone
+        let cpsr = Cpsr::new_with_raw_value(vcpu.regs.cpsr);
+        assert_eq!(cpsr.mode(), Ok(ProcessorMode::Svc));
+        assert!(cpsr.t());
+    }
+}
@@ -0,0 +1,549 @@
+//! Virtual interrupt injection for guests
+//!
+//! There are two ways to give a guest interrupts:
+//!
+//! * [`Vgic`] uses the GICv3 virtual CPU interface. Virtual interrupts are
+//!   placed in the list registers (`ICH_LR<n>` and `ICH_LRC<n>`), and the guest
+//!   acknowledges and completes them through its own ICC_* registers without
+//!   trapping. When there are more pending interrupts than list registers, the
+//!   rest wait in a queue, and an underflow maintenance interrupt tells us when
+//!   there is room to move them in. The GICv3 virtualisation registers only
+//!   exist on Armv8-R, so this is only available there.
+//! * [`LegacyVgic`] is for processors without a GICv3 CPU interface. It drives
+//!   the virtual IRQ and FIQ lines in HCR (the VI and VF bits), and you
+//!   emulate the interrupt controller that the guest talks to, calling
+//!   [`LegacyVgic::acknowledge`] and [`LegacyVgic::end_of_interrupt`] as the
+//!   guest does.
+//!
+//! Either way, pass the HCR value you are going to run the guest with through
+//! `update_hcr` before entering the guest:
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::hyp::vgic::{Vgic, VirtualInterrupt};
+//!
+//! let mut vgic: Vgic<8> = Vgic::new();
+//! vgic.enable();
+//! vgic.inject(VirtualInterrupt::new(27)).unwrap();
+//! vcpu.hcr = vgic.update_hcr(vcpu.hcr);
+//! unsafe { vcpu.run() };
+//! ```
+
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+use arbitrary_int::u13;
+
+use crate::register::hyp::{Hcr, LrState};
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+use crate::register::hyp::{
+    IchEisr, IchElrsr, IchHcr, IchLr0, IchLr1, IchLr10, IchLr11, IchLr12, IchLr13, IchLr14,
+    IchLr15, IchLr2, IchLr3, IchLr4, IchLr5, IchLr6, IchLr7, IchLr8, IchLr9, IchLrc, IchLrc0,
+    IchLrc1, IchLrc10, IchLrc11, IchLrc12, IchLrc13, IchLrc14, IchLrc15, IchLrc2, IchLrc3, IchLrc4,
+    IchLrc5, IchLrc6, IchLrc7, IchLrc8, IchLrc9, IchMisr, IchVtr,
+};
+
+/// Errors that can occur when injecting a virtual interrupt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VgicError {
+    /// There is no room to hold another pending interrupt
+    #[error("too many virtual interrupts are pending")]
+    Full,
+}
+
+/// A virtual interrupt to give to a guest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VirtualInterrupt {
+    /// The INTID the guest sees
+    pub intid: u32,
+    /// The priority of the interrupt, where lower values are more urgent
+    pub priority: u8,
+    /// Deliver the interrupt as Group 1 (an IRQ) rather than Group 0 (an FIQ)
+    pub group1: bool,
+    /// The physical interrupt behind this one, if any
+    ///
+    /// When the guest deactivates the virtual interrupt, the GIC deactivates
+    /// this physical INTID too. It must be below 1020.
+    pub physical: Option<u16>,
+    /// Ask for a maintenance interrupt when the guest completes this interrupt
+    ///
+    /// This is ignored when `physical` is set.
+    pub notify_eoi: bool,
+}
+
+impl VirtualInterrupt {
+    /// Create a Group 1 interrupt of middling priority
+    pub const fn new(intid: u32) -> VirtualInterrupt {
+        VirtualInterrupt {
+            intid,
+            priority: 0x80,
+            group1: true,
+            physical: None,
+            notify_eoi: false,
+        }
+    }
+
+    /// Encode this interrupt for the upper half of a list register
+    #[cfg(any(test, doc, arm_architecture = "v8-r"))]
+    fn lrc(&self, state: LrState) -> IchLrc {
+        let lrc = IchLrc::new_with_raw_value(0)
+            .with_state(state)
+            .with_group1(self.group1)
+            .with_priority(self.priority);
+        match self.physical {
+            Some(pintid) => lrc.with_hw(true).with_pintid(u13::new(pintid)),
+            None => lrc.with_eoi(self.notify_eoi),
+        }
+    }
+}
+
+/// A fixed-size collection of virtual interrupts and their states
+#[derive(Debug)]
+struct Queue<const N: usize> {
+    entries: [Option<(VirtualInterrupt, LrState)>; N],
+}
+
+impl<const N: usize> Queue<N> {
+    const fn new() -> Queue<N> {
+        Queue { entries: [None; N] }
+    }
+
+    fn find(&self, intid: u32) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|e| matches!(e, Some((irq, _)) if irq.intid == intid))
+    }
+
+    fn state(&self, intid: u32) -> LrState {
+        match self.find(intid) {
+            Some(idx) => self.entries[idx].map_or(LrState::Invalid, |(_, s)| s),
+            None => LrState::Invalid,
+        }
+    }
+
+    fn push(&mut self, irq: VirtualInterrupt, state: LrState) -> Result<(), VgicError> {
+        let slot = self
+            .entries
+            .iter_mut()
+            .find(|e| e.is_none())
+            .ok_or(VgicError::Full)?;
+        *slot = Some((irq, state));
+        Ok(())
+    }
+
+    /// Find the most urgent interrupt in the given state
+    fn highest(&self, state: LrState) -> Option<usize> {
+        let mut best: Option<(usize, u8)> = None;
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if let Some((irq, s)) = entry {
+                if *s == state && best.is_none_or(|(_, p)| irq.priority < p) {
+                    best = Some((idx, irq.priority));
+                }
+            }
+        }
+        best.map(|(idx, _)| idx)
+    }
+
+    fn any_pending(&self, group1: bool) -> bool {
+        // An interrupt that is active as well can't be taken again until the
+        // guest has finished with it
+        self.entries
+            .iter()
+            .flatten()
+            .any(|(irq, s)| irq.group1 == group1 && *s == LrState::Pending)
+    }
+
+    #[cfg(any(test, doc, arm_architecture = "v8-r"))]
+    fn is_empty(&self) -> bool {
+        self.entries.iter().all(Option::is_none)
+    }
+}
+
+/// Read list register `n`, giving the virtual INTID and the upper half
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+fn read_lr(n: usize) -> (u32, IchLrc) {
+    match n {
+        0 => (IchLr0::read().0, IchLrc0::read()),
+        1 => (IchLr1::read().0, IchLrc1::read()),
+        2 => (IchLr2::read().0, IchLrc2::read()),
+        3 => (IchLr3::read().0, IchLrc3::read()),
+        4 => (IchLr4::read().0, IchLrc4::read()),
+        5 => (IchLr5::read().0, IchLrc5::read()),
+        6 => (IchLr6::read().0, IchLrc6::read()),
+        7 => (IchLr7::read().0, IchLrc7::read()),
+        8 => (IchLr8::read().0, IchLrc8::read()),
+        9 => (IchLr9::read().0, IchLrc9::read()),
+        10 => (IchLr10::read().0, IchLrc10::read()),
+        11 => (IchLr11::read().0, IchLrc11::read()),
+        12 => (IchLr12::read().0, IchLrc12::read()),
+        13 => (IchLr13::read().0, IchLrc13::read()),
+        14 => (IchLr14::read().0, IchLrc14::read()),
+        15 => (IchLr15::read().0, IchLrc15::read()),
+        _ => panic!("no list register {}", n),
+    }
+}
+
+/// Write list register `n`
+///
+/// The lower half goes first, so the interrupt only becomes valid once the
+/// INTID is in place.
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+fn write_lr(n: usize, intid: u32, lrc: IchLrc) {
+    // Safety: the list registers only affect what the guest sees through
+    // its virtual CPU interface
+    unsafe {
+        match n {
+            0 => (IchLr0::write(IchLr0(intid)), IchLrc0::write(lrc)),
+            1 => (IchLr1::write(IchLr1(intid)), IchLrc1::write(lrc)),
+            2 => (IchLr2::write(IchLr2(intid)), IchLrc2::write(lrc)),
+            3 => (IchLr3::write(IchLr3(intid)), IchLrc3::write(lrc)),
+            4 => (IchLr4::write(IchLr4(intid)), IchLrc4::write(lrc)),
+            5 => (IchLr5::write(IchLr5(intid)), IchLrc5::write(lrc)),
+            6 => (IchLr6::write(IchLr6(intid)), IchLrc6::write(lrc)),
+            7 => (IchLr7::write(IchLr7(intid)), IchLrc7::write(lrc)),
+            8 => (IchLr8::write(IchLr8(intid)), IchLrc8::write(lrc)),
+            9 => (IchLr9::write(IchLr9(intid)), IchLrc9::write(lrc)),
+            10 => (IchLr10::write(IchLr10(intid)), IchLrc10::write(lrc)),
+            11 => (IchLr11::write(IchLr11(intid)), IchLrc11::write(lrc)),
+            12 => (IchLr12::write(IchLr12(intid)), IchLrc12::write(lrc)),
+            13 => (IchLr13::write(IchLr13(intid)), IchLrc13::write(lrc)),
+            14 => (IchLr14::write(IchLr14(intid)), IchLrc14::write(lrc)),
+            15 => (IchLr15::write(IchLr15(intid)), IchLrc15::write(lrc)),
+            _ => panic!("no list register {}", n),
+        };
+    }
+}
+
+/// Gives a guest interrupts using the GICv3 list registers
+///
+/// Interrupts that don't fit in the list registers are held back, up to `N`
+/// of them.
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+#[derive(Debug)]
+pub struct Vgic<const N: usize> {
+    num_lrs: usize,
+    queue: Queue<N>,
+}
+
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+impl<const N: usize> Vgic<N> {
+    /// Create a vGIC that uses every list register ICH_VTR says we have
+    pub fn new() -> Vgic<N> {
+        Vgic {
+            num_lrs: IchVtr::read().num_list_registers().min(16),
+            queue: Queue::new(),
+        }
+    }
+
+    /// How many list registers are in use
+    pub fn num_list_registers(&self) -> usize {
+        self.num_lrs
+    }
+
+    /// Turn on the virtual CPU interface (ICH_HCR.En)
+    pub fn enable(&self) {
+        // Safety: this only affects the guest's virtual CPU interface
+        unsafe { IchHcr::write(IchHcr::read().with_en(true)) }
+    }
+
+    /// Make a virtual interrupt pending
+    ///
+    /// An interrupt the guest is already handling is made pending again, and
+    /// one that is already pending is left alone. If every list register is
+    /// busy, the interrupt is queued until one is free.
+    pub fn inject(&mut self, irq: VirtualInterrupt) -> Result<(), VgicError> {
+        for n in 0..self.num_lrs {
+            let (intid, lrc) = read_lr(n);
+            if lrc.state() == LrState::Invalid || intid != irq.intid {
+                continue;
+            }
+            // A hardware interrupt can't fire again until the guest has
+            // deactivated it, so there's nothing to do for those
+            if lrc.state() == LrState::Active && !lrc.hw() {
+                write_lr(n, intid, lrc.with_state(LrState::PendingActive));
+            }
+            return Ok(());
+        }
+        if self.queue.find(irq.intid).is_some() {
+            return Ok(());
+        }
+        match self.free_lr() {
+            Some(n) => write_lr(n, irq.intid, irq.lrc(LrState::Pending)),
+            None => {
+                self.queue.push(irq, LrState::Pending)?;
+                self.set_underflow(true);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the state of a virtual interrupt
+    ///
+    /// Queued interrupts are reported as pending.
+    pub fn state(&self, intid: u32) -> LrState {
+        for n in 0..self.num_lrs {
+            let (lr_intid, lrc) = read_lr(n);
+            if lrc.state() != LrState::Invalid && lr_intid == intid {
+                return lrc.state();
+            }
+        }
+        self.queue.state(intid)
+    }
+
+    /// Handle a maintenance interrupt
+    ///
+    /// `on_eoi` is called with the INTID of each interrupt the guest has
+    /// completed that was injected with `notify_eoi` set. Those list
+    /// registers, and any others that have emptied, are then refilled from
+    /// the queue. Returns the reasons for the maintenance interrupt.
+    pub fn handle_maintenance<F>(&mut self, mut on_eoi: F) -> IchMisr
+    where
+        F: FnMut(u32),
+    {
+        let misr = IchMisr::read();
+        if misr.eoi() {
+            let eisr = IchEisr::read();
+            for n in 0..self.num_lrs {
+                if eisr.has_eoi(n) {
+                    let (intid, _) = read_lr(n);
+                    write_lr(n, 0, IchLrc::new_with_raw_value(0));
+                    on_eoi(intid);
+                }
+            }
+        }
+        self.refill();
+        misr
+    }
+
+    /// Set up HCR so the guest's interrupts go through the virtual CPU
+    /// interface
+    ///
+    /// This routes physical IRQs and FIQs to Hyp mode, and clears the legacy
+    /// virtual IRQ and FIQ lines.
+    pub fn update_hcr(&self, hcr: Hcr) -> Hcr {
+        hcr.with_imo(true)
+            .with_fmo(true)
+            .with_vi(false)
+            .with_vf(false)
+    }
+
+    fn free_lr(&self) -> Option<usize> {
+        let elrsr = IchElrsr::read();
+        (0..self.num_lrs).find(|&n| elrsr.is_empty(n))
+    }
+
+    /// Move queued interrupts into free list registers, most urgent first
+    fn refill(&mut self) {
+        while let Some(idx) = self.queue.highest(LrState::Pending) {
+            let Some(n) = self.free_lr() else {
+                break;
+            };
+            if let Some((irq, _)) = self.queue.entries[idx].take() {
+                write_lr(n, irq.intid, irq.lrc(LrState::Pending));
+            }
+        }
+        self.set_underflow(!self.queue.is_empty());
+    }
+
+    /// Ask for a maintenance interrupt when the list registers are nearly
+    /// empty (ICH_HCR.UIE)
+    fn set_underflow(&self, enable: bool) {
+        // Safety: this only affects the guest's virtual CPU interface
+        unsafe { IchHcr::write(IchHcr::read().with_uie(enable)) }
+    }
+}
+
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+impl<const N: usize> Default for Vgic<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gives a guest interrupts using the virtual IRQ and FIQ lines in HCR
+///
+/// This tracks up to `N` pending or active interrupts. You emulate the
+/// guest's interrupt controller: when it acknowledges an interrupt, call
+/// [`LegacyVgic::acknowledge`], and when it completes one, call
+/// [`LegacyVgic::end_of_interrupt`].
+#[derive(Debug)]
+pub struct LegacyVgic<const N: usize> {
+    queue: Queue<N>,
+}
+
+impl<const N: usize> LegacyVgic<N> {
+    /// Create a virtual interrupt controller with nothing pending
+    pub const fn new() -> LegacyVgic<N> {
+        LegacyVgic {
+            queue: Queue::new(),
+        }
+    }
+
+    /// Make a virtual interrupt pending
+    ///
+    /// An interrupt the guest is already handling is made pending again, and
+    /// one that is already pending is left alone.
+    pub fn inject(&mut self, irq: VirtualInterrupt) -> Result<(), VgicError> {
+        match self.queue.find(irq.intid) {
+            Some(idx) => {
+                if let Some((_, state @ LrState::Active)) = &mut self.queue.entries[idx] {
+                    *state = LrState::PendingActive;
+                }
+                Ok(())
+            }
+            None => self.queue.push(irq, LrState::Pending),
+        }
+    }
+
+    /// The guest has acknowledged an interrupt
+    ///
+    /// The most urgent pending interrupt becomes active, and is returned.
+    pub fn acknowledge(&mut self) -> Option<VirtualInterrupt> {
+        let idx = self.queue.highest(LrState::Pending)?;
+        let (irq, state) = self.queue.entries[idx].as_mut()?;
+        *state = LrState::Active;
+        Some(*irq)
+    }
+
+    /// The guest has completed an interrupt
+    ///
+    /// If the interrupt was made pending again while it was active, it goes
+    /// back to being pending.
+    pub fn end_of_interrupt(&mut self, intid: u32) {
+        if let Some(idx) = self.queue.find(intid) {
+            let entry = &mut self.queue.entries[idx];
+            match entry {
+                Some((_, state @ LrState::PendingActive)) => *state = LrState::Pending,
+                _ => *entry = None,
+            }
+        }
+    }
+
+    /// Get the state of a virtual interrupt
+    pub fn state(&self, intid: u32) -> LrState {
+        self.queue.state(intid)
+    }
+
+    /// Set up HCR to signal the guest's pending interrupts
+    ///
+    /// This routes physical IRQs and FIQs to Hyp mode, and asserts the virtual
+    /// IRQ (VI) or FIQ (VF) line while a Group 1 or Group 0 interrupt is
+    /// pending.
+    pub fn update_hcr(&self, hcr: Hcr) -> Hcr {
+        hcr.with_imo(true)
+            .with_fmo(true)
+            .with_vi(self.queue.any_pending(true))
+            .with_vf(self.queue.any_pending(false))
+    }
+}
+
+impl<const N: usize> Default for LegacyVgic<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sim::{self, Encoding};
+
+    /// Set up two list registers, with ICH_ELRSR following their states
+    fn two_list_registers() {
+        sim::reset();
+        sim::set(Encoding::of::<IchVtr>(), 1);
+        sim::on_read(Encoding::of::<IchElrsr>(), |regs| {
+            let mut elrsr = 0;
+            for (n, enc) in [Encoding::of::<IchLrc0>(), Encoding::of::<IchLrc1>()]
+                .into_iter()
+                .enumerate()
+            {
+                if regs.get(enc) >> 30 == 0 {
+                    elrsr |= 1 << n;
+                }
+            }
+            elrsr
+        });
+    }
+
+    #[test]
+    fn overflow_is_queued() {
+        two_list_registers();
+        let mut vgic: Vgic<4> = Vgic::new();
+        assert_eq!(vgic.num_list_registers(), 2);
+
+        let mut urgent = VirtualInterrupt::new(40);
+        urgent.priority = 0x10;
+        vgic.inject(VirtualInterrupt::new(27)).unwrap();
+        vgic.inject(VirtualInterrupt::new(30)).unwrap();
+        vgic.inject(VirtualInterrupt::new(33)).unwrap();
+        vgic.inject(urgent).unwrap();
+        vgic.inject(VirtualInterrupt::new(27)).unwrap();
+
+        assert_eq!(sim::get(Encoding::of::<IchLr0>()), 27);
+        assert_eq!(sim::get(Encoding::of::<IchLrc0>()), 0x5080_0000);
+        assert_eq!(sim::get(Encoding::of::<IchLr1>()), 30);
+        assert_eq!(vgic.state(33), LrState::Pending);
+        assert!(IchHcr::read().uie());
+
+        // The guest finishes with INTID 27
+        sim::set(Encoding::of::<IchLrc0>(), 0);
+        vgic.handle_maintenance(|_| panic!("no EOI maintenance was requested"));
+
+        assert_eq!(sim::get(Encoding::of::<IchLr0>()), 40);
+        assert_eq!(sim::get(Encoding::of::<IchLrc0>()), 0x5010_0000);
+        assert!(IchHcr::read().uie());
+        assert_eq!(vgic.state(27), LrState::Invalid);
+    }
+
+    #[test]
+    fn eoi_maintenance() {
+        two_list_registers();
+        let mut vgic: Vgic<4> = Vgic::new();
+        let mut irq = VirtualInterrupt::new(64);
+        irq.notify_eoi = true;
+        vgic.inject(irq).unwrap();
+        assert_eq!(sim::get(Encoding::of::<IchLrc0>()), 0x5080_0200);
+
+        // The guest makes it active, and it fires again
+        sim::set(Encoding::of::<IchLrc0>(), 0x9080_0200);
+        vgic.inject(irq).unwrap();
+        assert_eq!(vgic.state(64), LrState::PendingActive);
+
+        // The guest completes it
+        sim::set(Encoding::of::<IchLrc0>(), 0x1080_0200);
+        sim::set(Encoding::of::<IchMisr>(), 1);
+        sim::set(Encoding::of::<IchEisr>(), 1);
+        let mut completed = None;
+        let misr = vgic.handle_maintenance(|intid| completed = Some(intid));
+        assert!(misr.eoi());
+        assert_eq!(completed, Some(64));
+        assert_eq!(sim::get(Encoding::of::<IchLrc0>()), 0);
+        assert!(!IchHcr::read().uie());
+    }
+
+    #[test]
+    fn legacy_lines() {
+        let mut vgic: LegacyVgic<4> = LegacyVgic::new();
+        let mut fiq = VirtualInterrupt::new(5);
+        fiq.group1 = false;
+        vgic.inject(VirtualInterrupt::new(27)).unwrap();
+        vgic.inject(fiq).unwrap();
+
+        let hcr = vgic.update_hcr(Hcr::new_with_raw_value(0));
+        assert!(hcr.imo() && hcr.fmo() && hcr.vi() && hcr.vf());
+
+        assert_eq!(vgic.acknowledge().map(|irq| irq.intid), Some(27));
+        vgic.inject(VirtualInterrupt::new(27)).unwrap();
+        assert_eq!(vgic.state(27), LrState::PendingActive);
+        assert_eq!(vgic.acknowledge().map(|irq| irq.intid), Some(5));
+
+        let hcr = vgic.update_hcr(hcr);
+        assert!(!hcr.vi() && !hcr.vf());
+
+        vgic.end_of_interrupt(27);
+        vgic.end_of_interrupt(5);
+        assert_eq!(vgic.state(27), LrState::Pending);
+        assert_eq!(vgic.state(5), LrState::Invalid);
+        assert!(vgic.update_hcr(hcr).vi());
+    }
+}
@@ -51,3 +51,32 @@ where
     }
     result
 }
+
+/// An interrupt controller, which tells us which interrupt to handle
+///
+/// This is implemented by each of the interrupt controller drivers in this
+/// crate, so an IRQ handler can be written once for all of them.
+pub trait InterruptController {
+    /// Acknowledge the highest priority pending interrupt
+    ///
+    /// Gives the ID of the interrupt, or `None` if there wasn't one (or it was
+    /// spurious). Pass the ID to
+    /// [`end_of_interrupt`](InterruptController::end_of_interrupt) once the
+    /// interrupt has been handled.
+    fn acknowledge(&mut self) -> Option<u32>;
+
+    /// Tell the controller that we have finished handling an interrupt
+    fn end_of_interrupt(&mut self, id: u32);
+
+    /// Handle every pending interrupt, one at a time
+    fn handle_pending<F>(&mut self, mut handler: F)
+    where
+        F: FnMut(u32),
+        Self: Sized,
+    {
+        while let Some(id) = self.acknowledge() {
+            handler(id);
+            self.end_of_interrupt(id);
+        }
+    }
+}
@@ -6,9 +6,15 @@
 #![deny(clippy::unnecessary_safety_comment)]
 #![deny(clippy::unnecessary_safety_doc)]
 
+#[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+extern crate std;
+
 pub mod cache;
+pub mod cpu;
+pub mod el;
 pub mod interrupt;
 pub mod register;
+pub mod vic;
 
 #[cfg(any(doc, armv7_or_higher))]
 #[path = "asmv7.rs"]
@@ -18,9 +24,27 @@ pub mod asm;
 #[path = "asmv4.rs"]
 pub mod asm;
 
+#[cfg(any(test, doc, armv7_or_higher))]
+pub mod bench;
+
+#[cfg(any(test, doc, armv7_or_higher, arm_architecture = "v6"))]
+pub mod features;
+
+#[cfg(any(test, doc, arm_architecture = "v7-r", arm_architecture = "v7-a"))]
+pub mod gdbstub;
+
 #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
 pub mod generic_timer;
 
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod gicv2;
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+pub mod gicv3;
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+pub mod hyp;
+
 #[cfg(any(test, arm_profile = "a", arm_profile = "legacy"))]
 pub mod mmu;
 
@@ -30,9 +54,27 @@ pub mod pmsav7;
 #[cfg(any(test, arm_architecture = "v8-r"))]
 pub mod pmsav8;
 
+#[cfg(any(test, doc, armv7_or_higher))]
+pub mod pmu;
+
+#[cfg(any(test, doc, armv7_or_higher))]
+pub mod profiler;
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+pub mod psci;
+
+#[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+pub mod sim;
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+pub mod smccc;
+
 #[cfg(target_arch = "arm")]
 pub mod stacks;
 
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod stage2;
+
 mod critical_section;
 
 /// Generate an SVC call with no parameters.
@@ -239,7 +281,7 @@ macro_rules! svc6 {
 macro_rules! hvc {
     ($r0:expr) => {
         unsafe {
-            core::arch::asm!("hvc {arg}", arg = const $r0);
+            core::arch::asm!(".arch_extension virt", "hvc {arg}", arg = const $r0);
         }
     }
 }
@@ -260,6 +302,8 @@ macro_rules! hvc1 {
         let arg0: u32 = $arg0;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
@@ -286,6 +330,8 @@ macro_rules! hvc2 {
         let arg1: u32 = $arg1;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
@@ -314,6 +360,8 @@ macro_rules! hvc3 {
         let arg2: u32 = $arg2;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
@@ -344,6 +392,8 @@ macro_rules! hvc4 {
         let arg3: u32 = $arg3;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
@@ -376,6 +426,8 @@ macro_rules! hvc5 {
         let arg4: u32 = $arg4;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
@@ -410,6 +462,8 @@ macro_rules! hvc6 {
         let arg5: u32 = $arg5;
         unsafe {
             core::arch::asm!(
+                // Armv7-A needs this for HVC
+                ".arch_extension virt",
                 // Do the Hyper-call
                 "hvc     {arg}",
                 arg = const $num,
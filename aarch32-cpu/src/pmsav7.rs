@@ -65,7 +65,7 @@ impl Mpu {
         if idx >= self.num_iregions() {
             return None;
         }
-        register::Rgnr::write(register::Rgnr(idx as u32));
+        register::Rgnr::write(register::Rgnr::new_with_raw_value(0).with_region(idx));
         let base = register::Irbar::read().0 as *mut u8;
         let rsr = register::Irsr::read();
         let racr = register::Iracr::read();
@@ -96,7 +96,7 @@ impl Mpu {
         if idx >= self.num_dregions() {
             return None;
         }
-        register::Rgnr::write(register::Rgnr(idx as u32));
+        register::Rgnr::write(register::Rgnr::new_with_raw_value(0).with_region(idx));
         let base = register::Drbar::read().0 as *mut u8;
         let rsr = register::Drsr::read();
         let racr = register::Dracr::read();
@@ -130,7 +130,7 @@ impl Mpu {
             return Err(Error::TooManyRegions);
         }
         for (idx, region) in config.iregions.iter().enumerate() {
-            register::Rgnr::write(register::Rgnr(idx as u32));
+            register::Rgnr::write(register::Rgnr::new_with_raw_value(0).with_region(idx as u8));
             if !region.size.is_aligned(region.base) {
                 return Err(Error::UnalignedRegion(region.base));
             }
@@ -158,7 +158,7 @@ impl Mpu {
             if !region.size.is_aligned(region.base) {
                 return Err(Error::UnalignedRegion(region.base));
             }
-            register::Rgnr::write(register::Rgnr(idx as u32));
+            register::Rgnr::write(register::Rgnr::new_with_raw_value(0).with_region(idx as u8));
             register::Drbar::write(register::Drbar(region.base as u32));
             register::Drsr::write({
                 let mut out = register::Drsr::new_with_raw_value(0);
@@ -526,4 +526,77 @@ mod test {
         let mem_attr2 = mem_attr_bits.decode();
         assert_eq!(Some(mem_attr), mem_attr2);
     }
+
+    #[test]
+    fn configure_dregions() {
+        use crate::sim::{self, Access, Encoding};
+
+        sim::reset();
+        // no instruction regions, two data regions
+        sim::set(Encoding::of::<register::Mpuir>(), 2 << 8);
+        sim::add_bank(
+            Encoding::of::<register::Rgnr>(),
+            &[
+                Encoding::of::<register::Drbar>(),
+                Encoding::of::<register::Drsr>(),
+                Encoding::of::<register::Dracr>(),
+            ],
+        );
+
+        let regions = [
+            Region {
+                base: core::ptr::null_mut(),
+                size: RegionSize::_1M,
+                subregion_mask: 0,
+                enabled: true,
+                no_exec: false,
+                mem_attr: MemAttr::WriteBackWriteAlloc { shareable: false },
+                access_perms: AccessPerms::PrivReadWrite,
+            },
+            Region {
+                base: 0x2000_0000 as *mut u8,
+                size: RegionSize::_64K,
+                subregion_mask: 0x81,
+                enabled: true,
+                no_exec: true,
+                mem_attr: MemAttr::Device { shareable: true },
+                access_perms: AccessPerms::ReadWrite,
+            },
+        ];
+        let mut mpu = unsafe { Mpu::new() };
+        mpu.configure(&Config {
+            background_config: true,
+            iregions: &[],
+            dregions: &regions,
+        })
+        .unwrap();
+
+        let writes: std::vec::Vec<Encoding> = sim::take_log()
+            .into_iter()
+            .filter_map(|access| match access {
+                Access::Write(encoding, _) => Some(encoding),
+                Access::Read(..) => None,
+            })
+            .collect();
+        assert_eq!(
+            writes,
+            [
+                Encoding::of::<register::Rgnr>(),
+                Encoding::of::<register::Drbar>(),
+                Encoding::of::<register::Drsr>(),
+                Encoding::of::<register::Dracr>(),
+                Encoding::of::<register::Rgnr>(),
+                Encoding::of::<register::Drbar>(),
+                Encoding::of::<register::Drsr>(),
+                Encoding::of::<register::Dracr>(),
+                Encoding::of::<register::Sctlr>(),
+            ]
+        );
+        assert!(register::Sctlr::read().br());
+
+        for (idx, region) in regions.iter().enumerate() {
+            assert_eq!(mpu.get_dregion(idx as u8).as_ref(), Some(region));
+        }
+        assert_eq!(mpu.get_dregion(2), None);
+    }
 }
@@ -60,7 +60,7 @@ impl El1Mpu {
         if idx >= self.num_regions() {
             return None;
         }
-        register::Prselr::write(register::Prselr(idx as u32));
+        register::Prselr::write(register::Prselr::new_with_raw_value(0).with_region(idx));
         let prbar = register::Prbar::read();
         let prlar = register::Prlar::read();
         let start_addr = prbar.base_address();
@@ -101,7 +101,7 @@ impl El1Mpu {
         if region.mair > 7 {
             return Err(Error::InvalidMair(region.mair));
         }
-        register::Prselr::write(register::Prselr(idx as u32));
+        register::Prselr::write(register::Prselr::new_with_raw_value(0).with_region(idx));
         register::Prbar::write({
             let mut bar = register::Prbar::new_with_raw_value(0);
             bar.set_base(u26::from_u32(start >> 6));
@@ -238,7 +238,7 @@ impl El2Mpu {
         if idx >= self.num_regions() {
             return None;
         }
-        register::Hprselr::write(register::Hprselr(idx as u32));
+        register::Hprselr::write(register::Hprselr::new_with_raw_value(0).with_region(idx));
         let hprbar = register::Hprbar::read();
         let hprlar = register::Hprlar::read();
         let start_addr = (hprbar.base().value() << 6) as *const u8;
@@ -279,7 +279,7 @@ impl El2Mpu {
         if region.mair > 7 {
             return Err(Error::InvalidMair(region.mair));
         }
-        register::Hprselr::write(register::Hprselr(idx as u32));
+        register::Hprselr::write(register::Hprselr::new_with_raw_value(0).with_region(idx));
         register::Hprbar::write({
             let mut bar = register::Hprbar::new_with_raw_value(0);
             bar.set_base(u26::from_u32(start >> 6));
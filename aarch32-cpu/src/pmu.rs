@@ -0,0 +1,487 @@
+//! Support for the Performance Monitors Extension (PMU)
+//!
+//! See Chapter C12: The Performance Monitors Extension in [Arm Architecture
+//! Reference Manual ARMv7-A and ARMv7-R edition][armv7]
+//!
+//! [armv7]: https://developer.arm.com/documentation/ddi0406/latest
+
+use core::marker::PhantomData;
+
+use arbitrary_int::u5;
+
+use crate::register;
+
+/// Ways this API can fail
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// All the event counters are in use
+    NoCountersAvailable,
+}
+
+/// An event that a PMU event counter can count
+///
+/// These are the common architectural and microarchitectural events. Not
+/// every event is implemented on every processor - check [`Pmu::is_supported`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// Instruction architecturally executed, condition code check pass, software increment
+    SoftwareIncrement,
+    /// Level 1 instruction cache refill
+    L1ICacheRefill,
+    /// Level 1 instruction TLB refill
+    L1ITlbRefill,
+    /// Level 1 data cache refill
+    L1DCacheRefill,
+    /// Level 1 data cache access
+    L1DCacheAccess,
+    /// Level 1 data TLB refill
+    L1DTlbRefill,
+    /// Instruction architecturally executed, condition code check pass, load
+    LoadRetired,
+    /// Instruction architecturally executed, condition code check pass, store
+    StoreRetired,
+    /// Instruction architecturally executed
+    InstructionsRetired,
+    /// Exception taken
+    ExceptionTaken,
+    /// Instruction architecturally executed, condition code check pass, exception return
+    ExceptionReturn,
+    /// Instruction architecturally executed, condition code check pass, write to CONTEXTIDR
+    ContextIdWrite,
+    /// Instruction architecturally executed, condition code check pass, software change of the PC
+    PcWrite,
+    /// Instruction architecturally executed, immediate branch
+    BranchImmediate,
+    /// Instruction architecturally executed, condition code check pass, procedure return
+    BranchReturn,
+    /// Instruction architecturally executed, condition code check pass, unaligned load or store
+    UnalignedLoadStore,
+    /// Mispredicted or not predicted branch speculatively executed
+    BranchMispredicted,
+    /// Cycle
+    CpuCycles,
+    /// Predictable branch speculatively executed
+    BranchPredicted,
+    /// Data memory access
+    MemoryAccess,
+    /// Level 1 instruction cache access
+    L1ICacheAccess,
+    /// Level 1 data cache write-back
+    L1DCacheWriteback,
+    /// Level 2 data cache access
+    L2DCacheAccess,
+    /// Level 2 data cache refill
+    L2DCacheRefill,
+    /// Level 2 data cache write-back
+    L2DCacheWriteback,
+    /// Bus access
+    BusAccess,
+    /// Local memory error
+    MemoryError,
+    /// Operation speculatively executed
+    InstructionsSpeculated,
+    /// Instruction architecturally executed, condition code check pass, write to TTBR
+    TtbrWrite,
+    /// Bus cycle
+    BusCycles,
+    /// Some other (probably implementation defined) event number
+    Other(u16),
+}
+
+impl Event {
+    /// Get the event number, as written to PMXEVTYPER
+    pub const fn number(self) -> u16 {
+        match self {
+            Event::SoftwareIncrement => 0x00,
+            Event::L1ICacheRefill => 0x01,
+            Event::L1ITlbRefill => 0x02,
+            Event::L1DCacheRefill => 0x03,
+            Event::L1DCacheAccess => 0x04,
+            Event::L1DTlbRefill => 0x05,
+            Event::LoadRetired => 0x06,
+            Event::StoreRetired => 0x07,
+            Event::InstructionsRetired => 0x08,
+            Event::ExceptionTaken => 0x09,
+            Event::ExceptionReturn => 0x0A,
+            Event::ContextIdWrite => 0x0B,
+            Event::PcWrite => 0x0C,
+            Event::BranchImmediate => 0x0D,
+            Event::BranchReturn => 0x0E,
+            Event::UnalignedLoadStore => 0x0F,
+            Event::BranchMispredicted => 0x10,
+            Event::CpuCycles => 0x11,
+            Event::BranchPredicted => 0x12,
+            Event::MemoryAccess => 0x13,
+            Event::L1ICacheAccess => 0x14,
+            Event::L1DCacheWriteback => 0x15,
+            Event::L2DCacheAccess => 0x16,
+            Event::L2DCacheRefill => 0x17,
+            Event::L2DCacheWriteback => 0x18,
+            Event::BusAccess => 0x19,
+            Event::MemoryError => 0x1A,
+            Event::InstructionsSpeculated => 0x1B,
+            Event::TtbrWrite => 0x1C,
+            Event::BusCycles => 0x1D,
+            Event::Other(n) => n,
+        }
+    }
+}
+
+/// Controls which privilege levels a counter counts in
+///
+/// The default is to count at PL0 and PL1, but not in Hyp mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    /// Don't count at PL1
+    pub exclude_pl1: bool,
+    /// Don't count at PL0 (User mode)
+    pub exclude_user: bool,
+    /// Also count in Hyp mode
+    pub include_hyp: bool,
+}
+
+impl Filter {
+    /// Get the filter bits, as used in PMXEVTYPER and PMCCFILTR
+    const fn bits(self) -> u32 {
+        ((self.exclude_pl1 as u32) << 31)
+            | ((self.exclude_user as u32) << 30)
+            | ((self.include_hyp as u32) << 27)
+    }
+}
+
+/// An allocated event counter
+///
+/// Get one of these from [`Pmu::allocate`], and give it back with [`Pmu::free`].
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Counter {
+    index: u8,
+    event: Event,
+}
+
+impl Counter {
+    /// Which event counter (`PMEVCNTR<n>`) is this?
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Which event is this counter counting?
+    pub fn event(&self) -> Event {
+        self.event
+    }
+}
+
+/// PMSELR.SEL value for the cycle counter, and its bit in the PMCNTENSET etc.
+const CYCLE_COUNTER: u8 = 31;
+
+/// Represents the Performance Monitors for this core
+///
+/// Counters are only 32 bits wide, but if you call [`Pmu::handle_overflow`]
+/// from the PMU overflow interrupt handler, we extend them to 64 bits. The PMU
+/// interrupt number is specific to your SoC.
+///
+/// This type is not [Send] because it is a per-core type and should not be moved across
+/// cores on an SMP system.
+pub struct Pmu {
+    num_counters: u8,
+    allocated: u32,
+    high: [u32; 32],
+    _phantom: PhantomData<*const u8>,
+}
+
+impl Pmu {
+    /// Create a PMU handle
+    ///
+    /// All the counters are stopped and reset.
+    ///
+    /// # Safety
+    ///
+    /// Only create one of these at any given time, as they access shared
+    /// mutable state within the processor and do read-modify-writes on that state.
+    pub unsafe fn new() -> Pmu {
+        let pmcr = register::Pmcr::read();
+        let num_counters = pmcr.n().value();
+        let pmu = Pmu {
+            num_counters,
+            allocated: 0,
+            high: [0; 32],
+            _phantom: PhantomData,
+        };
+        unsafe {
+            register::Pmcntenclr::write(register::Pmcntenclr::new_with_raw_value(u32::MAX));
+            register::Pmintenclr::write(register::Pmintenclr::new_with_raw_value(u32::MAX));
+            register::Pmovsr::write(register::Pmovsr::new_with_raw_value(u32::MAX));
+            register::Pmcr::write(pmcr.with_e(false).with_p(true).with_c(true));
+        }
+        pmu
+    }
+
+    /// How many event counters are there (not including the cycle counter)?
+    pub fn num_counters(&self) -> u8 {
+        self.num_counters
+    }
+
+    /// Is this event supported by this processor?
+    ///
+    /// Only answers for common events (numbers below 0x40), and returns
+    /// `false` for anything else.
+    pub fn is_supported(&self, event: Event) -> bool {
+        match event.number() {
+            n @ 0..=31 => register::Pmceid0::read().id(usize::from(n)),
+            n @ 32..=63 => register::Pmceid1::read().id(usize::from(n - 32)),
+            _ => false,
+        }
+    }
+
+    /// Start all the enabled counters
+    pub fn start(&mut self) {
+        self.modify_pmcr(|pmcr| pmcr.with_e(true));
+    }
+
+    /// Stop all the counters
+    pub fn stop(&mut self) {
+        self.modify_pmcr(|pmcr| pmcr.with_e(false));
+    }
+
+    /// Reset all the event counters (but not the cycle counter) to zero
+    pub fn reset_counters(&mut self) {
+        self.modify_pmcr(|pmcr| pmcr.with_p(true));
+        for idx in 0..self.num_counters {
+            self.high[usize::from(idx)] = 0;
+        }
+        self.clear_overflow(self.all_counters_mask());
+    }
+
+    /// Find a free event counter and set it counting the given event
+    ///
+    /// The counter starts at zero, and is enabled, but will only count when
+    /// the PMU is started with [`Pmu::start`].
+    pub fn allocate(&mut self, event: Event) -> Result<Counter, Error> {
+        self.allocate_filtered(event, Filter::default())
+    }
+
+    /// Find a free event counter and set it counting the given event, at the given privilege levels
+    pub fn allocate_filtered(&mut self, event: Event, filter: Filter) -> Result<Counter, Error> {
+        let index = (0..self.num_counters)
+            .find(|idx| self.allocated & (1 << idx) == 0)
+            .ok_or(Error::NoCountersAvailable)?;
+        self.allocated |= 1 << index;
+        select(index);
+        unsafe {
+            register::Pmxevtyper::write(
+                register::Pmxevtyper::new_with_raw_value(filter.bits())
+                    .with_evt_count(event.number()),
+            );
+            register::Pmxevcntr::write(register::Pmxevcntr(0));
+        }
+        self.high[usize::from(index)] = 0;
+        self.clear_overflow(1 << index);
+        unsafe {
+            register::Pmcntenset::write(register::Pmcntenset::new_with_raw_value(1 << index));
+        }
+        Ok(Counter { index, event })
+    }
+
+    /// Stop an event counter and make it available again
+    pub fn free(&mut self, counter: Counter) {
+        let mask = 1 << counter.index;
+        unsafe {
+            register::Pmcntenclr::write(register::Pmcntenclr::new_with_raw_value(mask));
+            register::Pmintenclr::write(register::Pmintenclr::new_with_raw_value(mask));
+        }
+        self.clear_overflow(mask);
+        self.allocated &= !mask;
+    }
+
+    /// Read an event counter, extended to 64 bits
+    pub fn read(&self, counter: &Counter) -> u64 {
+        select(counter.index);
+        let low = register::Pmxevcntr::read().0;
+        self.extend(counter.index, low)
+    }
+
+    /// Set an event counter back to zero
+    pub fn reset(&mut self, counter: &Counter) {
+        select(counter.index);
+        unsafe {
+            register::Pmxevcntr::write(register::Pmxevcntr(0));
+        }
+        self.high[usize::from(counter.index)] = 0;
+        self.clear_overflow(1 << counter.index);
+    }
+
+    /// Set an event counter to the given value
+    ///
+    /// The bottom 32 bits go into the counter, and the top 32 bits are kept
+    /// in software.
+    pub fn set(&mut self, counter: &Counter, value: u64) {
+        select(counter.index);
+        unsafe {
+            register::Pmxevcntr::write(register::Pmxevcntr(value as u32));
+        }
+        self.high[usize::from(counter.index)] = (value >> 32) as u32;
+        self.clear_overflow(1 << counter.index);
+    }
+
+    /// Enable the cycle counter
+    ///
+    /// If `divide_by_64` is set, it counts once every 64 cycles.
+    pub fn enable_cycle_counter(&mut self, divide_by_64: bool) {
+        self.modify_pmcr(|pmcr| pmcr.with_d(divide_by_64));
+        unsafe {
+            register::Pmcntenset::write(register::Pmcntenset::new_with_raw_value(0).with_c(true));
+        }
+    }
+
+    /// Disable the cycle counter
+    pub fn disable_cycle_counter(&mut self) {
+        unsafe {
+            register::Pmcntenclr::write(register::Pmcntenclr::new_with_raw_value(0).with_c(true));
+        }
+    }
+
+    /// Choose which privilege levels the cycle counter counts in
+    ///
+    /// Requires PMUv2 or later.
+    pub fn set_cycle_filter(&mut self, filter: Filter) {
+        select(CYCLE_COUNTER);
+        unsafe {
+            register::Pmxevtyper::write(register::Pmxevtyper::new_with_raw_value(filter.bits()));
+        }
+    }
+
+    /// Read the cycle counter, extended to 64 bits
+    pub fn read_cycles(&self) -> u64 {
+        let low = register::Pmccntr::read().0;
+        self.extend(CYCLE_COUNTER, low)
+    }
+
+    /// Reset the cycle counter to zero
+    pub fn reset_cycles(&mut self) {
+        self.modify_pmcr(|pmcr| pmcr.with_c(true));
+        self.high[usize::from(CYCLE_COUNTER)] = 0;
+        self.clear_overflow(1 << CYCLE_COUNTER);
+    }
+
+    /// Set the cycle counter to the given value
+    pub fn set_cycles(&mut self, value: u64) {
+        unsafe {
+            register::Pmccntr::write(register::Pmccntr(value as u32));
+        }
+        self.high[usize::from(CYCLE_COUNTER)] = (value >> 32) as u32;
+        self.clear_overflow(1 << CYCLE_COUNTER);
+    }
+
+    /// Raise an interrupt when the given counter overflows
+    pub fn enable_overflow_interrupt(&mut self, counter: &Counter) {
+        unsafe {
+            register::Pmintenset::write(register::Pmintenset::new_with_raw_value(
+                1 << counter.index,
+            ));
+        }
+    }
+
+    /// Raise an interrupt when the cycle counter overflows
+    pub fn enable_cycle_overflow_interrupt(&mut self) {
+        unsafe {
+            register::Pmintenset::write(register::Pmintenset::new_with_raw_value(0).with_c(true));
+        }
+    }
+
+    /// Handle the PMU overflow interrupt
+    ///
+    /// Updates the top 32 bits of any counters which have overflowed, and
+    /// clears the overflow flags. Returns a bitmask of the counters which
+    /// overflowed, where bit 31 is the cycle counter.
+    pub fn handle_overflow(&mut self) -> u32 {
+        let overflowed =
+            register::Pmovsr::read().raw_value() & (self.all_counters_mask() | 1 << 31);
+        self.clear_overflow(overflowed);
+        for (idx, high) in self.high.iter_mut().enumerate() {
+            if overflowed & (1 << idx) != 0 {
+                *high = high.wrapping_add(1);
+            }
+        }
+        overflowed
+    }
+
+    /// Combine the top 32 bits we keep with the bottom 32 bits from the counter
+    fn extend(&self, index: u8, low: u32) -> u64 {
+        let pending = register::Pmovsr::read().raw_value() & (1 << index) != 0;
+        extend(self.high[usize::from(index)], low, pending)
+    }
+
+    fn all_counters_mask(&self) -> u32 {
+        (1u32 << self.num_counters).wrapping_sub(1)
+    }
+
+    fn clear_overflow(&mut self, mask: u32) {
+        unsafe {
+            register::Pmovsr::write(register::Pmovsr::new_with_raw_value(mask));
+        }
+    }
+
+    fn modify_pmcr<F>(&mut self, f: F)
+    where
+        F: FnOnce(register::Pmcr) -> register::Pmcr,
+    {
+        // Don't write back the write-only reset bits
+        let pmcr = register::Pmcr::read().with_p(false).with_c(false);
+        unsafe {
+            register::Pmcr::write(f(pmcr));
+        }
+    }
+}
+
+/// Select an event counter with PMSELR
+fn select(index: u8) {
+    unsafe {
+        register::Pmselr::write(register::Pmselr::new_with_raw_value(0).with_sel(u5::new(index)));
+    }
+    #[cfg(target_arch = "arm")]
+    crate::asm::isb();
+}
+
+/// Extend a 32-bit counter value to 64 bits
+///
+/// If the counter has overflowed but the interrupt hasn't been handled yet,
+/// a small `low` value belongs to the next `high` value.
+const fn extend(high: u32, low: u32, overflow_pending: bool) -> u64 {
+    let high = if overflow_pending && low < 0x8000_0000 {
+        high.wrapping_add(1)
+    } else {
+        high
+    };
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extend_counter() {
+        assert_eq!(extend(0, 5, false), 5);
+        assert_eq!(extend(2, 5, false), 0x2_0000_0005);
+        // overflowed, but the interrupt hasn't run yet
+        assert_eq!(extend(2, 5, true), 0x3_0000_0005);
+        // overflowed after we read the counter
+        assert_eq!(extend(2, 0xFFFF_FFF0, true), 0x2_FFFF_FFF0);
+    }
+
+    #[test]
+    fn filter_bits() {
+        assert_eq!(Filter::default().bits(), 0);
+        let filter = Filter {
+            exclude_pl1: true,
+            exclude_user: false,
+            include_hyp: true,
+        };
+        assert_eq!(filter.bits(), 0x8800_0000);
+    }
+}
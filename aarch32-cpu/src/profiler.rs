@@ -0,0 +1,344 @@
+//! A statistical profiler, driven by PMU overflow interrupts
+//!
+//! A PMU counter is set up to overflow every `period` events (or cycles). In
+//! the PMU overflow interrupt handler, you pass the interrupted PC and CPSR to
+//! [`Profiler::handle_interrupt`], which records them in a [`SampleBuffer`].
+//! Some other piece of code can then drain the buffer, and send the samples
+//! to a host for analysis.
+//!
+//! ```rust,ignore
+//! static SAMPLES: SampleBuffer<1024> = SampleBuffer::new();
+//!
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _irq_handler(frame: &aarch32_rt::IrqFrame) {
+//!     // check your interrupt controller says this is the PMU interrupt, then:
+//!     critical_section::with(|cs| {
+//!         let mut state = STATE.borrow_ref_mut(cs);
+//!         let (pmu, profiler) = state.as_mut().unwrap();
+//!         profiler.handle_interrupt(pmu, frame.pc, frame.cpsr, &SAMPLES);
+//!     });
+//! }
+//! ```
+//!
+//! ## Output format
+//!
+//! [`Header::encode`] produces a 16 byte header, which is:
+//!
+//! * The magic number `A32P` (4 bytes)
+//! * The format version, currently 1 (1 byte)
+//! * Reserved (1 byte)
+//! * The event number, or `0xFFFF` for cycles (2 bytes, little-endian)
+//! * The sampling period (4 bytes, little-endian)
+//! * The number of samples dropped because the buffer was full (4 bytes, little-endian)
+//!
+//! It is followed by any number of 5 byte samples from [`Sample::encode`],
+//! which are:
+//!
+//! * The interrupted PC (4 bytes, little-endian)
+//! * The bottom 8 bits of the interrupted CPSR, which gives the mode, the
+//!   Thumb bit, and the interrupt masks (1 byte)
+//!
+//! A host tool can then look up each PC in the ELF file's symbol table.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::pmu::{self, Counter, Event, Pmu};
+
+/// What the profiler counts, to decide when to take a sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Source {
+    /// Use the cycle counter
+    Cycles,
+    /// Use an event counter, counting the given event
+    Event(Event),
+}
+
+/// A single profiling sample
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sample {
+    /// The address of the interrupted instruction
+    pub pc: u32,
+    /// The CPSR of the interrupted code
+    pub cpsr: u32,
+}
+
+impl Sample {
+    /// The size of an encoded sample
+    pub const ENCODED_LEN: usize = 5;
+
+    /// Encode this sample in the compact binary format
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let pc = self.pc.to_le_bytes();
+        [pc[0], pc[1], pc[2], pc[3], self.cpsr as u8]
+    }
+}
+
+/// The header for a stream of encoded samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Header {
+    /// What was being counted
+    pub source: Source,
+    /// How many events between samples
+    pub period: u32,
+    /// How many samples were lost because the buffer was full
+    pub dropped: u32,
+}
+
+impl Header {
+    /// The size of an encoded header
+    pub const ENCODED_LEN: usize = 16;
+
+    /// The format version we produce
+    pub const VERSION: u8 = 1;
+
+    /// Encode this header in the compact binary format
+    pub fn encode(&self) -> [u8; Self::ENCODED_LEN] {
+        let event = match self.source {
+            Source::Cycles => 0xFFFF,
+            Source::Event(event) => event.number(),
+        };
+        let mut out = [0u8; Self::ENCODED_LEN];
+        out[0..4].copy_from_slice(b"A32P");
+        out[4] = Self::VERSION;
+        out[6..8].copy_from_slice(&event.to_le_bytes());
+        out[8..12].copy_from_slice(&self.period.to_le_bytes());
+        out[12..16].copy_from_slice(&self.dropped.to_le_bytes());
+        out
+    }
+}
+
+/// A lock-free ring buffer of samples
+///
+/// There must only be one producer (the interrupt handler) and one consumer
+/// (whatever calls [`SampleBuffer::pop`]) on any given core.
+///
+/// `N` must be a power of two, so the slot index stays in step when the read
+/// and write counters wrap.
+pub struct SampleBuffer<const N: usize> {
+    samples: [UnsafeCell<Sample>; N],
+    /// Where the next sample will be written. Only written by the producer.
+    head: AtomicU32,
+    /// Where the next sample will be read from. Only written by the consumer.
+    tail: AtomicU32,
+    dropped: AtomicU32,
+}
+
+// Safety: The producer only writes to slots the consumer has finished with,
+// and the consumer only reads slots the producer has finished with.
+unsafe impl<const N: usize> Sync for SampleBuffer<N> {}
+
+impl<const N: usize> SampleBuffer<N> {
+    /// Create a new, empty, buffer
+    pub const fn new() -> SampleBuffer<N> {
+        const { assert!(N.is_power_of_two(), "N must be a power of two") };
+        SampleBuffer {
+            samples: [const { UnsafeCell::new(Sample { pc: 0, cpsr: 0 }) }; N],
+            head: AtomicU32::new(0),
+            tail: AtomicU32::new(0),
+            dropped: AtomicU32::new(0),
+        }
+    }
+
+    /// Add a sample to the buffer
+    ///
+    /// Returns `false` (and counts a dropped sample) if the buffer is full.
+    ///
+    /// Only call this from one place, like an interrupt handler.
+    pub fn push(&self, sample: Sample) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head.wrapping_sub(tail) as usize >= N {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+        let slot = &self.samples[head as usize % N];
+        unsafe {
+            slot.get().write(sample);
+        }
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    /// Take the oldest sample from the buffer
+    ///
+    /// Only call this from one place, like your main loop.
+    pub fn pop(&self) -> Option<Sample> {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let slot = &self.samples[tail as usize % N];
+        let sample = unsafe { slot.get().read() };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Some(sample)
+    }
+
+    /// How many samples are waiting to be read?
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        head.wrapping_sub(tail) as usize
+    }
+
+    /// Is the buffer empty?
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// How many samples have been lost because the buffer was full?
+    pub fn dropped(&self) -> u32 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> Default for SampleBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A statistical profiler, using a PMU counter overflow interrupt
+pub struct Profiler {
+    source: Source,
+    counter: Option<Counter>,
+    period: u32,
+}
+
+impl Profiler {
+    /// Start profiling, taking a sample every `period` events
+    ///
+    /// You must route the PMU overflow interrupt to this core in your
+    /// interrupt controller, and call [`Profiler::handle_interrupt`] when it
+    /// fires. The PMU is started if it wasn't already.
+    pub fn start(pmu: &mut Pmu, source: Source, period: u32) -> Result<Profiler, pmu::Error> {
+        let counter = match source {
+            Source::Cycles => {
+                pmu.enable_cycle_counter(false);
+                pmu.enable_cycle_overflow_interrupt();
+                None
+            }
+            Source::Event(event) => {
+                let counter = pmu.allocate(event)?;
+                pmu.enable_overflow_interrupt(&counter);
+                Some(counter)
+            }
+        };
+        let profiler = Profiler {
+            source,
+            counter,
+            period,
+        };
+        profiler.rearm(pmu);
+        pmu.start();
+        Ok(profiler)
+    }
+
+    /// Stop profiling, and release the counter
+    pub fn stop(self, pmu: &mut Pmu) {
+        match self.counter {
+            Some(counter) => pmu.free(counter),
+            None => pmu.disable_cycle_counter(),
+        }
+    }
+
+    /// Get the header for the samples we are producing
+    pub fn header<const N: usize>(&self, buffer: &SampleBuffer<N>) -> Header {
+        Header {
+            source: self.source,
+            period: self.period,
+            dropped: buffer.dropped(),
+        }
+    }
+
+    /// Handle the PMU overflow interrupt
+    ///
+    /// Pass in the return state of the interrupted code. Returns `true` if a
+    /// sample was taken.
+    pub fn handle_interrupt<const N: usize>(
+        &self,
+        pmu: &mut Pmu,
+        pc: u32,
+        cpsr: u32,
+        buffer: &SampleBuffer<N>,
+    ) -> bool {
+        let overflowed = pmu.handle_overflow();
+        let bit = match &self.counter {
+            Some(counter) => counter.index(),
+            None => 31,
+        };
+        if overflowed & (1 << bit) == 0 {
+            return false;
+        }
+        self.rearm(pmu);
+        buffer.push(Sample { pc, cpsr });
+        true
+    }
+
+    /// Set our counter so it overflows after `period` more events
+    fn rearm(&self, pmu: &mut Pmu) {
+        let start = u64::from(self.period.wrapping_neg());
+        match &self.counter {
+            Some(counter) => pmu.set(counter, start),
+            None => pmu.set_cycles(start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ring_buffer() {
+        let buffer: SampleBuffer<2> = SampleBuffer::new();
+        assert!(buffer.push(Sample { pc: 1, cpsr: 0x1F }));
+        assert!(buffer.push(Sample { pc: 2, cpsr: 0x1F }));
+        assert!(!buffer.push(Sample { pc: 3, cpsr: 0x1F }));
+        assert_eq!(buffer.dropped(), 1);
+        assert_eq!(buffer.pop(), Some(Sample { pc: 1, cpsr: 0x1F }));
+        assert!(buffer.push(Sample { pc: 4, cpsr: 0x3F }));
+        assert_eq!(buffer.pop(), Some(Sample { pc: 2, cpsr: 0x1F }));
+        assert_eq!(buffer.pop(), Some(Sample { pc: 4, cpsr: 0x3F }));
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    fn counters_wrap() {
+        let buffer: SampleBuffer<4> = SampleBuffer::new();
+        buffer.head.store(u32::MAX - 1, Ordering::Relaxed);
+        buffer.tail.store(u32::MAX - 1, Ordering::Relaxed);
+        for pc in 0..4 {
+            assert!(buffer.push(Sample { pc, cpsr: 0x1F }));
+        }
+        assert!(!buffer.push(Sample { pc: 4, cpsr: 0x1F }));
+        assert_eq!(buffer.len(), 4);
+        for pc in 0..4 {
+            assert_eq!(buffer.pop(), Some(Sample { pc, cpsr: 0x1F }));
+        }
+        assert_eq!(buffer.pop(), None);
+    }
+
+    #[test]
+    fn encoding() {
+        let sample = Sample {
+            pc: 0x1234_5678,
+            cpsr: 0x6000_003F,
+        };
+        assert_eq!(sample.encode(), [0x78, 0x56, 0x34, 0x12, 0x3F]);
+        let header = Header {
+            source: Source::Event(Event::L1DCacheRefill),
+            period: 1000,
+            dropped: 2,
+        };
+        assert_eq!(
+            header.encode(),
+            [b'A', b'3', b'2', b'P', 1, 0, 3, 0, 0xE8, 0x03, 0, 0, 2, 0, 0, 0]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! A client for the Arm Power State Coordination Interface (PSCI)
+//!
+//! PSCI lets you turn cores on and off, and power off or reset the system, by
+//! asking firmware or a hypervisor to do it for you. The calls are made using
+//! the [SMC Calling Convention](crate::smccc).
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::psci::Psci;
+//!
+//! let psci = Psci::detect().unwrap();
+//! // Start core 1, in the same mode as us, at `secondary_entry`
+//! unsafe { psci.cpu_on(1, secondary_entry as usize, 0).unwrap() };
+//! ```
+//!
+//! See [Arm Power State Coordination Interface][psci].
+//!
+//! [psci]: https://developer.arm.com/documentation/den0022/latest/
+
+use crate::smccc::Conduit;
+
+/// The PSCI_VERSION function ID
+pub const PSCI_VERSION: u32 = 0x8400_0000;
+/// The CPU_OFF function ID
+pub const CPU_OFF: u32 = 0x8400_0002;
+/// The CPU_ON function ID (32-bit)
+pub const CPU_ON: u32 = 0x8400_0003;
+/// The AFFINITY_INFO function ID (32-bit)
+pub const AFFINITY_INFO: u32 = 0x8400_0004;
+/// The SYSTEM_OFF function ID
+pub const SYSTEM_OFF: u32 = 0x8400_0008;
+/// The SYSTEM_RESET function ID
+pub const SYSTEM_RESET: u32 = 0x8400_0009;
+
+/// Errors a PSCI call can return
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error {
+    /// The function is not implemented
+    NotSupported,
+    /// An argument was invalid
+    InvalidParameters,
+    /// The call was not allowed
+    Denied,
+    /// The core is already on
+    AlreadyOn,
+    /// The core is already being turned on
+    OnPending,
+    /// Something went wrong in the firmware
+    InternalFailure,
+    /// The core does not exist
+    NotPresent,
+    /// The core is disabled
+    Disabled,
+    /// The entry point address was invalid
+    InvalidAddress,
+    /// A value the PSCI specification doesn't define
+    Unknown(i32),
+}
+
+impl Error {
+    /// Convert a PSCI return code into a result
+    pub const fn check(code: i32) -> Result<u32, Error> {
+        match code {
+            0.. => Ok(code as u32),
+            -1 => Err(Error::NotSupported),
+            -2 => Err(Error::InvalidParameters),
+            -3 => Err(Error::Denied),
+            -4 => Err(Error::AlreadyOn),
+            -5 => Err(Error::OnPending),
+            -6 => Err(Error::InternalFailure),
+            -7 => Err(Error::NotPresent),
+            -8 => Err(Error::Disabled),
+            -9 => Err(Error::InvalidAddress),
+            _ => Err(Error::Unknown(code)),
+        }
+    }
+}
+
+/// A PSCI version number
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Version {
+    /// The major version
+    pub major: u16,
+    /// The minor version
+    pub minor: u16,
+}
+
+/// The power state of a core, as reported by AFFINITY_INFO
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AffinityState {
+    /// At least one core in the affinity instance is on
+    On,
+    /// Every core in the affinity instance is off
+    Off,
+    /// A core in the affinity instance is being turned on
+    OnPending,
+}
+
+/// A PSCI client
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Psci {
+    conduit: Conduit,
+}
+
+impl Psci {
+    /// Create a PSCI client which makes calls with the given conduit
+    pub const fn new(conduit: Conduit) -> Psci {
+        Psci { conduit }
+    }
+
+    /// Create a PSCI client, guessing which conduit to use
+    ///
+    /// See [`Conduit::detect`]. Returns `None` if there's no conduit, or if the
+    /// conduit doesn't answer PSCI_VERSION.
+    ///
+    /// Note that if we guess wrong, the call to PSCI_VERSION may cause an
+    /// Undefined Instruction exception.
+    pub fn detect() -> Option<Psci> {
+        let psci = Psci::new(Conduit::detect()?);
+        match Error::check(psci.call(PSCI_VERSION, [0; 5]) as i32) {
+            Ok(_) => Some(psci),
+            Err(_) => None,
+        }
+    }
+
+    /// Which conduit does this client use?
+    pub const fn conduit(&self) -> Conduit {
+        self.conduit
+    }
+
+    /// Get the version of PSCI that the firmware implements
+    pub fn psci_version(&self) -> Version {
+        let version = self.call(PSCI_VERSION, [0; 5]);
+        Version {
+            major: (version >> 16) as u16,
+            minor: version as u16,
+        }
+    }
+
+    /// Turn on a core
+    ///
+    /// The core is given by its MPIDR affinity fields. It starts at
+    /// `entry_point`, in the same mode and Security State as us, with the MMU
+    /// and caches off and `context_id` in `r0`.
+    ///
+    /// # Safety
+    ///
+    /// `entry_point` must be the physical address of code that can start a
+    /// core from cold - it will need its own stack.
+    pub unsafe fn cpu_on(
+        &self,
+        target_cpu: u32,
+        entry_point: usize,
+        context_id: u32,
+    ) -> Result<(), Error> {
+        Error::check(self.call(CPU_ON, [target_cpu, entry_point as u32, context_id, 0, 0]) as i32)
+            .map(|_| ())
+    }
+
+    /// Turn off this core
+    ///
+    /// Only returns if the core couldn't be turned off.
+    pub fn cpu_off(&self) -> Error {
+        Self::failure(self.call(CPU_OFF, [0; 5]))
+    }
+
+    /// Turn off the system
+    ///
+    /// Only returns if the system couldn't be turned off.
+    pub fn system_off(&self) -> Error {
+        Self::failure(self.call(SYSTEM_OFF, [0; 5]))
+    }
+
+    /// Reset the system
+    ///
+    /// Only returns if the system couldn't be reset.
+    pub fn system_reset(&self) -> Error {
+        Self::failure(self.call(SYSTEM_RESET, [0; 5]))
+    }
+
+    /// Find out whether a core (or group of cores) is on
+    ///
+    /// The cores are given by their MPIDR affinity fields, and
+    /// `lowest_affinity_level` says which of those fields to ignore (0 means
+    /// look at a single core).
+    pub fn affinity_info(
+        &self,
+        target_affinity: u32,
+        lowest_affinity_level: u32,
+    ) -> Result<AffinityState, Error> {
+        match Error::check(self.call(
+            AFFINITY_INFO,
+            [target_affinity, lowest_affinity_level, 0, 0, 0],
+        ) as i32)?
+        {
+            0 => Ok(AffinityState::On),
+            1 => Ok(AffinityState::Off),
+            2 => Ok(AffinityState::OnPending),
+            other => Err(Error::Unknown(other as i32)),
+        }
+    }
+
+    /// Make a call, and return `r0`
+    fn call(&self, function_id: u32, args: [u32; 5]) -> u32 {
+        // Safety: we only make the calls defined in this module, which are
+        // safe unless documented otherwise at the call site
+        unsafe { self.conduit.call(function_id, args)[0] }
+    }
+
+    /// Handle the result of a call that doesn't return if it works
+    fn failure(code: u32) -> Error {
+        match Error::check(code as i32) {
+            Err(e) => e,
+            Ok(value) => Error::Unknown(value as i32),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn return_codes() {
+        assert_eq!(Error::check(0), Ok(0));
+        assert_eq!(Error::check(-1), Err(Error::NotSupported));
+        assert_eq!(Error::check(-4), Err(Error::AlreadyOn));
+        assert_eq!(Error::check(-9), Err(Error::InvalidAddress));
+        assert_eq!(Error::check(-10), Err(Error::Unknown(-10)));
+        assert_eq!(Psci::failure(0), Error::Unknown(0));
+    }
+}
@@ -3,10 +3,13 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// HPRSELR (*Hyp Protection Region Selection Register*)
-#[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Hprselr(pub u32);
+pub struct Hprselr {
+    /// The selected region number
+    #[bits(0..=7, rw)]
+    region: u8,
+}
 
 impl SysReg for Hprselr {
     const CP: u32 = 15;
@@ -22,7 +25,7 @@ impl Hprselr {
     #[inline]
     /// Reads HPRSELR (*Hyp Protection Region Selection Register*)
     pub fn read() -> Hprselr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -35,7 +38,7 @@ impl Hprselr {
     /// Controls what appears in HPRLAR and HPRBAR
     pub fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
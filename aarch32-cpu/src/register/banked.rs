@@ -0,0 +1,301 @@
+//! Access to the banked SP, LR and SPSR of other processor modes
+//!
+//! Each processor mode (apart from System mode, which shares with User mode)
+//! has its own copy of some core registers. This module gives you a type for
+//! each one, like [`SpIrq`] or [`SpsrSvc`].
+//!
+//! There are two ways to access them:
+//!
+//! * `read` and `write` use the MRS (banked register) and MSR (banked
+//!   register) instructions. These are part of the Virtualization Extensions,
+//!   so they are only available on Armv7-A and Armv8-R. On an Armv7-A
+//!   processor without the Virtualization Extensions (like the Cortex-A9)
+//!   they are UNDEFINED - see
+//!   [`CpuFeatures::virtualization_extensions`](crate::features::CpuFeatures::virtualization_extensions).
+//! * `read_via_mode_switch` and `write_via_mode_switch` briefly switch into
+//!   the other mode, with interrupts masked, and then switch back. These work
+//!   on every processor, but only from a privileged mode other than Hyp mode.
+//!
+//! The Hyp mode registers can only be accessed with the banked register
+//! instructions, because you cannot switch into Hyp mode with an MSR.
+//!
+//! Using the banked register instructions to access the registers belonging
+//! to the current mode is UNPREDICTABLE, except for ELR_hyp, which Hyp mode
+//! may read and write this way. Hyp mode has no other way to get at it.
+//!
+//! Monitor mode only exists on processors with the Security Extensions, so
+//! the Monitor mode registers are only available on Armv6 and Armv7-A.
+
+use super::cpsr::ProcessorMode;
+use super::Cpsr;
+
+/// Values we can hold in a banked register
+trait Value {
+    fn from_raw(raw: u32) -> Self;
+    fn to_raw(self) -> u32;
+}
+
+impl Value for u32 {
+    fn from_raw(raw: u32) -> Self {
+        raw
+    }
+
+    fn to_raw(self) -> u32 {
+        self
+    }
+}
+
+impl Value for Cpsr {
+    fn from_raw(raw: u32) -> Self {
+        Cpsr::new_with_raw_value(raw)
+    }
+
+    fn to_raw(self) -> u32 {
+        self.raw_value()
+    }
+}
+
+/// Defines a type for a banked register
+///
+/// * `$sysm` is the register name for the banked MRS/MSR instructions.
+/// * `$switch_mode` is the mode we switch into to get at the register, if we
+///   can, and `$get`/`$set` are the instructions that copy it to/from `r1`.
+macro_rules! banked {
+    (
+        $(#[$attr:meta])*
+        $name:ident: $ty:ty, $mode:ident, $sysm:literal
+        $(, via $switch_mode:ident, $get:literal, $set:literal)?
+    ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+        pub struct $name;
+
+        impl $name {
+            /// The mode this register belongs to
+            pub const MODE: ProcessorMode = ProcessorMode::$mode;
+
+            #[doc = concat!("Read `", $sysm, "` with an MRS (banked register) instruction")]
+            #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+            #[inline]
+            pub fn read() -> $ty {
+                let r: u32;
+                #[cfg(any(arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+                unsafe {
+                    core::arch::asm!(
+                        ".arch_extension virt",
+                        concat!("mrs {r}, ", $sysm),
+                        r = out(reg) r,
+                        options(nomem, nostack, preserves_flags)
+                    );
+                }
+                #[cfg(not(any(arm_architecture = "v7-a", arm_architecture = "v8-r")))]
+                {
+                    r = 0;
+                }
+                <$ty as Value>::from_raw(r)
+            }
+
+            #[doc = concat!("Write `", $sysm, "` with an MSR (banked register) instruction")]
+            ///
+            /// # Safety
+            ///
+            /// The other mode will use this value when it next runs. You must
+            /// make sure it is valid for that mode.
+            #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+            #[inline]
+            pub unsafe fn write(value: $ty) {
+                let _raw = <$ty as Value>::to_raw(value);
+                #[cfg(any(arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+                unsafe {
+                    core::arch::asm!(
+                        ".arch_extension virt",
+                        concat!("msr ", $sysm, ", {r}"),
+                        r = in(reg) _raw,
+                        options(nomem, nostack, preserves_flags)
+                    );
+                }
+            }
+
+            $(
+                #[doc = concat!("Read `", $sysm, "` by switching into ", stringify!($switch_mode), " mode")]
+                ///
+                /// IRQs and FIQs are masked while we are in the other mode.
+                ///
+                /// # Safety
+                ///
+                /// You must be in a privileged mode other than Hyp mode. The
+                /// Monitor mode registers can only be accessed from Secure
+                /// state.
+                #[cfg_attr(not(feature = "check-asm"), inline)]
+                #[cfg_attr(armv6_or_lower, instruction_set(arm::a32))]
+                pub unsafe fn read_via_mode_switch() -> $ty {
+                    let r: u32;
+                    #[cfg(target_arch = "arm")]
+                    unsafe {
+                        // Only r0-r7 are never banked, so we pin our registers
+                        // to those.
+                        core::arch::asm!(
+                            "mrs r2, cpsr",
+                            "orr r3, r2, #0xC0",
+                            "bic r3, r3, #0x1F",
+                            "orr r3, r3, #{mode}",
+                            "msr cpsr_c, r3",
+                            $get,
+                            "msr cpsr_c, r2",
+                            mode = const ProcessorMode::$switch_mode as u32,
+                            out("r1") r,
+                            out("r2") _,
+                            out("r3") _,
+                            options(nomem, nostack, preserves_flags)
+                        );
+                    }
+                    #[cfg(not(target_arch = "arm"))]
+                    {
+                        r = 0;
+                    }
+                    <$ty as Value>::from_raw(r)
+                }
+
+                #[doc = concat!("Write `", $sysm, "` by switching into ", stringify!($switch_mode), " mode")]
+                ///
+                /// IRQs and FIQs are masked while we are in the other mode.
+                ///
+                /// # Safety
+                ///
+                /// You must be in a privileged mode other than Hyp mode. The
+                /// Monitor mode registers can only be accessed from Secure
+                /// state. The other mode will use this value when it next
+                /// runs, so you must make sure it is valid for that mode.
+                #[cfg_attr(not(feature = "check-asm"), inline)]
+                #[cfg_attr(armv6_or_lower, instruction_set(arm::a32))]
+                pub unsafe fn write_via_mode_switch(value: $ty) {
+                    let _raw = <$ty as Value>::to_raw(value);
+                    #[cfg(target_arch = "arm")]
+                    unsafe {
+                        core::arch::asm!(
+                            "mrs r2, cpsr",
+                            "orr r3, r2, #0xC0",
+                            "bic r3, r3, #0x1F",
+                            "orr r3, r3, #{mode}",
+                            "msr cpsr_c, r3",
+                            $set,
+                            "msr cpsr_c, r2",
+                            mode = const ProcessorMode::$switch_mode as u32,
+                            in("r1") _raw,
+                            out("r2") _,
+                            out("r3") _,
+                            options(nomem, nostack, preserves_flags)
+                        );
+                    }
+                }
+            )?
+        }
+    };
+}
+
+banked!(
+    /// SP_usr (*Stack Pointer for User and System mode*)
+    SpUsr: u32, Usr, "sp_usr", via Sys, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_usr (*Link Register for User and System mode*)
+    LrUsr: u32, Usr, "lr_usr", via Sys, "mov r1, lr", "mov lr, r1"
+);
+
+banked!(
+    /// SP_fiq (*Stack Pointer for FIQ mode*)
+    SpFiq: u32, Fiq, "sp_fiq", via Fiq, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_fiq (*Link Register for FIQ mode*)
+    LrFiq: u32, Fiq, "lr_fiq", via Fiq, "mov r1, lr", "mov lr, r1"
+);
+banked!(
+    /// SPSR_fiq (*Saved Program Status Register for FIQ mode*)
+    SpsrFiq: Cpsr, Fiq, "spsr_fiq", via Fiq, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+banked!(
+    /// SP_irq (*Stack Pointer for IRQ mode*)
+    SpIrq: u32, Irq, "sp_irq", via Irq, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_irq (*Link Register for IRQ mode*)
+    LrIrq: u32, Irq, "lr_irq", via Irq, "mov r1, lr", "mov lr, r1"
+);
+banked!(
+    /// SPSR_irq (*Saved Program Status Register for IRQ mode*)
+    SpsrIrq: Cpsr, Irq, "spsr_irq", via Irq, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+banked!(
+    /// SP_svc (*Stack Pointer for Supervisor mode*)
+    SpSvc: u32, Svc, "sp_svc", via Svc, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_svc (*Link Register for Supervisor mode*)
+    LrSvc: u32, Svc, "lr_svc", via Svc, "mov r1, lr", "mov lr, r1"
+);
+banked!(
+    /// SPSR_svc (*Saved Program Status Register for Supervisor mode*)
+    SpsrSvc: Cpsr, Svc, "spsr_svc", via Svc, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+banked!(
+    /// SP_abt (*Stack Pointer for Abort mode*)
+    SpAbt: u32, Abt, "sp_abt", via Abt, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_abt (*Link Register for Abort mode*)
+    LrAbt: u32, Abt, "lr_abt", via Abt, "mov r1, lr", "mov lr, r1"
+);
+banked!(
+    /// SPSR_abt (*Saved Program Status Register for Abort mode*)
+    SpsrAbt: Cpsr, Abt, "spsr_abt", via Abt, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+banked!(
+    /// SP_und (*Stack Pointer for Undefined mode*)
+    SpUnd: u32, Und, "sp_und", via Und, "mov r1, sp", "mov sp, r1"
+);
+banked!(
+    /// LR_und (*Link Register for Undefined mode*)
+    LrUnd: u32, Und, "lr_und", via Und, "mov r1, lr", "mov lr, r1"
+);
+banked!(
+    /// SPSR_und (*Saved Program Status Register for Undefined mode*)
+    SpsrUnd: Cpsr, Und, "spsr_und", via Und, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+#[cfg(any(test, doc, arm_architecture = "v6", arm_architecture = "v7-a"))]
+banked!(
+    /// SP_mon (*Stack Pointer for Monitor mode*)
+    SpMon: u32, Mon, "sp_mon", via Mon, "mov r1, sp", "mov sp, r1"
+);
+#[cfg(any(test, doc, arm_architecture = "v6", arm_architecture = "v7-a"))]
+banked!(
+    /// LR_mon (*Link Register for Monitor mode*)
+    LrMon: u32, Mon, "lr_mon", via Mon, "mov r1, lr", "mov lr, r1"
+);
+#[cfg(any(test, doc, arm_architecture = "v6", arm_architecture = "v7-a"))]
+banked!(
+    /// SPSR_mon (*Saved Program Status Register for Monitor mode*)
+    SpsrMon: Cpsr, Mon, "spsr_mon", via Mon, "mrs r1, spsr", "msr spsr_fsxc, r1"
+);
+
+banked!(
+    /// SP_hyp (*Stack Pointer for Hyp mode*)
+    SpHyp: u32, Hyp, "sp_hyp"
+);
+banked!(
+    /// ELR_hyp (*Exception Link Register for Hyp mode*)
+    ///
+    /// This holds the preferred return address for exceptions taken to Hyp
+    /// mode.
+    ElrHyp: u32, Hyp, "elr_hyp"
+);
+banked!(
+    /// SPSR_hyp (*Saved Program Status Register for Hyp mode*)
+    SpsrHyp: Cpsr, Hyp, "spsr_hyp"
+);
@@ -1,12 +1,47 @@
 //! Code for managing CLIDR (*Cache Level ID Register*)
 
+use arbitrary_int::{u2, u3};
+
 use crate::register::{SysReg, SysRegRead};
 
-/// CLIDR (*Cache Level ID Register*)
-#[derive(Debug, Copy, Clone)]
+/// The kind of cache at a given level
+#[bitbybit::bitenum(u3, exhaustive = false)]
+#[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Clidr(pub u32);
+pub enum CacheKind {
+    /// No cache at this level
+    NoCache = 0b000,
+    /// Instruction cache only
+    InstructionOnly = 0b001,
+    /// Data cache only
+    DataOnly = 0b010,
+    /// Separate instruction and data caches
+    Separate = 0b011,
+    /// Unified cache
+    Unified = 0b100,
+}
+
+/// CLIDR (*Cache Level ID Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clidr {
+    /// Inner cache boundary (Armv8 only)
+    #[bits(30..=31, rw)]
+    icb: u2,
+    /// Level of Unification Uniprocessor
+    #[bits(27..=29, rw)]
+    louu: u3,
+    /// Level of Coherence
+    #[bits(24..=26, rw)]
+    loc: u3,
+    /// Level of Unification Inner Shareable
+    #[bits(21..=23, rw)]
+    louis: u3,
+    /// The type of cache at each level, starting with L1
+    #[bits(0..=2, rw, stride = 3)]
+    ctype: [Option<CacheKind>; 7],
+}
 
 impl SysReg for Clidr {
     const CP: u32 = 15;
@@ -22,6 +57,22 @@ impl Clidr {
     #[inline]
     /// Reads CLIDR (*Cache Level ID Register*)
     pub fn read() -> Clidr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cortex_a9() {
+        // Separate L1 I and D caches, unified L2, LoC of 2
+        let clidr = Clidr::new_with_raw_value(0x0A20_0023);
+        assert_eq!(clidr.ctype(0), Ok(CacheKind::Separate));
+        assert_eq!(clidr.ctype(1), Ok(CacheKind::Unified));
+        assert_eq!(clidr.ctype(2), Ok(CacheKind::NoCache));
+        assert_eq!(clidr.loc().value(), 2);
+        assert_eq!(clidr.louis().value(), 1);
     }
 }
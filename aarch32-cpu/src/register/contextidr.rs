@@ -1,12 +1,20 @@
 //! Code for managing CONTEXTIDR (*Context ID Register*)
 
+use arbitrary_int::u24;
+
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// CONTEXTIDR (*Context ID Register*)
-#[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Contextidr(pub u32);
+pub struct Contextidr {
+    /// Process Identifier
+    #[bits(8..=31, rw)]
+    procid: u24,
+    /// Address Space Identifier
+    #[bits(0..=7, rw)]
+    asid: u8,
+}
 
 impl SysReg for Contextidr {
     const CP: u32 = 15;
@@ -22,7 +30,7 @@ impl Contextidr {
     #[inline]
     /// Reads CONTEXTIDR (*Context ID Register*)
     pub fn read() -> Contextidr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +45,7 @@ impl Contextidr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
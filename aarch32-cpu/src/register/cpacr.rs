@@ -2,11 +2,42 @@
 
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
-/// CPACR (*Architectural Feature Access Control Register*)
-#[derive(Debug, Copy, Clone)]
+/// Access rights for a coprocessor
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[derive(Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Cpacr(pub u32);
+pub enum CpAccess {
+    /// Any access generates an Undefined Instruction exception
+    Denied = 0b00,
+    /// Access at PL1 or higher only
+    PrivilegedOnly = 0b01,
+    /// Reserved, and behaves like [`CpAccess::Denied`]
+    Reserved = 0b10,
+    /// Full access
+    Full = 0b11,
+}
+
+/// CPACR (*Architectural Feature Access Control Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cpacr {
+    /// Disable Advanced SIMD functionality
+    #[bit(31, rw)]
+    asedis: bool,
+    /// Disable use of D16-D31 of the floating-point register file
+    #[bit(30, rw)]
+    d32dis: bool,
+    /// Trap access to the CP14 trace registers
+    #[bit(28, rw)]
+    trcdis: bool,
+    /// Access rights for CP11
+    #[bits(22..=23, rw)]
+    cp11: CpAccess,
+    /// Access rights for CP10
+    #[bits(20..=21, rw)]
+    cp10: CpAccess,
+}
 
 impl SysReg for Cpacr {
     const CP: u32 = 15;
@@ -22,7 +53,7 @@ impl Cpacr {
     #[inline]
     /// Reads CPACR (*Architectural Feature Access Control Register*)
     pub fn read() -> Cpacr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +68,7 @@ impl Cpacr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
@@ -1,12 +1,32 @@
 //! Code for managing CTR (*Cache Type Register*)
 
+use arbitrary_int::{u2, u3, u4};
+
 use crate::register::{SysReg, SysRegRead};
 
 /// CTR (*Cache Type Register*)
-#[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Ctr(pub u32);
+pub struct Ctr {
+    /// Register format (0b100 for Armv7 and later)
+    #[bits(29..=31, rw)]
+    format: u3,
+    /// Cache Writeback Granule, log2 of the number of words
+    #[bits(24..=27, rw)]
+    cwg: u4,
+    /// Exclusives Reservation Granule, log2 of the number of words
+    #[bits(20..=23, rw)]
+    erg: u4,
+    /// Smallest data cache line, log2 of the number of words
+    #[bits(16..=19, rw)]
+    dminline: u4,
+    /// Level 1 instruction cache policy
+    #[bits(14..=15, rw)]
+    l1ip: u2,
+    /// Smallest instruction cache line, log2 of the number of words
+    #[bits(0..=3, rw)]
+    iminline: u4,
+}
 
 impl SysReg for Ctr {
     const CP: u32 = 15;
@@ -22,6 +42,6 @@ impl Ctr {
     #[inline]
     /// Reads CTR (*Cache Type Register*)
     pub fn read() -> Ctr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
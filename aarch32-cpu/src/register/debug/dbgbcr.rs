@@ -0,0 +1,127 @@
+//! Code for managing DBGBCR (*Debug Breakpoint Control Register*)
+//!
+//! There is one of these for each Breakpoint Register Pair - see [`Dbgbcr0`](crate::register::Dbgbcr0) and friends.
+
+use arbitrary_int::{u2, u4, u5};
+
+/// DBGBCR (*Debug Breakpoint Control Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbcr {
+    /// Address range mask
+    #[bits(24..=28, rw)]
+    mask: u5,
+    /// Breakpoint Type
+    #[bits(20..=23, rw)]
+    bt: Option<BreakpointType>,
+    /// Linked Breakpoint number
+    #[bits(16..=19, rw)]
+    lbn: u4,
+    /// Security state control
+    #[bits(14..=15, rw)]
+    ssc: u2,
+    /// Hyp Mode Control
+    #[bit(13, rw)]
+    hmc: bool,
+    /// Byte address select
+    #[bits(5..=8, rw)]
+    bas: u4,
+    /// Privileged mode control
+    #[bits(1..=2, rw)]
+    pmc: u2,
+    /// Breakpoint enable
+    #[bit(0, rw)]
+    e: bool,
+}
+
+/// The kinds of breakpoint a Breakpoint Register Pair can implement
+#[bitbybit::bitenum(u4, exhaustive = false)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum BreakpointType {
+    /// Unlinked instruction address match
+    UnlinkedAddressMatch = 0b0000,
+    /// Linked instruction address match
+    LinkedAddressMatch = 0b0001,
+    /// Unlinked Context ID match
+    UnlinkedContextIdMatch = 0b0010,
+    /// Linked Context ID match
+    LinkedContextIdMatch = 0b0011,
+    /// Unlinked instruction address mismatch
+    UnlinkedAddressMismatch = 0b0100,
+    /// Linked instruction address mismatch
+    LinkedAddressMismatch = 0b0101,
+    /// Unlinked VMID match
+    UnlinkedVmidMatch = 0b1000,
+    /// Linked VMID match
+    LinkedVmidMatch = 0b1001,
+    /// Unlinked VMID and Context ID match
+    UnlinkedVmidContextIdMatch = 0b1010,
+    /// Linked VMID and Context ID match
+    LinkedVmidContextIdMatch = 0b1011,
+}
+
+impl Dbgbcr {
+    /// Read the DBGBCR with the given index
+    ///
+    /// Returns `None` if the index is larger than 15. Check
+    /// [`Dbgdidr::num_breakpoints`](crate::register::Dbgdidr::num_breakpoints)
+    /// to see how many are actually implemented.
+    pub fn read_indexed(idx: u8) -> Option<Dbgbcr> {
+        use crate::register::*;
+        let value = match idx {
+            0 => Dbgbcr0::read(),
+            1 => Dbgbcr1::read(),
+            2 => Dbgbcr2::read(),
+            3 => Dbgbcr3::read(),
+            4 => Dbgbcr4::read(),
+            5 => Dbgbcr5::read(),
+            6 => Dbgbcr6::read(),
+            7 => Dbgbcr7::read(),
+            8 => Dbgbcr8::read(),
+            9 => Dbgbcr9::read(),
+            10 => Dbgbcr10::read(),
+            11 => Dbgbcr11::read(),
+            12 => Dbgbcr12::read(),
+            13 => Dbgbcr13::read(),
+            14 => Dbgbcr14::read(),
+            15 => Dbgbcr15::read(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Write the DBGBCR with the given index
+    ///
+    /// Does nothing if the index is larger than 15.
+    ///
+    /// # Safety
+    ///
+    /// Enabling a breakpoint while Monitor debug-mode is enabled will cause
+    /// Prefetch Abort exceptions when the breakpoint matches.
+    pub unsafe fn write_indexed(idx: u8, value: Dbgbcr) {
+        use crate::register::*;
+        unsafe {
+            match idx {
+                0 => Dbgbcr0::write(value),
+                1 => Dbgbcr1::write(value),
+                2 => Dbgbcr2::write(value),
+                3 => Dbgbcr3::write(value),
+                4 => Dbgbcr4::write(value),
+                5 => Dbgbcr5::write(value),
+                6 => Dbgbcr6::write(value),
+                7 => Dbgbcr7::write(value),
+                8 => Dbgbcr8::write(value),
+                9 => Dbgbcr9::write(value),
+                10 => Dbgbcr10::write(value),
+                11 => Dbgbcr11::write(value),
+                12 => Dbgbcr12::write(value),
+                13 => Dbgbcr13::write(value),
+                14 => Dbgbcr14::write(value),
+                15 => Dbgbcr15::write(value),
+                _ => {}
+            }
+        }
+    }
+}
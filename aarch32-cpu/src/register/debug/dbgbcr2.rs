@@ -0,0 +1,43 @@
+//! Code for managing DBGBCR2 (*Debug Breakpoint Control Register 2*)
+
+use crate::register::{Dbgbcr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBCR2 (*Debug Breakpoint Control Register 2*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbcr2(pub u32);
+
+impl SysReg for Dbgbcr2 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 2;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegRead for Dbgbcr2 {}
+
+impl Dbgbcr2 {
+    #[inline]
+    /// Reads DBGBCR2 (*Debug Breakpoint Control Register 2*)
+    pub fn read() -> Dbgbcr {
+        Dbgbcr::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbcr2 {}
+
+impl Dbgbcr2 {
+    #[inline]
+    /// Writes DBGBCR2 (*Debug Breakpoint Control Register 2*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbcr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Code for managing DBGBCR5 (*Debug Breakpoint Control Register 5*)
+
+use crate::register::{Dbgbcr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBCR5 (*Debug Breakpoint Control Register 5*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbcr5(pub u32);
+
+impl SysReg for Dbgbcr5 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 5;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegRead for Dbgbcr5 {}
+
+impl Dbgbcr5 {
+    #[inline]
+    /// Reads DBGBCR5 (*Debug Breakpoint Control Register 5*)
+    pub fn read() -> Dbgbcr {
+        Dbgbcr::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbcr5 {}
+
+impl Dbgbcr5 {
+    #[inline]
+    /// Writes DBGBCR5 (*Debug Breakpoint Control Register 5*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbcr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
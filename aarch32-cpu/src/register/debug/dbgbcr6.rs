@@ -0,0 +1,43 @@
+//! Code for managing DBGBCR6 (*Debug Breakpoint Control Register 6*)
+
+use crate::register::{Dbgbcr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBCR6 (*Debug Breakpoint Control Register 6*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbcr6(pub u32);
+
+impl SysReg for Dbgbcr6 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 6;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegRead for Dbgbcr6 {}
+
+impl Dbgbcr6 {
+    #[inline]
+    /// Reads DBGBCR6 (*Debug Breakpoint Control Register 6*)
+    pub fn read() -> Dbgbcr {
+        Dbgbcr::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbcr6 {}
+
+impl Dbgbcr6 {
+    #[inline]
+    /// Writes DBGBCR6 (*Debug Breakpoint Control Register 6*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbcr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Code for managing DBGBVR (*Debug Breakpoint Value Register*)
+//!
+//! There is one of these for each Breakpoint Register Pair - see [`Dbgbvr0`](crate::register::Dbgbvr0) and friends.
+
+/// DBGBVR (*Debug Breakpoint Value Register*)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbvr(pub u32);
+
+impl Dbgbvr {
+    /// Read the DBGBVR with the given index
+    ///
+    /// Returns `None` if the index is larger than 15. Check
+    /// [`Dbgdidr::num_breakpoints`](crate::register::Dbgdidr::num_breakpoints)
+    /// to see how many are actually implemented.
+    pub fn read_indexed(idx: u8) -> Option<Dbgbvr> {
+        use crate::register::*;
+        let value = match idx {
+            0 => Dbgbvr0::read(),
+            1 => Dbgbvr1::read(),
+            2 => Dbgbvr2::read(),
+            3 => Dbgbvr3::read(),
+            4 => Dbgbvr4::read(),
+            5 => Dbgbvr5::read(),
+            6 => Dbgbvr6::read(),
+            7 => Dbgbvr7::read(),
+            8 => Dbgbvr8::read(),
+            9 => Dbgbvr9::read(),
+            10 => Dbgbvr10::read(),
+            11 => Dbgbvr11::read(),
+            12 => Dbgbvr12::read(),
+            13 => Dbgbvr13::read(),
+            14 => Dbgbvr14::read(),
+            15 => Dbgbvr15::read(),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Write the DBGBVR with the given index
+    ///
+    /// Does nothing if the index is larger than 15.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write_indexed(idx: u8, value: Dbgbvr) {
+        use crate::register::*;
+        unsafe {
+            match idx {
+                0 => Dbgbvr0::write(value),
+                1 => Dbgbvr1::write(value),
+                2 => Dbgbvr2::write(value),
+                3 => Dbgbvr3::write(value),
+                4 => Dbgbvr4::write(value),
+                5 => Dbgbvr5::write(value),
+                6 => Dbgbvr6::write(value),
+                7 => Dbgbvr7::write(value),
+                8 => Dbgbvr8::write(value),
+                9 => Dbgbvr9::write(value),
+                10 => Dbgbvr10::write(value),
+                11 => Dbgbvr11::write(value),
+                12 => Dbgbvr12::write(value),
+                13 => Dbgbvr13::write(value),
+                14 => Dbgbvr14::write(value),
+                15 => Dbgbvr15::write(value),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Code for managing DBGBVR14 (*Debug Breakpoint Value Register 14*)
+
+use crate::register::{Dbgbvr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBVR14 (*Debug Breakpoint Value Register 14*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbvr14(pub u32);
+
+impl SysReg for Dbgbvr14 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 14;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegRead for Dbgbvr14 {}
+
+impl Dbgbvr14 {
+    #[inline]
+    /// Reads DBGBVR14 (*Debug Breakpoint Value Register 14*)
+    pub fn read() -> Dbgbvr {
+        Dbgbvr(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbvr14 {}
+
+impl Dbgbvr14 {
+    #[inline]
+    /// Writes DBGBVR14 (*Debug Breakpoint Value Register 14*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbvr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Code for managing DBGBVR5 (*Debug Breakpoint Value Register 5*)
+
+use crate::register::{Dbgbvr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBVR5 (*Debug Breakpoint Value Register 5*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbvr5(pub u32);
+
+impl SysReg for Dbgbvr5 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 5;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegRead for Dbgbvr5 {}
+
+impl Dbgbvr5 {
+    #[inline]
+    /// Reads DBGBVR5 (*Debug Breakpoint Value Register 5*)
+    pub fn read() -> Dbgbvr {
+        Dbgbvr(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbvr5 {}
+
+impl Dbgbvr5 {
+    #[inline]
+    /// Writes DBGBVR5 (*Debug Breakpoint Value Register 5*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbvr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
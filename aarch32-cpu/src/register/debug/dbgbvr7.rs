@@ -0,0 +1,43 @@
+//! Code for managing DBGBVR7 (*Debug Breakpoint Value Register 7*)
+
+use crate::register::{Dbgbvr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBVR7 (*Debug Breakpoint Value Register 7*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbvr7(pub u32);
+
+impl SysReg for Dbgbvr7 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 7;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegRead for Dbgbvr7 {}
+
+impl Dbgbvr7 {
+    #[inline]
+    /// Reads DBGBVR7 (*Debug Breakpoint Value Register 7*)
+    pub fn read() -> Dbgbvr {
+        Dbgbvr(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbvr7 {}
+
+impl Dbgbvr7 {
+    #[inline]
+    /// Writes DBGBVR7 (*Debug Breakpoint Value Register 7*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbvr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! Code for managing DBGBVR9 (*Debug Breakpoint Value Register 9*)
+
+use crate::register::{Dbgbvr, SysReg, SysRegRead, SysRegWrite};
+
+/// DBGBVR9 (*Debug Breakpoint Value Register 9*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgbvr9(pub u32);
+
+impl SysReg for Dbgbvr9 {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 9;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegRead for Dbgbvr9 {}
+
+impl Dbgbvr9 {
+    #[inline]
+    /// Reads DBGBVR9 (*Debug Breakpoint Value Register 9*)
+    pub fn read() -> Dbgbvr {
+        Dbgbvr(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Dbgbvr9 {}
+
+impl Dbgbvr9 {
+    #[inline]
+    /// Writes DBGBVR9 (*Debug Breakpoint Value Register 9*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Dbgbvr) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Code for managing DBGDIDR (*Debug ID Register*)
+
+use arbitrary_int::u4;
+
+use crate::register::{SysReg, SysRegRead};
+
+/// DBGDIDR (*Debug ID Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgdidr {
+    /// Number of Watchpoint Register Pairs, minus one
+    #[bits(28..=31, r)]
+    wrps: u4,
+    /// Number of Breakpoint Register Pairs, minus one
+    #[bits(24..=27, r)]
+    brps: u4,
+    /// Number of Breakpoint Register Pairs with Context ID comparison, minus one
+    #[bits(20..=23, r)]
+    ctx_cmps: u4,
+    /// Debug architecture version
+    #[bits(16..=19, r)]
+    version: u4,
+    /// Debug Power-down Support
+    #[bit(15, r)]
+    devid_imp: bool,
+    /// Secure User halting debug not implemented
+    #[bit(14, r)]
+    nsuhd_imp: bool,
+    /// Program Counter Sampling Register implemented
+    #[bit(13, r)]
+    pcsr_imp: bool,
+    /// Security Extensions implemented
+    #[bit(12, r)]
+    se_imp: bool,
+    /// Implementation-defined variant
+    #[bits(4..=7, r)]
+    variant: u4,
+    /// Implementation-defined revision
+    #[bits(0..=3, r)]
+    revision: u4,
+}
+
+impl Dbgdidr {
+    /// How many Breakpoint Register Pairs does this processor have?
+    pub fn num_breakpoints(&self) -> u8 {
+        self.brps().value() + 1
+    }
+
+    /// How many Watchpoint Register Pairs does this processor have?
+    pub fn num_watchpoints(&self) -> u8 {
+        self.wrps().value() + 1
+    }
+}
+
+impl SysReg for Dbgdidr {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 0;
+}
+
+impl SysRegRead for Dbgdidr {}
+
+impl Dbgdidr {
+    #[inline]
+    /// Reads DBGDIDR (*Debug ID Register*)
+    pub fn read() -> Dbgdidr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -0,0 +1,152 @@
+//! Code for managing DBGDSCRext (*Debug Status and Control Register, External View*)
+
+use arbitrary_int::{u2, u4};
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// DBGDSCRext (*Debug Status and Control Register, External View*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgdscrext {
+    /// DBGDTRRX register full
+    #[bit(30, r)]
+    rxfull: bool,
+    /// DBGDTRTX register full
+    #[bit(29, r)]
+    txfull: bool,
+    /// Non-secure state status
+    #[bit(18, r)]
+    ns: bool,
+    /// Secure PL1 Non-Invasive Debug Disable
+    #[bit(17, r)]
+    spniddis: bool,
+    /// Secure PL1 Invasive Debug Disable
+    #[bit(16, r)]
+    spiddis: bool,
+    /// Monitor debug-mode enable
+    #[bit(15, rw)]
+    mdbgen: bool,
+    /// Halting debug-mode enable
+    #[bit(14, r)]
+    hdbgen: bool,
+    /// Execute ARM instruction enable
+    #[bit(13, r)]
+    itren: bool,
+    /// User mode access to Debug Communications Channel disable
+    #[bit(12, rw)]
+    udccdis: bool,
+    /// Interrupts Disable
+    #[bit(11, r)]
+    intdis: bool,
+    /// Force Debug Acknowledge
+    #[bit(10, r)]
+    dbgack: bool,
+    /// Sticky Undefined Instruction
+    #[bit(8, r)]
+    und_l: bool,
+    /// Sticky Asynchronous Abort
+    #[bit(7, r)]
+    adabort_l: bool,
+    /// Sticky Synchronous Data Abort
+    #[bit(6, r)]
+    sdabort_l: bool,
+    /// Method of Debug Entry
+    #[bits(2..=5, r)]
+    moe: u4,
+    /// Processor Restarted and Halted
+    #[bits(0..=1, r)]
+    restarted_halted: u2,
+}
+
+/// The reasons for entering debug state, as found in [`Dbgdscrext::moe`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum MethodOfEntry {
+    /// Halt request debug event
+    HaltRequest = 0b0000,
+    /// Breakpoint debug event
+    Breakpoint = 0b0001,
+    /// Asynchronous Watchpoint debug event
+    AsyncWatchpoint = 0b0010,
+    /// BKPT instruction debug event
+    BkptInstruction = 0b0011,
+    /// External debug request debug event
+    ExternalDebugRequest = 0b0100,
+    /// Vector catch debug event
+    VectorCatch = 0b0101,
+    /// OS Unlock catch debug event
+    OsUnlockCatch = 0b1000,
+    /// Synchronous Watchpoint debug event
+    SyncWatchpoint = 0b1010,
+}
+
+impl Dbgdscrext {
+    /// Decode the Method of Debug Entry field
+    pub fn method_of_entry(&self) -> Option<MethodOfEntry> {
+        match self.moe().value() {
+            0b0000 => Some(MethodOfEntry::HaltRequest),
+            0b0001 => Some(MethodOfEntry::Breakpoint),
+            0b0010 => Some(MethodOfEntry::AsyncWatchpoint),
+            0b0011 => Some(MethodOfEntry::BkptInstruction),
+            0b0100 => Some(MethodOfEntry::ExternalDebugRequest),
+            0b0101 => Some(MethodOfEntry::VectorCatch),
+            0b1000 => Some(MethodOfEntry::OsUnlockCatch),
+            0b1010 => Some(MethodOfEntry::SyncWatchpoint),
+            _ => None,
+        }
+    }
+}
+
+impl SysReg for Dbgdscrext {
+    const CP: u32 = 14;
+    const CRN: u32 = 0;
+    const OP1: u32 = 0;
+    const CRM: u32 = 2;
+    const OP2: u32 = 2;
+}
+
+impl SysRegRead for Dbgdscrext {}
+
+impl Dbgdscrext {
+    #[inline]
+    /// Reads DBGDSCRext (*Debug Status and Control Register, External View*)
+    pub fn read() -> Dbgdscrext {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl SysRegWrite for Dbgdscrext {}
+
+impl Dbgdscrext {
+    #[inline]
+    /// Writes DBGDSCRext (*Debug Status and Control Register, External View*)
+    ///
+    /// # Safety
+    ///
+    /// Enabling Monitor debug-mode will cause any programmed breakpoints to
+    /// generate Prefetch Abort exceptions. Ensure you have a handler installed.
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+
+    #[inline]
+    /// Modify DBGDSCRext (*Debug Status and Control Register, External View*)
+    ///
+    /// # Safety
+    ///
+    /// See docs for [Self::write].
+    pub unsafe fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        unsafe {
+            Self::write(value);
+        }
+    }
+}
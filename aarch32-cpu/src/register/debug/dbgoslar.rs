@@ -0,0 +1,40 @@
+//! Code for managing DBGOSLAR (*Debug OS Lock Access Register*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+/// DBGOSLAR (*Debug OS Lock Access Register*)
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgoslar(pub u32);
+
+impl Dbgoslar {
+    /// The key which locks the OS Lock. Any other value unlocks it.
+    pub const LOCK_KEY: u32 = 0xC5AC_CE55;
+}
+
+impl SysReg for Dbgoslar {
+    const CP: u32 = 14;
+    const CRN: u32 = 1;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 4;
+}
+
+impl SysRegWrite for Dbgoslar {}
+
+impl Dbgoslar {
+    #[inline]
+    /// Writes DBGOSLAR (*Debug OS Lock Access Register*)
+    pub fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+
+    #[inline]
+    /// Clear the OS Lock, allowing access to the debug registers
+    pub fn unlock() {
+        Self::write(Dbgoslar(0));
+    }
+}
@@ -0,0 +1,46 @@
+//! Code for managing DBGOSLSR (*Debug OS Lock Status Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+/// DBGOSLSR (*Debug OS Lock Status Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbgoslsr {
+    /// OS Lock implemented (bit 3 of the OSLM field)
+    #[bit(3, r)]
+    oslm1: bool,
+    /// 32-bit access
+    #[bit(2, r)]
+    ntt: bool,
+    /// OS Lock Status
+    #[bit(1, r)]
+    oslk: bool,
+    /// OS Lock implemented (bit 0 of the OSLM field)
+    #[bit(0, r)]
+    oslm0: bool,
+}
+
+impl Dbgoslsr {
+    /// Is the OS Lock implemented?
+    pub fn lock_implemented(&self) -> bool {
+        self.oslm0() || self.oslm1()
+    }
+}
+
+impl SysReg for Dbgoslsr {
+    const CP: u32 = 14;
+    const CRN: u32 = 1;
+    const OP1: u32 = 0;
+    const CRM: u32 = 1;
+    const OP2: u32 = 4;
+}
+
+impl SysRegRead for Dbgoslsr {}
+
+impl Dbgoslsr {
+    #[inline]
+    /// Reads DBGOSLSR (*Debug OS Lock Status Register*)
+    pub fn read() -> Dbgoslsr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -0,0 +1,79 @@
+//! Access registers for the Arm Debug architecture (CP14)
+
+pub mod dbgbcr;
+pub mod dbgbcr0;
+pub mod dbgbcr1;
+pub mod dbgbcr10;
+pub mod dbgbcr11;
+pub mod dbgbcr12;
+pub mod dbgbcr13;
+pub mod dbgbcr14;
+pub mod dbgbcr15;
+pub mod dbgbcr2;
+pub mod dbgbcr3;
+pub mod dbgbcr4;
+pub mod dbgbcr5;
+pub mod dbgbcr6;
+pub mod dbgbcr7;
+pub mod dbgbcr8;
+pub mod dbgbcr9;
+pub mod dbgbvr;
+pub mod dbgbvr0;
+pub mod dbgbvr1;
+pub mod dbgbvr10;
+pub mod dbgbvr11;
+pub mod dbgbvr12;
+pub mod dbgbvr13;
+pub mod dbgbvr14;
+pub mod dbgbvr15;
+pub mod dbgbvr2;
+pub mod dbgbvr3;
+pub mod dbgbvr4;
+pub mod dbgbvr5;
+pub mod dbgbvr6;
+pub mod dbgbvr7;
+pub mod dbgbvr8;
+pub mod dbgbvr9;
+pub mod dbgdidr;
+pub mod dbgdscrext;
+pub mod dbgoslar;
+pub mod dbgoslsr;
+
+pub use dbgbcr::{BreakpointType, Dbgbcr};
+pub use dbgbcr0::Dbgbcr0;
+pub use dbgbcr1::Dbgbcr1;
+pub use dbgbcr10::Dbgbcr10;
+pub use dbgbcr11::Dbgbcr11;
+pub use dbgbcr12::Dbgbcr12;
+pub use dbgbcr13::Dbgbcr13;
+pub use dbgbcr14::Dbgbcr14;
+pub use dbgbcr15::Dbgbcr15;
+pub use dbgbcr2::Dbgbcr2;
+pub use dbgbcr3::Dbgbcr3;
+pub use dbgbcr4::Dbgbcr4;
+pub use dbgbcr5::Dbgbcr5;
+pub use dbgbcr6::Dbgbcr6;
+pub use dbgbcr7::Dbgbcr7;
+pub use dbgbcr8::Dbgbcr8;
+pub use dbgbcr9::Dbgbcr9;
+pub use dbgbvr::Dbgbvr;
+pub use dbgbvr0::Dbgbvr0;
+pub use dbgbvr1::Dbgbvr1;
+pub use dbgbvr10::Dbgbvr10;
+pub use dbgbvr11::Dbgbvr11;
+pub use dbgbvr12::Dbgbvr12;
+pub use dbgbvr13::Dbgbvr13;
+pub use dbgbvr14::Dbgbvr14;
+pub use dbgbvr15::Dbgbvr15;
+pub use dbgbvr2::Dbgbvr2;
+pub use dbgbvr3::Dbgbvr3;
+pub use dbgbvr4::Dbgbvr4;
+pub use dbgbvr5::Dbgbvr5;
+pub use dbgbvr6::Dbgbvr6;
+pub use dbgbvr7::Dbgbvr7;
+pub use dbgbvr8::Dbgbvr8;
+pub use dbgbvr9::Dbgbvr9;
+pub use dbgdidr::Dbgdidr;
+pub use dbgdscrext::{Dbgdscrext, MethodOfEntry};
+pub use dbgoslar::Dbgoslar;
+pub use dbgoslsr::Dbgoslsr;
@@ -21,6 +21,9 @@ pub struct Hcr {
     /// TVM - Trap Memory controls
     #[bit(26, rw)]
     tvm: bool,
+    /// TTLB - Trap TLB maintenance operations
+    #[bit(25, rw)]
+    ttlb: bool,
     /// TPU - Trap cache maintenance instructions that operate to the Point of Unification
     #[bit(24, rw)]
     tpu: bool,
@@ -36,6 +39,9 @@ pub struct Hcr {
     /// TIDCP - Trap IMPLEMENTATION DEFINED functionality
     #[bit(20, rw)]
     tidcp: bool,
+    /// TSC - Trap SMC instructions
+    #[bit(19, rw)]
+    tsc: bool,
     /// TID3 - Trap ID group 3
     #[bit(18, rw)]
     tid3: bool,
@@ -81,10 +87,19 @@ pub struct Hcr {
     /// FMO - FIQ Mask Override
     #[bit(3, rw)]
     fmo: bool,
+    /// PTW - Protected Table Walk
+    ///
+    /// Makes a stage 2 Permission fault out of a stage 1 translation table
+    /// walk that touches stage 2 Device or Strongly-ordered memory.
+    #[bit(2, rw)]
+    ptw: bool,
     /// SWIO - Set/Way Invalidation Override
     #[bit(1, rw)]
     swio: bool,
     /// VM - Virtualization enable
+    ///
+    /// Turns on stage 2 translation for the Non-secure PL1&0 translation
+    /// regime, using the tables in VTTBR.
     #[bit(0, rw)]
     vm: bool,
 }
@@ -0,0 +1,109 @@
+//! Code for managing HTCR (*Hyp Translation Control Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::u3;
+
+/// HTCR (*Hyp Translation Control Register*)
+///
+/// Controls the Hyp mode (stage 1) translation table walks, which always use
+/// the Long-descriptor format.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Htcr {
+    /// Shareability for memory associated with the translation table walks
+    #[bits(12..=13, rw)]
+    sh0: Option<Shareability>,
+    /// Outer Cacheability for memory associated with the translation table walks
+    #[bits(10..=11, rw)]
+    orgn0: Cacheability,
+    /// Inner Cacheability for memory associated with the translation table walks
+    #[bits(8..=9, rw)]
+    irgn0: Cacheability,
+    /// The size offset of the memory region addressed by HTTBR
+    ///
+    /// The region size is 2^(32 - T0SZ) bytes.
+    #[bits(0..=2, rw)]
+    t0sz: u3,
+}
+
+/// Cacheability of the memory holding translation tables
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Cacheability {
+    /// Non-cacheable
+    NonCacheable = 0b00,
+    /// Write-Back Write-Allocate Cacheable
+    WriteBackWriteAlloc = 0b01,
+    /// Write-Through Cacheable
+    WriteThrough = 0b10,
+    /// Write-Back no Write-Allocate Cacheable
+    WriteBackNoWriteAlloc = 0b11,
+}
+
+/// Shareability of memory
+#[bitbybit::bitenum(u2, exhaustive = false)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Shareability {
+    /// Non-shareable
+    NonShareable = 0b00,
+    /// Outer Shareable
+    OuterShareable = 0b10,
+    /// Inner Shareable
+    InnerShareable = 0b11,
+}
+
+impl SysReg for Htcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 2;
+    const OP1: u32 = 4;
+    const CRM: u32 = 0;
+    const OP2: u32 = 2;
+}
+
+impl SysRegRead for Htcr {}
+
+impl SysRegWrite for Htcr {}
+
+impl Htcr {
+    /// Read HTCR (*Hyp Translation Control Register*)
+    #[inline]
+    pub fn read() -> Htcr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    /// Write HTCR (*Hyp Translation Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Changing how Hyp mode translation table walks work while the Hyp mode
+    /// MMU is on can change the memory map under your feet.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        // Bit 31 is RES1
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value() | (1 << 31));
+        }
+    }
+
+    /// Modify HTCR (*Hyp Translation Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// See [`Htcr::write`].
+    #[inline]
+    pub unsafe fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        unsafe {
+            Self::write(value);
+        }
+    }
+}
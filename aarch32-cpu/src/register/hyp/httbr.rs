@@ -0,0 +1,49 @@
+//! Code for managing HTTBR (*Hyp Translation Table Base Register*)
+
+use crate::register::{SysReg64, SysRegRead64, SysRegWrite64};
+
+use arbitrary_int::u40;
+
+/// HTTBR (*Hyp Translation Table Base Register*)
+///
+/// Holds the base address of the Hyp mode translation table.
+#[bitbybit::bitfield(u64, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Httbr {
+    /// Translation table base address
+    ///
+    /// The table must be aligned to its size, so the bottom bits of this
+    /// field must be zero.
+    #[bits(0..=39, rw)]
+    baddr: u40,
+}
+
+impl SysReg64 for Httbr {
+    const CP: u32 = 15;
+    const OP1: u32 = 4;
+    const CRM: u32 = 2;
+}
+
+impl SysRegRead64 for Httbr {}
+
+impl SysRegWrite64 for Httbr {}
+
+impl Httbr {
+    /// Read HTTBR (*Hyp Translation Table Base Register*)
+    #[inline]
+    pub fn read() -> Httbr {
+        Self::new_with_raw_value(<Self as SysRegRead64>::read_raw())
+    }
+
+    /// Write HTTBR (*Hyp Translation Table Base Register*)
+    ///
+    /// # Safety
+    ///
+    /// You must supply the address of a valid Hyp mode translation table.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite64>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Code for managing ICH_EISR (*Interrupt Controller End of Interrupt Status Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+/// ICH_EISR (*Interrupt Controller End of Interrupt Status Register*)
+///
+/// Bit `n` is set if list register `n` has an EOI maintenance interrupt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchEisr(pub u32);
+
+impl IchEisr {
+    /// Does list register `lr` have an EOI maintenance interrupt?
+    pub fn has_eoi(&self, lr: usize) -> bool {
+        lr < 16 && (self.0 & (1 << lr)) != 0
+    }
+}
+
+impl SysReg for IchEisr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 3;
+}
+
+impl crate::register::SysRegRead for IchEisr {}
+
+impl IchEisr {
+    #[inline]
+    /// Reads ICH_EISR (*Interrupt Controller End of Interrupt Status Register*)
+    pub fn read() -> IchEisr {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -0,0 +1,36 @@
+//! Code for managing ICH_ELRSR (*Interrupt Controller Empty List Register Status Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+/// ICH_ELRSR (*Interrupt Controller Empty List Register Status Register*)
+///
+/// Bit `n` is set if list register `n` holds no valid interrupt.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchElrsr(pub u32);
+
+impl IchElrsr {
+    /// Is list register `lr` free for a new interrupt?
+    pub fn is_empty(&self, lr: usize) -> bool {
+        lr < 16 && (self.0 & (1 << lr)) != 0
+    }
+}
+
+impl SysReg for IchElrsr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegRead for IchElrsr {}
+
+impl IchElrsr {
+    #[inline]
+    /// Reads ICH_ELRSR (*Interrupt Controller Empty List Register Status Register*)
+    pub fn read() -> IchElrsr {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
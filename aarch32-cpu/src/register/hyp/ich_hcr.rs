@@ -0,0 +1,87 @@
+//! Code for managing ICH_HCR (*Interrupt Controller Hyp Control Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::u5;
+
+/// ICH_HCR (*Interrupt Controller Hyp Control Register*)
+///
+/// Controls the virtual CPU interface, and which maintenance interrupts are
+/// generated.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchHcr {
+    /// Counts EOIs that didn't match a list register
+    #[bits(27..=31, rw)]
+    eoi_count: u5,
+    /// Trap EL1 writes to ICC_DIR
+    #[bit(14, rw)]
+    tdir: bool,
+    /// Trap EL1 accesses to ICC_* registers for Group 1 interrupts
+    #[bit(12, rw)]
+    tall1: bool,
+    /// Trap EL1 accesses to ICC_* registers for Group 0 interrupts
+    #[bit(11, rw)]
+    tall0: bool,
+    /// Trap EL1 accesses to common ICC_* registers
+    #[bit(10, rw)]
+    tc: bool,
+    /// Maintenance interrupt when the guest disables Group 1 interrupts
+    #[bit(7, rw)]
+    vgrp1die: bool,
+    /// Maintenance interrupt when the guest enables Group 1 interrupts
+    #[bit(6, rw)]
+    vgrp1eie: bool,
+    /// Maintenance interrupt when the guest disables Group 0 interrupts
+    #[bit(5, rw)]
+    vgrp0die: bool,
+    /// Maintenance interrupt when the guest enables Group 0 interrupts
+    #[bit(4, rw)]
+    vgrp0eie: bool,
+    /// Maintenance interrupt when no list register is pending
+    #[bit(3, rw)]
+    npie: bool,
+    /// Maintenance interrupt while EOI count is non-zero
+    #[bit(2, rw)]
+    lrenpie: bool,
+    /// Underflow maintenance interrupt, when at most one list register is valid
+    #[bit(1, rw)]
+    uie: bool,
+    /// Enable the virtual CPU interface
+    #[bit(0, rw)]
+    en: bool,
+}
+
+impl SysReg for IchHcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegRead for IchHcr {}
+
+impl IchHcr {
+    #[inline]
+    /// Reads ICH_HCR (*Interrupt Controller Hyp Control Register*)
+    pub fn read() -> IchHcr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchHcr {}
+
+impl IchHcr {
+    #[inline]
+    /// Writes ICH_HCR (*Interrupt Controller Hyp Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Code for managing ICH_LR0 (*Interrupt Controller List Register 0*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LR0 (*Interrupt Controller List Register 0*)
+///
+/// Holds the virtual INTID of the interrupt in this list register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLr0(pub u32);
+
+impl SysReg for IchLr0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 12;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegRead for IchLr0 {}
+
+impl IchLr0 {
+    #[inline]
+    /// Reads ICH_LR0 (*Interrupt Controller List Register 0*)
+    pub fn read() -> IchLr0 {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLr0 {}
+
+impl IchLr0 {
+    #[inline]
+    /// Writes ICH_LR0 (*Interrupt Controller List Register 0*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Code for managing ICH_LR15 (*Interrupt Controller List Register 15*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LR15 (*Interrupt Controller List Register 15*)
+///
+/// Holds the virtual INTID of the interrupt in this list register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLr15(pub u32);
+
+impl SysReg for IchLr15 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 13;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegRead for IchLr15 {}
+
+impl IchLr15 {
+    #[inline]
+    /// Reads ICH_LR15 (*Interrupt Controller List Register 15*)
+    pub fn read() -> IchLr15 {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLr15 {}
+
+impl IchLr15 {
+    #[inline]
+    /// Writes ICH_LR15 (*Interrupt Controller List Register 15*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Code for managing ICH_LR6 (*Interrupt Controller List Register 6*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LR6 (*Interrupt Controller List Register 6*)
+///
+/// Holds the virtual INTID of the interrupt in this list register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLr6(pub u32);
+
+impl SysReg for IchLr6 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 12;
+    const OP2: u32 = 6;
+}
+
+impl crate::register::SysRegRead for IchLr6 {}
+
+impl IchLr6 {
+    #[inline]
+    /// Reads ICH_LR6 (*Interrupt Controller List Register 6*)
+    pub fn read() -> IchLr6 {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLr6 {}
+
+impl IchLr6 {
+    #[inline]
+    /// Writes ICH_LR6 (*Interrupt Controller List Register 6*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Code for managing ICH_LR7 (*Interrupt Controller List Register 7*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LR7 (*Interrupt Controller List Register 7*)
+///
+/// Holds the virtual INTID of the interrupt in this list register.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLr7(pub u32);
+
+impl SysReg for IchLr7 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 12;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegRead for IchLr7 {}
+
+impl IchLr7 {
+    #[inline]
+    /// Reads ICH_LR7 (*Interrupt Controller List Register 7*)
+    pub fn read() -> IchLr7 {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLr7 {}
+
+impl IchLr7 {
+    #[inline]
+    /// Writes ICH_LR7 (*Interrupt Controller List Register 7*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Code for managing the layout of `ICH_LRC<n>` (*Interrupt Controller List Registers*)
+//!
+//! Each list register is split into two 32-bit halves on AArch32. `ICH_LR<n>`
+//! holds the virtual INTID, and `ICH_LRC<n>` holds everything else.
+
+use arbitrary_int::u13;
+
+/// The upper half of a list register, from `ICH_LRC<n>`
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLrc {
+    /// The state of the virtual interrupt
+    #[bits(30..=31, rw)]
+    state: LrState,
+    /// The virtual interrupt is linked to a physical interrupt
+    ///
+    /// Deactivating the virtual interrupt deactivates `pintid` too.
+    #[bit(29, rw)]
+    hw: bool,
+    /// The virtual interrupt is in Group 1
+    #[bit(28, rw)]
+    group1: bool,
+    /// The priority of the virtual interrupt
+    #[bits(16..=23, rw)]
+    priority: u8,
+    /// The physical INTID, when `hw` is set
+    #[bits(0..=12, rw)]
+    pintid: u13,
+    /// Generate a maintenance interrupt when the guest EOIs the interrupt
+    ///
+    /// This shares bit 9 with `pintid`, and is only used when `hw` is clear.
+    #[bit(9, rw)]
+    eoi: bool,
+}
+
+/// The state of a virtual interrupt in a list register
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum LrState {
+    /// Not pending or active - the list register is free
+    Invalid = 0b00,
+    /// Pending
+    Pending = 0b01,
+    /// Active
+    Active = 0b10,
+    /// Active, and pending again
+    PendingActive = 0b11,
+}
@@ -0,0 +1,43 @@
+//! Code for managing ICH_LRC2 (*Interrupt Controller List Register 2, upper half*)
+
+use crate::register::{IchLrc, SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LRC2 (*Interrupt Controller List Register 2, upper half*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLrc2(pub u32);
+
+impl SysReg for IchLrc2 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 14;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegRead for IchLrc2 {}
+
+impl IchLrc2 {
+    #[inline]
+    /// Reads ICH_LRC2 (*Interrupt Controller List Register 2, upper half*)
+    pub fn read() -> IchLrc {
+        IchLrc::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLrc2 {}
+
+impl IchLrc2 {
+    #[inline]
+    /// Writes ICH_LRC2 (*Interrupt Controller List Register 2, upper half*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: IchLrc) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
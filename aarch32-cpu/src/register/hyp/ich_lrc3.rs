@@ -0,0 +1,43 @@
+//! Code for managing ICH_LRC3 (*Interrupt Controller List Register 3, upper half*)
+
+use crate::register::{IchLrc, SysReg, SysRegRead, SysRegWrite};
+
+/// ICH_LRC3 (*Interrupt Controller List Register 3, upper half*)
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchLrc3(pub u32);
+
+impl SysReg for IchLrc3 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 14;
+    const OP2: u32 = 3;
+}
+
+impl crate::register::SysRegRead for IchLrc3 {}
+
+impl IchLrc3 {
+    #[inline]
+    /// Reads ICH_LRC3 (*Interrupt Controller List Register 3, upper half*)
+    pub fn read() -> IchLrc {
+        IchLrc::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchLrc3 {}
+
+impl IchLrc3 {
+    #[inline]
+    /// Writes ICH_LRC3 (*Interrupt Controller List Register 3, upper half*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: IchLrc) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Code for managing ICH_MISR (*Interrupt Controller Maintenance Interrupt State Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+/// ICH_MISR (*Interrupt Controller Maintenance Interrupt State Register*)
+///
+/// Says which maintenance interrupts are asserted.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchMisr {
+    /// The guest has disabled Group 1 interrupts (and `ICH_HCR.VGrp1DIE` is set)
+    #[bit(7, r)]
+    vgrp1d: bool,
+    /// The guest has enabled Group 1 interrupts (and `ICH_HCR.VGrp1EIE` is set)
+    #[bit(6, r)]
+    vgrp1e: bool,
+    /// The guest has disabled Group 0 interrupts (and `ICH_HCR.VGrp0DIE` is set)
+    #[bit(5, r)]
+    vgrp0d: bool,
+    /// The guest has enabled Group 0 interrupts (and `ICH_HCR.VGrp0EIE` is set)
+    #[bit(4, r)]
+    vgrp0e: bool,
+    /// No list register is in the pending state (and `ICH_HCR.NPIE` is set)
+    #[bit(3, r)]
+    np: bool,
+    /// EOI count is non-zero (and `ICH_HCR.LRENPIE` is set)
+    #[bit(2, r)]
+    lrenp: bool,
+    /// At most one list register is valid (and `ICH_HCR.UIE` is set)
+    #[bit(1, r)]
+    u: bool,
+    /// At least one list register has an EOI maintenance interrupt - see ICH_EISR
+    #[bit(0, r)]
+    eoi: bool,
+}
+
+impl SysReg for IchMisr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegRead for IchMisr {}
+
+impl IchMisr {
+    #[inline]
+    /// Reads ICH_MISR (*Interrupt Controller Maintenance Interrupt State Register*)
+    pub fn read() -> IchMisr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -0,0 +1,75 @@
+//! Code for managing ICH_VMCR (*Interrupt Controller Virtual Machine Control Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::u3;
+
+/// ICH_VMCR (*Interrupt Controller Virtual Machine Control Register*)
+///
+/// Gives the hypervisor access to the guest's view of its CPU interface, so it
+/// can be saved and restored.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchVmcr {
+    /// Virtual Priority Mask (the guest's ICC_PMR)
+    #[bits(24..=31, rw)]
+    vpmr: u8,
+    /// Virtual Binary Point Register, Group 0
+    #[bits(21..=23, rw)]
+    vbpr0: u3,
+    /// Virtual Binary Point Register, Group 1
+    #[bits(18..=20, rw)]
+    vbpr1: u3,
+    /// Virtual EOI mode
+    #[bit(9, rw)]
+    veoim: bool,
+    /// Virtual Common Binary Point Register
+    #[bit(4, rw)]
+    vcbpr: bool,
+    /// Virtual FIQ enable - signal Group 0 virtual interrupts as FIQs
+    #[bit(3, rw)]
+    vfiqen: bool,
+    /// Virtual Acknowledge Control
+    #[bit(2, rw)]
+    vackctl: bool,
+    /// Virtual Group 1 interrupt enable
+    #[bit(1, rw)]
+    veng1: bool,
+    /// Virtual Group 0 interrupt enable
+    #[bit(0, rw)]
+    veng0: bool,
+}
+
+impl SysReg for IchVmcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegRead for IchVmcr {}
+
+impl IchVmcr {
+    #[inline]
+    /// Reads ICH_VMCR (*Interrupt Controller Virtual Machine Control Register*)
+    pub fn read() -> IchVmcr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IchVmcr {}
+
+impl IchVmcr {
+    #[inline]
+    /// Writes ICH_VMCR (*Interrupt Controller Virtual Machine Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
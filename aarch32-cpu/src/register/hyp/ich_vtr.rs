@@ -0,0 +1,62 @@
+//! Code for managing ICH_VTR (*Interrupt Controller VGIC Type Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+use arbitrary_int::{u3, u5};
+
+/// ICH_VTR (*Interrupt Controller VGIC Type Register*)
+///
+/// Describes what the virtual CPU interface supports.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IchVtr {
+    /// The number of virtual priority bits, minus one
+    #[bits(29..=31, r)]
+    pri_bits: u3,
+    /// The number of virtual preemption bits, minus one
+    #[bits(26..=28, r)]
+    pre_bits: u3,
+    /// The number of virtual interrupt identifier bits (`0b000` for 16, `0b001` for 24)
+    #[bits(23..=25, r)]
+    id_bits: u3,
+    /// Supports virtual SEIs
+    #[bit(22, r)]
+    seis: bool,
+    /// Supports non-zero Affinity 3 values
+    #[bit(21, r)]
+    a3v: bool,
+    /// Does *not* support direct injection of virtual LPIs
+    #[bit(20, r)]
+    nv4: bool,
+    /// Supports separate trapping of EL1 writes to ICC_DIR
+    #[bit(19, r)]
+    tds: bool,
+    /// The number of list registers, minus one
+    #[bits(0..=4, r)]
+    list_regs: u5,
+}
+
+impl IchVtr {
+    /// The number of list registers
+    pub fn num_list_registers(&self) -> usize {
+        usize::from(self.list_regs().value()) + 1
+    }
+}
+
+impl SysReg for IchVtr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 4;
+    const CRM: u32 = 11;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegRead for IchVtr {}
+
+impl IchVtr {
+    #[inline]
+    /// Reads ICH_VTR (*Interrupt Controller VGIC Type Register*)
+    pub fn read() -> IchVtr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -19,8 +19,99 @@ pub mod hpfar;
 pub mod hsctlr;
 pub mod hsr;
 pub mod hstr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod htcr;
 pub mod htpidr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod httbr;
 pub mod hvbar;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_eisr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_elrsr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_hcr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr0;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr1;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr10;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr11;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr12;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr13;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr14;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr15;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr2;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr3;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr4;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr5;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr6;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr7;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr8;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lr9;
+pub mod ich_lrc;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc0;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc1;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc10;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc11;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc12;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc13;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc14;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc15;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc2;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc3;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc4;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc5;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc6;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc7;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc8;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_lrc9;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_misr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_vmcr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub mod ich_vtr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod tlbiallh;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod tlbiallnsnh;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod tlbimvah;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod vtcr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod vttbr;
 
 pub use hacr::Hacr;
 pub use hactlr::Hactlr;
@@ -41,5 +132,96 @@ pub use hpfar::Hpfar;
 pub use hsctlr::Hsctlr;
 pub use hsr::Hsr;
 pub use hstr::Hstr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use htcr::Htcr;
 pub use htpidr::Htpidr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use httbr::Httbr;
 pub use hvbar::Hvbar;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_eisr::IchEisr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_elrsr::IchElrsr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_hcr::IchHcr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr0::IchLr0;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr1::IchLr1;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr10::IchLr10;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr11::IchLr11;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr12::IchLr12;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr13::IchLr13;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr14::IchLr14;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr15::IchLr15;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr2::IchLr2;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr3::IchLr3;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr4::IchLr4;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr5::IchLr5;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr6::IchLr6;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr7::IchLr7;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr8::IchLr8;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lr9::IchLr9;
+pub use ich_lrc::{IchLrc, LrState};
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc0::IchLrc0;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc1::IchLrc1;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc10::IchLrc10;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc11::IchLrc11;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc12::IchLrc12;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc13::IchLrc13;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc14::IchLrc14;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc15::IchLrc15;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc2::IchLrc2;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc3::IchLrc3;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc4::IchLrc4;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc5::IchLrc5;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc6::IchLrc6;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc7::IchLrc7;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc8::IchLrc8;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_lrc9::IchLrc9;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_misr::IchMisr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_vmcr::IchVmcr;
+#[cfg(any(test, doc, arm_architecture = "v8-r"))]
+pub use ich_vtr::IchVtr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use tlbiallh::TlbIAllH;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use tlbiallnsnh::TlbIAllNsNh;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use tlbimvah::TlbIMvaH;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use vtcr::Vtcr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use vttbr::Vttbr;
@@ -0,0 +1,24 @@
+//! Code for managing TLBIALLH (*TLB Invalidate All Hyp Register*)
+
+use crate::register::SysReg;
+
+/// TLBIALLH (*TLB Invalidate All Hyp Register*)
+pub struct TlbIAllH;
+
+impl SysReg for TlbIAllH {
+    const CP: u32 = 15;
+    const CRN: u32 = 8;
+    const OP1: u32 = 4;
+    const CRM: u32 = 7;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegWrite for TlbIAllH {}
+
+impl TlbIAllH {
+    #[inline]
+    /// Writes 0 to TLBIALLH (*TLB Invalidate All Hyp Register*) to trigger operation
+    pub fn write() {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(0) }
+    }
+}
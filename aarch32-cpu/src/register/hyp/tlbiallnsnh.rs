@@ -0,0 +1,24 @@
+//! Code for managing TLBIALLNSNH (*TLB Invalidate All Non-secure Non-Hyp Register*)
+
+use crate::register::SysReg;
+
+/// TLBIALLNSNH (*TLB Invalidate All Non-secure Non-Hyp Register*)
+pub struct TlbIAllNsNh;
+
+impl SysReg for TlbIAllNsNh {
+    const CP: u32 = 15;
+    const CRN: u32 = 8;
+    const OP1: u32 = 4;
+    const CRM: u32 = 7;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegWrite for TlbIAllNsNh {}
+
+impl TlbIAllNsNh {
+    #[inline]
+    /// Writes 0 to TLBIALLNSNH (*TLB Invalidate All Non-secure Non-Hyp Register*) to trigger operation
+    pub fn write() {
+        unsafe { <Self as crate::register::SysRegWrite>::write_raw(0) }
+    }
+}
@@ -0,0 +1,37 @@
+//! Code for managing TLBIMVAH (*TLB Invalidate by MVA Hyp Register*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// TLBIMVAH (*TLB Invalidate by MVA Hyp Register*)
+pub struct TlbIMvaH(pub u32);
+
+impl TlbIMvaH {
+    #[inline]
+    /// Create a new TLBIMVAH containing the Hyp mode Virtual Address to invalidate
+    pub const fn new(addr: u32) -> Self {
+        Self(addr)
+    }
+}
+
+impl SysReg for TlbIMvaH {
+    const CP: u32 = 15;
+    const CRN: u32 = 8;
+    const OP1: u32 = 4;
+    const CRM: u32 = 7;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegWrite for TlbIMvaH {}
+
+impl TlbIMvaH {
+    #[inline]
+    /// Writes TLBIMVAH (*TLB Invalidate by MVA Hyp Register*) to trigger operation
+    pub fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
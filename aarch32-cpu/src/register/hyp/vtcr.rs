@@ -0,0 +1,97 @@
+//! Code for managing VTCR (*Virtualization Translation Control Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::{u2, u4};
+
+use super::htcr::{Cacheability, Shareability};
+
+/// VTCR (*Virtualization Translation Control Register*)
+///
+/// Controls the stage 2 translation table walks for the Non-secure PL1&0
+/// translation regime.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vtcr {
+    /// Shareability for memory associated with the translation table walks
+    #[bits(12..=13, rw)]
+    sh0: Option<Shareability>,
+    /// Outer Cacheability for memory associated with the translation table walks
+    #[bits(10..=11, rw)]
+    orgn0: Cacheability,
+    /// Inner Cacheability for memory associated with the translation table walks
+    #[bits(8..=9, rw)]
+    irgn0: Cacheability,
+    /// Starting level for the translation table walk
+    ///
+    /// `0b00` starts at level 2, and `0b01` starts at level 1.
+    #[bits(6..=7, rw)]
+    sl0: u2,
+    /// Sign extension bit - must be a copy of T0SZ bit 3
+    #[bit(4, rw)]
+    s: bool,
+    /// The size offset of the memory region addressed by VTTBR
+    ///
+    /// This is a signed value. The region size is 2^(32 - T0SZ) bytes, so
+    /// values from -8 up to 0 give an IPA space of 40 to 32 bits.
+    #[bits(0..=3, rw)]
+    t0sz: u4,
+}
+
+impl SysReg for Vtcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 2;
+    const OP1: u32 = 4;
+    const CRM: u32 = 1;
+    const OP2: u32 = 2;
+}
+
+impl SysRegRead for Vtcr {}
+
+impl SysRegWrite for Vtcr {}
+
+impl Vtcr {
+    /// Get the size of the IPA space, in bits
+    pub fn ipa_bits(&self) -> u32 {
+        // sign-extend the four bit field
+        let t0sz = ((self.t0sz().value() << 4) as i8) >> 4;
+        (32 - t0sz as i32) as u32
+    }
+
+    /// Read VTCR (*Virtualization Translation Control Register*)
+    #[inline]
+    pub fn read() -> Vtcr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    /// Write VTCR (*Virtualization Translation Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Changing how stage 2 translation table walks work while stage 2
+    /// translation is enabled can change a guest's memory map under its feet.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        // Bit 31 is RES1
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value() | (1 << 31));
+        }
+    }
+
+    /// Modify VTCR (*Virtualization Translation Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// See [`Vtcr::write`].
+    #[inline]
+    pub unsafe fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        unsafe {
+            Self::write(value);
+        }
+    }
+}
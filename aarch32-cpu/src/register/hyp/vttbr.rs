@@ -0,0 +1,56 @@
+//! Code for managing VTTBR (*Virtualization Translation Table Base Register*)
+
+use crate::register::{SysReg64, SysRegRead64, SysRegWrite64};
+
+use arbitrary_int::u40;
+
+/// VTTBR (*Virtualization Translation Table Base Register*)
+///
+/// Holds the base address of the stage 2 translation table, and the VMID of
+/// the guest that table belongs to.
+#[bitbybit::bitfield(u64, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vttbr {
+    /// Virtual Machine Identifier
+    ///
+    /// Tags the TLB entries created by stage 2 translation, so you don't have
+    /// to invalidate the TLB when switching between guests.
+    #[bits(48..=55, rw)]
+    vmid: u8,
+    /// Translation table base address
+    ///
+    /// The table must be aligned to its size, so the bottom bits of this
+    /// field must be zero.
+    #[bits(0..=39, rw)]
+    baddr: u40,
+}
+
+impl SysReg64 for Vttbr {
+    const CP: u32 = 15;
+    const OP1: u32 = 6;
+    const CRM: u32 = 2;
+}
+
+impl SysRegRead64 for Vttbr {}
+
+impl SysRegWrite64 for Vttbr {}
+
+impl Vttbr {
+    /// Read VTTBR (*Virtualization Translation Table Base Register*)
+    #[inline]
+    pub fn read() -> Vttbr {
+        Self::new_with_raw_value(<Self as SysRegRead64>::read_raw())
+    }
+
+    /// Write VTTBR (*Virtualization Translation Table Base Register*)
+    ///
+    /// # Safety
+    ///
+    /// You must supply the address of a valid stage 2 translation table.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite64>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! Code for managing ICC_AP0R2 (*Interrupt Controller Active Priorities Group 0 Register 2*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICC_AP0R2 (*Interrupt Controller Active Priorities Group 0 Register 2*)
+///
+/// Records which Group 0 priorities have an active interrupt. Save and restore it
+/// (and the others of its kind) when switching context, but don't otherwise
+/// change it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccAp0r2(pub u32);
+
+impl SysReg for IccAp0r2 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 6;
+}
+
+impl crate::register::SysRegRead for IccAp0r2 {}
+
+impl IccAp0r2 {
+    #[inline]
+    /// Reads ICC_AP0R2 (*Interrupt Controller Active Priorities Group 0 Register 2*)
+    pub fn read() -> IccAp0r2 {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccAp0r2 {}
+
+impl IccAp0r2 {
+    #[inline]
+    /// Writes ICC_AP0R2 (*Interrupt Controller Active Priorities Group 0 Register 2*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
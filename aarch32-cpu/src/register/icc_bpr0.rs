@@ -0,0 +1,49 @@
+//! Code for managing ICC_BPR0 (*Interrupt Controller Binary Point Register 0*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::u3;
+
+/// ICC_BPR0 (*Interrupt Controller Binary Point Register 0*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccBpr0 {
+    /// Splits priority values into a group priority, used for preemption, and a
+    /// subpriority
+    #[bits(0..=2, rw)]
+    binary_point: u3,
+}
+
+impl SysReg for IccBpr0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 3;
+}
+
+impl crate::register::SysRegRead for IccBpr0 {}
+
+impl IccBpr0 {
+    #[inline]
+    /// Reads ICC_BPR0 (*Interrupt Controller Binary Point Register 0*)
+    pub fn read() -> IccBpr0 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccBpr0 {}
+
+impl IccBpr0 {
+    #[inline]
+    /// Writes ICC_BPR0 (*Interrupt Controller Binary Point Register 0*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,73 @@
+//! Code for managing ICC_CTLR (*Interrupt Controller Control Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+use arbitrary_int::u3;
+
+/// ICC_CTLR (*Interrupt Controller Control Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccCtlr {
+    /// Supports the extended SPI and PPI ranges
+    #[bit(19, r)]
+    extrange: bool,
+    /// Supports SGIs to Affinity 0 values from 16 to 255
+    #[bit(18, r)]
+    rss: bool,
+    /// Supports non-zero Affinity 3 values
+    #[bit(15, r)]
+    a3v: bool,
+    /// Supports local generation of SEIs
+    #[bit(14, r)]
+    seis: bool,
+    /// The number of INTID bits (`0b000` for 16, `0b001` for 24)
+    #[bits(11..=13, r)]
+    idbits: u3,
+    /// The number of priority bits, minus one
+    #[bits(8..=10, r)]
+    pribits: u3,
+    /// Priority Mask Hint Enable
+    #[bit(6, rw)]
+    pmhe: bool,
+    /// Writing EOIR only drops the running priority, and ICC_DIR deactivates
+    /// the interrupt
+    #[bit(1, rw)]
+    eoimode: bool,
+    /// ICC_BPR0 is used for Group 1 interrupts as well
+    #[bit(0, rw)]
+    cbpr: bool,
+}
+
+impl SysReg for IccCtlr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+    const OP2: u32 = 4;
+}
+
+impl crate::register::SysRegRead for IccCtlr {}
+
+impl IccCtlr {
+    #[inline]
+    /// Reads ICC_CTLR (*Interrupt Controller Control Register*)
+    pub fn read() -> IccCtlr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccCtlr {}
+
+impl IccCtlr {
+    #[inline]
+    /// Writes ICC_CTLR (*Interrupt Controller Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Code for managing ICC_DIR (*Interrupt Controller Deactivate Interrupt Register*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+use arbitrary_int::u24;
+
+/// ICC_DIR (*Interrupt Controller Deactivate Interrupt Register*)
+///
+/// When ICC_CTLR.EOImode is set, writing EOIR only drops the running priority,
+/// and writing this deactivates the interrupt.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccDir {
+    /// The INTID of the interrupt being deactivated
+    #[bits(0..=23, rw)]
+    intid: u24,
+}
+
+impl SysReg for IccDir {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 11;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegWrite for IccDir {}
+
+impl IccDir {
+    #[inline]
+    /// Writes ICC_DIR (*Interrupt Controller Deactivate Interrupt Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
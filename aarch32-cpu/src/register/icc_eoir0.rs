@@ -0,0 +1,40 @@
+//! Code for managing ICC_EOIR0 (*Interrupt Controller End Of Interrupt Register 0*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+use arbitrary_int::u24;
+
+/// ICC_EOIR0 (*Interrupt Controller End Of Interrupt Register 0*)
+///
+/// Writing this tells the GIC that we have finished with a Group 0 interrupt.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccEoir0 {
+    /// The INTID of the interrupt being completed
+    #[bits(0..=23, rw)]
+    intid: u24,
+}
+
+impl SysReg for IccEoir0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegWrite for IccEoir0 {}
+
+impl IccEoir0 {
+    #[inline]
+    /// Writes ICC_EOIR0 (*Interrupt Controller End Of Interrupt Register 0*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
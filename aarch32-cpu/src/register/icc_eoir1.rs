@@ -0,0 +1,40 @@
+//! Code for managing ICC_EOIR1 (*Interrupt Controller End Of Interrupt Register 1*)
+
+use crate::register::{SysReg, SysRegWrite};
+
+use arbitrary_int::u24;
+
+/// ICC_EOIR1 (*Interrupt Controller End Of Interrupt Register 1*)
+///
+/// Writing this tells the GIC that we have finished with a Group 1 interrupt.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccEoir1 {
+    /// The INTID of the interrupt being completed
+    #[bits(0..=23, rw)]
+    intid: u24,
+}
+
+impl SysReg for IccEoir1 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegWrite for IccEoir1 {}
+
+impl IccEoir1 {
+    #[inline]
+    /// Writes ICC_EOIR1 (*Interrupt Controller End Of Interrupt Register 1*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Code for managing ICC_HPPIR0 (*Interrupt Controller Highest Priority Pending Interrupt Register 0*)
+
+use crate::register::{SysReg, SysRegRead};
+
+use arbitrary_int::u24;
+
+/// ICC_HPPIR0 (*Interrupt Controller Highest Priority Pending Interrupt Register 0*)
+///
+/// Says which Group 0 interrupt would be acknowledged, without acknowledging it.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccHppir0 {
+    /// The INTID of the highest priority pending interrupt
+    ///
+    /// This is 1023 if there is no pending interrupt.
+    #[bits(0..=23, r)]
+    intid: u24,
+}
+
+impl SysReg for IccHppir0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegRead for IccHppir0 {}
+
+impl IccHppir0 {
+    #[inline]
+    /// Reads ICC_HPPIR0 (*Interrupt Controller Highest Priority Pending Interrupt Register 0*)
+    pub fn read() -> IccHppir0 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
@@ -0,0 +1,36 @@
+//! Code for managing ICC_IAR0 (*Interrupt Controller Interrupt Acknowledge Register 0*)
+
+use crate::register::{SysReg, SysRegRead};
+
+use arbitrary_int::u24;
+
+/// ICC_IAR0 (*Interrupt Controller Interrupt Acknowledge Register 0*)
+///
+/// Reading this acknowledges the highest priority pending Group 0 interrupt.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccIar0 {
+    /// The INTID of the interrupt that was acknowledged
+    ///
+    /// This is 1023 if there was no pending interrupt to acknowledge.
+    #[bits(0..=23, r)]
+    intid: u24,
+}
+
+impl SysReg for IccIar0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 8;
+    const OP2: u32 = 0;
+}
+
+impl crate::register::SysRegRead for IccIar0 {}
+
+impl IccIar0 {
+    #[inline]
+    /// Reads ICC_IAR0 (*Interrupt Controller Interrupt Acknowledge Register 0*)
+    pub fn read() -> IccIar0 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
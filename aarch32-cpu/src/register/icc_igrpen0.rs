@@ -0,0 +1,46 @@
+//! Code for managing ICC_IGRPEN0 (*Interrupt Controller Interrupt Group 0 Enable Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICC_IGRPEN0 (*Interrupt Controller Interrupt Group 0 Enable Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccIgrpen0 {
+    /// Group 0 interrupts are enabled
+    #[bit(0, rw)]
+    enable: bool,
+}
+
+impl SysReg for IccIgrpen0 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+    const OP2: u32 = 6;
+}
+
+impl crate::register::SysRegRead for IccIgrpen0 {}
+
+impl IccIgrpen0 {
+    #[inline]
+    /// Reads ICC_IGRPEN0 (*Interrupt Controller Interrupt Group 0 Enable Register*)
+    pub fn read() -> IccIgrpen0 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccIgrpen0 {}
+
+impl IccIgrpen0 {
+    #[inline]
+    /// Writes ICC_IGRPEN0 (*Interrupt Controller Interrupt Group 0 Enable Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Code for managing ICC_IGRPEN1 (*Interrupt Controller Interrupt Group 1 Enable Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICC_IGRPEN1 (*Interrupt Controller Interrupt Group 1 Enable Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccIgrpen1 {
+    /// Group 1 interrupts are enabled
+    #[bit(0, rw)]
+    enable: bool,
+}
+
+impl SysReg for IccIgrpen1 {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+    const OP2: u32 = 7;
+}
+
+impl crate::register::SysRegRead for IccIgrpen1 {}
+
+impl IccIgrpen1 {
+    #[inline]
+    /// Reads ICC_IGRPEN1 (*Interrupt Controller Interrupt Group 1 Enable Register*)
+    pub fn read() -> IccIgrpen1 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccIgrpen1 {}
+
+impl IccIgrpen1 {
+    #[inline]
+    /// Writes ICC_IGRPEN1 (*Interrupt Controller Interrupt Group 1 Enable Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
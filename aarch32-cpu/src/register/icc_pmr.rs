@@ -3,10 +3,13 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// ICC_PMR (*Interrupt Controller Interrupt Priority Mask Register*)
-#[derive(Debug, Copy, Clone)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct IccPmr(pub u32);
+pub struct IccPmr {
+    /// The priority mask level
+    #[bits(0..=7, rw)]
+    priority: u8,
+}
 
 impl SysReg for IccPmr {
     const CP: u32 = 15;
@@ -22,7 +25,7 @@ impl IccPmr {
     #[inline]
     /// Reads ICC_PMR (*Interrupt Controller Interrupt Priority Mask Register*)
     pub fn read() -> IccPmr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +40,7 @@ impl IccPmr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
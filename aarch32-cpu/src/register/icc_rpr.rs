@@ -0,0 +1,32 @@
+//! Code for managing ICC_RPR (*Interrupt Controller Running Priority Register*)
+
+use crate::register::{SysReg, SysRegRead};
+
+/// ICC_RPR (*Interrupt Controller Running Priority Register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccRpr {
+    /// The priority of the highest priority active interrupt
+    ///
+    /// This is 0xFF if no interrupt is active.
+    #[bits(0..=7, r)]
+    priority: u8,
+}
+
+impl SysReg for IccRpr {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 11;
+    const OP2: u32 = 3;
+}
+
+impl crate::register::SysRegRead for IccRpr {}
+
+impl IccRpr {
+    #[inline]
+    /// Reads ICC_RPR (*Interrupt Controller Running Priority Register*)
+    pub fn read() -> IccRpr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
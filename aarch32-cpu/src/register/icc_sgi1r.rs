@@ -0,0 +1,59 @@
+//! Code for managing ICC_SGI1R (*Interrupt Controller Software Generated Interrupt Group 1 Register*)
+
+use crate::register::{SysReg64, SysRegWrite64};
+
+use arbitrary_int::u4;
+
+/// ICC_SGI1R (*Interrupt Controller Software Generated Interrupt Group 1 Register*)
+///
+/// Writing this sends a Group 1 SGI, for the current Security state, to the
+/// selected PEs.
+#[bitbybit::bitfield(u64, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccSgi1r {
+    /// The Affinity 3 value of the target PEs
+    #[bits(48..=55, rw)]
+    aff3: u8,
+    /// The range selector - `target_list` covers Affinity 0 values `rs * 16`
+    /// to `rs * 16 + 15`
+    #[bits(44..=47, rw)]
+    rs: u4,
+    /// Interrupt Routing Mode - send to every PE except this one, rather than
+    /// to `target_list`
+    #[bit(40, rw)]
+    irm: bool,
+    /// The Affinity 2 value of the target PEs
+    #[bits(32..=39, rw)]
+    aff2: u8,
+    /// The INTID of the SGI
+    #[bits(24..=27, rw)]
+    intid: u4,
+    /// The Affinity 1 value of the target PEs
+    #[bits(16..=23, rw)]
+    aff1: u8,
+    /// One bit for each Affinity 0 value that should get the SGI
+    #[bits(0..=15, rw)]
+    target_list: u16,
+}
+
+impl SysReg64 for IccSgi1r {
+    const CP: u32 = 15;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+}
+
+impl crate::register::SysRegWrite64 for IccSgi1r {}
+
+impl IccSgi1r {
+    #[inline]
+    /// Writes ICC_SGI1R (*Interrupt Controller Software Generated Interrupt Group 1 Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite64>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,52 @@
+//! Code for managing ICC_SRE (*Interrupt Controller System Register Enable register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// ICC_SRE (*Interrupt Controller System Register Enable register*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IccSre {
+    /// Disable IRQ bypass
+    #[bit(2, rw)]
+    dib: bool,
+    /// Disable FIQ bypass
+    #[bit(1, rw)]
+    dfb: bool,
+    /// Use the System register interface, rather than the memory-mapped one
+    #[bit(0, rw)]
+    sre: bool,
+}
+
+impl SysReg for IccSre {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 12;
+    const OP2: u32 = 5;
+}
+
+impl crate::register::SysRegRead for IccSre {}
+
+impl IccSre {
+    #[inline]
+    /// Reads ICC_SRE (*Interrupt Controller System Register Enable register*)
+    pub fn read() -> IccSre {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for IccSre {}
+
+impl IccSre {
+    #[inline]
+    /// Writes ICC_SRE (*Interrupt Controller System Register Enable register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -3,10 +3,15 @@
 use crate::register::{SysReg, SysRegRead};
 
 /// IMP_CBAR (*Configuration Base Address Register*)
+///
+/// This gives the base address of the private peripherals, such as the GIC.
+/// Cortex-A processors (like the Cortex-A7, A9 and A15) call it CBAR, and put
+/// it at a different encoding to the Cortex-R52.
 #[derive(Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ImpCbar(pub u32);
 
+#[cfg(not(arm_architecture = "v7-a"))]
 impl SysReg for ImpCbar {
     const CP: u32 = 15;
     const CRN: u32 = 15;
@@ -15,6 +20,15 @@ impl SysReg for ImpCbar {
     const OP2: u32 = 0;
 }
 
+#[cfg(arm_architecture = "v7-a")]
+impl SysReg for ImpCbar {
+    const CP: u32 = 15;
+    const CRN: u32 = 15;
+    const OP1: u32 = 4;
+    const CRM: u32 = 0;
+    const OP2: u32 = 0;
+}
+
 impl SysRegRead for ImpCbar {}
 
 impl ImpCbar {
@@ -25,17 +39,34 @@ impl ImpCbar {
     }
 
     /// Get the periphbase address
+    ///
+    /// This is where the Cortex-R52 puts it. Cortex-A processors each align
+    /// PERIPHBASE differently, so on Armv7-A mask the register with
+    /// [`Layout::periphbase_mask`](crate::gicv2::Layout::periphbase_mask)
+    /// instead.
+    #[cfg_attr(
+        arm_architecture = "v7-a",
+        deprecated(note = "mask with `gicv2::Layout::periphbase_mask` instead")
+    )]
     pub fn periphbase(self) -> *mut u32 {
         (self.0 & 0xFFF00000) as *mut u32
     }
 }
 
+#[cfg(not(arm_architecture = "v7-a"))]
 impl core::fmt::Debug for ImpCbar {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "IMP_CBAR {{ {:010p} }}", self.periphbase())
     }
 }
 
+#[cfg(arm_architecture = "v7-a")]
+impl core::fmt::Debug for ImpCbar {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "IMP_CBAR {{ {:#010x} }}", self.0)
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for ImpCbar {
     fn format(&self, f: defmt::Formatter) {
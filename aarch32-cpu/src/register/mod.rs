@@ -9,6 +9,7 @@ pub mod aidr;
 pub mod aifsr;
 pub mod amair0;
 pub mod amair1;
+pub mod banked;
 pub mod bpiall;
 pub mod ccsidr;
 pub mod clidr;
@@ -26,6 +27,8 @@ pub mod dccmvau;
 pub mod dccsw;
 pub mod dcimvac;
 pub mod dcisw;
+#[cfg(any(test, doc, armv7_or_higher))]
+pub mod debug;
 pub mod dfar;
 #[cfg(armv5te_or_higher)]
 pub mod dfsr;
@@ -35,7 +38,32 @@ pub mod drbar;
 pub mod drsr;
 pub mod dspsr;
 pub mod fcseidr;
+pub mod icc_ap0r0;
+pub mod icc_ap0r1;
+pub mod icc_ap0r2;
+pub mod icc_ap0r3;
+pub mod icc_ap1r0;
+pub mod icc_ap1r1;
+pub mod icc_ap1r2;
+pub mod icc_ap1r3;
+pub mod icc_asgi1r;
+pub mod icc_bpr0;
+pub mod icc_bpr1;
+pub mod icc_ctlr;
+pub mod icc_dir;
+pub mod icc_eoir0;
+pub mod icc_eoir1;
+pub mod icc_hppir0;
+pub mod icc_hppir1;
+pub mod icc_iar0;
+pub mod icc_iar1;
+pub mod icc_igrpen0;
+pub mod icc_igrpen1;
 pub mod icc_pmr;
+pub mod icc_rpr;
+pub mod icc_sgi0r;
+pub mod icc_sgi1r;
+pub mod icc_sre;
 pub mod iciallu;
 pub mod id_afr0;
 pub mod id_dfr0;
@@ -64,6 +92,8 @@ pub mod mair1;
 pub mod midr;
 pub mod mpidr;
 pub mod mpuir;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod mvbar;
 pub mod nsacr;
 pub mod par;
 pub mod pmccfiltr;
@@ -93,14 +123,22 @@ pub mod pmxevtyper;
 pub mod revidr;
 pub mod rgnr;
 pub mod rvbar;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod scr;
 pub mod sctlr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub mod sder;
+#[cfg(any(test, doc, armv7_or_higher))]
+pub mod snapshot;
 pub mod tcmtr;
 pub mod tlbiall;
 pub mod tlbtr;
 pub mod tpidrprw;
 pub mod tpidruro;
 pub mod tpidrurw;
+pub mod ttbcr;
 pub mod ttbr0;
+pub mod ttbr1;
 #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
 pub mod vbar;
 pub mod vmpidr;
@@ -139,7 +177,32 @@ pub use drbar::Drbar;
 pub use drsr::Drsr;
 pub use dspsr::Dspsr;
 pub use fcseidr::Fcseidr;
+pub use icc_ap0r0::IccAp0r0;
+pub use icc_ap0r1::IccAp0r1;
+pub use icc_ap0r2::IccAp0r2;
+pub use icc_ap0r3::IccAp0r3;
+pub use icc_ap1r0::IccAp1r0;
+pub use icc_ap1r1::IccAp1r1;
+pub use icc_ap1r2::IccAp1r2;
+pub use icc_ap1r3::IccAp1r3;
+pub use icc_asgi1r::IccAsgi1r;
+pub use icc_bpr0::IccBpr0;
+pub use icc_bpr1::IccBpr1;
+pub use icc_ctlr::IccCtlr;
+pub use icc_dir::IccDir;
+pub use icc_eoir0::IccEoir0;
+pub use icc_eoir1::IccEoir1;
+pub use icc_hppir0::IccHppir0;
+pub use icc_hppir1::IccHppir1;
+pub use icc_iar0::IccIar0;
+pub use icc_iar1::IccIar1;
+pub use icc_igrpen0::IccIgrpen0;
+pub use icc_igrpen1::IccIgrpen1;
 pub use icc_pmr::IccPmr;
+pub use icc_rpr::IccRpr;
+pub use icc_sgi0r::IccSgi0r;
+pub use icc_sgi1r::IccSgi1r;
+pub use icc_sre::IccSre;
 pub use iciallu::Iciallu;
 pub use id_afr0::IdAfr0;
 pub use id_dfr0::IdDfr0;
@@ -167,6 +230,8 @@ pub use mair1::Mair1;
 pub use midr::Midr;
 pub use mpidr::Mpidr;
 pub use mpuir::Mpuir;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use mvbar::Mvbar;
 pub use nsacr::Nsacr;
 pub use par::Par;
 pub use pmccfiltr::Pmccfiltr;
@@ -196,14 +261,22 @@ pub use pmxevtyper::Pmxevtyper;
 pub use revidr::Revidr;
 pub use rgnr::Rgnr;
 pub use rvbar::Rvbar;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use scr::Scr;
 pub use sctlr::Sctlr;
+#[cfg(any(test, doc, arm_architecture = "v7-a"))]
+pub use sder::Sder;
+#[cfg(any(test, doc, armv7_or_higher))]
+pub use snapshot::Snapshot;
 pub use tcmtr::Tcmtr;
 pub use tlbiall::TlbIAll;
 pub use tlbtr::Tlbtr;
 pub use tpidrprw::Tpidrprw;
 pub use tpidruro::Tpidruro;
 pub use tpidrurw::Tpidrurw;
+pub use ttbcr::Ttbcr;
 pub use ttbr0::Ttbr0;
+pub use ttbr1::Ttbr1;
 #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
 pub use vbar::Vbar;
 pub use vmpidr::Vmpidr;
@@ -226,6 +299,9 @@ pub mod generic_timer;
 #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
 pub use generic_timer::*;
 
+#[cfg(any(test, doc, armv7_or_higher))]
+pub use debug::*;
+
 pub use imp::*;
 
 /// Describes a 32-bit System Register
@@ -266,7 +342,11 @@ pub trait SysRegRead: SysReg {
                 options(nomem, nostack, preserves_flags)
             );
         }
-        #[cfg(not(target_arch = "arm"))]
+        #[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+        {
+            r = crate::sim::read(crate::sim::Encoding::of::<Self>()) as u32;
+        }
+        #[cfg(all(not(target_arch = "arm"), not(any(test, feature = "sim"))))]
         {
             r = 0;
         }
@@ -298,6 +378,8 @@ pub trait SysRegWrite: SysReg {
                 options(nomem, nostack, preserves_flags)
             );
         }
+        #[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+        crate::sim::write(crate::sim::Encoding::of::<Self>(), u64::from(_value));
     }
 }
 
@@ -334,7 +416,13 @@ pub trait SysRegRead64: SysReg64 {
                 options(nomem, nostack, preserves_flags)
             );
         }
-        #[cfg(not(target_arch = "arm"))]
+        #[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+        {
+            let r = crate::sim::read(crate::sim::Encoding::of64::<Self>());
+            r_lo = r as u32;
+            r_hi = (r >> 32) as u32;
+        }
+        #[cfg(all(not(target_arch = "arm"), not(any(test, feature = "sim"))))]
         {
             r_lo = 0;
             r_hi = 0;
@@ -367,5 +455,7 @@ pub trait SysRegWrite64: SysReg64 {
                 options(nomem, nostack, preserves_flags)
             );
         }
+        #[cfg(all(not(target_arch = "arm"), any(test, feature = "sim")))]
+        crate::sim::write(crate::sim::Encoding::of64::<Self>(), _value);
     }
 }
@@ -0,0 +1,49 @@
+//! Code for managing MVBAR (*Monitor Vector Base Address Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// MVBAR (*Monitor Vector Base Address Register*)
+///
+/// Part of the Security Extensions. It can only be accessed from Secure PL1
+/// modes.
+///
+/// There is no `modify` method because this register holds a single 32-bit address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mvbar(pub u32);
+
+impl SysReg for Mvbar {
+    const CP: u32 = 15;
+    const CRN: u32 = 12;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 1;
+}
+
+impl SysRegRead for Mvbar {}
+
+impl SysRegWrite for Mvbar {}
+
+impl Mvbar {
+    /// Read MVBAR (*Monitor Vector Base Address Register*)
+    #[inline]
+    pub fn read() -> Mvbar {
+        Self(<Self as SysRegRead>::read_raw())
+    }
+
+    /// Write MVBAR (*Monitor Vector Base Address Register*)
+    ///
+    /// # Safety
+    ///
+    /// You must supply a correctly-aligned address of a valid Arm AArch32
+    /// Monitor Vector Table.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        // Safety: Writing this register is atomic
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.0);
+        }
+    }
+}
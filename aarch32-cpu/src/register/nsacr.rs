@@ -1,12 +1,27 @@
 //! Code for managing NSACR (*Non-Secure Access Control Register*)
 
-use crate::register::{SysReg, SysRegRead};
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// NSACR (*Non-Secure Access Control Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Nsacr(pub u32);
+pub struct Nsacr {
+    /// Disable Non-secure access to the CP14 trace registers
+    #[bit(20, rw)]
+    nstrcdis: bool,
+    /// Disable Non-secure Advanced SIMD functionality
+    #[bit(15, rw)]
+    nsasedis: bool,
+    /// Disable Non-secure use of D16-D31
+    #[bit(14, rw)]
+    nsd32dis: bool,
+    /// Allow Non-secure access to CP11
+    #[bit(11, rw)]
+    cp11: bool,
+    /// Allow Non-secure access to CP10
+    #[bit(10, rw)]
+    cp10: bool,
+}
 
 impl SysReg for Nsacr {
     const CP: u32 = 15;
@@ -22,6 +37,24 @@ impl Nsacr {
     #[inline]
     /// Reads NSACR (*Non-Secure Access Control Register*)
     pub fn read() -> Nsacr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+}
+
+impl crate::register::SysRegWrite for Nsacr {}
+
+impl Nsacr {
+    #[inline]
+    /// Writes NSACR (*Non-Secure Access Control Register*)
+    ///
+    /// Only Secure PL1 modes can write this register.
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
     }
 }
@@ -3,10 +3,25 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMCCFILTR (*Performance Monitors Cycle Count Filter Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmccfiltr(pub u32);
+pub struct Pmccfiltr {
+    /// Don't count at PL1
+    #[bit(31, rw)]
+    p: bool,
+    /// Don't count at PL0
+    #[bit(30, rw)]
+    u: bool,
+    /// Non-secure PL1 filtering bit
+    #[bit(29, rw)]
+    nsk: bool,
+    /// Non-secure PL0 filtering bit
+    #[bit(28, rw)]
+    nsu: bool,
+    /// Count at PL2 (EL2)
+    #[bit(27, rw)]
+    nsh: bool,
+}
 
 impl SysReg for Pmccfiltr {
     const CP: u32 = 15;
@@ -22,7 +37,7 @@ impl Pmccfiltr {
     #[inline]
     /// Reads PMCCFILTR (*Performance Monitors Cycle Count Filter Register*)
     pub fn read() -> Pmccfiltr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +52,7 @@ impl Pmccfiltr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
@@ -3,10 +3,13 @@
 use crate::register::{SysReg, SysRegRead};
 
 /// PMCEID0 (*Performance Monitors Common Event Identification Register 0*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmceid0(pub u32);
+pub struct Pmceid0 {
+    /// Which common events (0x00 to 0x1F) are implemented
+    #[bit(0, rw)]
+    id: [bool; 32],
+}
 
 impl SysReg for Pmceid0 {
     const CP: u32 = 15;
@@ -22,6 +25,6 @@ impl Pmceid0 {
     #[inline]
     /// Reads PMCEID0 (*Performance Monitors Common Event Identification Register 0*)
     pub fn read() -> Pmceid0 {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
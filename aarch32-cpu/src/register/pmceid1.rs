@@ -3,10 +3,13 @@
 use crate::register::{SysReg, SysRegRead};
 
 /// PMCEID1 (*Performance Monitors Common Event Identification Register 1*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmceid1(pub u32);
+pub struct Pmceid1 {
+    /// Which common events (0x20 to 0x3F) are implemented
+    #[bit(0, rw)]
+    id: [bool; 32],
+}
 
 impl SysReg for Pmceid1 {
     const CP: u32 = 15;
@@ -22,6 +25,6 @@ impl Pmceid1 {
     #[inline]
     /// Reads PMCEID1 (*Performance Monitors Common Event Identification Register 1*)
     pub fn read() -> Pmceid1 {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
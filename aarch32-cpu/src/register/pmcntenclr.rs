@@ -3,10 +3,16 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMCNTENCLR (*Performance Monitors Count Enable Clear Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmcntenclr(pub u32);
+pub struct Pmcntenclr {
+    /// Cycle counter bit
+    #[bit(31, rw)]
+    c: bool,
+    /// Event counter bits, one per counter
+    #[bit(0, rw)]
+    p: [bool; 31],
+}
 
 impl SysReg for Pmcntenclr {
     const CP: u32 = 15;
@@ -22,7 +28,7 @@ impl Pmcntenclr {
     #[inline]
     /// Reads PMCNTENCLR (*Performance Monitors Count Enable Clear Register*)
     pub fn read() -> Pmcntenclr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +43,7 @@ impl Pmcntenclr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
@@ -3,10 +3,31 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMEVTYPER1 (*Performance Monitors Event Type Register 1*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmevtyper1(pub u32);
+pub struct Pmevtyper1 {
+    /// Don't count at PL1
+    #[bit(31, rw)]
+    p: bool,
+    /// Don't count at PL0
+    #[bit(30, rw)]
+    u: bool,
+    /// Non-secure PL1 filtering bit
+    #[bit(29, rw)]
+    nsk: bool,
+    /// Non-secure PL0 filtering bit
+    #[bit(28, rw)]
+    nsu: bool,
+    /// Count at PL2 (EL2)
+    #[bit(27, rw)]
+    nsh: bool,
+    /// Multithreading bit (Armv8 only)
+    #[bit(26, rw)]
+    mt: bool,
+    /// The event to count
+    #[bits(0..=15, rw)]
+    evt_count: u16,
+}
 
 impl SysReg for Pmevtyper1 {
     const CP: u32 = 15;
@@ -22,7 +43,7 @@ impl Pmevtyper1 {
     #[inline]
     /// Reads PMEVTYPER1 (*Performance Monitors Event Type Register 1*)
     pub fn read() -> Pmevtyper1 {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +58,7 @@ impl Pmevtyper1 {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
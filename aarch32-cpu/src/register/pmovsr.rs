@@ -3,10 +3,16 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMOVSR (*Performance Monitor Overflow Flag Status Clear Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmovsr(pub u32);
+pub struct Pmovsr {
+    /// Cycle counter bit
+    #[bit(31, rw)]
+    c: bool,
+    /// Event counter bits, one per counter
+    #[bit(0, rw)]
+    p: [bool; 31],
+}
 
 impl SysReg for Pmovsr {
     const CP: u32 = 15;
@@ -22,7 +28,7 @@ impl Pmovsr {
     #[inline]
     /// Reads PMOVSR (*Performance Monitor Overflow Flag Status Clear Register*)
     pub fn read() -> Pmovsr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +43,7 @@ impl Pmovsr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
@@ -1,12 +1,17 @@
 //! Code for managing PMSELR (*Performance Monitors Event Counter Selection Register*)
 
+use arbitrary_int::u5;
+
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMSELR (*Performance Monitors Event Counter Selection Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmselr(pub u32);
+pub struct Pmselr {
+    /// The selected event counter
+    #[bits(0..=4, rw)]
+    sel: u5,
+}
 
 impl SysReg for Pmselr {
     const CP: u32 = 15;
@@ -22,7 +27,7 @@ impl Pmselr {
     #[inline]
     /// Reads PMSELR (*Performance Monitors Event Counter Selection Register*)
     pub fn read() -> Pmselr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +42,7 @@ impl Pmselr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
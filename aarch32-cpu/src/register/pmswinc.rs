@@ -3,10 +3,13 @@
 use crate::register::{SysReg, SysRegWrite};
 
 /// PMSWINC (*Performance Monitors Software Increment Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmswinc(pub u32);
+pub struct Pmswinc {
+    /// Software increment bits, one per event counter
+    #[bit(0, rw)]
+    p: [bool; 31],
+}
 
 impl SysReg for Pmswinc {
     const CP: u32 = 15;
@@ -27,7 +30,7 @@ impl Pmswinc {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
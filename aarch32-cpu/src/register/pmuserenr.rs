@@ -3,10 +3,22 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// PMUSERENR (*Performance Monitors User Enable Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Pmuserenr(pub u32);
+pub struct Pmuserenr {
+    /// Event counter read enable (Armv8 only)
+    #[bit(3, rw)]
+    er: bool,
+    /// Cycle counter read enable (Armv8 only)
+    #[bit(2, rw)]
+    cr: bool,
+    /// Software increment write enable (Armv8 only)
+    #[bit(1, rw)]
+    sw: bool,
+    /// User mode access enable
+    #[bit(0, rw)]
+    en: bool,
+}
 
 impl SysReg for Pmuserenr {
     const CP: u32 = 15;
@@ -22,7 +34,7 @@ impl Pmuserenr {
     #[inline]
     /// Reads PMUSERENR (*Performance Monitors User Enable Register*)
     pub fn read() -> Pmuserenr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +49,7 @@ impl Pmuserenr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
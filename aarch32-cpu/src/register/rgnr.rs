@@ -5,10 +5,13 @@ use crate::register::{SysReg, SysRegRead};
 use super::SysRegWrite;
 
 /// RGNR (*MPU Region Number Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Rgnr(pub u32);
+pub struct Rgnr {
+    /// The selected region number
+    #[bits(0..=7, rw)]
+    region: u8,
+}
 
 impl SysReg for Rgnr {
     const CP: u32 = 15;
@@ -24,7 +27,7 @@ impl Rgnr {
     #[inline]
     /// Reads RGNR (*MPU Region Number Register*)
     pub fn read() -> Rgnr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -36,6 +39,6 @@ impl Rgnr {
     ///
     /// This affects what DRACR, IRACR, IRSR and DRSR give you.
     pub fn write(value: Rgnr) {
-        unsafe { <Self as SysRegWrite>::write_raw(value.0) }
+        unsafe { <Self as SysRegWrite>::write_raw(value.raw_value()) }
     }
 }
@@ -0,0 +1,98 @@
+//! Code for managing SCR (*Secure Configuration Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// SCR (*Secure Configuration Register*)
+///
+/// Part of the Security Extensions. It can only be accessed from Secure PL1
+/// modes - accessing it from Non-secure state is UNDEFINED.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scr {
+    /// Secure Instruction Fetch - stop Secure state fetching instructions from
+    /// Non-secure memory
+    #[bit(9, rw)]
+    sif: bool,
+    /// Hyp Call enable - make HVC instructions available in Non-secure PL1
+    /// modes
+    #[bit(8, rw)]
+    hce: bool,
+    /// Secure Monitor Call disable - make SMC instructions UNDEFINED
+    #[bit(7, rw)]
+    scd: bool,
+    /// Not Early Termination - disable early termination of data operations
+    #[bit(6, rw)]
+    net: bool,
+    /// Allow Non-secure state to modify CPSR.A
+    #[bit(5, rw)]
+    aw: bool,
+    /// Allow Non-secure state to modify CPSR.F
+    #[bit(4, rw)]
+    fw: bool,
+    /// Take External Aborts to Monitor mode
+    #[bit(3, rw)]
+    ea: bool,
+    /// Take FIQs to Monitor mode
+    #[bit(2, rw)]
+    fiq: bool,
+    /// Take IRQs to Monitor mode
+    #[bit(1, rw)]
+    irq: bool,
+    /// Non-secure - modes other than Monitor mode are in Non-secure state
+    #[bit(0, rw)]
+    ns: bool,
+}
+
+impl SysReg for Scr {
+    const CP: u32 = 15;
+    const CRN: u32 = 1;
+    const OP1: u32 = 0;
+    const CRM: u32 = 1;
+    const OP2: u32 = 0;
+}
+
+impl SysRegRead for Scr {}
+
+impl SysRegWrite for Scr {}
+
+impl Scr {
+    /// Read SCR (*Secure Configuration Register*)
+    #[inline]
+    pub fn read() -> Scr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    /// Write SCR (*Secure Configuration Register*)
+    ///
+    /// # Safety
+    ///
+    /// Setting the NS bit outside of Monitor mode moves the processor into
+    /// Non-secure state, where Secure memory can no longer be accessed. You
+    /// must make sure the code that runs next can cope with that.
+    #[inline]
+    pub unsafe fn write(value: Self) {
+        // Safety: Writing this register is atomic
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+        #[cfg(target_arch = "arm")]
+        crate::asm::isb();
+    }
+
+    /// Modify SCR (*Secure Configuration Register*)
+    ///
+    /// # Safety
+    ///
+    /// See [`Scr::write`].
+    #[inline]
+    pub unsafe fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        unsafe {
+            Self::write(value);
+        }
+    }
+}
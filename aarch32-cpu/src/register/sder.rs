@@ -0,0 +1,58 @@
+//! Code for managing SDER (*Secure Debug Enable Register*)
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// SDER (*Secure Debug Enable Register*)
+///
+/// Part of the Security Extensions. It can only be accessed from Secure PL1
+/// modes.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sder {
+    /// Secure User Non-Invasive Debug Enable
+    #[bit(1, rw)]
+    suniden: bool,
+    /// Secure User Invasive Debug Enable
+    #[bit(0, rw)]
+    suiden: bool,
+}
+
+impl SysReg for Sder {
+    const CP: u32 = 15;
+    const CRN: u32 = 1;
+    const OP1: u32 = 0;
+    const CRM: u32 = 1;
+    const OP2: u32 = 1;
+}
+
+impl SysRegRead for Sder {}
+
+impl SysRegWrite for Sder {}
+
+impl Sder {
+    /// Read SDER (*Secure Debug Enable Register*)
+    #[inline]
+    pub fn read() -> Sder {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    /// Write SDER (*Secure Debug Enable Register*)
+    #[inline]
+    pub fn write(value: Self) {
+        // Safety: Writing this register is atomic
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+
+    /// Modify SDER (*Secure Debug Enable Register*)
+    #[inline]
+    pub fn modify<F>(f: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        let mut value = Self::read();
+        f(&mut value);
+        Self::write(value);
+    }
+}
@@ -0,0 +1,596 @@
+//! Code for capturing a snapshot of the system registers
+//!
+//! Useful for attaching to crash reports. Register values are kept raw, so
+//! the snapshot can be serialised and decoded later, on a host.
+
+use super::cpsr::ProcessorMode;
+use super::*;
+
+/// The most MPU regions we record
+pub const MAX_REGIONS: usize = 16;
+
+/// The value of every system register we could safely read
+///
+/// Registers that don't exist on the architecture we were built for are left
+/// out. The ID registers (ID_PFR0 and so on), ACTLR, CPACR, CONTEXTIDR and
+/// IFAR need Armv6. MPIDR, REVIDR, CLIDR, ADFSR, AIFSR and the thread ID
+/// registers need Armv7, because some Armv6 processors don't have them.
+///
+/// Registers which can only be read at PL1 or higher are in
+/// [`Snapshot::system`], which is `None` if the snapshot was captured in User
+/// mode. Likewise the Hyp mode registers in [`Snapshot::hyp`] are only
+/// captured in Hyp mode.
+///
+/// Use `{:#x?}` to get something readable from the `Debug` output.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// CPSR (*Current Program Status Register*)
+    pub cpsr: u32,
+    /// TPIDRURW (*User Read/Write Thread ID Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub tpidrurw: u32,
+    /// TPIDRURO (*User Read-Only Thread ID Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub tpidruro: u32,
+    /// Registers only readable at PL1 or higher
+    pub system: Option<SystemRegisters>,
+    /// Generic Timer registers, if the timer is implemented
+    #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+    pub timer: Option<TimerRegisters>,
+    /// Hyp mode registers, if captured in Hyp mode
+    #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+    pub hyp: Option<HypRegisters>,
+}
+
+/// Registers readable at PL1 or higher
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemRegisters {
+    /// MIDR (*Main ID Register*)
+    pub midr: u32,
+    /// REVIDR (*Revision ID Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub revidr: u32,
+    /// MPIDR (*Multiprocessor Affinity Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub mpidr: u32,
+    /// CTR (*Cache Type Register*)
+    pub ctr: u32,
+    /// CLIDR (*Cache Level ID Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub clidr: u32,
+    /// ID_PFR0 and ID_PFR1 (*Processor Feature Registers*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub id_pfr: [u32; 2],
+    /// ID_DFR0 (*Debug Feature Register 0*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub id_dfr0: u32,
+    /// ID_AFR0 (*Auxiliary Feature Register 0*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub id_afr0: u32,
+    /// ID_MMFR0 to ID_MMFR3 (*Memory Model Feature Registers*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub id_mmfr: [u32; 4],
+    /// ID_ISAR0 to ID_ISAR5 (*Instruction Set Attribute Registers*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub id_isar: [u32; 6],
+    /// SCTLR (*System Control Register*)
+    pub sctlr: u32,
+    /// ACTLR (*Auxiliary Control Register*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub actlr: u32,
+    /// CPACR (*Architectural Feature Access Control Register*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub cpacr: u32,
+    /// CONTEXTIDR (*Context ID Register*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub contextidr: u32,
+    /// TPIDRPRW (*PL1 only Thread ID Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub tpidrprw: u32,
+    /// VBAR (*Vector Base Address Register*)
+    #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+    pub vbar: u32,
+    /// DFSR (*Data Fault Status Register*)
+    #[cfg(armv5te_or_higher)]
+    pub dfsr: u32,
+    /// IFSR (*Instruction Fault Status Register*)
+    #[cfg(armv5te_or_higher)]
+    pub ifsr: u32,
+    /// DFAR (*Data Fault Address Register*)
+    pub dfar: u32,
+    /// IFAR (*Instruction Fault Address Register*)
+    #[cfg(any(test, doc, armv6_or_higher))]
+    pub ifar: u32,
+    /// ADFSR (*Auxiliary Data Fault Status Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub adfsr: u32,
+    /// AIFSR (*Auxiliary Instruction Fault Status Register*)
+    #[cfg(any(test, doc, armv7_or_higher))]
+    pub aifsr: u32,
+    /// PMSAv7 MPU state
+    #[cfg(any(test, arm_architecture = "v7-r"))]
+    pub pmsav7: Pmsav7Registers,
+    /// PMSAv8 EL1 MPU state
+    #[cfg(any(test, arm_architecture = "v8-r"))]
+    pub pmsav8: Pmsav8Registers,
+    /// VMSAv7 MMU state
+    #[cfg(any(test, arm_architecture = "v7-a"))]
+    pub vmsa: VmsaRegisters,
+}
+
+/// A PMSAv7 MPU region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pmsav7Region {
+    /// DRBAR (*Data Region Base Address Register*)
+    pub drbar: u32,
+    /// DRSR (*Data Region Size and Enable Register*)
+    pub drsr: u32,
+    /// DRACR (*Data Region Access Control Register*)
+    pub dracr: u32,
+}
+
+/// PMSAv7 MPU state
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pmsav7Registers {
+    /// MPUIR (*MPU Type Register*)
+    pub mpuir: u32,
+    /// The data (or unified) regions, up to the number given in MPUIR
+    pub regions: [Pmsav7Region; MAX_REGIONS],
+}
+
+/// A PMSAv8 MPU region
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pmsav8Region {
+    /// PRBAR or HPRBAR (*Protection Region Base Address Register*)
+    pub prbar: u32,
+    /// PRLAR or HPRLAR (*Protection Region Limit Address Register*)
+    pub prlar: u32,
+}
+
+/// PMSAv8 MPU state
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pmsav8Registers {
+    /// MPUIR or HMPUIR (*MPU Type Register*)
+    pub mpuir: u32,
+    /// MAIR0 and MAIR1, or HMAIR0 and HMAIR1 (*Memory Attribute Indirection Registers*)
+    pub mair: [u32; 2],
+    /// The regions, up to the number given in MPUIR
+    pub regions: [Pmsav8Region; MAX_REGIONS],
+}
+
+/// VMSAv7 MMU state
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VmsaRegisters {
+    /// TTBR0 (*Translation Table Base Register 0*)
+    pub ttbr0: u32,
+    /// TTBR1 (*Translation Table Base Register 1*)
+    pub ttbr1: u32,
+    /// TTBCR (*Translation Table Base Control Register*)
+    pub ttbcr: u32,
+    /// DACR (*Domain Access Control Register*)
+    pub dacr: u32,
+    /// PAR (*Physical Address Register*)
+    pub par: u32,
+    /// MAIR0 and MAIR1 (*Memory Attribute Indirection Registers*)
+    pub mair: [u32; 2],
+}
+
+/// Generic Timer state
+///
+/// When the Virtualization Extensions are implemented, the hypervisor decides
+/// whether PL1 can use the physical timer and counter (with CNTHCTL.PL1PCEN
+/// and CNTHCTL.PL1PCTEN), and PL1 can't find out what it decided. So unless
+/// the snapshot is captured in Hyp mode, or the processor has no
+/// Virtualization Extensions, the physical timer fields are `None` rather than
+/// risk an Undefined Instruction exception.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerRegisters {
+    /// CNTFRQ (*Counter-timer Frequency Register*)
+    pub cntfrq: u32,
+    /// CNTKCTL (*Counter-timer Kernel Control Register*)
+    pub cntkctl: u32,
+    /// CNTP_CTL (*Counter-timer Physical Timer Control Register*)
+    pub cntp_ctl: Option<u32>,
+    /// CNTP_CVAL (*Counter-timer Physical Timer CompareValue Register*)
+    pub cntp_cval: Option<u64>,
+    /// CNTV_CTL (*Counter-timer Virtual Timer Control Register*)
+    pub cntv_ctl: u32,
+    /// CNTV_CVAL (*Counter-timer Virtual Timer CompareValue Register*)
+    pub cntv_cval: u64,
+    /// CNTPCT (*Counter-timer Physical Count Register*)
+    pub cntpct: Option<u64>,
+    /// CNTVCT (*Counter-timer Virtual Count Register*)
+    pub cntvct: u64,
+}
+
+/// Hyp mode registers
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypRegisters {
+    /// HSCTLR (*Hyp System Control Register*)
+    pub hsctlr: u32,
+    /// HCR (*Hyp Configuration Register*)
+    pub hcr: u32,
+    /// HCR2 (*Hyp Configuration Register 2*)
+    pub hcr2: u32,
+    /// HSR (*Hyp Syndrome Register*)
+    pub hsr: u32,
+    /// HDFAR (*Hyp Data Fault Address Register*)
+    pub hdfar: u32,
+    /// HIFAR (*Hyp Instruction Fault Address Register*)
+    pub hifar: u32,
+    /// HPFAR (*Hyp IPA Fault Address Register*)
+    pub hpfar: u32,
+    /// HVBAR (*Hyp Vector Base Address Register*)
+    pub hvbar: u32,
+    /// HDCR (*Hyp Debug Control Register*)
+    pub hdcr: u32,
+    /// HCPTR (*Hyp Architectural Feature Trap Register*)
+    pub hcptr: u32,
+    /// HSTR (*Hyp System Trap Register*)
+    pub hstr: u32,
+    /// VMSAv7 Hyp mode and stage 2 translation state
+    #[cfg(any(test, arm_architecture = "v7-a"))]
+    pub vmsa: HypVmsaRegisters,
+    /// PMSAv8 EL2 MPU state
+    #[cfg(any(test, arm_architecture = "v8-r"))]
+    pub pmsav8: Pmsav8Registers,
+}
+
+/// VMSAv7 Hyp mode and stage 2 translation registers
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HypVmsaRegisters {
+    /// HTCR (*Hyp Translation Control Register*)
+    pub htcr: u32,
+    /// HTTBR (*Hyp Translation Table Base Register*)
+    pub httbr: u64,
+    /// VTCR (*Virtualization Translation Control Register*)
+    pub vtcr: u32,
+    /// VTTBR (*Virtualization Translation Table Base Register*)
+    pub vttbr: u64,
+}
+
+impl Snapshot {
+    /// Read every register which is safe to read at the current privilege level
+    ///
+    /// To read the MPU regions, the region selection register (RGNR, PRSELR
+    /// or HPRSELR) is changed and then restored, so don't call this while
+    /// something else might be configuring the MPU.
+    pub fn capture() -> Snapshot {
+        let cpsr = Cpsr::read();
+        let mode = cpsr.mode();
+        let system = if matches!(mode, Ok(ProcessorMode::Usr)) {
+            None
+        } else {
+            Some(SystemRegisters::capture())
+        };
+        Snapshot {
+            cpsr: cpsr.raw_value(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            tpidrurw: <Tpidrurw as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            tpidruro: <Tpidruro as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+            timer: match &system {
+                // ID_PFR1.GenTimer
+                Some(system) if (system.id_pfr[1] >> 16) & 0xF != 0 => {
+                    // ID_PFR1.Virtualization
+                    let physical = matches!(mode, Ok(ProcessorMode::Hyp))
+                        || (system.id_pfr[1] >> 12) & 0xF == 0;
+                    Some(TimerRegisters::capture(physical))
+                }
+                _ => None,
+            },
+            #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+            hyp: if matches!(mode, Ok(ProcessorMode::Hyp)) {
+                Some(HypRegisters::capture())
+            } else {
+                None
+            },
+            system,
+        }
+    }
+}
+
+impl SystemRegisters {
+    fn capture() -> SystemRegisters {
+        SystemRegisters {
+            midr: <Midr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            revidr: <Revidr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            mpidr: <Mpidr as SysRegRead>::read_raw(),
+            ctr: <Ctr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            clidr: <Clidr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            id_pfr: [
+                <IdPfr0 as SysRegRead>::read_raw(),
+                <IdPfr1 as SysRegRead>::read_raw(),
+            ],
+            #[cfg(any(test, doc, armv6_or_higher))]
+            id_dfr0: <IdDfr0 as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            id_afr0: <IdAfr0 as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            id_mmfr: [
+                <IdMmfr0 as SysRegRead>::read_raw(),
+                <IdMmfr1 as SysRegRead>::read_raw(),
+                <IdMmfr2 as SysRegRead>::read_raw(),
+                <IdMmfr3 as SysRegRead>::read_raw(),
+            ],
+            #[cfg(any(test, doc, armv6_or_higher))]
+            id_isar: [
+                <IdIsar0 as SysRegRead>::read_raw(),
+                <IdIsar1 as SysRegRead>::read_raw(),
+                <IdIsar2 as SysRegRead>::read_raw(),
+                <IdIsar3 as SysRegRead>::read_raw(),
+                <IdIsar4 as SysRegRead>::read_raw(),
+                <IdIsar5 as SysRegRead>::read_raw(),
+            ],
+            sctlr: <Sctlr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            actlr: <Actlr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            cpacr: <Cpacr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            contextidr: <Contextidr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            tpidrprw: <Tpidrprw as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+            vbar: <Vbar as SysRegRead>::read_raw(),
+            #[cfg(armv5te_or_higher)]
+            dfsr: <Dfsr as SysRegRead>::read_raw(),
+            #[cfg(armv5te_or_higher)]
+            ifsr: <Ifsr as SysRegRead>::read_raw(),
+            dfar: <Dfar as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv6_or_higher))]
+            ifar: <Ifar as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            adfsr: <Adfsr as SysRegRead>::read_raw(),
+            #[cfg(any(test, doc, armv7_or_higher))]
+            aifsr: <Aifsr as SysRegRead>::read_raw(),
+            #[cfg(any(test, arm_architecture = "v7-r"))]
+            pmsav7: Pmsav7Registers::capture(),
+            #[cfg(any(test, arm_architecture = "v8-r"))]
+            pmsav8: Pmsav8Registers::capture_el1(),
+            #[cfg(any(test, arm_architecture = "v7-a"))]
+            vmsa: VmsaRegisters {
+                ttbr0: <Ttbr0 as SysRegRead>::read_raw(),
+                ttbr1: <Ttbr1 as SysRegRead>::read_raw(),
+                ttbcr: <Ttbcr as SysRegRead>::read_raw(),
+                dacr: <Dacr as SysRegRead>::read_raw(),
+                par: <Par as SysRegRead>::read_raw(),
+                mair: [
+                    <Mair0 as SysRegRead>::read_raw(),
+                    <Mair1 as SysRegRead>::read_raw(),
+                ],
+            },
+        }
+    }
+}
+
+#[cfg(any(test, arm_architecture = "v7-r"))]
+impl Pmsav7Registers {
+    fn capture() -> Pmsav7Registers {
+        let mpuir = Mpuir::read();
+        let count = usize::from(mpuir.dregions()).min(MAX_REGIONS);
+        let mut regions = [Pmsav7Region::default(); MAX_REGIONS];
+        let old_rgnr = <Rgnr as SysRegRead>::read_raw();
+        for (idx, region) in regions.iter_mut().enumerate().take(count) {
+            select_region::<Rgnr>(idx as u32);
+            *region = Pmsav7Region {
+                drbar: <Drbar as SysRegRead>::read_raw(),
+                drsr: <Drsr as SysRegRead>::read_raw(),
+                dracr: <Dracr as SysRegRead>::read_raw(),
+            };
+        }
+        select_region::<Rgnr>(old_rgnr);
+        Pmsav7Registers {
+            mpuir: mpuir.raw_value(),
+            regions,
+        }
+    }
+}
+
+#[cfg(any(test, arm_architecture = "v8-r"))]
+impl Pmsav8Registers {
+    fn capture_el1() -> Pmsav8Registers {
+        let mpuir = Mpuir::read();
+        let count = usize::from(mpuir.dregions()).min(MAX_REGIONS);
+        let mut regions = [Pmsav8Region::default(); MAX_REGIONS];
+        let old_prselr = <Prselr as SysRegRead>::read_raw();
+        for (idx, region) in regions.iter_mut().enumerate().take(count) {
+            select_region::<Prselr>(idx as u32);
+            *region = Pmsav8Region {
+                prbar: <Prbar as SysRegRead>::read_raw(),
+                prlar: <Prlar as SysRegRead>::read_raw(),
+            };
+        }
+        select_region::<Prselr>(old_prselr);
+        Pmsav8Registers {
+            mpuir: mpuir.raw_value(),
+            mair: [
+                <Mair0 as SysRegRead>::read_raw(),
+                <Mair1 as SysRegRead>::read_raw(),
+            ],
+            regions,
+        }
+    }
+
+    fn capture_el2() -> Pmsav8Registers {
+        let hmpuir = Hmpuir::read();
+        let count = usize::from(hmpuir.region()).min(MAX_REGIONS);
+        let mut regions = [Pmsav8Region::default(); MAX_REGIONS];
+        let old_hprselr = <Hprselr as SysRegRead>::read_raw();
+        for (idx, region) in regions.iter_mut().enumerate().take(count) {
+            select_region::<Hprselr>(idx as u32);
+            *region = Pmsav8Region {
+                prbar: <Hprbar as SysRegRead>::read_raw(),
+                prlar: <Hprlar as SysRegRead>::read_raw(),
+            };
+        }
+        select_region::<Hprselr>(old_hprselr);
+        Pmsav8Registers {
+            mpuir: hmpuir.raw_value(),
+            mair: [
+                <Hmair0 as SysRegRead>::read_raw(),
+                <Hmair1 as SysRegRead>::read_raw(),
+            ],
+            regions,
+        }
+    }
+}
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+impl TimerRegisters {
+    fn capture(physical: bool) -> TimerRegisters {
+        TimerRegisters {
+            cntfrq: <Cntfrq as SysRegRead>::read_raw(),
+            cntkctl: <Cntkctl as SysRegRead>::read_raw(),
+            cntp_ctl: physical.then(<CntpCtl as SysRegRead>::read_raw),
+            cntp_cval: physical.then(<CntpCval as SysRegRead64>::read_raw),
+            cntv_ctl: <CntvCtl as SysRegRead>::read_raw(),
+            cntv_cval: <CntvCval as SysRegRead64>::read_raw(),
+            cntpct: physical.then(<CntPct as SysRegRead64>::read_raw),
+            cntvct: <CntVct as SysRegRead64>::read_raw(),
+        }
+    }
+}
+
+#[cfg(any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+impl HypRegisters {
+    fn capture() -> HypRegisters {
+        HypRegisters {
+            hsctlr: <Hsctlr as SysRegRead>::read_raw(),
+            hcr: <Hcr as SysRegRead>::read_raw(),
+            hcr2: <Hcr2 as SysRegRead>::read_raw(),
+            hsr: <Hsr as SysRegRead>::read_raw(),
+            hdfar: <Hdfar as SysRegRead>::read_raw(),
+            hifar: <Hifar as SysRegRead>::read_raw(),
+            hpfar: <Hpfar as SysRegRead>::read_raw(),
+            hvbar: <Hvbar as SysRegRead>::read_raw(),
+            hdcr: <Hdcr as SysRegRead>::read_raw(),
+            hcptr: <Hcptr as SysRegRead>::read_raw(),
+            hstr: <Hstr as SysRegRead>::read_raw(),
+            #[cfg(any(test, arm_architecture = "v7-a"))]
+            vmsa: HypVmsaRegisters {
+                htcr: <Htcr as SysRegRead>::read_raw(),
+                httbr: <Httbr as SysRegRead64>::read_raw(),
+                vtcr: <Vtcr as SysRegRead>::read_raw(),
+                vttbr: <Vttbr as SysRegRead64>::read_raw(),
+            },
+            #[cfg(any(test, arm_architecture = "v8-r"))]
+            pmsav8: Pmsav8Registers::capture_el2(),
+        }
+    }
+}
+
+/// Write a region selection register, and wait for it to take effect
+#[cfg(any(test, arm_architecture = "v7-r", arm_architecture = "v8-r"))]
+fn select_region<R: SysRegWrite>(idx: u32) {
+    unsafe {
+        R::write_raw(idx);
+    }
+    #[cfg(target_arch = "arm")]
+    crate::asm::isb();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::sim::{self, Encoding};
+
+    #[test]
+    fn capture() {
+        sim::reset();
+        // CPSR reads as zero on the host. That isn't User mode, so we get the
+        // PL1 registers.
+        sim::set(Encoding::of::<Midr>(), 0x410F_C0F1);
+        sim::set(Encoding::of::<Sctlr>(), 0x00C5_0078);
+        sim::set(Encoding::of::<Ttbr0>(), 0x8000_4059);
+        sim::set(Encoding::of::<Ttbr1>(), 0x8010_0059);
+        sim::set(Encoding::of::<Ttbcr>(), 0x0000_0002);
+        sim::set(Encoding::of::<Mair1>(), 0x0044_FF04);
+        // two PMSAv7 regions
+        sim::set(Encoding::of::<Mpuir>(), 2 << 8);
+        sim::add_bank(
+            Encoding::of::<Rgnr>(),
+            &[Encoding::of::<Drbar>(), Encoding::of::<Drsr>()],
+        );
+        sim::set_banked(Encoding::of::<Drbar>(), 1, 0x2000_0000);
+        sim::set_banked(Encoding::of::<Drsr>(), 1, 0x0000_0023);
+        sim::set(Encoding::of::<Rgnr>(), 5);
+
+        let snapshot = Snapshot::capture();
+        let system = snapshot.system.unwrap();
+        assert_eq!(system.midr, 0x410F_C0F1);
+        assert_eq!(system.sctlr, 0x00C5_0078);
+        assert_eq!(system.vmsa.ttbr0, 0x8000_4059);
+        assert_eq!(system.vmsa.ttbr1, 0x8010_0059);
+        assert_eq!(system.vmsa.ttbcr, 0x0000_0002);
+        assert_eq!(system.vmsa.mair, [0, 0x0044_FF04]);
+        assert_eq!(system.pmsav7.mpuir, 2 << 8);
+        assert_eq!(system.pmsav7.regions[0], Pmsav7Region::default());
+        assert_eq!(
+            system.pmsav7.regions[1],
+            Pmsav7Region {
+                drbar: 0x2000_0000,
+                drsr: 0x0000_0023,
+                dracr: 0,
+            }
+        );
+        // the selected region is put back
+        assert_eq!(sim::get(Encoding::of::<Rgnr>()), 5);
+        // ID_PFR1 says there's no timer
+        assert!(snapshot.timer.is_none());
+        assert!(snapshot.hyp.is_none());
+    }
+
+    #[test]
+    fn capture_timer() {
+        sim::reset();
+        sim::set(Encoding::of::<Cntfrq>(), 62_500_000);
+        sim::set(Encoding::of::<CntvCtl>(), 0b101);
+        sim::set(Encoding::of64::<CntvCval>(), 0x1_0000_0000);
+        sim::set(Encoding::of::<CntpCtl>(), 0b001);
+        sim::set(Encoding::of64::<CntPct>(), 1234);
+
+        // GenTimer, but no Virtualization Extensions
+        sim::set(Encoding::of::<IdPfr1>(), 1 << 16);
+        let timer = Snapshot::capture().timer.unwrap();
+        assert_eq!(timer.cntfrq, 62_500_000);
+        assert_eq!(timer.cntv_ctl, 0b101);
+        assert_eq!(timer.cntv_cval, 0x1_0000_0000);
+        assert_eq!(timer.cntp_ctl, Some(0b001));
+        assert_eq!(timer.cntpct, Some(1234));
+
+        // With the Virtualization Extensions, PL1 leaves the physical timer
+        // alone
+        sim::set(Encoding::of::<IdPfr1>(), 1 << 16 | 1 << 12);
+        let timer = Snapshot::capture().timer.unwrap();
+        assert_eq!(timer.cntv_ctl, 0b101);
+        assert_eq!(timer.cntp_ctl, None);
+        assert_eq!(timer.cntp_cval, None);
+        assert_eq!(timer.cntpct, None);
+    }
+}
@@ -0,0 +1,62 @@
+//! Code for managing TTBCR (*Translation Table Base Control Register*)
+
+use arbitrary_int::u3;
+
+use crate::register::{SysReg, SysRegRead, SysRegWrite};
+
+/// TTBCR (*Translation Table Base Control Register*)
+///
+/// The fields here are for the Short-descriptor translation table format,
+/// which is used when [`Ttbcr::eae`] is `false`.
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ttbcr {
+    /// Extended Address Enable
+    ///
+    /// Selects the Long-descriptor translation table format.
+    #[bit(31, rw)]
+    eae: bool,
+    /// Translation table walk Disable for TTBR1
+    #[bit(5, rw)]
+    pd1: bool,
+    /// Translation table walk Disable for TTBR0
+    #[bit(4, rw)]
+    pd0: bool,
+    /// The width of the base address held in TTBR0
+    ///
+    /// When this is zero, TTBR1 is not used.
+    #[bits(0..=2, rw)]
+    n: u3,
+}
+
+impl SysReg for Ttbcr {
+    const CP: u32 = 15;
+    const CRN: u32 = 2;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 2;
+}
+
+impl crate::register::SysRegRead for Ttbcr {}
+
+impl crate::register::SysRegWrite for Ttbcr {}
+
+impl Ttbcr {
+    #[inline]
+    /// Reads TTBCR (*Translation Table Base Control Register*)
+    pub fn read() -> Ttbcr {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    #[inline]
+    /// Writes TTBCR (*Translation Table Base Control Register*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Code for managing TTBR1 (*Translation Table Base Register 1*)
+
+use arbitrary_int::u18;
+
+use crate::register::{ttbr0::Region, SysReg, SysRegRead, SysRegWrite};
+
+/// TTBR1 (*Translation Table Base Register 1*)
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ttbr1 {
+    /// Translation table base 1 address
+    #[bits(14..=31, rw)]
+    addr: u18,
+
+    /// Inner Region
+    ///
+    /// See docs for the [`Ttbr1::c`] bit.
+    #[bit(6, rw)]
+    irgn: bool,
+
+    /// Not Outer Shareable
+    ///
+    /// Ignored when [`Ttbr1::s`] is `false`.
+    #[bit(5, rw)]
+    nos: bool,
+
+    /// Region bits
+    ///
+    /// Indicates the Outer cacheability attributes for the memory associated
+    /// with the translation table walks.
+    #[bits(3..=4, rw)]
+    rgn: Region,
+
+    /// Shareable
+    #[bit(1, rw)]
+    s: bool,
+
+    /// Cacheable
+    ///
+    /// Works the same way as [`Ttbr0::c`](crate::register::Ttbr0::c).
+    #[bit(0, rw)]
+    c: bool,
+}
+
+impl Ttbr1 {
+    /// Get the address
+    ///
+    /// Returns a full 32-bit memory address.
+    pub fn get_address(&self) -> usize {
+        (self.raw_value() & 0xFFFF_C000) as usize
+    }
+
+    /// Change the address
+    ///
+    /// Pass a full 32-bit memory address. It will be shifted before being stored in the returned value.
+    pub fn with_address(self, address: usize) -> Self {
+        let addr = u18::from_u32((address >> 14) as u32);
+        self.with_addr(addr)
+    }
+}
+
+impl SysReg for Ttbr1 {
+    const CP: u32 = 15;
+    const CRN: u32 = 2;
+    const OP1: u32 = 0;
+    const CRM: u32 = 0;
+    const OP2: u32 = 1;
+}
+
+impl crate::register::SysRegRead for Ttbr1 {}
+
+impl crate::register::SysRegWrite for Ttbr1 {}
+
+impl Ttbr1 {
+    #[inline]
+    /// Reads TTBR1 (*Translation Table Base Register 1*)
+    pub fn read() -> Ttbr1 {
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
+    }
+
+    #[inline]
+    /// Writes TTBR1 (*Translation Table Base Register 1*)
+    ///
+    /// # Safety
+    ///
+    /// Ensure that this value is appropriate for this register
+    pub unsafe fn write(value: Self) {
+        unsafe {
+            <Self as SysRegWrite>::write_raw(value.raw_value());
+        }
+    }
+}
@@ -3,10 +3,28 @@
 use crate::register::{SysReg, SysRegRead, SysRegWrite};
 
 /// VMPIDR (*Virtualization Multiprocessor ID Register*)
-#[derive(Debug, Clone, Copy)]
-#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[bitbybit::bitfield(u32, debug, defmt_bitfields(feature = "defmt"))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
-pub struct Vmpidr(pub u32);
+pub struct Vmpidr {
+    /// Indicates the register uses the Multiprocessing Extensions format
+    #[bit(31, rw)]
+    m: bool,
+    /// Is this processor part of a uniprocessor system?
+    #[bit(30, rw)]
+    u: bool,
+    /// Are the lowest level of affinity threads within a core?
+    #[bit(24, rw)]
+    mt: bool,
+    /// Affinity level 2
+    #[bits(16..=23, rw)]
+    aff2: u8,
+    /// Affinity level 1
+    #[bits(8..=15, rw)]
+    aff1: u8,
+    /// Affinity level 0
+    #[bits(0..=7, rw)]
+    aff0: u8,
+}
 
 impl SysReg for Vmpidr {
     const CP: u32 = 15;
@@ -22,7 +40,7 @@ impl Vmpidr {
     #[inline]
     /// Reads VMPIDR (*Virtualization Multiprocessor ID Register*)
     pub fn read() -> Vmpidr {
-        Self(<Self as SysRegRead>::read_raw())
+        Self::new_with_raw_value(<Self as SysRegRead>::read_raw())
     }
 }
 
@@ -37,7 +55,7 @@ impl Vmpidr {
     /// Ensure that this value is appropriate for this register
     pub unsafe fn write(value: Self) {
         unsafe {
-            <Self as SysRegWrite>::write_raw(value.0);
+            <Self as SysRegWrite>::write_raw(value.raw_value());
         }
     }
 }
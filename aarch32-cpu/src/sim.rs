@@ -0,0 +1,244 @@
+//! A simulated register file, for testing drivers on the host
+//!
+//! When the `sim` feature is enabled and we are not compiling for an Arm
+//! target, every [`SysRegRead`](crate::register::SysRegRead) and
+//! [`SysRegWrite`](crate::register::SysRegWrite) access (and their 64-bit
+//! equivalents) goes to a thread-local register file instead. Every register
+//! starts as zero.
+//!
+//! You can pre-load register values, install hooks to model side-effects, and
+//! check the log of accesses afterwards:
+//!
+//! ```rust
+//! # #[cfg(feature = "sim")] {
+//! use aarch32_cpu::register::{Drbar, Mpuir, Rgnr};
+//! use aarch32_cpu::sim::{self, Access, Encoding};
+//!
+//! sim::reset();
+//! // Two data regions
+//! sim::set(Encoding::of::<Mpuir>(), 2 << 8);
+//! // DRBAR is banked, and RGNR selects the bank
+//! sim::add_bank(Encoding::of::<Rgnr>(), &[Encoding::of::<Drbar>()]);
+//!
+//! Rgnr::write(Rgnr::new_with_raw_value(1));
+//! Drbar::write(Drbar(0x2000_0000));
+//!
+//! assert_eq!(sim::get_banked(Encoding::of::<Drbar>(), 1), 0x2000_0000);
+//! assert_eq!(
+//!     sim::take_log(),
+//!     [
+//!         Access::Write(Encoding::of::<Rgnr>(), 1),
+//!         Access::Write(Encoding::of::<Drbar>(), 0x2000_0000),
+//!     ]
+//! );
+//! # }
+//! ```
+//!
+//! Each test thread gets its own register file, so tests can run in parallel.
+
+use core::cell::RefCell;
+use std::boxed::Box;
+use std::collections::HashMap;
+use std::thread_local;
+use std::vec::Vec;
+
+use crate::register::{SysReg, SysReg64};
+
+/// Identifies a system register by how it is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Encoding {
+    /// A 32-bit register, accessed with MRC/MCR
+    Reg32 {
+        /// Co-processor number
+        cp: u32,
+        /// CRn argument
+        crn: u32,
+        /// Op1 argument
+        op1: u32,
+        /// CRm argument
+        crm: u32,
+        /// Op2 argument
+        op2: u32,
+    },
+    /// A 64-bit register, accessed with MRRC/MCRR
+    Reg64 {
+        /// Co-processor number
+        cp: u32,
+        /// Op1 argument
+        op1: u32,
+        /// CRm argument
+        crm: u32,
+    },
+}
+
+impl Encoding {
+    /// The encoding for a 32-bit register type
+    pub const fn of<R: SysReg + ?Sized>() -> Encoding {
+        Encoding::Reg32 {
+            cp: R::CP,
+            crn: R::CRN,
+            op1: R::OP1,
+            crm: R::CRM,
+            op2: R::OP2,
+        }
+    }
+
+    /// The encoding for a 64-bit register type
+    pub const fn of64<R: SysReg64 + ?Sized>() -> Encoding {
+        Encoding::Reg64 {
+            cp: R::CP,
+            op1: R::OP1,
+            crm: R::CRM,
+        }
+    }
+}
+
+/// A logged register access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// A register was read, and gave this value
+    Read(Encoding, u64),
+    /// A register was written with this value
+    Write(Encoding, u64),
+}
+
+/// The stored register values
+///
+/// This is what hooks get to work with.
+#[derive(Debug, Default)]
+pub struct RegisterFile {
+    values: HashMap<Encoding, u64>,
+    banked: HashMap<(Encoding, u64), u64>,
+}
+
+impl RegisterFile {
+    /// Get the stored value of a register
+    pub fn get(&self, encoding: Encoding) -> u64 {
+        self.values.get(&encoding).copied().unwrap_or(0)
+    }
+
+    /// Set the stored value of a register
+    pub fn set(&mut self, encoding: Encoding, value: u64) {
+        self.values.insert(encoding, value);
+    }
+
+    /// Get the stored value of one bank of a banked register
+    pub fn get_banked(&self, encoding: Encoding, bank: u64) -> u64 {
+        self.banked.get(&(encoding, bank)).copied().unwrap_or(0)
+    }
+
+    /// Set the stored value of one bank of a banked register
+    pub fn set_banked(&mut self, encoding: Encoding, bank: u64, value: u64) {
+        self.banked.insert((encoding, bank), value);
+    }
+}
+
+type ReadHook = Box<dyn FnMut(&mut RegisterFile) -> u64>;
+type WriteHook = Box<dyn FnMut(&mut RegisterFile, u64)>;
+
+#[derive(Default)]
+struct Simulator {
+    registers: RegisterFile,
+    read_hooks: HashMap<Encoding, ReadHook>,
+    write_hooks: HashMap<Encoding, WriteHook>,
+    log: Vec<Access>,
+}
+
+thread_local! {
+    static SIMULATOR: RefCell<Simulator> = RefCell::new(Simulator::default());
+}
+
+/// Clear all register values, hooks, and the access log
+pub fn reset() {
+    SIMULATOR.with_borrow_mut(|sim| *sim = Simulator::default());
+}
+
+/// Get the stored value of a register, without logging an access
+pub fn get(encoding: Encoding) -> u64 {
+    SIMULATOR.with_borrow(|sim| sim.registers.get(encoding))
+}
+
+/// Set the stored value of a register, without logging an access
+pub fn set(encoding: Encoding, value: u64) {
+    SIMULATOR.with_borrow_mut(|sim| sim.registers.set(encoding, value));
+}
+
+/// Get the stored value of one bank of a banked register
+pub fn get_banked(encoding: Encoding, bank: u64) -> u64 {
+    SIMULATOR.with_borrow(|sim| sim.registers.get_banked(encoding, bank))
+}
+
+/// Set the stored value of one bank of a banked register
+pub fn set_banked(encoding: Encoding, bank: u64, value: u64) {
+    SIMULATOR.with_borrow_mut(|sim| sim.registers.set_banked(encoding, bank, value));
+}
+
+/// Run a function instead of the default behaviour when a register is read
+///
+/// The value the function returns is what the read gives.
+pub fn on_read<F>(encoding: Encoding, hook: F)
+where
+    F: FnMut(&mut RegisterFile) -> u64 + 'static,
+{
+    SIMULATOR.with_borrow_mut(|sim| {
+        sim.read_hooks.insert(encoding, Box::new(hook));
+    });
+}
+
+/// Run a function instead of the default behaviour when a register is written
+pub fn on_write<F>(encoding: Encoding, hook: F)
+where
+    F: FnMut(&mut RegisterFile, u64) + 'static,
+{
+    SIMULATOR.with_borrow_mut(|sim| {
+        sim.write_hooks.insert(encoding, Box::new(hook));
+    });
+}
+
+/// Make some registers banked, with the bank chosen by a selector register
+///
+/// For example, the PMSAv7 region registers are selected by RGNR, and the
+/// PMSAv8 region registers are selected by PRSELR.
+pub fn add_bank(selector: Encoding, registers: &[Encoding]) {
+    for &encoding in registers {
+        on_read(encoding, move |regs| {
+            regs.get_banked(encoding, regs.get(selector))
+        });
+        on_write(encoding, move |regs, value| {
+            regs.set_banked(encoding, regs.get(selector), value)
+        });
+    }
+}
+
+/// Get a copy of the log of register accesses
+pub fn log() -> Vec<Access> {
+    SIMULATOR.with_borrow(|sim| sim.log.clone())
+}
+
+/// Take the log of register accesses, leaving it empty
+pub fn take_log() -> Vec<Access> {
+    SIMULATOR.with_borrow_mut(|sim| core::mem::take(&mut sim.log))
+}
+
+/// Perform a simulated read
+pub(crate) fn read(encoding: Encoding) -> u64 {
+    SIMULATOR.with_borrow_mut(|sim| {
+        let value = match sim.read_hooks.get_mut(&encoding) {
+            Some(hook) => hook(&mut sim.registers),
+            None => sim.registers.get(encoding),
+        };
+        sim.log.push(Access::Read(encoding, value));
+        value
+    })
+}
+
+/// Perform a simulated write
+pub(crate) fn write(encoding: Encoding, value: u64) {
+    SIMULATOR.with_borrow_mut(|sim| {
+        sim.log.push(Access::Write(encoding, value));
+        match sim.write_hooks.get_mut(&encoding) {
+            Some(hook) => hook(&mut sim.registers, value),
+            None => sim.registers.set(encoding, value),
+        }
+    });
+}
@@ -0,0 +1,177 @@
+//! Support for the Arm SMC Calling Convention (SMCCC)
+//!
+//! SMCCC defines how to make calls into firmware or a hypervisor with the
+//! `SMC` and `HVC` instructions. The function ID goes in `r0`, arguments go in
+//! `r1` upwards, and results come back in `r0` to `r3`.
+//!
+//! Rust's inline assembly cannot use `r6` or `r7`, so we only support passing
+//! five arguments (in `r1` to `r5`).
+//!
+//! See [SMC Calling Convention][smccc].
+//!
+//! [smccc]: https://developer.arm.com/documentation/den0028/latest/
+
+/// The SMCCC_VERSION function ID
+pub const SMCCC_VERSION: u32 = 0x8000_0000;
+
+/// The value returned in `r0` for a function ID that isn't implemented
+pub const NOT_SUPPORTED: i32 = -1;
+
+/// Make an SMCCC call with the SMC instruction
+///
+/// Returns the values left in `r0` to `r3`.
+///
+/// On Armv8-R there is no EL3, so SMC is UNDEFINED.
+///
+/// # Safety
+///
+/// The function you call may do anything, including turning off this core.
+/// You must make sure `function_id` and `args` describe a call that is safe
+/// to make.
+#[inline]
+pub unsafe fn smc_call(function_id: u32, args: [u32; 5]) -> [u32; 4] {
+    #[cfg(any(arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+    {
+        let r0: u32;
+        let r1: u32;
+        let r2: u32;
+        let r3: u32;
+        unsafe {
+            core::arch::asm!(
+                ".arch_extension sec",
+                "smc #0",
+                inout("r0") function_id => r0,
+                inout("r1") args[0] => r1,
+                inout("r2") args[1] => r2,
+                inout("r3") args[2] => r3,
+                inout("r4") args[3] => _,
+                inout("r5") args[4] => _,
+            );
+        }
+        [r0, r1, r2, r3]
+    }
+    #[cfg(not(any(arm_architecture = "v7-a", arm_architecture = "v8-r")))]
+    {
+        let _ = (function_id, args);
+        [0; 4]
+    }
+}
+
+/// Make an SMCCC call with the HVC instruction
+///
+/// Returns the values left in `r0` to `r3`.
+///
+/// # Safety
+///
+/// The function you call may do anything, including turning off this core.
+/// You must make sure `function_id` and `args` describe a call that is safe
+/// to make.
+#[inline]
+pub unsafe fn hvc_call(function_id: u32, args: [u32; 5]) -> [u32; 4] {
+    #[cfg(any(arm_architecture = "v7-a", arm_architecture = "v8-r"))]
+    {
+        let r0: u32;
+        let r1: u32;
+        let r2: u32;
+        let r3: u32;
+        unsafe {
+            core::arch::asm!(
+                ".arch_extension virt",
+                "hvc #0",
+                inout("r0") function_id => r0,
+                inout("r1") args[0] => r1,
+                inout("r2") args[1] => r2,
+                inout("r3") args[2] => r3,
+                inout("r4") args[3] => _,
+                inout("r5") args[4] => _,
+            );
+        }
+        [r0, r1, r2, r3]
+    }
+    #[cfg(not(any(arm_architecture = "v7-a", arm_architecture = "v8-r")))]
+    {
+        let _ = (function_id, args);
+        [0; 4]
+    }
+}
+
+/// The instruction used to make an SMCCC call
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Conduit {
+    /// Use the SMC instruction, to call Secure firmware
+    Smc,
+    /// Use the HVC instruction, to call a hypervisor
+    Hvc,
+}
+
+impl Conduit {
+    /// Guess which conduit to use, based on where we are running
+    ///
+    /// * In Hyp mode, we use SMC, because HVC would call ourselves.
+    /// * In a PL1 mode on Armv8-R, we use HVC, because there is no EL3.
+    /// * In a PL1 mode on Armv7-A, we use HVC if the processor has the
+    ///   Virtualization Extensions (as we might be running under a
+    ///   hypervisor), SMC if it only has the Security Extensions, and HVC if
+    ///   it has neither. QEMU's `virt` machine without EL2 and EL3 hides both
+    ///   extensions, and emulates the firmware behind HVC.
+    ///
+    /// Returns `None` in User mode and in Monitor mode.
+    ///
+    /// If your firmware tells you which conduit to use (like the `method`
+    /// property of the `/psci` node in a Device Tree), use that instead.
+    pub fn detect() -> Option<Conduit> {
+        use crate::el::ExceptionLevel;
+        match crate::el::current().0 {
+            ExceptionLevel::El2 if cfg!(arm_architecture = "v8-r") => None,
+            ExceptionLevel::El2 => Some(Conduit::Smc),
+            ExceptionLevel::El1 if cfg!(arm_architecture = "v8-r") => Some(Conduit::Hvc),
+            ExceptionLevel::El1 => {
+                let features = crate::features::CpuFeatures::detect();
+                if features.security_extensions && !features.virtualization_extensions {
+                    Some(Conduit::Smc)
+                } else {
+                    Some(Conduit::Hvc)
+                }
+            }
+            ExceptionLevel::El0 | ExceptionLevel::El3 => None,
+        }
+    }
+
+    /// Make an SMCCC call using this conduit
+    ///
+    /// Returns the values left in `r0` to `r3`.
+    ///
+    /// # Safety
+    ///
+    /// See [`smc_call`] and [`hvc_call`].
+    #[inline]
+    pub unsafe fn call(self, function_id: u32, args: [u32; 5]) -> [u32; 4] {
+        match self {
+            Conduit::Smc => unsafe { smc_call(function_id, args) },
+            Conduit::Hvc => unsafe { hvc_call(function_id, args) },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::register::IdPfr1;
+    use crate::sim::{self, Encoding};
+
+    #[test]
+    fn detect_at_pl1() {
+        // CPSR reads as zero on the host, which counts as PL1
+        sim::reset();
+        // neither extension, like QEMU's virt machine without EL2 or EL3
+        assert_eq!(Conduit::detect(), Some(Conduit::Hvc));
+        // ID_PFR1.Security
+        sim::set(Encoding::of::<IdPfr1>(), 1 << 4);
+        assert_eq!(Conduit::detect(), Some(Conduit::Smc));
+        // ID_PFR1.Virtualization
+        sim::set(Encoding::of::<IdPfr1>(), 1 << 4 | 1 << 12);
+        assert_eq!(Conduit::detect(), Some(Conduit::Hvc));
+    }
+}
@@ -0,0 +1,432 @@
+//! Support for stage 2 translation, using the Long-descriptor format
+//!
+//! A hypervisor uses stage 2 translation to control which memory a guest can
+//! see. The guest's own MMU turns Virtual Addresses into Intermediate Physical
+//! Addresses (IPAs), and then the stage 2 tables turn IPAs into Physical
+//! Addresses. Anything the guest touches that isn't mapped causes a stage 2
+//! abort, which is taken to Hyp mode.
+//!
+//! [`Stage2Tables`] covers a 4 GiB IPA space, mapped in 2 MiB blocks:
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::register::{Hcr, TlbIAllNsNh, Vtcr, Vttbr};
+//! use aarch32_cpu::stage2::{Stage2Attributes, Stage2Tables};
+//!
+//! static mut GUEST_TABLES: Stage2Tables = Stage2Tables::new();
+//!
+//! let tables = unsafe { &mut *core::ptr::addr_of_mut!(GUEST_TABLES) };
+//! // Guest RAM at IPA 0x4000_0000 is really at 0x8000_0000
+//! tables.map(0x4000_0000, 0x8000_0000, 0x0400_0000, Stage2Attributes::RAM).unwrap();
+//! // The guest can see the UART
+//! tables.map(0x0900_0000, 0x0900_0000, 0x0020_0000, Stage2Attributes::DEVICE).unwrap();
+//! unsafe {
+//!     Vtcr::write(Stage2Tables::vtcr());
+//!     Vttbr::write(tables.vttbr(1));
+//! }
+//! TlbIAllNsNh::write();
+//! Hcr::modify(|h| h.set_vm(true));
+//! ```
+//!
+//! The tables are read by the hardware using their address, so this assumes
+//! that Hyp mode addresses are Physical Addresses (i.e. the Hyp mode MMU is
+//! off, or flat mapped). If you change the tables while a guest is running,
+//! execute a DSB and then invalidate the stage 2 TLB entries with
+//! [`TlbIAllNsNh`](crate::register::TlbIAllNsNh).
+
+use arbitrary_int::{u2, u28, u4};
+
+use crate::register::hyp::htcr::{Cacheability, Shareability};
+use crate::register::{Vtcr, Vttbr};
+
+/// Number of entries in a level 1 table covering a 32-bit IPA space
+pub const NUM_L1_ENTRIES: usize = 4;
+
+/// Number of entries in a level 2 or level 3 table
+pub const NUM_TABLE_ENTRIES: usize = 512;
+
+/// Size of the memory covered by a level 2 block
+pub const L2_BLOCK_SIZE: u32 = 2 * 1024 * 1024;
+
+/// Errors that can occur when changing stage 2 tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Stage2Error {
+    /// An address or size was not a multiple of the block size
+    #[error("address or size is not aligned to the block size")]
+    Misaligned,
+    /// The region goes past the end of the IPA or physical address space
+    #[error("region is outside the address space")]
+    OutOfRange,
+}
+
+/// Stage 2 access permissions (S2AP)
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum S2Ap {
+    /// All accesses generate a stage 2 Permission fault
+    NoAccess = 0b00,
+    /// Read-only
+    ReadOnly = 0b01,
+    /// Write-only
+    WriteOnly = 0b10,
+    /// Read and write
+    ReadWrite = 0b11,
+}
+
+/// Stage 2 memory attributes (MemAttr)
+///
+/// These are combined with the guest's stage 1 attributes, and whichever is
+/// more restrictive wins.
+#[bitbybit::bitenum(u4, exhaustive = false)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum MemAttr {
+    /// Strongly-ordered memory
+    StronglyOrdered = 0b0000,
+    /// Device memory
+    Device = 0b0001,
+    /// Normal memory, Inner and Outer Non-cacheable
+    NormalNonCacheable = 0b0101,
+    /// Normal memory, Inner and Outer Write-Through Cacheable
+    NormalWriteThrough = 0b1010,
+    /// Normal memory, Inner and Outer Write-Back Cacheable
+    NormalWriteBack = 0b1111,
+}
+
+/// The type of a Long-descriptor
+#[bitbybit::bitenum(u2, exhaustive = true)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, PartialEq, Eq)]
+pub enum DescriptorType {
+    /// Access generates a Translation fault
+    Invalid = 0b00,
+    /// Maps a block of memory (at level 1 or level 2)
+    Block = 0b01,
+    /// Also generates a Translation fault
+    Reserved = 0b10,
+    /// Points to the next level table (at level 1 or level 2), or maps a page
+    /// (at level 3)
+    TableOrPage = 0b11,
+}
+
+/// Attributes for a block or page of memory mapped at stage 2
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stage2Attributes {
+    /// Access permissions
+    pub access: S2Ap,
+    /// Memory attributes
+    pub mem_attr: MemAttr,
+    /// Shareability (ignored for Device and Strongly-ordered memory)
+    pub shareability: Shareability,
+    /// XN bit
+    pub execute_never: bool,
+}
+
+impl Stage2Attributes {
+    /// Read-write, executable, Inner Shareable, Write-Back Cacheable memory
+    pub const RAM: Stage2Attributes = Stage2Attributes {
+        access: S2Ap::ReadWrite,
+        mem_attr: MemAttr::NormalWriteBack,
+        shareability: Shareability::InnerShareable,
+        execute_never: false,
+    };
+
+    /// Read-write, execute-never Device memory
+    pub const DEVICE: Stage2Attributes = Stage2Attributes {
+        access: S2Ap::ReadWrite,
+        mem_attr: MemAttr::Device,
+        shareability: Shareability::NonShareable,
+        execute_never: true,
+    };
+}
+
+/// A stage 2 Long-descriptor
+///
+/// Depending on the type and the level of the table it is in, this either
+/// maps a block or page of memory, or points at the next level table.
+#[bitbybit::bitfield(u64, default = 0, defmt_fields(feature = "defmt"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(PartialEq, Eq)]
+pub struct Descriptor {
+    /// Execute-never bit
+    #[bit(54, rw)]
+    xn: bool,
+    /// Contiguous hint
+    #[bit(52, rw)]
+    contiguous: bool,
+    /// Bits 39 to 12 of the output address, or of the next level table
+    #[bits(12..=39, rw)]
+    addr: u28,
+    /// Access Flag
+    #[bit(10, rw)]
+    af: bool,
+    /// Shareability
+    #[bits(8..=9, rw)]
+    sh: Option<Shareability>,
+    /// Stage 2 access permissions
+    #[bits(6..=7, rw)]
+    s2ap: S2Ap,
+    /// Stage 2 memory attributes
+    #[bits(2..=5, rw)]
+    mem_attr: Option<MemAttr>,
+    /// Descriptor type
+    #[bits(0..=1, rw)]
+    descriptor_type: DescriptorType,
+}
+
+impl core::fmt::Debug for Descriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Descriptor {{ type={:?} addr={:#x} xn={} af={} sh={:#b} s2ap={:#b} mem_attr={:#b} }}",
+            self.descriptor_type(),
+            self.address(),
+            self.xn() as u8,
+            self.af() as u8,
+            (self.raw_value() >> 8) & 0b11,
+            self.s2ap() as u8,
+            (self.raw_value() >> 2) & 0b1111,
+        )
+    }
+}
+
+impl Descriptor {
+    /// A descriptor which causes a stage 2 Translation fault on any access
+    pub const FAULT: Descriptor = Descriptor::new_with_raw_value(0);
+
+    /// Make a descriptor which maps a block of memory (at level 1 or level 2)
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 4 KiB, or above 40 bits.
+    pub const fn new_block(phys_addr: u64, attrs: Stage2Attributes) -> Self {
+        Self::new_output(phys_addr, attrs, DescriptorType::Block)
+    }
+
+    /// Make a descriptor which maps a 4 KiB page of memory (at level 3)
+    ///
+    /// # Panics
+    ///
+    /// Physical address not aligned to 4 KiB, or above 40 bits.
+    pub const fn new_page(phys_addr: u64, attrs: Stage2Attributes) -> Self {
+        Self::new_output(phys_addr, attrs, DescriptorType::TableOrPage)
+    }
+
+    /// Make a descriptor which points at the next level table (at level 1 or
+    /// level 2)
+    ///
+    /// # Panics
+    ///
+    /// Table address not aligned to 4 KiB, or above 40 bits.
+    pub const fn new_table(table_addr: u64) -> Self {
+        Self::DEFAULT
+            .with_addr(Self::addr_bits(table_addr))
+            .with_descriptor_type(DescriptorType::TableOrPage)
+    }
+
+    /// Get the output address (or next level table address)
+    pub const fn address(&self) -> u64 {
+        (self.addr().value() as u64) << 12
+    }
+
+    /// Get the attributes of a block or page descriptor
+    ///
+    /// Returns `None` if the attributes use reserved values.
+    pub fn attributes(&self) -> Option<Stage2Attributes> {
+        Some(Stage2Attributes {
+            access: self.s2ap(),
+            mem_attr: self.mem_attr().ok()?,
+            shareability: self.sh().ok()?,
+            execute_never: self.xn(),
+        })
+    }
+
+    /// Make a block or page descriptor
+    const fn new_output(
+        phys_addr: u64,
+        attrs: Stage2Attributes,
+        descriptor_type: DescriptorType,
+    ) -> Self {
+        Self::DEFAULT
+            .with_xn(attrs.execute_never)
+            .with_addr(Self::addr_bits(phys_addr))
+            .with_af(true)
+            .with_sh(attrs.shareability)
+            .with_s2ap(attrs.access)
+            .with_mem_attr(attrs.mem_attr)
+            .with_descriptor_type(descriptor_type)
+    }
+
+    /// Convert an address into the value for the `addr` field
+    const fn addr_bits(addr: u64) -> u28 {
+        if addr & 0xFFF != 0 {
+            panic!("stage 2 address must be aligned to 4 KiB");
+        }
+        if addr >> 40 != 0 {
+            panic!("stage 2 address must fit in 40 bits");
+        }
+        u28::new((addr >> 12) as u32)
+    }
+}
+
+/// A set of stage 2 tables covering a 4 GiB IPA space with 2 MiB blocks
+///
+/// This is one level 1 table, pointing at four level 2 tables. It takes up a
+/// little over 16 KiB.
+#[repr(C, align(4096))]
+#[derive(Debug)]
+pub struct Stage2Tables {
+    /// The level 2 tables, one per GiB
+    l2: [[Descriptor; NUM_TABLE_ENTRIES]; NUM_L1_ENTRIES],
+    /// The level 1 table (which must be aligned to 32 bytes)
+    l1: [Descriptor; NUM_L1_ENTRIES],
+}
+
+impl Stage2Tables {
+    /// Make a set of tables with nothing mapped
+    pub const fn new() -> Self {
+        Self {
+            l2: [[Descriptor::FAULT; NUM_TABLE_ENTRIES]; NUM_L1_ENTRIES],
+            l1: [Descriptor::FAULT; NUM_L1_ENTRIES],
+        }
+    }
+
+    /// Map `size` bytes of IPA space starting at `ipa` to physical memory
+    /// starting at `phys_addr`
+    ///
+    /// All three values must be multiples of 2 MiB.
+    pub fn map(
+        &mut self,
+        ipa: u32,
+        phys_addr: u64,
+        size: u32,
+        attrs: Stage2Attributes,
+    ) -> Result<(), Stage2Error> {
+        if phys_addr % L2_BLOCK_SIZE as u64 != 0 {
+            return Err(Stage2Error::Misaligned);
+        }
+        if phys_addr + size as u64 > 1 << 40 {
+            return Err(Stage2Error::OutOfRange);
+        }
+        for (idx, offset) in Self::blocks(ipa, size)? {
+            self.l2[idx / NUM_TABLE_ENTRIES][idx % NUM_TABLE_ENTRIES] =
+                Descriptor::new_block(phys_addr + offset as u64, attrs);
+        }
+        Ok(())
+    }
+
+    /// Unmap `size` bytes of IPA space starting at `ipa`
+    ///
+    /// Both values must be multiples of 2 MiB.
+    pub fn unmap(&mut self, ipa: u32, size: u32) -> Result<(), Stage2Error> {
+        for (idx, _offset) in Self::blocks(ipa, size)? {
+            self.l2[idx / NUM_TABLE_ENTRIES][idx % NUM_TABLE_ENTRIES] = Descriptor::FAULT;
+        }
+        Ok(())
+    }
+
+    /// Get the level 2 descriptor which covers the given IPA
+    pub fn lookup(&self, ipa: u32) -> Descriptor {
+        let idx = (ipa / L2_BLOCK_SIZE) as usize;
+        self.l2[idx / NUM_TABLE_ENTRIES][idx % NUM_TABLE_ENTRIES]
+    }
+
+    /// Get a VTTBR value which uses these tables, for the guest with the given
+    /// VMID
+    ///
+    /// This also points the level 1 table at the level 2 tables, so call it
+    /// after these tables have reached their final address.
+    pub fn vttbr(&mut self, vmid: u8) -> Vttbr {
+        for (l1, l2) in self.l1.iter_mut().zip(self.l2.iter()) {
+            *l1 = Descriptor::new_table(l2.as_ptr() as usize as u64);
+        }
+        Vttbr::new_with_raw_value(0)
+            .with_vmid(vmid)
+            .with_baddr(arbitrary_int::u40::new(self.l1.as_ptr() as usize as u64))
+    }
+
+    /// Get a VTCR value which matches these tables
+    ///
+    /// This gives a 32-bit IPA space, starting the table walk at level 1, with
+    /// the tables in Inner Shareable Write-Back Cacheable memory.
+    pub const fn vtcr() -> Vtcr {
+        Vtcr::new_with_raw_value(0)
+            .with_sh0(Shareability::InnerShareable)
+            .with_orgn0(Cacheability::WriteBackWriteAlloc)
+            .with_irgn0(Cacheability::WriteBackWriteAlloc)
+            .with_sl0(u2::new(0b01))
+            .with_s(false)
+            .with_t0sz(u4::new(0))
+    }
+
+    /// Work out which level 2 entries cover a region
+    ///
+    /// Yields the index of each entry (across all the level 2 tables), and its
+    /// offset from the start of the region.
+    fn blocks(ipa: u32, size: u32) -> Result<impl Iterator<Item = (usize, u32)>, Stage2Error> {
+        if ipa % L2_BLOCK_SIZE != 0 || size % L2_BLOCK_SIZE != 0 {
+            return Err(Stage2Error::Misaligned);
+        }
+        if ipa as u64 + size as u64 > 1 << 32 {
+            return Err(Stage2Error::OutOfRange);
+        }
+        let first = (ipa / L2_BLOCK_SIZE) as usize;
+        let count = (size / L2_BLOCK_SIZE) as usize;
+        Ok((0..count).map(move |n| (first + n, n as u32 * L2_BLOCK_SIZE)))
+    }
+}
+
+impl Default for Stage2Tables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn descriptors() {
+        assert_eq!(core::mem::size_of::<Descriptor>(), 8);
+        let ram = Descriptor::new_block(0x4000_0000, Stage2Attributes::RAM);
+        assert_eq!(ram.raw_value(), 0x4000_07FD);
+        assert_eq!(ram.attributes(), Some(Stage2Attributes::RAM));
+        let device = Descriptor::new_block(0x0900_0000, Stage2Attributes::DEVICE);
+        assert_eq!(device.raw_value(), 0x0040_0000_0900_04C5);
+        let table = Descriptor::new_table(0x8000_1000);
+        assert_eq!(table.raw_value(), 0x8000_1003);
+        assert_eq!(table.address(), 0x8000_1000);
+    }
+
+    #[test]
+    fn map_and_unmap() {
+        let mut tables = Stage2Tables::new();
+        tables
+            .map(0x4000_0000, 0x8000_0000, 0x0040_0000, Stage2Attributes::RAM)
+            .unwrap();
+        assert_eq!(tables.lookup(0x4000_0000).address(), 0x8000_0000);
+        assert_eq!(tables.lookup(0x4020_0000).address(), 0x8020_0000);
+        assert_eq!(tables.lookup(0x4040_0000), Descriptor::FAULT);
+        tables.unmap(0x4020_0000, 0x0020_0000).unwrap();
+        assert_eq!(tables.lookup(0x4020_0000), Descriptor::FAULT);
+        assert_eq!(
+            tables.map(0x4010_0000, 0, 0x0020_0000, Stage2Attributes::RAM),
+            Err(Stage2Error::Misaligned)
+        );
+        assert_eq!(
+            tables.map(0xFFE0_0000, 0, 0x0040_0000, Stage2Attributes::RAM),
+            Err(Stage2Error::OutOfRange)
+        );
+        tables
+            .map(0xFFE0_0000, 0, 0x0020_0000, Stage2Attributes::DEVICE)
+            .unwrap();
+        assert_eq!(Stage2Tables::vtcr().ipa_bits(), 32);
+    }
+}
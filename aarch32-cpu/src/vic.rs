@@ -0,0 +1,430 @@
+//! Drivers for the PL190 and PL192 Vectored Interrupt Controllers
+//!
+//! Many ARM926 and ARM1176 SoCs use a PL190 or PL192 VIC, and QEMU's
+//! Versatile/AB machine has a PL190 whichever processor it is given. Both
+//! have 32 interrupt lines, each of which can be sent to IRQ or FIQ, and can
+//! hold the address of a handler for each interrupt. The PL190 has 16 vector
+//! slots (and a default vector for everything else), while the PL192 has a
+//! vector and a priority level for every line.
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::vic::Pl190;
+//!
+//! let mut vic = unsafe { Pl190::new(0x1014_0000 as *mut u32) };
+//! vic.set_vector(0, TIMER_LINE, timer_handler);
+//! vic.set_default_vector(other_handler);
+//! vic.set_enabled(TIMER_LINE, true);
+//!
+//! // then, in the IRQ handler
+//! vic.dispatch();
+//! ```
+//!
+//! With the `vic-vectored-irq` feature of `aarch32-rt`, the run-time reads
+//! VICVECTADDR itself and calls the handler directly, so you don't need to
+//! call [`Pl190::dispatch`].
+//!
+//! Methods that take an interrupt line panic if it isn't below [`NUM_LINES`].
+//!
+//! Two VICs can be daisy-chained, with the secondary VIC's interrupt feeding
+//! the primary. The vectored interrupt hardware handles this for you, and
+//! [`DaisyChain`] does the same for [`InterruptController`] users.
+
+use crate::interrupt::InterruptController;
+
+/// The number of interrupt lines on a VIC
+pub const NUM_LINES: u32 = 32;
+
+/// The number of vector slots on a PL190
+pub const PL190_NUM_VECTORS: u32 = 16;
+
+/// The number of priority levels on a PL192
+pub const PL192_NUM_PRIORITIES: u8 = 16;
+
+/// The offset of VICVECTADDR from the start of a PL190
+pub const PL190_VECTADDR_OFFSET: usize = 0x030;
+
+/// The offset of VICADDRESS (the PL192's VICVECTADDR) from the start of a
+/// PL192
+pub const PL192_VECTADDR_OFFSET: usize = 0xF00;
+
+/// The offset of VICSOFTINTCLEAR from the start of a PL190 or PL192
+///
+/// This is for handlers written in assembly, which can't use the drivers.
+pub const SOFTINTCLEAR_OFFSET: usize = 0x01C;
+
+/// A function the VIC can call when an interrupt occurs
+pub type Handler = extern "C" fn();
+
+/// VICIRQSTATUS (*IRQ Status Register*)
+const VICIRQSTATUS: usize = 0x000;
+/// VICFIQSTATUS (*FIQ Status Register*)
+const VICFIQSTATUS: usize = 0x004;
+/// VICRAWINTR (*Raw Interrupt Status Register*)
+const VICRAWINTR: usize = 0x008;
+/// VICINTSELECT (*Interrupt Select Register*)
+const VICINTSELECT: usize = 0x00C;
+/// VICINTENABLE (*Interrupt Enable Register*)
+const VICINTENABLE: usize = 0x010;
+/// VICINTENCLEAR (*Interrupt Enable Clear Register*)
+const VICINTENCLEAR: usize = 0x014;
+/// VICSOFTINT (*Software Interrupt Register*)
+const VICSOFTINT: usize = 0x018;
+/// VICSOFTINTCLEAR (*Software Interrupt Clear Register*)
+const VICSOFTINTCLEAR: usize = SOFTINTCLEAR_OFFSET;
+/// VICSWPRIORITYMASK (*Software Priority Mask Register*), PL192 only
+const VICSWPRIORITYMASK: usize = 0x024;
+/// VICPRIORITYDAISY (*Vector Priority Register for Daisy Chain*), PL192 only
+const VICPRIORITYDAISY: usize = 0x028;
+/// VICDEFVECTADDR (*Default Vector Address Register*), PL190 only
+const VICDEFVECTADDR: usize = 0x034;
+/// VICVECTADDRn (*Vector Address Registers*)
+const VICVECTADDRN: usize = 0x100;
+/// VICVECTCNTLn (*Vector Control Registers*) on the PL190, and
+/// VICVECTPRIORITYn (*Vector Priority Registers*) on the PL192
+const VICVECTCNTLN: usize = 0x200;
+
+/// The enable bit in VICVECTCNTLn
+const VECTCNTL_ENABLE: u32 = 1 << 5;
+
+/// Methods that the PL190 and PL192 have in common
+macro_rules! common_methods {
+    ($ty:ident, $vectaddr:expr) => {
+        /// Enable or disable an interrupt line
+        pub fn set_enabled(&mut self, line: u32, enable: bool) {
+            let offset = if enable { VICINTENABLE } else { VICINTENCLEAR };
+            self.write(offset, bit(line));
+        }
+
+        /// Is this interrupt line enabled?
+        pub fn is_enabled(&self, line: u32) -> bool {
+            self.read(VICINTENABLE) & bit(line) != 0
+        }
+
+        /// Send an interrupt line to FIQ, rather than IRQ
+        pub fn set_fiq(&mut self, line: u32, fiq: bool) {
+            let value = self.read(VICINTSELECT) & !bit(line);
+            self.write(VICINTSELECT, value | if fiq { bit(line) } else { 0 });
+        }
+
+        /// The enabled lines that are asserting IRQ, one bit per line
+        pub fn irq_status(&self) -> u32 {
+            self.read(VICIRQSTATUS)
+        }
+
+        /// The enabled lines that are asserting FIQ, one bit per line
+        pub fn fiq_status(&self) -> u32 {
+            self.read(VICFIQSTATUS)
+        }
+
+        /// Every line that is asserted, whether enabled or not
+        pub fn raw_status(&self) -> u32 {
+            self.read(VICRAWINTR)
+        }
+
+        /// Assert an interrupt line from software
+        ///
+        /// It stays asserted until you call
+        #[doc = concat!("[`", stringify!($ty), "::clear_software_interrupt`].")]
+        pub fn set_software_interrupt(&mut self, line: u32) {
+            self.write(VICSOFTINT, bit(line));
+        }
+
+        /// Stop asserting an interrupt line from software
+        pub fn clear_software_interrupt(&mut self, line: u32) {
+            self.write(VICSOFTINTCLEAR, bit(line));
+        }
+
+        /// Read VICVECTADDR, to get the address of the handler for the
+        /// highest priority interrupt
+        ///
+        /// This also tells the VIC that we are handling that interrupt, so
+        /// it only signals interrupts with a higher priority until you call
+        #[doc = concat!("[`", stringify!($ty), "::end_of_vector`].")]
+        pub fn vector_address(&mut self) -> usize {
+            self.read($vectaddr) as usize
+        }
+
+        /// Tell the VIC we have finished with the interrupt we got from
+        #[doc = concat!("[`", stringify!($ty), "::vector_address`].")]
+        pub fn end_of_vector(&mut self) {
+            self.write($vectaddr, 0);
+        }
+
+        /// Call the handler for the highest priority interrupt
+        ///
+        /// This is what an IRQ handler should do if the run-time isn't doing it
+        /// for you.
+        pub fn dispatch(&mut self) {
+            let address = self.vector_address();
+            if address != 0 {
+                // Safety: the only non-zero addresses we put in the VIC are
+                // those of handlers
+                let handler: Handler = unsafe { core::mem::transmute(address) };
+                handler();
+            }
+            self.end_of_vector();
+        }
+
+        fn read(&self, offset: usize) -> u32 {
+            // Safety: we were given the address of a VIC
+            unsafe { self.base.byte_add(offset).read_volatile() }
+        }
+
+        fn write(&mut self, offset: usize, value: u32) {
+            // Safety: we were given the address of a VIC
+            unsafe { self.base.byte_add(offset).write_volatile(value) }
+        }
+    };
+}
+
+/// Implements [`InterruptController`] for a VIC
+macro_rules! impl_interrupt_controller {
+    ($ty:ident) => {
+        /// Treats the VIC as a plain interrupt controller, where the ID is the
+        /// line
+        ///
+        /// This reports the lowest numbered line asserting IRQ, and doesn't use
+        /// the vector or priority hardware. The handler must stop the line
+        /// being asserted before it returns.
+        impl InterruptController for $ty {
+            fn acknowledge(&mut self) -> Option<u32> {
+                match self.irq_status() {
+                    0 => None,
+                    status => Some(status.trailing_zeros()),
+                }
+            }
+
+            fn end_of_interrupt(&mut self, _id: u32) {}
+        }
+    };
+}
+
+/// A PL190 Vectored Interrupt Controller
+#[derive(Debug)]
+pub struct Pl190 {
+    base: *mut u32,
+}
+
+// Safety: the VIC's registers can be accessed from any core
+unsafe impl Send for Pl190 {}
+
+impl Pl190 {
+    /// Create a driver for the PL190 at this address
+    ///
+    /// # Safety
+    ///
+    /// The address must be that of a PL190, mapped as Device memory, and
+    /// nothing else may use the PL190 while this driver exists.
+    pub const unsafe fn new(base: *mut u32) -> Pl190 {
+        Pl190 { base }
+    }
+
+    common_methods!(Pl190, PL190_VECTADDR_OFFSET);
+
+    /// Put the handler for an interrupt line in a vector slot
+    ///
+    /// Slot 0 has the highest priority, and slot 15 the lowest.
+    pub fn set_vector(&mut self, slot: u32, line: u32, handler: Handler) {
+        assert!(slot < PL190_NUM_VECTORS && line < NUM_LINES);
+        let slot = 4 * slot as usize;
+        self.write(VICVECTADDRN + slot, handler as Handler as usize as u32);
+        self.write(VICVECTCNTLN + slot, VECTCNTL_ENABLE | line);
+    }
+
+    /// Empty a vector slot
+    pub fn clear_vector(&mut self, slot: u32) {
+        assert!(slot < PL190_NUM_VECTORS);
+        let slot = 4 * slot as usize;
+        self.write(VICVECTCNTLN + slot, 0);
+        self.write(VICVECTADDRN + slot, 0);
+    }
+
+    /// Set the handler for interrupts that aren't in a vector slot
+    ///
+    /// On a primary VIC with a daisy-chained secondary, this is only used when
+    /// the secondary isn't interrupting.
+    pub fn set_default_vector(&mut self, handler: Handler) {
+        self.write(VICDEFVECTADDR, handler as Handler as usize as u32);
+    }
+}
+
+impl_interrupt_controller!(Pl190);
+
+/// A PL192 Vectored Interrupt Controller
+#[derive(Debug)]
+pub struct Pl192 {
+    base: *mut u32,
+}
+
+// Safety: the VIC's registers can be accessed from any core
+unsafe impl Send for Pl192 {}
+
+impl Pl192 {
+    /// Create a driver for the PL192 at this address
+    ///
+    /// # Safety
+    ///
+    /// The address must be that of a PL192, mapped as Device memory, and
+    /// nothing else may use the PL192 while this driver exists.
+    pub const unsafe fn new(base: *mut u32) -> Pl192 {
+        Pl192 { base }
+    }
+
+    common_methods!(Pl192, PL192_VECTADDR_OFFSET);
+
+    /// Set the handler for an interrupt line
+    pub fn set_vector(&mut self, line: u32, handler: Handler) {
+        assert!(line < NUM_LINES);
+        self.write(
+            VICVECTADDRN + 4 * line as usize,
+            handler as Handler as usize as u32,
+        );
+    }
+
+    /// Set the priority of an interrupt line, where 0 is the most urgent and
+    /// 15 the least
+    pub fn set_priority(&mut self, line: u32, priority: u8) {
+        assert!(line < NUM_LINES && priority < PL192_NUM_PRIORITIES);
+        self.write(VICVECTCNTLN + 4 * line as usize, priority as u32);
+    }
+
+    /// Choose which priority levels may interrupt, with one bit per level
+    pub fn set_priority_mask(&mut self, mask: u16) {
+        self.write(VICSWPRIORITYMASK, mask as u32);
+    }
+
+    /// Set the priority of the interrupt from a daisy-chained secondary VIC
+    pub fn set_daisy_priority(&mut self, priority: u8) {
+        assert!(priority < PL192_NUM_PRIORITIES);
+        self.write(VICPRIORITYDAISY, priority as u32);
+    }
+}
+
+impl_interrupt_controller!(Pl192);
+
+/// A primary interrupt controller, with a secondary one daisy-chained to it
+///
+/// Interrupts from the primary come first, and keep their IDs. Interrupts from
+/// the secondary get IDs starting at [`DaisyChain::SECONDARY_BASE`].
+#[derive(Debug)]
+pub struct DaisyChain<P, S> {
+    /// The controller that interrupts the processor
+    pub primary: P,
+    /// The controller that interrupts the primary
+    pub secondary: S,
+}
+
+impl<P, S> DaisyChain<P, S> {
+    /// Added to the IDs of the secondary's interrupts
+    pub const SECONDARY_BASE: u32 = NUM_LINES;
+}
+
+impl<P, S> InterruptController for DaisyChain<P, S>
+where
+    P: InterruptController,
+    S: InterruptController,
+{
+    fn acknowledge(&mut self) -> Option<u32> {
+        self.primary.acknowledge().or_else(|| {
+            self.secondary
+                .acknowledge()
+                .map(|id| id + Self::SECONDARY_BASE)
+        })
+    }
+
+    fn end_of_interrupt(&mut self, id: u32) {
+        match id.checked_sub(Self::SECONDARY_BASE) {
+            Some(id) => self.secondary.end_of_interrupt(id),
+            None => self.primary.end_of_interrupt(id),
+        }
+    }
+}
+
+/// This line's bit, in a 32-bit register
+const fn bit(line: u32) -> u32 {
+    assert!(line < NUM_LINES, "interrupt line out of range");
+    1 << line
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    extern "C" fn handler() {}
+
+    #[test]
+    fn pl190() {
+        let mut regs = [0u32; 0x300 / 4];
+        let mut vic = unsafe { Pl190::new(regs.as_mut_ptr()) };
+        vic.set_enabled(4, true);
+        vic.set_enabled(5, false);
+        vic.set_fiq(7, true);
+        vic.set_software_interrupt(9);
+        vic.set_vector(2, 4, handler);
+        vic.end_of_vector();
+
+        assert_eq!(regs[VICINTENABLE / 4], 1 << 4);
+        assert_eq!(regs[VICINTENCLEAR / 4], 1 << 5);
+        assert_eq!(regs[VICINTSELECT / 4], 1 << 7);
+        assert_eq!(regs[VICSOFTINT / 4], 1 << 9);
+        assert_eq!(
+            regs[VICVECTADDRN / 4 + 2],
+            handler as Handler as usize as u32
+        );
+        assert_eq!(regs[VICVECTCNTLN / 4 + 2], 0x20 | 4);
+
+        regs[VICIRQSTATUS / 4] = 0b1100;
+        let mut vic = unsafe { Pl190::new(regs.as_mut_ptr()) };
+        assert_eq!(vic.acknowledge(), Some(2));
+        vic.clear_vector(2);
+        assert_eq!(regs[VICVECTCNTLN / 4 + 2], 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "interrupt line out of range")]
+    fn line_out_of_range() {
+        let mut regs = [0u32; 0x300 / 4];
+        let mut vic = unsafe { Pl190::new(regs.as_mut_ptr()) };
+        vic.set_software_interrupt(NUM_LINES);
+    }
+
+    #[test]
+    fn pl192() {
+        let mut regs = [0u32; 0x1000 / 4];
+        let mut vic = unsafe { Pl192::new(regs.as_mut_ptr()) };
+        vic.set_vector(20, handler);
+        vic.set_priority(20, 3);
+        vic.set_priority_mask(0x00FF);
+        vic.set_daisy_priority(15);
+        regs[PL192_VECTADDR_OFFSET / 4] = 0x1234;
+
+        assert_eq!(
+            regs[VICVECTADDRN / 4 + 20],
+            handler as Handler as usize as u32
+        );
+        assert_eq!(regs[VICVECTCNTLN / 4 + 20], 3);
+        assert_eq!(regs[VICSWPRIORITYMASK / 4], 0xFF);
+        assert_eq!(regs[VICPRIORITYDAISY / 4], 15);
+        let mut vic = unsafe { Pl192::new(regs.as_mut_ptr()) };
+        assert_eq!(vic.vector_address(), 0x1234);
+    }
+
+    #[test]
+    fn daisy_chain() {
+        let mut primary = [0u32; 0x300 / 4];
+        let mut secondary = [0u32; 0x300 / 4];
+        secondary[VICIRQSTATUS / 4] = 1 << 3;
+        let mut chain = DaisyChain {
+            primary: unsafe { Pl190::new(primary.as_mut_ptr()) },
+            secondary: unsafe { Pl190::new(secondary.as_mut_ptr()) },
+        };
+        assert_eq!(chain.acknowledge(), Some(35));
+
+        primary[VICIRQSTATUS / 4] = 1 << 31;
+        let mut chain = DaisyChain {
+            primary: unsafe { Pl190::new(primary.as_mut_ptr()) },
+            secondary: unsafe { Pl190::new(secondary.as_mut_ptr()) },
+        };
+        assert_eq!(chain.acknowledge(), Some(31));
+    }
+}
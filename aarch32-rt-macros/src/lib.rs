@@ -115,6 +115,8 @@ enum Exception {
     Undefined,
     SupervisorCall,
     HypervisorCall,
+    HypTrap,
+    SecureMonitorCall,
     PrefetchAbort,
     DataAbort,
     Irq,
@@ -126,6 +128,8 @@ impl std::fmt::Display for Exception {
             Exception::Undefined => write!(f, "Undefined"),
             Exception::SupervisorCall => write!(f, "SupervisorCall"),
             Exception::HypervisorCall => write!(f, "HypervisorCall"),
+            Exception::HypTrap => write!(f, "HypTrap"),
+            Exception::SecureMonitorCall => write!(f, "SecureMonitorCall"),
             Exception::PrefetchAbort => write!(f, "PrefetchAbort"),
             Exception::DataAbort => write!(f, "DataAbort"),
             Exception::Irq => write!(f, "Irq"),
@@ -166,6 +170,8 @@ impl std::fmt::Display for Exception {
 /// * Undefined (creates `_undefined_handler`)
 /// * SupervisorCall (creates `_svc_handler`)
 /// * HypervisorCall (creates `_hvc_handler`)
+/// * HypTrap (creates `_hyp_trap_handler`)
+/// * SecureMonitorCall (creates `_smc_handler`)
 /// * PrefetchAbort (creates `_prefetch_abort_handler`)
 /// * DataAbort (creates `_data_abort_handler`)
 /// * Irq (creates `_irq_handler`) - although people should prefer `#[irq]`.
@@ -269,6 +275,8 @@ fn handle_vector(args: TokenStream, input: TokenStream, kind: VectorKind) -> Tok
                 }
                 "SupervisorCall" => Exception::SupervisorCall,
                 "HypervisorCall" => Exception::HypervisorCall,
+                "HypTrap" => Exception::HypTrap,
+                "SecureMonitorCall" => Exception::SecureMonitorCall,
                 "PrefetchAbort" => {
                     if !returns_never && f.sig.unsafety.is_none() {
                         return parse::Error::new(
@@ -422,6 +430,39 @@ fn handle_vector(args: TokenStream, input: TokenStream, kind: VectorKind) -> Tok
                 }
             )
         }
+        // extern "C" fn _hyp_trap_handler(hsr: u32, frame: &mut TrapFrame);
+        Exception::HypTrap => {
+            let tramp_ident = Ident::new("__aarch32_rt_hyp_trap_handler", Span::call_site());
+            quote!(
+                #(#cfgs)*
+                #(#attrs)*
+                #[doc(hidden)]
+                #[export_name = "_hyp_trap_handler"]
+                pub unsafe extern "C" fn #tramp_ident(hsr: u32, frame: &mut aarch32_rt::TrapFrame) {
+                    #f
+
+                    #func_name(hsr, frame)
+                }
+            )
+        }
+        // extern "C" fn _smc_handler(caller: World, worlds: &mut Worlds) -> World;
+        Exception::SecureMonitorCall => {
+            let tramp_ident = Ident::new("__aarch32_rt_smc_handler", Span::call_site());
+            quote!(
+                #(#cfgs)*
+                #(#attrs)*
+                #[doc(hidden)]
+                #[export_name = "_smc_handler"]
+                pub unsafe extern "C" fn #tramp_ident(
+                    caller: aarch32_rt::monitor::World,
+                    worlds: &mut aarch32_rt::monitor::Worlds,
+                ) -> aarch32_rt::monitor::World {
+                    #f
+
+                    #func_name(caller, worlds)
+                }
+            )
+        }
         // extern "C" fn _irq_handler(addr: usize);
         Exception::Irq => {
             let tramp_ident = Ident::new("__aarch32_rt_irq_handler", Span::call_site());
@@ -18,7 +18,7 @@ REGION_ALIAS("DATA", SRAM);
 REGION_ALIAS("STACKS", SRAM);
 -------------
 
-The AArch32 platform uses seven separate stacks. The default sizes for each are
+The AArch32 platform uses eight separate stacks. The default sizes for each are
 given at the bottom of this file. However, your `memory.x` can provide an
 alternative size for any (or all) of them, provided that size is a multiple of
 eight bytes. For example, your `memory.x` might include:
@@ -28,6 +28,7 @@ PROVIDE(_und_stack_size = 3456);
 PROVIDE(_svc_stack_size = 3456);
 PROVIDE(_abt_stack_size = 3456);
 PROVIDE(_hyp_stack_size = 3456);
+PROVIDE(_mon_stack_size = 3456);
 PROVIDE(_irq_stack_size = 3456);
 PROVIDE(_fiq_stack_size = 3456);
 PROVIDE(_sys_stack_size = 3456);
@@ -171,7 +172,7 @@ SECTIONS {
 
     /* # Stacks
      *
-     * Space for all seven stacks.
+     * Space for all eight stacks.
      */
     .stacks (NOLOAD) : ALIGN(_stack_alignment)
     {
@@ -208,6 +209,13 @@ SECTIONS {
 
         . += _inter_stack_padding;
 
+        /* Stack for MON mode */
+        _mon_stack_low_end = .;
+        . += (_mon_stack_size * _num_cores);
+        _mon_stack_high_end = .;
+
+        . += _inter_stack_padding;
+
         /* Stack for IRQ mode */
         _irq_stack_low_end = .;
         . += (_irq_stack_size * _num_cores);
@@ -251,6 +259,8 @@ PROVIDE(_hyp_stack_size = 16K);
 PROVIDE(_irq_stack_size = 64);
 PROVIDE(_fiq_stack_size = 64);
 PROVIDE(_sys_stack_size = 16K);
+/* Only the `secure-monitor` feature uses the MON stack */
+PROVIDE(_mon_stack_size = 0);
 /* Default to one CPU core (i.e. one copy of each stack) */
 PROVIDE(_num_cores      = 1);
 
@@ -267,6 +277,10 @@ PROVIDE(_inter_stack_padding = 0);
 /* Set this to 1 in memory.x to remove the filler section pushing the stacks to the end of STACKS. */
 PROVIDE(_pack_stacks = 0);
 
+/* The HACTLR value to use when dropping from EL2 to EL1 on Armv8-R. The default lets EL1 access all of the */
+/* Cortex-R52's IMPLEMENTATION DEFINED registers. Clear bits to keep them for EL2. */
+PROVIDE(_hactlr_value = 0xB783);
+
 /* Weak aliases for ASM default handlers */
 PROVIDE(_start                      = _default_start);
 PROVIDE(_asm_undefined_handler      = _asm_default_undefined_handler);
@@ -274,17 +288,34 @@ PROVIDE(_asm_svc_handler            = _asm_default_svc_handler);
 PROVIDE(_asm_hvc_handler            = _asm_default_hvc_handler);
 PROVIDE(_asm_prefetch_abort_handler = _asm_default_prefetch_abort_handler);
 PROVIDE(_asm_data_abort_handler     = _asm_default_data_abort_handler);
-/* TODO: Hyp handler goes here */
+PROVIDE(_asm_hyp_trap_handler       = _asm_default_hyp_trap_handler);
 PROVIDE(_asm_irq_handler            = _asm_default_irq_handler);
 PROVIDE(_asm_fiq_handler            = _asm_default_fiq_handler);
+PROVIDE(_asm_smc_handler            = _asm_default_smc_handler);
+PROVIDE(_asm_monitor_prefetch_abort_handler = _asm_default_monitor_handler);
+PROVIDE(_asm_monitor_data_abort_handler     = _asm_default_monitor_handler);
+PROVIDE(_asm_monitor_irq_handler            = _asm_default_monitor_handler);
+PROVIDE(_asm_monitor_fiq_handler            = _asm_default_monitor_handler);
+PROVIDE(_asm_hyp_undefined_handler         = _asm_default_hyp_handler);
+PROVIDE(_asm_hyp_svc_handler               = _asm_default_hyp_handler);
+PROVIDE(_asm_hyp_prefetch_abort_handler    = _asm_default_hyp_handler);
+PROVIDE(_asm_hyp_data_abort_handler        = _asm_default_hyp_handler);
+PROVIDE(_asm_hyp_irq_handler               = _asm_default_hyp_handler);
+PROVIDE(_asm_hyp_fiq_handler               = _asm_default_hyp_handler);
+
+/* Use the normal vector table in Hyp mode, unless the `el2-monitor` feature supplies a separate one */
+PROVIDE(_hyp_vector_table = _vector_table);
 
 /* Weak aliases for C default handlers */
+PROVIDE(_el2_init               = _default_el2_init);
 PROVIDE(_undefined_handler      = _default_handler);
 PROVIDE(_svc_handler            = _default_handler);
 PROVIDE(_hvc_handler            = _default_handler);
 PROVIDE(_prefetch_abort_handler = _default_handler);
 PROVIDE(_data_abort_handler     = _default_handler);
-/* TODO: Hyp handler goes here */
+PROVIDE(_debug_monitor_handler  = _default_handler);
+PROVIDE(_smc_handler            = _default_handler);
+PROVIDE(_hyp_trap_handler       = _default_handler);
 PROVIDE(_irq_handler            = _default_handler);
 /* NB: There is no default C-language FIQ handler */
 
@@ -307,6 +338,10 @@ ASSERT(_abt_stack_size % _stack_alignment == 0, "
 ERROR(aarch32-rt): ABT stack size (_abt_stack_size) is not a multiple of the stack alignment");
 ASSERT(_hyp_stack_size % _stack_alignment == 0, "
 ERROR(aarch32-rt): HYP stack size (_hyp_stack_size) is not a multiple of the stack alignment");
+ASSERT(_mon_stack_size % _stack_alignment == 0, "
+ERROR(aarch32-rt): MON stack size (_mon_stack_size) is not a multiple of the stack alignment");
+ASSERT(_mon_stack_size != 0 || !DEFINED(_monitor_vector_table), "
+ERROR(aarch32-rt): The secure-monitor feature needs a MON stack - set _mon_stack_size in memory.x");
 ASSERT(_irq_stack_size % _stack_alignment == 0, "
 ERROR(aarch32-rt): IRQ stack size (_irq_stack_size) is not a multiple of the stack alignment");
 ASSERT(_fiq_stack_size % _stack_alignment == 0, "
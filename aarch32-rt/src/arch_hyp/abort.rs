@@ -1,9 +1,11 @@
-//! Data and Prefetch Abort handlers for Armv8-R at EL2
+//! Data and Prefetch Abort handlers for Hyp mode (EL2) on Armv7-A and Armv8-R
 
 core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
     .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
 
     .section .text._asm_default_data_abort_handler
 
@@ -42,6 +44,8 @@ core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
     .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
 
     .section .text._asm_default_prefetch_abort_handler
 
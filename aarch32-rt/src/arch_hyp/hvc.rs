@@ -0,0 +1,105 @@
+//! HVC and Hyp Trap handlers for Hyp mode (EL2) on Armv7-A and Armv8-R
+//!
+//! They share one section, so that linking in the HVC handler always brings
+//! in the default Hyp Trap handler it falls back to. The linker only finds out
+//! that `_asm_hyp_trap_handler` is used once it has pulled in the HVC handler,
+//! which is too late for the `PROVIDE`d default to be kept on its own.
+
+#[cfg(target_arch = "arm")]
+core::arch::global_asm!(
+    r#"
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
+
+    .section .text._asm_default_hvc_handler
+    .arm
+
+    // Called from the vector table when we have an hypervisor call, or when
+    // anything else traps to Hyp mode (this is the Hyp Trap vector).
+    //
+    // If the HSR says this was an HVC, saves state and calls a C-compatible
+    // handler like `extern "C" fn _hvc_handler(hsr: u32, frame: &Frame) -> u32;`
+    //
+    // Otherwise, jumps to `_asm_hyp_trap_handler`.
+    .global _asm_default_hvc_handler
+    .type _asm_default_hvc_handler, %function
+    _asm_default_hvc_handler:
+        push    {{ r12, lr }}             // give us R12 and LR to work with
+        mrc     p15, 4, r12, c5, c2, 0    // r12 = HSR value
+        lsr     r12, r12, #26             // r12 = HSR.EC
+        cmp     r12, #{ec_hvc}            // was it an HVC instruction?
+        popne   {{ r12, lr }}             // if not, put R12 and LR back
+        ldrne   pc, =_asm_hyp_trap_handler // and handle it as a trap
+        mrs     lr, elr_hyp               // grab elr
+        mrs     r12, spsr_hyp             // grab spsr
+        push    {{ r12, lr }}             // push them to stack
+        mov     r12, sp                   // align SP down to eight byte boundary using R12
+        and     r12, r12, 7               //
+        sub     sp, r12                   // SP now aligned - only push 64-bit values from here
+        push    {{ r0-r6, r12 }}          // push frame and alignment amount to stack
+        mov     r12, sp                   // r12 = pointer to Frame
+    "#,
+    crate::save_fpu_context!(),
+    r#"
+        mrc     p15, 4, r0, c5, c2, 0     // r0 = HSR value
+        mov     r1, r12                   // r1 = frame pointer
+        bl      _hvc_handler
+        mov     lr, r0                    // copy return value into LR, because we're about to use r0 in the FPU restore
+    "#,
+    crate::restore_fpu_context!(),
+    r#"
+        pop     {{ r0-r6, r12 }}          // restore frame and alignment
+        mov     r0, lr                    // copy return value from lr back to r0, overwriting saved r0
+        add     sp, r12                   // restore SP alignment using R12
+        pop     {{ r12, lr }}             // pop elr and spsr from stack
+        msr     elr_hyp, lr               // restore elr
+        msr     spsr_hyp, r12             // restore spsr
+        pop     {{ r12, lr }}             // pop R12 and LR from stack
+        eret                              // Return from the asm handler
+    .size _asm_default_hvc_handler, . - _asm_default_hvc_handler
+
+    // Called from `_asm_default_hvc_handler` when something other than an HVC
+    // instruction traps to Hyp mode - like a trapped WFI, a trapped CP15
+    // access, or a Stage 2 abort.
+    //
+    // Saves state and calls a C-compatible handler like
+    // `extern "C" fn _hyp_trap_handler(hsr: u32, frame: &mut TrapFrame);`
+    //
+    // The handler can change any register in the frame, including where
+    // execution resumes.
+    .global _asm_default_hyp_trap_handler
+    .type _asm_default_hyp_trap_handler, %function
+    _asm_default_hyp_trap_handler:
+        sub     sp, sp, #8                // make room for ELR and SPSR
+        push    {{ r0-r12, lr }}          // save the trapped code's registers
+        mrs     r0, elr_hyp               // grab ELR
+        mrs     r1, spsr_hyp              // grab SPSR
+        add     r2, sp, #{elr_offset}     //
+        stm     r2, {{ r0, r1 }}          // store them in the TrapFrame
+        mov     r4, sp                    // r4 = pointer to TrapFrame
+        and     r5, r4, 7                 // align SP down to eight byte boundary using R5
+        sub     sp, r5                    // SP now aligned - only push 64-bit values from here
+    "#,
+    crate::save_fpu_context!(),
+    r#"
+        mrc     p15, 4, r0, c5, c2, 0     // r0 = HSR value
+        mov     r1, r4                    // r1 = frame pointer
+        bl      _hyp_trap_handler
+    "#,
+    crate::restore_fpu_context!(),
+    r#"
+        add     sp, r5                    // restore SP alignment using R5
+        add     r2, sp, #{elr_offset}     //
+        ldm     r2, {{ r0, r1 }}          // load ELR and SPSR from the TrapFrame
+        msr     elr_hyp, r0               // restore ELR
+        msr     spsr_hyp, r1              // restore SPSR
+        pop     {{ r0-r12, lr }}          // restore the trapped code's registers
+        add     sp, sp, #8                // drop ELR and SPSR
+        eret                              // Return from the asm handler
+    .size _asm_default_hyp_trap_handler, . - _asm_default_hyp_trap_handler
+    "#,
+    ec_hvc = const aarch32_cpu::register::hyp::hsr::ExceptionClass::Hvc as u8,
+    elr_offset = const core::mem::offset_of!(crate::TrapFrame, elr),
+);
@@ -1,16 +1,18 @@
-//! IRQ handler for Armv8-R at EL2
+//! IRQ handler for Hyp mode (EL2) on Armv7-A and Armv8-R
 
 #[cfg(target_arch = "arm")]
 core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
     .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
 
     .section .text._asm_default_irq_handler
 
     // Called from the vector table when we have an interrupt.
     // Saves state and calls a C-compatible handler like
-    // `extern "C" fn _irq_handler();`
+    // `extern "C" fn _irq_handler(frame: &IrqFrame);`
     .global _asm_default_irq_handler
     .type _asm_default_irq_handler, %function
     _asm_default_irq_handler:
@@ -21,9 +23,11 @@ core::arch::global_asm!(
         and     r12, r12, 7               //
         sub     sp, r12                   // SP now aligned - only push 64-bit values from here (4)
         push    {{ r0-r2, r12 }}          // save ELR, SPSR, padding and alignment amount (5)
+        mov     r12, sp                   // remember where ELR and SPSR were saved
     "#,
     crate::save_fpu_context!(),
     r#"
+        mov     r0, r12                   // pass the return state to the handler
         bl      _irq_handler              // call C handler (they may choose to re-enable interrupts)
     "#,
     crate::restore_fpu_context!(),
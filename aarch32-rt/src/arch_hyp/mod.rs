@@ -0,0 +1,17 @@
+//! ASM routines for Hyp mode (EL2) on Armv7-A and Armv8-R
+//!
+//! With the `el2-monitor` feature the application's own exceptions are handled
+//! in EL1 by `arch_v7`, so we only need the Hyp Trap handlers and the Hyp
+//! vector table.
+
+#[cfg(feature = "el2-mode")]
+mod abort;
+mod hvc;
+#[cfg(feature = "el2-mode")]
+mod interrupt;
+#[cfg(feature = "el2-mode")]
+mod svc;
+#[cfg(feature = "el2-mode")]
+mod undefined;
+#[cfg(feature = "el2-monitor")]
+mod vectors;
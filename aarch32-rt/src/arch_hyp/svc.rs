@@ -1,10 +1,12 @@
-//! SVC handler for Armv8-R at EL2
+//! SVC handler for Hyp mode (EL2) on Armv7-A and Armv8-R
 
 #[cfg(target_arch = "arm")]
 core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
     .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
 
     // Called from the vector table when we have an hypervisor call from Hyp
     // mode (which seems to end up in this SVC handler).
@@ -1,10 +1,12 @@
-//! Undefined handler for Armv8-R at EL2
+//! Undefined handler for Hyp mode (EL2) on Armv7-A and Armv8-R
 
 #[cfg(target_arch = "arm")]
 core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
     .fpu vfp3
+    // Armv7-A needs this for ELR_hyp, SPSR_hyp and ERET
+    .arch_extension virt
 
     // Called from the vector table when we have an undefined exception.
     // Saves state and calls a C-compatible handler like
@@ -0,0 +1,40 @@
+//! Hyp vector table for the `el2-monitor` feature on Armv8-R
+
+// The Hyp Vector Table.
+//
+// HVBAR must be 32-byte aligned, and Hyp mode always runs in Arm state.
+#[cfg(target_arch = "arm")]
+core::arch::global_asm!(
+    r#"
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3
+
+    .pushsection .text._hyp_vector_table
+    .arm
+    .global _hyp_vector_table
+    .type _hyp_vector_table, %function
+    .align 5
+    _hyp_vector_table:
+        b       .                         // not used
+        ldr     pc, =_asm_hyp_undefined_handler
+        ldr     pc, =_asm_hyp_svc_handler
+        ldr     pc, =_asm_hyp_prefetch_abort_handler
+        ldr     pc, =_asm_hyp_data_abort_handler
+        ldr     pc, =_asm_hvc_handler     // the Hyp Trap vector
+        ldr     pc, =_asm_hyp_irq_handler
+        ldr     pc, =_asm_hyp_fiq_handler
+    .size _hyp_vector_table, . - _hyp_vector_table
+    .popsection
+
+    // Used for any exception taken to Hyp mode that isn't an HVC or a trap,
+    // unless you supply your own handler. It just spins.
+    .pushsection .text._asm_default_hyp_handler
+    .arm
+    .global _asm_default_hyp_handler
+    .type _asm_default_hyp_handler, %function
+    _asm_default_hyp_handler:
+        b       .
+    .size _asm_default_hyp_handler, . - _asm_default_hyp_handler
+    .popsection
+    "#,
+);
@@ -2,6 +2,34 @@
 
 use crate::{Cpsr, ProcessorMode};
 
+/// Calls `_irq_handler`
+#[cfg(not(feature = "vic-vectored-irq"))]
+macro_rules! call_irq_handler {
+    () => {
+        r#"
+        bl      _irq_handler              // call C handler in the selected handler mode (they may choose to re-enable interrupts)
+        "#
+    };
+}
+
+/// Calls the handler that the VIC gives us in VICVECTADDR, in the selected
+/// handler mode (they may choose to re-enable interrupts), and then tells the
+/// VIC that the interrupt has been handled
+#[cfg(feature = "vic-vectored-irq")]
+macro_rules! call_irq_handler {
+    () => {
+        r#"
+        ldr     r0, =_vic_vectaddr
+        ldr     r1, [r0]                  // reading VICVECTADDR gives the handler for the highest priority interrupt
+        cmp     r1, #0                    // skip it if there isn't one
+        movne   lr, pc                    // PC reads as the address of the instruction after the BX
+        bxne    r1                        // call the handler, which may be Thumb code
+        ldr     r0, =_vic_vectaddr
+        str     r0, [r0]                  // writing VICVECTADDR tells the VIC we are done
+        "#
+    };
+}
+
 #[cfg(target_arch = "arm")]
 core::arch::global_asm!(
     r#"
@@ -41,9 +69,7 @@ core::arch::global_asm!(
         push    {{ r0-r3, r12, lr }}      // push alignment amount (in LR) and preserved registers (7)
      "#,
     crate::save_fpu_context!(),
-    r#"
-        bl      _irq_handler              // call C handler in the selected handler mode (they may choose to re-enable interrupts)
-    "#,
+    call_irq_handler!(),
     crate::restore_fpu_context!(),
     r#"
         pop     {{ r0-r3, r12, lr }}      // restore alignment amount (in LR) and preserved registers to undo (7)
@@ -78,3 +78,46 @@ core::arch::global_asm!(
    "#,
     abt_mode = const crate::ProcessorMode::Abt as u8,
 );
+
+core::arch::global_asm!(
+    r#"
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3
+
+    // An alternative to `_asm_default_prefetch_abort_handler`, for use with a
+    // debug monitor. Saves the full register state of the interrupted code
+    // and calls a C-compatible handler like
+    // `extern "C" fn _debug_monitor_handler(regs: &mut Registers);`
+    //
+    // Any changes the handler makes to the registers are applied on return.
+    // Note that we save the banked SP and LR from SYS/USR mode.
+    .pushsection .text._asm_debug_monitor_handler
+    .arm
+    .global _asm_debug_monitor_handler
+    .type _asm_debug_monitor_handler, %function
+    _asm_debug_monitor_handler:
+        sub     lr, lr, #4                // Subtract 4 from LR, see p.1212 of the ARMv7-A architecture manual.
+        srsfd   sp!, #{abt_mode}          // store return state to ABT stack - these become PC and CPSR
+        sub     sp, sp, #60               // make space for R0-R14
+        stmia   sp, {{ r0-r14 }}^         // save SYS/USR mode R0-R14
+        mov     r4, sp                    // keep a pointer to the saved registers in R4
+        and     r5, sp, 7                 // align SP down to eight byte boundary using R5
+        sub     sp, r5                    // SP now aligned - only push 64-bit values from here
+    "#,
+    crate::save_fpu_context!(),
+    r#"
+        mov     r0, r4                    // Pass the saved registers to the handler
+        bl      _debug_monitor_handler    // call C handler
+    "#,
+    crate::restore_fpu_context!(),
+    r#"
+        mov     sp, r4                    // go back to the saved registers
+        ldmia   sp, {{ r0-r14 }}^         // restore SYS/USR mode R0-R14, which may have been modified
+        nop                               // don't touch banked registers immediately after LDM ^
+        add     sp, sp, #60               // discard the saved registers
+        rfefd   sp!                       // return from exception
+    .size _asm_debug_monitor_handler, . - _asm_debug_monitor_handler
+    .popsection
+   "#,
+    abt_mode = const crate::ProcessorMode::Abt as u8,
+);
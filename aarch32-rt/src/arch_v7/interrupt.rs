@@ -7,8 +7,8 @@ core::arch::global_asm!(
     .fpu vfp3
 
     // Called from the vector table when we have an interrupt. Saves state and
-    // calls a C-compatible handler like `extern "C" fn _irq_handler();` in
-    // system mode (or SVC mode if the `svc-stack-interrupt` feature is
+    // calls a C-compatible handler like `extern "C" fn _irq_handler(frame: &IrqFrame);`
+    // in system mode (or SVC mode if the `svc-stack-interrupt` feature is
     // enabled).
     //
     // We call the C-compatible handler in a different mode because when when an
@@ -39,9 +39,12 @@ core::arch::global_asm!(
         and     lr, sp, 7                 // align SP down to eight byte boundary using LR
         sub     sp, lr                    // SP now aligned - only push 64-bit values from here (4)
         push    {{ r0-r3, r12, lr }}      // push alignment amount (in LR) and preserved registers (5)
+        add     r12, sp, lr               // find the return state stored in (1), skipping over (5), (4)
+        add     r12, r12, 28              //   and (3)
      "#,
     crate::save_fpu_context!(),
     r#"
+        mov     r0, r12                   // pass the return state to the handler
         bl      _irq_handler              // call C handler (they may choose to re-enable interrupts)
     "#,
     crate::restore_fpu_context!(),
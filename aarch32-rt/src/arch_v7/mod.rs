@@ -1,6 +1,9 @@
 //! ASM routines for for Armv7 and higher
 
 mod abort;
+// With `el2-monitor`, HVCs go to the Hyp vector table, and `arch_hyp` handles
+// them instead
+#[cfg(not(feature = "el2-monitor"))]
 mod hvc;
 mod interrupt;
 mod svc;
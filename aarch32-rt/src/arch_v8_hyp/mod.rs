@@ -1,7 +0,0 @@
-//! ASM routines for Armv8-R at EL2
-
-mod abort;
-mod hvc;
-mod interrupt;
-mod svc;
-mod undefined;
@@ -27,20 +27,48 @@
 //
 //! - `el2-mode`: Leave the processor in EL2/PL2 mode on boot-up, and expect to
 //!   handle interrupts in HYP mode using ELR_hyp. Useful if you want to write a
-//!   hypervisor or other low-level firmware.
+//!   hypervisor or other low-level firmware. Supported on Armv8-R, and on
+//!   Armv7-A processors with the Virtualization Extensions (like the
+//!   Cortex-A15). The processor must boot in Hyp mode.
+//!
+//! - `el2-monitor`: On Armv8-R, run the application in EL1 as usual, but leave
+//!   HVBAR pointing at a separate Hyp vector table, `_hyp_vector_table`. A
+//!   supervisor or safety monitor linked into the same image can then handle
+//!   HVCs, traps and EL2 MPU faults from the application in Hyp mode, on the
+//!   HYP stack, while the application handles its own exceptions through
+//!   `_vector_table`. The processor must boot in Hyp mode.
+//!
+//! - `secure-monitor`: On Armv7-A, run a Secure Monitor. The start-up code
+//!   gives Monitor mode a stack and points MVBAR at `_monitor_vector_table`,
+//!   and SMC instructions from either world call `_smc_handler`. The processor
+//!   must boot in Secure state. See the [`monitor`] module.
 //!
 //! - `svc-stack-interrupt`: Use the SVC stack when an interrupt occurs, instead
 //!   of using the SYS stack. Useful if you are writing an RTOS and your SYS
 //!   stack is actually the USR stack for the running task.
 //!
+//! - `vic-vectored-irq`: On Armv4 to Armv6, when an interrupt occurs, read
+//!   VICVECTADDR from a PL190 or PL192 Vectored Interrupt Controller and call
+//!   the handler it gives, instead of calling `_irq_handler`. VICVECTADDR is
+//!   written once the handler returns, to tell the VIC the interrupt is done.
+//!   You must give the address of VICVECTADDR in `_vic_vectaddr`. See
+//!   `aarch32_cpu::vic` for a driver that can set up the vectors.
+//!
 //! ## Information about the Run-Time
 //!
 //! Transferring from System Mode to User Mode (i.e. implementing an RTOS) is
 //! not handled here.
 //!
 //! If your processor starts in Hyp mode, this runtime will be transfer it to
-//! System mode. If you wish to write a hypervisor, you will need to replace
-//! this library with something more advanced.
+//! System mode, unless you enable the `el2-mode` feature. With that feature,
+//! `kmain` runs in Hyp mode, HVBAR points at our vector table, and anything
+//! that traps to Hyp mode arrives at `_hvc_handler` or `_hyp_trap_handler`.
+//!
+//! With the `el2-monitor` feature, `kmain` runs in System mode, but Hyp mode
+//! keeps its own vector table. HVC instructions executed by the application
+//! call `_hvc_handler` in Hyp mode, and anything else that traps to Hyp mode
+//! calls `_hyp_trap_handler`. Exceptions taken in Hyp mode itself go to the
+//! `_asm_hyp_xxx_handler` functions, which spin unless you supply your own.
 //!
 //! We assume that a set of symbols exist, either for constants or for C
 //! compatible functions or for naked raw-assembly functions. They are described
@@ -66,12 +94,28 @@
 //!   when in Abort mode; will be padded to a multiple of 8.
 //! * `_hyp_stack_size` - the number of bytes to be reserved for stack space
 //!   when in Hyp mode; will be padded to a multiple of 8.
+//! * `_mon_stack_size` - the number of bytes to be reserved for stack space
+//!   when in Monitor mode; will be padded to a multiple of 8. Defaults to zero,
+//!   so you must set it if you use the `secure-monitor` feature.
 //! * `_sys_stack_size` - the number of bytes to be reserved for stack space
 //!   when in System mode; will be padded to a multiple of 8.
 //! * `__sdata` - the start of initialised data in RAM. Must be 4-byte aligned.
 //! * `__edata` - the end of initialised data in RAM. Must be 4-byte aligned.
 //! * `__sidata` - the start of the initialisation values for data, in read-only
 //!   memory. Must be 4-byte aligned.
+//! * `_hactlr_value` - the value written to HACTLR before dropping from EL2 to
+//!   EL1 on Armv8-R, which says which IMPLEMENTATION DEFINED registers EL1 may
+//!   access. Defaults to `0xB783`, which grants access to everything on a
+//!   Cortex-R52 (CPUACTLR, CDBGDCI, FLASHIFREGIONR, PERIPHPREGIONR, QOSR,
+//!   BUSTIMEOUTR, INTMONR, ERR and TESTR1). Clear bits to keep registers away
+//!   from EL1 - for example, `PROVIDE(_hactlr_value = 0xB603);` stops EL1 from
+//!   moving the Flash and peripheral port regions. On Armv7-A we leave HACTLR
+//!   alone; set it in `_el2_init` if your processor needs it.
+//! * `_vic_vectaddr` - the address of VICVECTADDR, if you use the
+//!   `vic-vectored-irq` feature. That's the PL190's base address plus `0x030`,
+//!   or the PL192's base address plus `0xF00` - for example,
+//!   `_vic_vectaddr = 0x10140030;` for the PL190 on the Versatile/AB. There is
+//!   no default.
 //!
 //! Using our default start-up function `_default_start`, the memory between
 //! `__sbss` and `__ebss` is zeroed, and the memory between `__sdata` and
@@ -94,7 +138,9 @@
 //! +------------------+
 //! |     IRQ Stack    | } _irq_stack_size * _num_cores bytes
 //! +------------------+
-//! |     HYP Stack    | } _hyp_stack_size * _num_cores bytes (only used on Armv8-R)
+//! |     MON Stack    | } _mon_stack_size * _num_cores bytes (only used with `secure-monitor`)
+//! +------------------+
+//! |     HYP Stack    | } _hyp_stack_size * _num_cores bytes (only used in Hyp mode)
 //! +------------------+
 //! |     ABT Stack    | } _abt_stack_size * _num_cores bytes
 //! +------------------+
@@ -115,8 +161,9 @@
 //! ### Main Function
 //!
 //! The symbol `kmain` should be an `extern "C"` function. It is called in SYS
-//! mode after all the global variables have been initialised. There is no
-//! default - this function is mandatory.
+//! mode (or HYP mode, with the `el2-mode` feature) after all the global
+//! variables have been initialised. There is no default - this function is
+//! mandatory.
 //!
 //! ```rust
 //! #[unsafe(no_mangle)]
@@ -141,6 +188,29 @@
 //! }
 //! ```
 //!
+//! ### EL2 Initialisation Function
+//!
+//! If the processor boots in Hyp mode and we are dropping to EL1 (i.e. without
+//! the `el2-mode` feature), the symbol `_el2_init` should be an `extern "C"`
+//! function. It is called in HYP mode, on the HYP stack, just before we leave
+//! EL2. Global variables have been initialised by then, HCPTR has been told
+//! not to trap the FPU, the FPU (if any) has been enabled, and HVBAR (and, on
+//! Armv8-R, HACTLR) have been set. It is
+//! your last chance to configure things only EL2 can touch - like HCR, the
+//! EL2 MPU or IMPLEMENTATION DEFINED registers - before the application
+//! starts.
+//!
+//! Our linker script PROVIDEs a default `_el2_init` symbol which is an alias
+//! for the `_default_el2_init` function, which does nothing. You can override
+//! it by defining your own `_el2_init` function, like:
+//!
+//! ```rust
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _el2_init() {
+//!     // lock down the EL2 MPU here
+//! }
+//! ```
+//!
 //! ### Undefined Handler
 //!
 //! The symbol `_undefined_handler` should be an `extern "C"` function. It is
@@ -255,6 +325,9 @@
 //! default assembly trampoline, along with registers r0 through r5, in the form
 //! of a reference to a `Frame` structure.
 //!
+//! With the `el2-mode` feature, an SVC instruction executed in Hyp mode also
+//! calls this function, with an HSR Exception Class of `Svc` rather than `Hvc`.
+//!
 //! Our linker script PROVIDEs a default `_hvc_handler` symbol which is an alias
 //! for the `_default_handler` function. You can override it by defining your
 //! own `_hvc_handler` function, like:
@@ -286,6 +359,94 @@
 //! let hsr = aarch32_cpu::register::Hsr::new_with_raw_value(hsr);
 //! ```
 //!
+//! ### Hyp Trap Handler
+//!
+//! With the `el2-mode` or `el2-monitor` features, the symbol
+//! `_hyp_trap_handler` should be an `extern "C"` function. It is called in
+//! HYP mode when something other than an HVC instruction is taken to the Hyp
+//! Trap vector. That includes trapped instructions (like WFI, or CP15
+//! accesses trapped by HCR and HSTR), SVC instructions from User mode that
+//! HCR.TGE sends to Hyp mode, and Prefetch or Data Aborts from a lower level
+//! (for example, Stage 2 translation faults, or EL2 MPU faults on Armv8-R).
+//!
+//! The function is passed the contents of the Hypervisor Syndrome Register
+//! (HSR), and a mutable reference to a `TrapFrame` holding all the general
+//! purpose registers of the trapped code, plus `ELR_hyp` and `SPSR_hyp`. Any
+//! changes to the frame are written back when the function returns. For a
+//! trapped instruction, `ELR_hyp` points at that instruction, so if you
+//! emulate it, you need to move `elr` on by the instruction length given in
+//! the HSR.
+//!
+//! Aborts taken from Hyp mode itself go to the Prefetch Abort and Data Abort
+//! handlers, as usual.
+//!
+//! Our linker script PROVIDEs a default `_hyp_trap_handler` symbol which is an
+//! alias for the `_default_handler` function. You can override it by defining
+//! your own `_hyp_trap_handler` function, or by using the
+//! `#[exception(HypTrap)]` attribute on a normal Rust function.
+//!
+//! ```rust,ignore
+//! use aarch32_cpu::register::{hyp::hsr::ExceptionClass, Hsr};
+//! use aarch32_rt::exception;
+//!
+//! #[exception(HypTrap)]
+//! fn hyp_trap_handler(hsr: u32, frame: &mut aarch32_rt::TrapFrame) {
+//!     let hsr = Hsr::new_with_raw_value(hsr);
+//!     match hsr.ec() {
+//!         Ok(ExceptionClass::TrappedWfiWfe) => {
+//!             // Skip over the WFI, which is 4 bytes long in Arm state
+//!             frame.elr += 4;
+//!         }
+//!         _ => panic!("Unexpected trap {:?}", hsr),
+//!     }
+//! }
+//! ```
+//!
+//! ### Secure Monitor Call Handler
+//!
+//! With the `secure-monitor` feature, the symbol `_smc_handler` should be an
+//! `extern "C"` function. It is called in MON mode when either world executes
+//! an [Secure Monitor Call Exception].
+//!
+//! [Secure Monitor Call Exception]:
+//!     https://developer.arm.com/documentation/ddi0406/c/System-Level-Architecture/The-System-Level-Programmers--Model/Exception-descriptions/Secure-Monitor-Call--SMC--exception?lang=en
+//!
+//! The function is passed the world that made the call, and the saved
+//! registers of both worlds. It returns the world to resume. This is how you
+//! switch between the Secure and Non-secure worlds - for example, the Secure
+//! world can start the Non-secure world by filling in its saved registers and
+//! then executing an SMC.
+//!
+//! Our linker script PROVIDEs a default `_smc_handler` symbol which is an alias
+//! for the `_default_handler` function. You can override it by defining your
+//! own `_smc_handler` function, or by using the `#[exception(SecureMonitorCall)]`
+//! attribute on a normal Rust function.
+//!
+//! ```rust,ignore
+//! use aarch32_rt::exception;
+//! use aarch32_rt::monitor::{World, Worlds};
+//!
+//! #[exception(SecureMonitorCall)]
+//! fn smc_handler(caller: World, worlds: &mut Worlds) -> World {
+//!     match caller {
+//!         World::Secure => {
+//!             // Start the Non-secure world at the address passed in R0, in
+//!             // SVC mode with interrupts masked
+//!             worlds.non_secure.pc = worlds.secure.r[0];
+//!             worlds.non_secure.cpsr = 0x1D3;
+//!             // and let it use the FPU, by setting FPEXC.EN
+//!             worlds.non_secure.fpu.fpexc = 1 << 30;
+//!             World::NonSecure
+//!         }
+//!         World::NonSecure => {
+//!             // Return an error code to the caller
+//!             worlds.non_secure.r[0] = 0xFFFF_FFFF;
+//!             World::NonSecure
+//!         }
+//!     }
+//! }
+//! ```
+//!
 //! ### Prefetch Abort Handler
 //!
 //! The symbol `_prefetch_abort_handler` should be an `extern "C"` function. It
@@ -344,6 +505,36 @@
 //! }
 //! ```
 //!
+//! ### Debug Monitor Handler
+//!
+//! On Armv7 and higher, we also provide an alternative Prefetch Abort
+//! trampoline called `_asm_debug_monitor_handler`, for use with a debug monitor
+//! like `aarch32_cpu::gdbstub`. Select it by adding this to your `memory.x`:
+//!
+//! ```text
+//! _asm_prefetch_abort_handler = _asm_debug_monitor_handler;
+//! ```
+//!
+//! It saves all of the interrupted registers, and calls an `extern "C"`
+//! function called `_debug_monitor_handler`, which is given a mutable reference
+//! to them. Any changes made are applied when the handler returns.
+//!
+//! ```rust,ignore
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _debug_monitor_handler(regs: &mut aarch32_cpu::gdbstub::Registers) {
+//!     // talk to the debugger
+//! }
+//! ```
+//!
+//! Note that the SP and LR saved are those of SYS mode (which is also USR
+//! mode), so it works best when the code being debugged runs in SYS mode.
+//!
+//! Our linker script PROVIDEs a default `_debug_monitor_handler` symbol which
+//! is an alias for the `_default_handler` function.
+//!
+//! To try this out on QEMU, use `-serial pty` (or a second `-serial` option)
+//! and point GDB at the pseudo-terminal with `target remote /dev/pts/N`.
+//!
 //! ### Data Abort Handler
 //!
 //! The symbol `_data_abort_handler` should be an `extern "C"` function. It is
@@ -426,6 +617,10 @@
 //! for `_default_handler`. You can override it by defining your own
 //! `_irq_handler` function.
 //!
+//! With the `vic-vectored-irq` feature, `_irq_handler` isn't used. The handler
+//! comes from the VIC instead, and should be an `extern "C" fn()`. Nothing is
+//! called if VICVECTADDR reads as zero.
+//!
 //! Expected prototype:
 //!
 //! ```rust
@@ -437,6 +632,16 @@
 //! }
 //! ```
 //!
+//! On Armv7 and higher, the handler is also passed a reference to the return
+//! state of the interrupted code, which you can use if you wish:
+//!
+//! ```rust
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _irq_handler(frame: &aarch32_rt::IrqFrame) {
+//!     // `frame.pc` is where the interrupted code will resume
+//! }
+//! ```
+//!
 //! You can also create a `_irq_handler` function by using the `#[irq]`
 //! attribute on a normal Rust function.
 //!
@@ -488,6 +693,13 @@
 //!   default handler will call `_data_abort_handler` in ABT mode, saving state
 //!   as required.
 //!
+//! * `_asm_hyp_trap_handler` - a naked function to call when something other
+//!   than an HVC instruction traps to Hyp mode, with the `el2-mode` or
+//!   `el2-monitor` features. Our linker script PROVIDEs a default function at
+//!   `_asm_default_hyp_trap_handler` but you can override it. The provided
+//!   default handler will call `_hyp_trap_handler` in HYP mode, saving state as
+//!   required.
+//!
 //! * `_asm_irq_handler` - a naked function to call when an Undefined Exception
 //!   occurs. Our linker script PROVIDEs a default function at
 //!   `_asm_default_irq_handler` but you can override it. The provided default
@@ -499,6 +711,26 @@
 //!   `_asm_default_fiq_handler` but you can override it. The provided default
 //!   just spins forever.
 //!
+//! * `_asm_smc_handler` - a naked function to call when a Secure Monitor Call
+//!   (SMC) Exception occurs, with the `secure-monitor` feature. Our linker
+//!   script PROVIDEs a default function at `_asm_default_smc_handler` but you
+//!   can override it. The provided default handler will save the calling
+//!   world's registers and call `_smc_handler` in MON mode.
+//!
+//! * `_asm_monitor_prefetch_abort_handler`, `_asm_monitor_data_abort_handler`,
+//!   `_asm_monitor_irq_handler` and `_asm_monitor_fiq_handler` - naked
+//!   functions to call when those exceptions are taken to MON mode (see the
+//!   `EA`, `IRQ` and `FIQ` bits in SCR). Our linker script PROVIDEs a default
+//!   function at `_asm_default_monitor_handler` for each, which just spins
+//!   forever.
+//!
+//! * `_asm_hyp_undefined_handler`, `_asm_hyp_svc_handler`,
+//!   `_asm_hyp_prefetch_abort_handler`, `_asm_hyp_data_abort_handler`,
+//!   `_asm_hyp_irq_handler` and `_asm_hyp_fiq_handler` - naked functions to
+//!   call when those exceptions are taken to HYP mode, with the `el2-monitor`
+//!   feature. Our linker script PROVIDEs a default function at
+//!   `_asm_default_hyp_handler` for each, which just spins forever.
+//!
 //! ## Outputs
 //!
 //! This library produces global symbols called:
@@ -514,14 +746,33 @@
 //!   calls `_prefetch_abort_handler`
 //! * `_asm_default_data_abort_handler` - assembly language trampoline that
 //!   calls `_data_abort_handler`
+//! * `_asm_debug_monitor_handler` - assembly language trampoline that calls
+//!   `_debug_monitor_handler` with all the saved registers (Armv7 and higher)
+//! * `_asm_default_hyp_trap_handler` - assembly language trampoline that calls
+//!   `_hyp_trap_handler` (with the `el2-mode` or `el2-monitor` features)
 //! * `_asm_default_irq_handler` - assembly language trampoline that calls
 //!   `_irq_handler`
 //! * `_asm_default_fiq_handler` - an FIQ handler that just spins
+//! * `_monitor_vector_table` - the start of the monitor vector table (with the
+//!   `secure-monitor` feature)
+//! * `_asm_default_smc_handler` - assembly language trampoline that saves the
+//!   calling world and calls `_smc_handler` (with the `secure-monitor` feature)
+//! * `_asm_default_monitor_handler` - a Monitor mode handler that just spins
+//!   (with the `secure-monitor` feature)
+//! * `_hyp_vector_table` - the start of the Hyp vector table (with the
+//!   `el2-monitor` feature)
+//! * `_asm_default_hyp_handler` - a Hyp mode handler that just spins (with the
+//!   `el2-monitor` feature)
 //! * `_default_handler` - a C compatible function that spins forever.
+//! * `_default_el2_init` - a C compatible function that does nothing, used as
+//!   the default `_el2_init` (Armv7-A and Armv8-R)
 //! * `_init_segments` - initialises `.bss` and `.data` and zeroes the stacks
 //! * `_stack_setup_preallocated` - initialises UND, SVC, ABT, IRQ, FIQ and SYS
 //!   stacks from the `.stacks` section defined in link.x, based on
-//!   _xxx_stack_size values, and the core number given in `r0`
+//!   _xxx_stack_size values, and the core number given in `r0` (plus the MON
+//!   stack, with the `secure-monitor` feature)
+//! * `_stack_setup_monitor` - initialises the MON stack for the core number
+//!   given in `r0` (with the `secure-monitor` feature)
 //! * `_xxx_stack_high_end` and `_xxx_stack_low_end` where the former is the top
 //!   and the latter the bottom of the stack for each mode (`und`, `svc`, `abt`,
 //!   `irq`, `fiq`, `sys`)
@@ -544,26 +795,72 @@
 #[cfg(target_arch = "arm")]
 use aarch32_cpu::register::{cpsr::ProcessorMode, Cpsr};
 
-#[cfg(any(
-    arm_architecture = "v7-a",
-    all(arm_architecture = "v8-r", not(feature = "el2-mode")),
-))]
-use aarch32_cpu::register::Hactlr;
-
 pub use aarch32_rt_macros::{entry, exception, irq};
 
-#[cfg(all(arm_architecture = "v8-r", feature = "el2-mode"))]
-mod arch_v8_hyp;
+#[cfg(any(
+    all(
+        any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+        feature = "el2-mode"
+    ),
+    all(arm_architecture = "v8-r", feature = "el2-monitor")
+))]
+mod arch_hyp;
 
 #[cfg(all(
     armv7_or_higher,
-    not(all(arm_architecture = "v8-r", feature = "el2-mode"))
+    not(all(
+        any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+        feature = "el2-mode"
+    ))
 ))]
 mod arch_v7;
 
 #[cfg(armv6_or_lower)]
 mod arch_v4;
 
+#[cfg(any(doc, feature = "secure-monitor"))]
+pub mod monitor;
+
+#[cfg(all(
+    target_arch = "arm",
+    not(doc),
+    feature = "secure-monitor",
+    not(arm_architecture = "v7-a")
+))]
+compile_error!("The `secure-monitor` feature is only supported on Armv7-A");
+
+#[cfg(all(
+    target_arch = "arm",
+    not(doc),
+    feature = "secure-monitor",
+    feature = "el2-mode"
+))]
+compile_error!("The `secure-monitor` and `el2-mode` features cannot be used together");
+
+#[cfg(all(
+    target_arch = "arm",
+    not(doc),
+    feature = "el2-monitor",
+    not(arm_architecture = "v8-r")
+))]
+compile_error!("The `el2-monitor` feature is only supported on Armv8-R");
+
+#[cfg(all(
+    target_arch = "arm",
+    not(doc),
+    feature = "el2-monitor",
+    feature = "el2-mode"
+))]
+compile_error!("The `el2-monitor` and `el2-mode` features cannot be used together");
+
+#[cfg(all(
+    target_arch = "arm",
+    not(doc),
+    feature = "vic-vectored-irq",
+    not(armv6_or_lower)
+))]
+compile_error!("The `vic-vectored-irq` feature is only supported on Armv4 to Armv6");
+
 pub mod sections;
 pub mod stacks;
 
@@ -578,6 +875,18 @@ pub extern "C" fn _default_handler() {
     }
 }
 
+/// Our default EL2 initialisation function, which does nothing.
+///
+/// `_default_start` calls `_el2_init` just before it drops from EL2 to EL1, and
+/// we end up here if the weak 'PROVIDE' in the link.x file hasn't been
+/// over-ridden.
+#[cfg(all(
+    any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+    not(feature = "el2-mode")
+))]
+#[unsafe(no_mangle)]
+pub extern "C" fn _default_el2_init() {}
+
 // The Interrupt Vector Table, and some default assembly-language handler.
 //
 // Needs to be aligned to 5bits/2^5 to be stored correctly in VBAR
@@ -620,6 +929,42 @@ pub struct Frame {
     pub r5: u32,
 }
 
+/// Return state saved on interrupt
+///
+/// This struct is very carefully designed to match the layout of the
+/// return state pushed to the stack in our IRQ handler on Armv7 and higher. It
+/// can be used to find out what code was interrupted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct IrqFrame {
+    /// Where execution will resume when the interrupt handler returns
+    pub pc: u32,
+    /// The CPSR of the interrupted code
+    pub cpsr: u32,
+}
+
+/// State saved when something traps to Hyp mode
+///
+/// This struct is very carefully designed to match the layout of the
+/// registers pushed to the stack in our Hyp Trap handler, which is only used
+/// with the `el2-mode` and `el2-monitor` features. Any changes you make are
+/// written back when the handler returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct TrapFrame {
+    /// R0 to R12 of the trapped code
+    pub r: [u32; 13],
+    /// LR of the trapped code (Hyp mode shares LR with User and System mode)
+    pub lr: u32,
+    /// Where execution will resume when the trap handler returns
+    ///
+    /// For a trapped instruction, this is the address of that instruction,
+    /// so you will need to move it on if you emulate the instruction.
+    pub elr: u32,
+    /// The CPSR of the trapped code
+    pub spsr: u32,
+}
+
 /// This macro expands to code for saving FPU context on entry to an exception
 /// handler. It pushes a multiple of eight bytes to preserve AAPCS alignment.
 /// It may damage R0-R3.
@@ -833,6 +1178,62 @@ macro_rules! system_init {
     };
 }
 
+/// This is for systems running a Secure Monitor
+///
+/// It gives Monitor mode a stack. The core number is in r0, and r1, r2, r12
+/// and LR may be damaged.
+#[cfg(all(target_arch = "arm", feature = "secure-monitor"))]
+macro_rules! monitor_stack_setup {
+    () => {
+        r#"
+        // Set stack pointer (right after) for MON mode
+        bl      _stack_setup_monitor
+        "#
+    };
+}
+
+/// This is for systems without a Secure Monitor
+///
+/// It does nothing
+#[cfg(all(target_arch = "arm", not(feature = "secure-monitor")))]
+macro_rules! monitor_stack_setup {
+    () => {
+        ""
+    };
+}
+
+/// This is for systems running a Secure Monitor
+///
+/// It points MVBAR at our Monitor Vector Table. It may damage r0.
+#[cfg(all(
+    any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+    not(feature = "el2-mode"),
+    feature = "secure-monitor"
+))]
+macro_rules! monitor_init {
+    () => {
+        r#"
+        // Set the MVBAR (for Monitor mode) to _monitor_vector_table
+        ldr     r0, =_monitor_vector_table
+        mcr     p15, 0, r0, c12, c0, 1
+        "#
+    };
+}
+
+/// This is for systems without a Secure Monitor
+///
+/// It does nothing
+#[cfg(all(
+    any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+    not(feature = "el2-mode"),
+    not(feature = "secure-monitor")
+))]
+macro_rules! monitor_init {
+    () => {
+        ""
+    };
+}
+
 // Shared library routines for all architectures
 #[cfg(target_arch = "arm")]
 core::arch::global_asm!(
@@ -881,6 +1282,9 @@ core::arch::global_asm!(
         ldr	    r1, =_fiq_stack_size
         muls    r1, r1, r0
         subs    sp, r2, r1
+    "#,
+    monitor_stack_setup!(),
+    r#"
         // Set stack pointer (right after) and mask interrupts for System mode (Mode 0x1F)
         msr     cpsr_c, {sys_mode}
         ldr	    r2, =_sys_stack_high_end
@@ -1021,10 +1425,35 @@ core::arch::global_asm!(
     "#
 );
 
+/// This is for Armv8-R systems dropping from EL2 to EL1
+///
+/// It sets HACTLR to `_hactlr_value`, to say which IMPLEMENTATION DEFINED
+/// registers EL1 may access
+#[cfg(all(arm_architecture = "v8-r", not(feature = "el2-mode")))]
+macro_rules! hactlr_init {
+    () => {
+        r#"
+        // Configure HACTLR to say what EL1 may access
+        ldr     r1, =_hactlr_value
+        mcr     p15, 4, r1, c1, c0, 1
+        "#
+    };
+}
+
+/// This is for Armv7-A systems dropping from EL2 to EL1
+///
+/// HACTLR is entirely IMPLEMENTATION DEFINED there, so we leave it alone
+#[cfg(all(arm_architecture = "v7-a", not(feature = "el2-mode")))]
+macro_rules! hactlr_init {
+    () => {
+        ""
+    };
+}
+
 // Start-up code for CPUs that *might* boot into EL2 but that we want in EL1.
-#[cfg(any(
-    arm_architecture = "v7-a",
-    all(arm_architecture = "v8-r", not(feature = "el2-mode")),
+#[cfg(all(
+    any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+    not(feature = "el2-mode")
 ))]
 core::arch::global_asm!(
     r#"
@@ -1037,6 +1466,8 @@ core::arch::global_asm!(
     .global _default_start
     .type _default_start, %function
     _default_start:
+        // Init .data and .bss
+        bl      _init_segments
         // Are we in EL2? If not, skip the EL2 setup portion
         mrs     r0, cpsr
         and     r0, r0, 0x1F
@@ -1044,34 +1475,44 @@ core::arch::global_asm!(
         bne     1f
         // Set stack pointer
         ldr     sp, =_hyp_stack_high_end
-        // Set the HVBAR (for EL2) to _vector_table
-        ldr     r1, =_vector_table
+        // Set the HVBAR (for EL2) to _hyp_vector_table, which is just
+        // _vector_table unless something (like `el2-monitor`) supplies one
+        ldr     r1, =_hyp_vector_table
         mcr     p15, 4, r1, c12, c0, 0
-        // Configure HACTLR to let us enter EL1
-        mrc     p15, 4, r1, c1, c0, 1
-        mov     r2, {hactlr_bits}
-        orr     r1, r1, r2
-        mcr     p15, 4, r1, c1, c0, 1
+        // Stop HCPTR trapping the FPU (TCP10, TCP11) before we turn it on
+        mrc     p15, 4, r1, c1, c1, 2
+        bic     r1, r1, #0xC00
+        mcr     p15, 4, r1, c1, c1, 2
+        isb
+    "#,
+    hactlr_init!(),
+    system_init!(),
+    r#"
+        // Let EL2 configure anything else before we leave
+        bl      _el2_init
         // Program the SPSR - enter system mode (0x1F) in Arm mode with IRQ, FIQ masked
         mov		r1, {sys_mode}
         msr		spsr_hyp, r1
-        adr		r1, 1f
+        adr		r1, 2f
         msr		elr_hyp, r1
         dsb
         isb
         eret
     1:
+        // We booted in EL1, so nobody has done this yet
+    "#,
+    system_init!(),
+    r#"
+    2:
         // Set the VBAR (for EL1) to _vector_table. NB: This isn't required on
         // Armv7-R because that only supports 'low' (default) or 'high'.
         ldr     r0, =_vector_table
         mcr     p15, 0, r0, c12, c0, 0
-        // Init .data and .bss
-        bl      _init_segments
         // Set up stacks.
         mov     r0, #0
         bl      _stack_setup_preallocated
     "#,
-    system_init!(),
+    monitor_init!(),
     r#"
         // Zero all registers before calling kmain
         mov     r0, 0
@@ -1095,19 +1536,6 @@ core::arch::global_asm!(
     .popsection
     "#,
     cpsr_mode_hyp = const ProcessorMode::Hyp as u8,
-    hactlr_bits = const {
-        Hactlr::new_with_raw_value(0)
-            .with_cpuactlr(true)
-            .with_cdbgdci(true)
-            .with_flashifregionr(true)
-            .with_periphpregionr(true)
-            .with_qosr(true)
-            .with_bustimeoutr(true)
-            .with_intmonr(true)
-            .with_err(true)
-            .with_testr1(true)
-            .raw_value()
-    },
     sys_mode = const {
         Cpsr::new_with_raw_value(0)
             .with_mode(ProcessorMode::Sys)
@@ -1117,10 +1545,13 @@ core::arch::global_asm!(
     }
 );
 
-// Start-up code for Armv8-R to stay in EL2.
+// Start-up code for Armv7-A and Armv8-R to stay in EL2.
 //
 // We boot into EL2, set up a HYP stack pointer, and run `kmain` in EL2.
-#[cfg(all(arm_architecture = "v8-r", feature = "el2-mode"))]
+#[cfg(all(
+    any(arm_architecture = "v7-a", arm_architecture = "v8-r"),
+    feature = "el2-mode"
+))]
 core::arch::global_asm!(
     r#"
     // Work around https://github.com/rust-lang/rust/issues/127269
@@ -1141,6 +1572,11 @@ core::arch::global_asm!(
         mrs     r0, CPSR
         orr     r0, {irq_fiq}
         msr     CPSR, r0
+        // Stop HCPTR trapping the FPU (TCP10, TCP11) before we turn it on
+        mrc     p15, 4, r1, c1, c1, 2
+        bic     r1, r1, #0xC00
+        mcr     p15, 4, r1, c1, c1, 2
+        isb
     "#,
     system_init!(),
     r#"
@@ -0,0 +1,557 @@
+//! Secure Monitor support, for Armv7-A processors with the Security Extensions
+//!
+//! Enabled with the `secure-monitor` feature. The start-up code sets MVBAR to
+//! `_monitor_vector_table` and gives Monitor mode a stack. When either world
+//! executes an SMC instruction, `_asm_default_smc_handler` saves the calling
+//! world's registers into a [`WorldContext`], moves the processor into Secure
+//! state, and calls:
+//!
+//! ```rust
+//! # use aarch32_rt::monitor::{World, Worlds};
+//! #[unsafe(no_mangle)]
+//! extern "C" fn _smc_handler(caller: World, worlds: &mut Worlds) -> World {
+//!     // do stuff here
+//!     caller
+//! }
+//! ```
+//!
+//! The handler can read and modify the saved registers of both worlds. It
+//! returns the world to resume, and that world's registers are restored before
+//! the monitor returns to it. Any changes made to the saved registers are
+//! applied at that point.
+//!
+//! When the FPU is in use (on an `eabihf` target, or with the `eabi-fpu`
+//! feature), the monitor also switches FPSCR, FPEXC and D0-D15 (or D0-D31,
+//! with the `fpu-d32` feature). A world starts with FPEXC clear, so set
+//! `fpu.fpexc` before you start a world that uses the FPU. Without the FPU in
+//! use, these registers are shared between the two worlds.
+//!
+//! The Security Extensions give the CP15 registers that are banked by SCR.NS,
+//! like SCTLR, VBAR, TTBR0 and the TPIDR registers, a Secure and a Non-secure
+//! copy, so the monitor doesn't need to switch them. `_smc_handler` runs with
+//! SCR.NS clear and so sees the Secure copies - use [`with_non_secure_banked`]
+//! to reach the Non-secure ones. The CP15 registers that are not banked, like
+//! CPACR and ACTLR, are shared between the two worlds and are not switched.
+//!
+//! The saved contexts live in a single static, so only one core may use the
+//! monitor.
+//!
+//! You can exercise this on QEMU with `-machine virt,secure=on`.
+
+/// One of the two Security States
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum World {
+    /// The Secure world
+    Secure = 0,
+    /// The Non-secure world
+    NonSecure = 1,
+}
+
+/// The SP, LR and SPSR of one processor mode
+///
+/// `_asm_default_smc_handler` relies on these being in this order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct ModeRegisters {
+    /// The Stack Pointer
+    pub sp: u32,
+    /// The Link Register
+    pub lr: u32,
+    /// The Saved Program Status Register
+    pub spsr: u32,
+}
+
+impl ModeRegisters {
+    const fn new() -> ModeRegisters {
+        ModeRegisters {
+            sp: 0,
+            lr: 0,
+            spsr: 0,
+        }
+    }
+}
+
+/// The FPU registers of one world, saved by the Secure Monitor
+///
+/// `_asm_default_smc_handler` relies on these being in this order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct FpuRegisters {
+    /// The Floating-Point Status and Control Register
+    pub fpscr: u32,
+    /// The Floating-Point Exception Control Register
+    pub fpexc: u32,
+    /// D0 to D31 (D16 to D31 are only switched with the `fpu-d32` feature)
+    pub d: [u64; 32],
+}
+
+impl FpuRegisters {
+    const fn new() -> FpuRegisters {
+        FpuRegisters {
+            fpscr: 0,
+            fpexc: 0,
+            d: [0; 32],
+        }
+    }
+}
+
+const _: () = assert!(core::mem::offset_of!(FpuRegisters, fpexc) == 4);
+const _: () = assert!(core::mem::offset_of!(FpuRegisters, d) == 8);
+
+/// The registers of one world, saved by the Secure Monitor
+///
+/// This struct is very carefully designed to match the offsets used by
+/// `_asm_default_smc_handler`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct WorldContext {
+    /// R0 to R12
+    pub r: [u32; 13],
+    /// The address of the instruction to execute on return
+    pub pc: u32,
+    /// The Program Status Register to use on return
+    pub cpsr: u32,
+    /// The Stack Pointer for User and System mode
+    pub sp_usr: u32,
+    /// The Link Register for User and System mode
+    pub lr_usr: u32,
+    /// Supervisor mode registers
+    pub svc: ModeRegisters,
+    /// Abort mode registers
+    pub abt: ModeRegisters,
+    /// Undefined mode registers
+    pub und: ModeRegisters,
+    /// IRQ mode registers
+    pub irq: ModeRegisters,
+    /// R8 to R12 for FIQ mode
+    pub r_fiq: [u32; 5],
+    /// FIQ mode registers
+    pub fiq: ModeRegisters,
+    /// FPU registers
+    pub fpu: FpuRegisters,
+}
+
+impl WorldContext {
+    /// Create a context with every register set to zero
+    pub const fn new() -> WorldContext {
+        WorldContext {
+            r: [0; 13],
+            pc: 0,
+            cpsr: 0,
+            sp_usr: 0,
+            lr_usr: 0,
+            svc: ModeRegisters::new(),
+            abt: ModeRegisters::new(),
+            und: ModeRegisters::new(),
+            irq: ModeRegisters::new(),
+            r_fiq: [0; 5],
+            fiq: ModeRegisters::new(),
+            fpu: FpuRegisters::new(),
+        }
+    }
+}
+
+impl Default for WorldContext {
+    fn default() -> Self {
+        WorldContext::new()
+    }
+}
+
+/// The saved contexts of both worlds
+///
+/// The Secure context must come first - `_asm_default_smc_handler` indexes
+/// this struct with the value of SCR.NS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct Worlds {
+    /// The Secure world's registers
+    pub secure: WorldContext,
+    /// The Non-secure world's registers
+    pub non_secure: WorldContext,
+}
+
+impl Worlds {
+    /// Get the saved registers for the given world
+    pub fn get_mut(&mut self, world: World) -> &mut WorldContext {
+        match world {
+            World::Secure => &mut self.secure,
+            World::NonSecure => &mut self.non_secure,
+        }
+    }
+}
+
+/// Run `f` with SCR.NS set, so it sees the Non-secure copies of the banked
+/// CP15 registers
+///
+/// For example, `_smc_handler` can use this to set the Non-secure VBAR before
+/// it starts the Non-secure world.
+///
+/// # Safety
+///
+/// Only call this in Monitor mode. In any other mode, setting SCR.NS moves
+/// the processor into Non-secure state.
+pub unsafe fn with_non_secure_banked<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let scr = aarch32_cpu::register::Scr::read();
+    // Safety: the caller promises we are in Monitor mode, which is always
+    // Secure
+    unsafe {
+        aarch32_cpu::register::Scr::write(scr.with_ns(true));
+    }
+    let result = f();
+    // Safety: as above
+    unsafe {
+        aarch32_cpu::register::Scr::write(scr);
+    }
+    result
+}
+
+/// This macro expands to code for saving the calling world's FPU registers
+/// into the `FpuRegisters` at R7, and leaves the FPU on for the monitor. It
+/// may damage R6.
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    not(any(target_abi = "eabihf", feature = "eabi-fpu"))
+))]
+macro_rules! save_world_fpu {
+    () => {
+        ""
+    };
+}
+
+/// This macro expands to code for restoring the resumed world's FPU registers
+/// from the `FpuRegisters` at R7. It may damage R6.
+///
+/// It should match `save_world_fpu!`.
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    not(any(target_abi = "eabihf", feature = "eabi-fpu"))
+))]
+macro_rules! restore_world_fpu {
+    () => {
+        ""
+    };
+}
+
+/// This macro expands to code for saving the calling world's FPU registers
+/// into the `FpuRegisters` at R7, and leaves the FPU on for the monitor. It
+/// may damage R6.
+///
+/// This version assumes 16 DP registers (a 'D16' or 'D16SP' FPU
+/// configuration).
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    any(target_abi = "eabihf", feature = "eabi-fpu"),
+    not(feature = "fpu-d32")
+))]
+macro_rules! save_world_fpu {
+    () => {
+        r#"
+        vmrs    r6, FPEXC
+        str     r6, [r7, #4]              // save FPEXC
+        orr     r6, r6, #0x40000000
+        vmsr    FPEXC, r6                 // set FPEXC.EN, so we can save the rest
+        vmrs    r6, FPSCR
+        str     r6, [r7]                  // save FPSCR
+        add     r6, r7, #8
+        vstmia  r6, {{ d0-d15 }}          // save D0-D15
+        "#
+    };
+}
+
+/// This macro expands to code for restoring the resumed world's FPU registers
+/// from the `FpuRegisters` at R7. It may damage R6.
+///
+/// This version assumes 16 DP registers (a 'D16' or 'D16SP' FPU
+/// configuration). It should match `save_world_fpu!`.
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    any(target_abi = "eabihf", feature = "eabi-fpu"),
+    not(feature = "fpu-d32")
+))]
+macro_rules! restore_world_fpu {
+    () => {
+        r#"
+        add     r6, r7, #8
+        vldmia  r6, {{ d0-d15 }}          // restore D0-D15
+        ldr     r6, [r7]
+        vmsr    FPSCR, r6                 // restore FPSCR
+        ldr     r6, [r7, #4]
+        vmsr    FPEXC, r6                 // restore FPEXC
+        "#
+    };
+}
+
+/// This macro expands to code for saving the calling world's FPU registers
+/// into the `FpuRegisters` at R7, and leaves the FPU on for the monitor. It
+/// may damage R6.
+///
+/// This version assumes 32 DP registers (a 'D32' FPU configuration).
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    any(target_abi = "eabihf", feature = "eabi-fpu"),
+    feature = "fpu-d32"
+))]
+macro_rules! save_world_fpu {
+    () => {
+        r#"
+        vmrs    r6, FPEXC
+        str     r6, [r7, #4]              // save FPEXC
+        orr     r6, r6, #0x40000000
+        vmsr    FPEXC, r6                 // set FPEXC.EN, so we can save the rest
+        vmrs    r6, FPSCR
+        str     r6, [r7]                  // save FPSCR
+        add     r6, r7, #8
+        vstmia  r6!, {{ d0-d15 }}         // save D0-D15
+        vstmia  r6, {{ d16-d31 }}         // save D16-D31
+        "#
+    };
+}
+
+/// This macro expands to code for restoring the resumed world's FPU registers
+/// from the `FpuRegisters` at R7. It may damage R6.
+///
+/// This version assumes 32 DP registers (a 'D32' FPU configuration). It
+/// should match `save_world_fpu!`.
+#[cfg(all(
+    target_arch = "arm",
+    feature = "secure-monitor",
+    any(target_abi = "eabihf", feature = "eabi-fpu"),
+    feature = "fpu-d32"
+))]
+macro_rules! restore_world_fpu {
+    () => {
+        r#"
+        add     r6, r7, #8
+        vldmia  r6!, {{ d0-d15 }}         // restore D0-D15
+        vldmia  r6, {{ d16-d31 }}         // restore D16-D31
+        ldr     r6, [r7]
+        vmsr    FPSCR, r6                 // restore FPSCR
+        ldr     r6, [r7, #4]
+        vmsr    FPEXC, r6                 // restore FPEXC
+        "#
+    };
+}
+
+#[cfg(all(target_arch = "arm", feature = "secure-monitor"))]
+#[unsafe(no_mangle)]
+static mut _aarch32_rt_worlds: Worlds = Worlds {
+    secure: WorldContext::new(),
+    non_secure: WorldContext::new(),
+};
+
+// The Monitor Vector Table, and the SMC trampoline.
+//
+// MVBAR must be 32-byte aligned, and Monitor mode always runs in Arm state.
+#[cfg(all(target_arch = "arm", feature = "secure-monitor"))]
+core::arch::global_asm!(
+    r#"
+    // Work around https://github.com/rust-lang/rust/issues/127269
+    .fpu vfp3
+
+    .pushsection .text._monitor_vector_table
+    .arm
+    .global _monitor_vector_table
+    .type _monitor_vector_table, %function
+    .align 5
+    _monitor_vector_table:
+        b       .                         // not used
+        b       .                         // not used
+        ldr     pc, =_asm_smc_handler
+        ldr     pc, =_asm_monitor_prefetch_abort_handler
+        ldr     pc, =_asm_monitor_data_abort_handler
+        b       .                         // not used
+        ldr     pc, =_asm_monitor_irq_handler
+        ldr     pc, =_asm_monitor_fiq_handler
+    .size _monitor_vector_table, . - _monitor_vector_table
+    .popsection
+
+    // Used for any exception taken to Monitor mode that isn't an SMC, unless
+    // you supply your own handler. It just spins.
+    .pushsection .text._asm_default_monitor_handler
+    .arm
+    .global _asm_default_monitor_handler
+    .type _asm_default_monitor_handler, %function
+    _asm_default_monitor_handler:
+        b       .
+    .size _asm_default_monitor_handler, . - _asm_default_monitor_handler
+    .popsection
+
+    // Called from the monitor vector table when we have a secure monitor call.
+    // Saves the calling world's registers, and calls a C-compatible handler like
+    // `extern "C" fn _smc_handler(caller: World, worlds: &mut Worlds) -> World;`
+    // then restores the registers of whichever world it returns.
+    .pushsection .text._asm_default_smc_handler
+    .arm
+    .global _asm_default_smc_handler
+    .type _asm_default_smc_handler, %function
+    _asm_default_smc_handler:
+        push    {{ r0, r1 }}              // give us R0 and R1 to work with
+        mrc     p15, 0, r0, c1, c1, 0     // r0 = SCR
+        and     r1, r0, #1                // r1 = SCR.NS, i.e. the calling world
+        bic     r0, r0, #1
+        mcr     p15, 0, r0, c1, c1, 0     // clear SCR.NS, so other modes are Secure
+        isb
+        ldr     r0, =_aarch32_rt_worlds
+        cmp     r1, #0
+        addne   r0, r0, #{context_size}   // r0 = the calling world's WorldContext
+        add     r0, r0, #8
+        stmia   r0, {{ r2-r12 }}          // save R2-R12
+        sub     r0, r0, #8
+        pop     {{ r2, r3 }}
+        stmia   r0, {{ r2, r3 }}          // save R0-R1
+        mov     r4, r0                    // r4 = WorldContext (R4 isn't banked)
+        mov     r5, r1                    // r5 = calling world
+        str     lr, [r4, #{pc}]           // save return address
+        mrs     r6, spsr
+        str     r6, [r4, #{cpsr}]         // save return CPSR
+        cps     #{sys_mode}
+        str     sp, [r4, #{sp_usr}]
+        str     lr, [r4, #{lr_usr}]
+        cps     #{svc_mode}
+        str     sp, [r4, #{svc}]
+        str     lr, [r4, #{svc} + 4]
+        mrs     r6, spsr
+        str     r6, [r4, #{svc} + 8]
+        cps     #{abt_mode}
+        str     sp, [r4, #{abt}]
+        str     lr, [r4, #{abt} + 4]
+        mrs     r6, spsr
+        str     r6, [r4, #{abt} + 8]
+        cps     #{und_mode}
+        str     sp, [r4, #{und}]
+        str     lr, [r4, #{und} + 4]
+        mrs     r6, spsr
+        str     r6, [r4, #{und} + 8]
+        cps     #{irq_mode}
+        str     sp, [r4, #{irq}]
+        str     lr, [r4, #{irq} + 4]
+        mrs     r6, spsr
+        str     r6, [r4, #{irq} + 8]
+        cps     #{fiq_mode}
+        add     r6, r4, #{r_fiq}
+        stmia   r6, {{ r8-r12 }}          // save R8_fiq-R12_fiq
+        str     sp, [r4, #{fiq}]
+        str     lr, [r4, #{fiq} + 4]
+        mrs     r6, spsr
+        str     r6, [r4, #{fiq} + 8]
+        cps     #{mon_mode}
+        add     r7, r4, #{fpu}            // r7 = the calling world's FpuRegisters
+    "#,
+    save_world_fpu!(),
+    r#"
+        mov     r0, r5                    // r0 = calling world
+        ldr     r1, =_aarch32_rt_worlds   // r1 = both WorldContexts
+        bl      _smc_handler
+        mov     r5, r0                    // r5 = the world to resume
+        ldr     r4, =_aarch32_rt_worlds
+        cmp     r5, #0
+        addne   r4, r4, #{context_size}   // r4 = the resumed world's WorldContext
+        add     r7, r4, #{fpu}            // r7 = the resumed world's FpuRegisters
+    "#,
+    restore_world_fpu!(),
+    r#"
+        cps     #{sys_mode}
+        ldr     sp, [r4, #{sp_usr}]
+        ldr     lr, [r4, #{lr_usr}]
+        cps     #{svc_mode}
+        ldr     sp, [r4, #{svc}]
+        ldr     lr, [r4, #{svc} + 4]
+        ldr     r6, [r4, #{svc} + 8]
+        msr     spsr_fsxc, r6
+        cps     #{abt_mode}
+        ldr     sp, [r4, #{abt}]
+        ldr     lr, [r4, #{abt} + 4]
+        ldr     r6, [r4, #{abt} + 8]
+        msr     spsr_fsxc, r6
+        cps     #{und_mode}
+        ldr     sp, [r4, #{und}]
+        ldr     lr, [r4, #{und} + 4]
+        ldr     r6, [r4, #{und} + 8]
+        msr     spsr_fsxc, r6
+        cps     #{irq_mode}
+        ldr     sp, [r4, #{irq}]
+        ldr     lr, [r4, #{irq} + 4]
+        ldr     r6, [r4, #{irq} + 8]
+        msr     spsr_fsxc, r6
+        cps     #{fiq_mode}
+        add     r6, r4, #{r_fiq}
+        ldmia   r6, {{ r8-r12 }}          // restore R8_fiq-R12_fiq
+        ldr     sp, [r4, #{fiq}]
+        ldr     lr, [r4, #{fiq} + 4]
+        ldr     r6, [r4, #{fiq} + 8]
+        msr     spsr_fsxc, r6
+        cps     #{mon_mode}
+        ldr     lr, [r4, #{pc}]           // restore return address
+        ldr     r6, [r4, #{cpsr}]
+        msr     spsr_fsxc, r6             // restore return CPSR
+        mrc     p15, 0, r6, c1, c1, 0
+        bic     r6, r6, #1
+        orr     r6, r6, r5
+        mcr     p15, 0, r6, c1, c1, 0     // SCR.NS = the resumed world
+        isb
+        ldmia   r4, {{ r0-r12 }}          // restore R0-R12
+        movs    pc, lr                    // return from the exception
+    .size _asm_default_smc_handler, . - _asm_default_smc_handler
+    .popsection
+    "#,
+    context_size = const core::mem::size_of::<WorldContext>(),
+    pc = const core::mem::offset_of!(WorldContext, pc),
+    cpsr = const core::mem::offset_of!(WorldContext, cpsr),
+    sp_usr = const core::mem::offset_of!(WorldContext, sp_usr),
+    lr_usr = const core::mem::offset_of!(WorldContext, lr_usr),
+    svc = const core::mem::offset_of!(WorldContext, svc),
+    abt = const core::mem::offset_of!(WorldContext, abt),
+    und = const core::mem::offset_of!(WorldContext, und),
+    irq = const core::mem::offset_of!(WorldContext, irq),
+    r_fiq = const core::mem::offset_of!(WorldContext, r_fiq),
+    fiq = const core::mem::offset_of!(WorldContext, fiq),
+    fpu = const core::mem::offset_of!(WorldContext, fpu),
+    sys_mode = const crate::ProcessorMode::Sys as u8,
+    svc_mode = const crate::ProcessorMode::Svc as u8,
+    abt_mode = const crate::ProcessorMode::Abt as u8,
+    und_mode = const crate::ProcessorMode::Und as u8,
+    irq_mode = const crate::ProcessorMode::Irq as u8,
+    fiq_mode = const crate::ProcessorMode::Fiq as u8,
+    mon_mode = const crate::ProcessorMode::Mon as u8,
+);
+
+// Gives Monitor mode a stack. Called by `_stack_setup_preallocated` with the
+// core number in r0. Returns in the mode it was called in, and may damage r1,
+// r2 and r12.
+#[cfg(all(target_arch = "arm", feature = "secure-monitor"))]
+core::arch::global_asm!(
+    r#"
+    .pushsection .text._stack_setup_monitor
+    .arm
+    .global _stack_setup_monitor
+    .type _stack_setup_monitor, %function
+    _stack_setup_monitor:
+        mrs     r12, cpsr
+        // Set stack pointer and mask interrupts for MON mode (Mode 0x16)
+        msr     cpsr_c, {mon_mode}
+        ldr     r2, =_mon_stack_high_end
+        ldr     r1, =_mon_stack_size
+        muls    r1, r1, r0
+        subs    sp, r2, r1
+        msr     cpsr_c, r12
+        bx      lr
+    .size _stack_setup_monitor, . - _stack_setup_monitor
+    .popsection
+    "#,
+    mon_mode = const {
+        crate::Cpsr::new_with_raw_value(0)
+            .with_mode(crate::ProcessorMode::Mon)
+            .with_i(true)
+            .with_f(true)
+            .raw_value()
+    },
+);
@@ -11,6 +11,10 @@ pub enum Stack {
     Abt,
     /// HYP mode stack, for EL2
     Hyp,
+    /// MON mode stack, for the Secure Monitor
+    ///
+    /// Only returned by [`Stack::iter`] with the `secure-monitor` feature.
+    Mon,
     /// IRQ mode stack, for interrupts
     Irq,
     /// FIQ mode stack, for fast interrupts
@@ -26,6 +30,7 @@ impl core::fmt::Display for Stack {
             Stack::Svc => "SVC",
             Stack::Abt => "ABT",
             Stack::Hyp => "HYP",
+            Stack::Mon => "MON",
             Stack::Irq => "IRQ",
             Stack::Fiq => "FIQ",
             Stack::Sys => "SYS",
@@ -95,6 +100,7 @@ impl Stack {
             static _svc_stack_size: u8;
             static _abt_stack_size: u8;
             static _hyp_stack_size: u8;
+            static _mon_stack_size: u8;
             static _irq_stack_size: u8;
             static _fiq_stack_size: u8;
             static _sys_stack_size: u8;
@@ -104,6 +110,7 @@ impl Stack {
             Stack::Svc => addr_of!(_svc_stack_size) as isize,
             Stack::Abt => addr_of!(_abt_stack_size) as isize,
             Stack::Hyp => addr_of!(_hyp_stack_size) as isize,
+            Stack::Mon => addr_of!(_mon_stack_size) as isize,
             Stack::Irq => addr_of!(_irq_stack_size) as isize,
             Stack::Fiq => addr_of!(_fiq_stack_size) as isize,
             Stack::Sys => addr_of!(_sys_stack_size) as isize,
@@ -119,6 +126,7 @@ impl Stack {
             static _svc_stack_high_end: u32;
             static _abt_stack_high_end: u32;
             static _hyp_stack_high_end: u32;
+            static _mon_stack_high_end: u32;
             static _irq_stack_high_end: u32;
             static _fiq_stack_high_end: u32;
             static _sys_stack_high_end: u32;
@@ -128,6 +136,7 @@ impl Stack {
             Stack::Svc => addr_of!(_svc_stack_high_end),
             Stack::Abt => addr_of!(_abt_stack_high_end),
             Stack::Hyp => addr_of!(_hyp_stack_high_end),
+            Stack::Mon => addr_of!(_mon_stack_high_end),
             Stack::Irq => addr_of!(_irq_stack_high_end),
             Stack::Fiq => addr_of!(_fiq_stack_high_end),
             Stack::Sys => addr_of!(_sys_stack_high_end),
@@ -164,7 +173,12 @@ impl Iterator for StackIter {
             Some(Stack::Und) => Some(Stack::Svc),
             Some(Stack::Svc) => Some(Stack::Abt),
             Some(Stack::Abt) => Some(Stack::Hyp),
+            // The MON stack is empty unless we're running a Secure Monitor
+            #[cfg(feature = "secure-monitor")]
+            Some(Stack::Hyp) => Some(Stack::Mon),
+            #[cfg(not(feature = "secure-monitor"))]
             Some(Stack::Hyp) => Some(Stack::Irq),
+            Some(Stack::Mon) => Some(Stack::Irq),
             Some(Stack::Irq) => Some(Stack::Fiq),
             Some(Stack::Fiq) => Some(Stack::Sys),
             Some(Stack::Sys) | None => None,
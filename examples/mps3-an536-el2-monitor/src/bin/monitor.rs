@@ -0,0 +1,113 @@
+//! EL2 monitor example
+//!
+//! The application runs in EL1, as usual. Before it starts, `_el2_init` asks
+//! EL2 to trap EL1's accesses to the CP15 c13 registers. The application then
+//! makes an HVC, which our `_hvc_handler` answers in Hyp mode, and reads
+//! TPIDRURO, which our `_hyp_trap_handler` emulates in Hyp mode.
+
+#![no_std]
+#![no_main]
+
+use aarch32_cpu::register::{
+    Cpsr, Hsr, Hstr, Tpidruro,
+    hyp::hsr::{InstructionLength, Iss},
+};
+use aarch32_rt::{entry, exception};
+use semihosting::println;
+
+/// The HVC number our monitor answers
+const HVC_INCREMENT: u16 = 0x1234;
+
+/// The value our monitor gives the application for TPIDRURO
+const EMULATED_TPIDRURO: u32 = 0x00C0_FFEE;
+
+/// Called in Hyp mode, just before the start-up code drops to EL1.
+#[unsafe(no_mangle)]
+extern "C" fn _el2_init() {
+    println!(
+        "EL2 init in {:?} mode: trapping CP15 c13 accesses from EL1",
+        Cpsr::read().mode().unwrap()
+    );
+    // Safety: only the application's accesses to c13 are affected, and we
+    // emulate the ones it makes
+    unsafe {
+        Hstr::write(Hstr(1 << 13));
+    }
+}
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up.
+#[entry]
+fn main() -> ! {
+    println!(
+        "Application running in {:?} mode",
+        Cpsr::read().mode().unwrap()
+    );
+
+    println!(
+        "Calling HVC {:#06x} with r0 = {:#010x}",
+        HVC_INCREMENT, 0x42
+    );
+    let value = aarch32_cpu::hvc1!(HVC_INCREMENT, 0x42);
+    println!("HVC returned {:#010x}", value);
+
+    println!("Reading TPIDRURO");
+    let value = Tpidruro::read().0;
+    println!("TPIDRURO = {:#010x}", value);
+
+    if value == EMULATED_TPIDRURO {
+        println!("Monitor example completed OK");
+        mps3_an536_el2_monitor::exit(0);
+    }
+    println!("TPIDRURO was not emulated!?");
+    mps3_an536_el2_monitor::exit(1);
+}
+
+/// Our monitor's HVC handler, which adds one to r0
+#[exception(HypervisorCall)]
+fn hvc_handler(hsr: u32, frame: &aarch32_rt::Frame) -> u32 {
+    let hsr = Hsr::new_with_raw_value(hsr);
+    println!(
+        "In hvc_handler in {:?} mode, imm16 = {:#06x}, r0 = {:#010x}",
+        Cpsr::read().mode().unwrap(),
+        hsr.iss().value(),
+        frame.r0
+    );
+    frame.r0 + 1
+}
+
+/// Our monitor's trap handler, which emulates reads of TPIDRURO
+#[exception(HypTrap)]
+fn hyp_trap_handler(hsr: u32, frame: &mut aarch32_rt::TrapFrame) {
+    let hsr = Hsr::new_with_raw_value(hsr);
+    let Some(Iss::TrappedCp15McrMrc(iss)) = hsr.get_iss() else {
+        panic!("Unexpected trap {:?}", hsr);
+    };
+    println!(
+        "In hyp_trap_handler in {:?} mode, trapped {} p15, {}, c{}, c{}, {}",
+        Cpsr::read().mode().unwrap(),
+        if iss.is_read() { "read of" } else { "write to" },
+        iss.opc1(),
+        iss.crn(),
+        iss.crm(),
+        iss.opc2()
+    );
+    let is_tpidruro = iss.opc1().value() == 0
+        && iss.crn().value() == 13
+        && iss.crm().value() == 0
+        && iss.opc2().value() == 3;
+    if !is_tpidruro || !iss.is_read() {
+        panic!("Unexpected access {:?}", iss);
+    }
+    match iss.rt().value() {
+        rt @ 0..=12 => frame.r[rt as usize] = EMULATED_TPIDRURO,
+        14 => frame.lr = EMULATED_TPIDRURO,
+        rt => panic!("Can't emulate a read into r{}", rt),
+    }
+    // Skip over the MRC
+    frame.elr += match hsr.il() {
+        InstructionLength::SixteenBit => 2,
+        InstructionLength::ThirtyTwoBit => 4,
+    };
+}
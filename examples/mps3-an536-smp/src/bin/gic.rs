@@ -34,7 +34,7 @@ const SGI_INTID: IntId = IntId::sgi(3);
 #[entry]
 fn main() -> ! {
     println!(
-        "I am core 0 - {:08x?}",
+        "I am core 0 - {:x?}",
         aarch32_cpu::register::Mpidr::read()
     );
 
@@ -108,7 +108,7 @@ fn main() -> ! {
 pub extern "C" fn kmain2() {
     critical_section::with(|cs| {
         println!(
-            "I am core 1 - {:08x?}",
+            "I am core 1 - {:x?}",
             aarch32_cpu::register::Mpidr::read()
         );
         let mut global_gic = GLOBAL_GIC.borrow_ref_mut(cs);
@@ -140,7 +140,7 @@ fn irq_handler() {
         GicCpuInterface::get_and_acknowledge_interrupt(InterruptGroup::Group1)
     {
         // handle the interrupt
-        if id.0 == 0x8000_0001 {
+        if id.aff0() == 1 {
             println!("- send SGI back to first core");
             GicCpuInterface::send_sgi(
                 SGI_INTID,
@@ -33,7 +33,7 @@ const CS_MUTEX_LOOPS: u32 = 1000;
 #[entry]
 fn main() -> ! {
     println!(
-        "I am core 0 - {:08x?}",
+        "I am core 0 - {:x?}",
         aarch32_cpu::register::Mpidr::read()
     );
 
@@ -97,7 +97,7 @@ fn main() -> ! {
 #[unsafe(no_mangle)]
 pub extern "C" fn kmain2() {
     println!(
-        "I am core 1 - {:08x?}",
+        "I am core 1 - {:x?}",
         aarch32_cpu::register::Mpidr::read()
     );
     CORE1_BOOTED.store(true, Ordering::SeqCst);
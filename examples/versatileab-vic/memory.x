@@ -0,0 +1,25 @@
+/*
+Memory configuration for the Arm Versatile Peripheral Board.
+
+See https://github.com/qemu/qemu/blob/master/hw/arm/versatilepb.c
+*/
+
+MEMORY {
+    SDRAM : ORIGIN = 0, LENGTH = 128M
+}
+
+REGION_ALIAS("VECTORS", SDRAM);
+REGION_ALIAS("CODE", SDRAM);
+REGION_ALIAS("DATA", SDRAM);
+REGION_ALIAS("STACKS", SDRAM);
+
+/* The PL190's VICVECTADDR, for the `vic-vectored-irq` feature */
+_vic_vectaddr = 0x10140030;
+
+PROVIDE(_hyp_stack_size = 16K);
+PROVIDE(_und_stack_size = 16K);
+PROVIDE(_svc_stack_size = 16K);
+PROVIDE(_abt_stack_size = 16K);
+PROVIDE(_irq_stack_size = 64);
+PROVIDE(_fiq_stack_size = 64);
+PROVIDE(_sys_stack_size = 16K);
@@ -0,0 +1,86 @@
+//! PL190 vectored interrupt hello-world.
+//!
+//! With the `vic-vectored-irq` feature, the run-time reads VICVECTADDR (at
+//! `_vic_vectaddr`, which our `memory.x` sets) and calls the handler the PL190
+//! gives it. There is no `_irq_handler`.
+
+#![no_std]
+#![no_main]
+
+use portable_atomic::{AtomicU32, Ordering::SeqCst};
+
+use aarch32_cpu::vic::Pl190;
+use aarch32_rt::entry;
+use semihosting::println;
+
+static MARKER: AtomicU32 = AtomicU32::new(0);
+
+// We can pick any two interrupt lines here
+const TEST_INTERRUPT1: u32 = 5;
+const TEST_INTERRUPT2: u32 = 6;
+
+// We can pick any vector slot here
+const TEST_VECTOR1: u32 = 2;
+
+/// Get a driver for the PL190
+fn vic() -> Pl190 {
+    // Safety: we only write whole registers, and the handlers only touch the
+    // lines they own
+    unsafe { versatileab_vic::vic() }
+}
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up.
+#[entry]
+fn my_main() -> ! {
+    // Safety: Not in a critical-section
+    unsafe {
+        aarch32_cpu::interrupt::enable();
+    }
+
+    println!("Setting up interrupts...");
+    let mut vic = vic();
+    vic.set_vector(TEST_VECTOR1, TEST_INTERRUPT1, vectored_handler);
+    vic.set_default_vector(default_handler);
+    vic.set_enabled(TEST_INTERRUPT1, true);
+    vic.set_enabled(TEST_INTERRUPT2, true);
+
+    println!("Firing vectored interrupt...");
+    vic.set_software_interrupt(TEST_INTERRUPT1);
+
+    // wait for it
+    for _ in 0..1_000 {
+        if MARKER.load(SeqCst) == 1 {
+            println!("Got interrupted :)");
+            // this one isn't in a vector slot
+            println!("Firing unvectored interrupt...");
+            vic.set_software_interrupt(TEST_INTERRUPT2);
+            break;
+        }
+    }
+
+    for _ in 0..1_000 {
+        if MARKER.load(SeqCst) == 2 {
+            println!("Default vector works. All done!");
+            versatileab_vic::exit(0);
+        }
+    }
+
+    println!("Not interrupted!?");
+    versatileab_vic::exit(1);
+}
+
+/// The handler in our vector slot, which the run-time calls directly
+extern "C" fn vectored_handler() {
+    println!("vectored_handler() fired");
+    vic().clear_software_interrupt(TEST_INTERRUPT1);
+    MARKER.store(1, SeqCst);
+}
+
+/// The handler for everything else
+extern "C" fn default_handler() {
+    println!("default_handler() fired");
+    vic().clear_software_interrupt(TEST_INTERRUPT2);
+    MARKER.store(2, SeqCst);
+}
@@ -5,16 +5,14 @@
 
 use portable_atomic::{AtomicU32, Ordering::SeqCst};
 
+use aarch32_cpu::vic::SOFTINTCLEAR_OFFSET;
 use aarch32_rt::entry;
-use pl190_vic::{InterruptId, InterruptType, Pl190Driver};
 use semihosting::println;
 
 static MARKER: AtomicU32 = AtomicU32::new(0);
 
-static PL190: Pl190Driver = unsafe { Pl190Driver::new_static(versatileab::PL190_BASE_ADDRESS) };
-
-// We can pick any interrupt ID value here
-const TEST_INTERRUPT1: InterruptId = InterruptId::new(1);
+// We can pick any interrupt line here
+const TEST_INTERRUPT1: u32 = 1;
 
 /// The entry-point to the Rust application.
 ///
@@ -25,10 +23,13 @@ fn my_main() -> ! {
     aarch32_cpu::asm::fiq_enable();
 
     println!("Setting up interrupts...");
-    PL190.set_interrupt_selection(TEST_INTERRUPT1, InterruptType::Fiq);
+    // Safety: the FIQ handler only writes VICSOFTINTCLEAR
+    let mut vic = unsafe { versatileab::vic() };
+    vic.set_fiq(TEST_INTERRUPT1, true);
+    vic.set_enabled(TEST_INTERRUPT1, true);
 
     println!("Firing interrupt...");
-    PL190.pend_sw_interrupt(TEST_INTERRUPT1);
+    vic.set_software_interrupt(TEST_INTERRUPT1);
 
     // wait for it
     for _ in 0..1_000 {
@@ -66,7 +67,7 @@ extern "C" fn _asm_fiq_handler() {
         // return from FIQ
         "SUBS    PC, LR, #4",
         marker = sym MARKER,
-        int_mask = const { TEST_INTERRUPT1.to_mask() },
-        intclear = const { versatileab::PL190_BASE_ADDRESS + 0x1C }
+        int_mask = const { 1 << TEST_INTERRUPT1 },
+        intclear = const { versatileab::PL190_BASE_ADDRESS + SOFTINTCLEAR_OFFSET }
     );
 }
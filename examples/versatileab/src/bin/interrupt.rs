@@ -5,24 +5,29 @@
 
 use portable_atomic::{AtomicU32, Ordering::SeqCst};
 
+use aarch32_cpu::vic::Pl190;
 use aarch32_rt::{entry, exception};
-use pl190_vic::{InterruptId, Pl190Driver, VectorId};
 use semihosting::println;
 
 static MARKER: AtomicU32 = AtomicU32::new(0);
 
-static PL190: Pl190Driver = unsafe { Pl190Driver::new_static(versatileab::PL190_BASE_ADDRESS) };
+// We can pick any three interrupt lines here
+const TEST_INTERRUPT1: u32 = 1;
+const TEST_INTERRUPT2: u32 = 2;
+const TEST_INTERRUPT3: u32 = 3;
 
-// We can pick any three interrupt ID values here
-const TEST_INTERRUPT1: InterruptId = InterruptId::new(1);
-const TEST_INTERRUPT2: InterruptId = InterruptId::new(2);
-const TEST_INTERRUPT3: InterruptId = InterruptId::new(3);
-
-// We can pick any two vector ID values here, as long as TEST_VECTOR1 is
+// We can pick any two vector slots here, as long as TEST_VECTOR1 is
 // higher than TEST_VECTOR2 (i.e. TEST_VECTOR2 is higher priority than
 // TEST_VECTOR1).
-const TEST_VECTOR1: VectorId = VectorId::new(7);
-const TEST_VECTOR2: VectorId = VectorId::new(4);
+const TEST_VECTOR1: u32 = 7;
+const TEST_VECTOR2: u32 = 4;
+
+/// Get a driver for the PL190
+fn vic() -> Pl190 {
+    // Safety: we only write whole registers, and the handlers only touch the
+    // lines they own
+    unsafe { versatileab::vic() }
+}
 
 /// The entry-point to the Rust application.
 ///
@@ -36,22 +41,23 @@ fn my_main() -> ! {
     }
 
     println!("Setting up interrupts...");
-    PL190.set_handler(TEST_INTERRUPT1, TEST_VECTOR1, Some(soft_handler1));
-    PL190.set_handler(TEST_INTERRUPT2, TEST_VECTOR2, Some(soft_handler2));
-    PL190.set_default_handler(catchall_handler);
-    PL190.enable_interrupt(TEST_INTERRUPT1);
-    PL190.enable_interrupt(TEST_INTERRUPT2);
-    PL190.enable_interrupt(TEST_INTERRUPT3);
+    let mut vic = vic();
+    vic.set_vector(TEST_VECTOR1, TEST_INTERRUPT1, soft_handler1);
+    vic.set_vector(TEST_VECTOR2, TEST_INTERRUPT2, soft_handler2);
+    vic.set_default_vector(catchall_handler);
+    vic.set_enabled(TEST_INTERRUPT1, true);
+    vic.set_enabled(TEST_INTERRUPT2, true);
+    vic.set_enabled(TEST_INTERRUPT3, true);
 
     println!("Firing interrupt...");
-    PL190.pend_sw_interrupt(TEST_INTERRUPT1);
+    vic.set_software_interrupt(TEST_INTERRUPT1);
 
     // wait for it
     for _ in 0..1_000 {
         if MARKER.load(SeqCst) == 1 {
             println!("Got interrupted :)");
             // this one has no handler
-            PL190.pend_sw_interrupt(TEST_INTERRUPT3);
+            vic.set_software_interrupt(TEST_INTERRUPT3);
             break;
         }
     }
@@ -69,28 +75,29 @@ fn my_main() -> ! {
 
 /// Our low-prio handler re-enables interrupts and triggers a second,
 /// higher-priority, handler.
-fn soft_handler1() {
+extern "C" fn soft_handler1() {
     unsafe {
         aarch32_cpu::interrupt::enable();
     }
     println!("> soft_handler1()");
-    PL190.clear_sw_interrupt(TEST_INTERRUPT1);
-    PL190.pend_sw_interrupt(TEST_INTERRUPT2);
+    let mut vic = vic();
+    vic.clear_software_interrupt(TEST_INTERRUPT1);
+    vic.set_software_interrupt(TEST_INTERRUPT2);
     println!("< soft_handler1()");
 }
 
 /// Our high-prio handler sets a global flag
-fn soft_handler2() {
+extern "C" fn soft_handler2() {
     println!("> soft_handler2()");
-    PL190.clear_sw_interrupt(TEST_INTERRUPT2);
+    vic().clear_software_interrupt(TEST_INTERRUPT2);
     MARKER.store(1, SeqCst);
     println!("< soft_handler2()");
 }
 
 /// Our catch-all handler sets a global flag
-fn catchall_handler() {
+extern "C" fn catchall_handler() {
     println!("catchall_handler() fired");
-    PL190.clear_sw_interrupt(TEST_INTERRUPT3);
+    vic().clear_software_interrupt(TEST_INTERRUPT3);
     MARKER.store(2, SeqCst);
 }
 
@@ -101,6 +108,6 @@ fn catchall_handler() {
 #[unsafe(link_section = ".text.some_other_section")]
 unsafe fn interrupt_handler() {
     println!("> interrupt_handler()");
-    PL190.irq_process();
+    vic().dispatch();
     println!("< interrupt_handler()");
 }
@@ -55,6 +55,19 @@ pub fn init() {
     });
 }
 
+/// Get a driver for our PL190
+///
+/// # Safety
+///
+/// Every driver this gives out talks to the same PL190, so don't let their
+/// uses clash. Each of our examples only ever writes whole registers that
+/// don't overlap, or reads and then writes VICVECTADDR in the IRQ handler.
+pub unsafe fn vic() -> aarch32_cpu::vic::Pl190 {
+    // Safety: this is the address of QEMU's PL190, and the caller promises
+    // their uses don't clash
+    unsafe { aarch32_cpu::vic::Pl190::new(PL190_BASE_ADDRESS as *mut u32) }
+}
+
 /// Exit from QEMU with code
 pub fn exit(code: i32) -> ! {
     if !IN_EXIT.swap(true, portable_atomic::Ordering::Relaxed) {
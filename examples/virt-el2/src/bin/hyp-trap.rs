@@ -0,0 +1,114 @@
+//! Hyp mode example for Arm Cortex-A15 on QEMU's virt machine
+//!
+//! We make an HVC from Hyp mode, and then start a tiny guest in SVC mode with
+//! HCR.TWI set. The guest's WFI traps to our `_hyp_trap_handler`, and the guest
+//! then makes an HVC to ask us to stop.
+
+#![no_std]
+#![no_main]
+
+use aarch32_cpu::register::{
+    Cpsr, Hcr, Hsr,
+    banked::{ElrHyp, SpSvc},
+    cpsr::ProcessorMode,
+    hyp::hsr::{ExceptionClass, InstructionLength},
+};
+use aarch32_rt::{entry, exception, stacks::Stack};
+use semihosting::println;
+
+/// The HVC which adds one to r0
+const HVC_INCREMENT: u16 = 0x0001;
+
+/// The HVC the guest makes when it's finished
+const HVC_EXIT: u16 = 0x00FF;
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `aarch32-rt`.
+#[entry]
+fn main() -> ! {
+    println!("Running in {:?} mode", Cpsr::read().mode().unwrap());
+
+    println!(
+        "Calling HVC {:#06x} with r0 = {:#010x}",
+        HVC_INCREMENT, 0x41
+    );
+    let value = aarch32_cpu::hvc1!(HVC_INCREMENT, 0x41);
+    println!("HVC returned {:#010x}", value);
+
+    println!("Trapping WFI, and starting the guest");
+    Hcr::modify(|hcr| hcr.set_twi(true));
+    start_guest(guest);
+}
+
+/// Drop from Hyp mode to `entry`, in SVC mode
+fn start_guest(entry: extern "C" fn() -> !) -> ! {
+    let entry = entry as *const () as usize;
+    let spsr = Cpsr::new_with_raw_value(0)
+        .with_mode(ProcessorMode::Svc)
+        .with_a(true)
+        .with_i(true)
+        .with_f(true)
+        .with_t(entry & 1 != 0);
+    // Safety: the guest only uses the SVC stack, which the start-up code has
+    // reserved for us but otherwise left alone
+    unsafe {
+        SpSvc::write(Stack::Svc.top(0).unwrap() as u32);
+        ElrHyp::write((entry & !1) as u32);
+        core::arch::asm!(
+            ".arch_extension virt",
+            "msr spsr_fsxc, {spsr}",
+            "eret",
+            spsr = in(reg) spsr.raw_value(),
+            options(noreturn)
+        );
+    }
+}
+
+/// Our guest, which runs in SVC mode
+extern "C" fn guest() -> ! {
+    println!("Guest running in {:?} mode", Cpsr::read().mode().unwrap());
+    println!("Guest executing WFI");
+    aarch32_cpu::asm::wfi();
+    println!("Guest back from WFI");
+    aarch32_cpu::hvc1!(HVC_EXIT, 0);
+    panic!("Guest resumed after asking to exit");
+}
+
+/// Our HVC handler, for calls from Hyp mode and from the guest
+#[exception(HypervisorCall)]
+fn hvc_handler(hsr: u32, frame: &aarch32_rt::Frame) -> u32 {
+    let hsr = Hsr::new_with_raw_value(hsr);
+    println!(
+        "In hvc_handler in {:?} mode, imm16 = {:#06x}, r0 = {:#010x}",
+        Cpsr::read().mode().unwrap(),
+        hsr.iss().value(),
+        frame.r0
+    );
+    match hsr.iss().value() as u16 {
+        HVC_INCREMENT => frame.r0 + 1,
+        HVC_EXIT => {
+            println!("Hyp trap test completed OK");
+            virt_el2::exit(0);
+        }
+        other => panic!("Unexpected HVC {:#06x}", other),
+    }
+}
+
+/// Our Hyp Trap handler, which skips over the guest's WFI
+#[exception(HypTrap)]
+fn hyp_trap_handler(hsr: u32, frame: &mut aarch32_rt::TrapFrame) {
+    let hsr = Hsr::new_with_raw_value(hsr);
+    if hsr.ec() != Ok(ExceptionClass::TrappedWfiWfe) {
+        panic!("Unexpected trap {:?}", hsr);
+    }
+    println!(
+        "In hyp_trap_handler in {:?} mode, trapped WFI from {:?} mode",
+        Cpsr::read().mode().unwrap(),
+        Cpsr::new_with_raw_value(frame.spsr).mode().unwrap()
+    );
+    frame.elr += match hsr.il() {
+        InstructionLength::SixteenBit => 2,
+        InstructionLength::ThirtyTwoBit => 4,
+    };
+}
@@ -0,0 +1,174 @@
+//! Secure Monitor example for Arm Cortex-A15 on QEMU's virt machine
+//!
+//! The Secure world makes an SMC to ask our `_smc_handler` to start the
+//! Non-secure world, which then makes an SMC to hand back to the Secure world.
+//! Each world writes its own value to TPIDRPRW, which the Security Extensions
+//! bank, and on hard-float targets to FPSCR, which the monitor switches.
+
+#![no_std]
+#![no_main]
+
+use aarch32_cpu::register::{Cpsr, Nsacr, Scr, Tpidrprw, cpsr::ProcessorMode};
+use aarch32_cpu::smccc::smc_call;
+use aarch32_rt::monitor::{World, Worlds, with_non_secure_banked};
+use aarch32_rt::{entry, exception};
+use semihosting::println;
+
+/// The SMC which starts the Non-secure world at the address in `r1`
+const SMC_START_NON_SECURE: u32 = 0x8200_0000;
+
+/// The SMC which hands the value in `r1` back to the Secure world
+const SMC_RETURN_TO_SECURE: u32 = 0x8200_0001;
+
+/// What the Secure world puts in TPIDRPRW
+const SECURE_TPIDRPRW: u32 = 0x5EC0_5EC0;
+
+/// What the Non-secure world puts in TPIDRPRW
+const NON_SECURE_TPIDRPRW: u32 = 0x0115_0115;
+
+/// What the Secure world puts in FPSCR - round towards zero
+#[cfg(target_abi = "eabihf")]
+const SECURE_FPSCR: u32 = 0x00C0_0000;
+
+/// What the Non-secure world puts in FPSCR - round towards plus infinity
+#[cfg(target_abi = "eabihf")]
+const NON_SECURE_FPSCR: u32 = 0x0040_0000;
+
+/// The stack for the Non-secure world
+static mut NON_SECURE_STACK: [u64; 1024] = [0; 1024];
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `aarch32-rt`, in the Secure world.
+#[entry]
+fn main() -> ! {
+    println!(
+        "Secure world running in {:?} mode, SCR.NS = {}",
+        Cpsr::read().mode().unwrap(),
+        Scr::read().ns()
+    );
+    // Safety: nothing else in this world uses TPIDRPRW
+    unsafe {
+        Tpidrprw::write(Tpidrprw(SECURE_TPIDRPRW));
+    }
+    #[cfg(target_abi = "eabihf")]
+    {
+        write_fpscr(SECURE_FPSCR);
+        println!("Secure FPSCR = {:#010x}", read_fpscr());
+    }
+    // Safety: the monitor gives the Non-secure world its own FPU registers
+    unsafe {
+        Nsacr::write(Nsacr::read().with_cp10(true).with_cp11(true));
+    }
+
+    println!("Starting the Non-secure world");
+    let entry = non_secure_main as *const () as usize as u32;
+    // Safety: our `_smc_handler` knows this call
+    let [value, ..] = unsafe { smc_call(SMC_START_NON_SECURE, [entry, 0, 0, 0, 0]) };
+    println!("Back in the Secure world, with {:#010x}", value);
+
+    let tpidrprw = Tpidrprw::read().0;
+    println!("Secure TPIDRPRW = {:#010x}", tpidrprw);
+    #[cfg(target_abi = "eabihf")]
+    let fpu_ok = {
+        let fpscr = read_fpscr();
+        println!("Secure FPSCR = {:#010x}", fpscr);
+        fpscr == SECURE_FPSCR
+    };
+    #[cfg(not(target_abi = "eabihf"))]
+    let fpu_ok = true;
+
+    if tpidrprw == SECURE_TPIDRPRW && value == NON_SECURE_TPIDRPRW && fpu_ok {
+        println!("Secure monitor example completed OK");
+        virt_secure::exit(0);
+    }
+    println!("The worlds were not kept apart!?");
+    virt_secure::exit(1);
+}
+
+/// Our Non-secure world, which runs in SVC mode
+extern "C" fn non_secure_main() -> ! {
+    println!(
+        "Non-secure world running in {:?} mode",
+        Cpsr::read().mode().unwrap()
+    );
+    #[cfg(target_abi = "eabihf")]
+    {
+        println!("Non-secure FPSCR = {:#010x}", read_fpscr());
+        write_fpscr(NON_SECURE_FPSCR);
+    }
+    // Safety: nothing else in this world uses TPIDRPRW
+    unsafe {
+        Tpidrprw::write(Tpidrprw(NON_SECURE_TPIDRPRW));
+    }
+    println!("Non-secure TPIDRPRW = {:#010x}", Tpidrprw::read().0);
+
+    println!("Returning to the Secure world");
+    // Safety: our `_smc_handler` knows this call
+    unsafe {
+        smc_call(SMC_RETURN_TO_SECURE, [Tpidrprw::read().0, 0, 0, 0, 0]);
+    }
+    panic!("Non-secure world resumed after handing back");
+}
+
+/// Our SMC handler, which switches between the two worlds
+#[exception(SecureMonitorCall)]
+fn smc_handler(caller: World, worlds: &mut Worlds) -> World {
+    let function_id = worlds.get_mut(caller).r[0];
+    println!(
+        "In smc_handler in {:?} mode, call {:#010x} from the {:?} world",
+        Cpsr::read().mode().unwrap(),
+        function_id,
+        caller
+    );
+    match (caller, function_id) {
+        (World::Secure, SMC_START_NON_SECURE) => {
+            let entry = worlds.secure.r[1];
+            let non_secure = &mut worlds.non_secure;
+            non_secure.pc = entry & !1;
+            non_secure.cpsr = Cpsr::new_with_raw_value(0)
+                .with_mode(ProcessorMode::Svc)
+                .with_a(true)
+                .with_i(true)
+                .with_f(true)
+                .with_t(entry & 1 != 0)
+                .raw_value();
+            non_secure.svc.sp = (&raw mut NON_SECURE_STACK).wrapping_add(1) as usize as u32;
+            // Set FPEXC.EN, so the Non-secure world can use the FPU
+            non_secure.fpu.fpexc = 1 << 30;
+            World::NonSecure
+        }
+        (World::NonSecure, SMC_RETURN_TO_SECURE) => {
+            // Safety: we are in Monitor mode
+            let non_secure = unsafe { with_non_secure_banked(Tpidrprw::read) };
+            println!(
+                "Monitor sees Secure TPIDRPRW = {:#010x}, Non-secure TPIDRPRW = {:#010x}",
+                Tpidrprw::read().0,
+                non_secure.0
+            );
+            worlds.secure.r[0] = worlds.non_secure.r[1];
+            World::Secure
+        }
+        _ => panic!("Unexpected SMC {:#010x}", function_id),
+    }
+}
+
+/// Read FPSCR (*Floating-Point Status and Control Register*)
+#[cfg(target_abi = "eabihf")]
+fn read_fpscr() -> u32 {
+    let value: u32;
+    // Safety: reading FPSCR has no side-effects
+    unsafe {
+        core::arch::asm!("vmrs {}, fpscr", out(reg) value, options(nomem, nostack, preserves_flags));
+    }
+    value
+}
+
+/// Write FPSCR (*Floating-Point Status and Control Register*)
+#[cfg(target_abi = "eabihf")]
+fn write_fpscr(value: u32) {
+    // Safety: we only change the rounding mode, which nothing relies on
+    unsafe {
+        core::arch::asm!("vmsr fpscr, {}", in(reg) value, options(nomem, nostack, preserves_flags));
+    }
+}
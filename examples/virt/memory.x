@@ -0,0 +1,25 @@
+/*
+Memory configuration for the QEMU virt machine.
+
+See https://github.com/qemu/qemu/blob/master/hw/arm/virt.c
+*/
+
+MEMORY {
+    SDRAM : ORIGIN = 0x40000000, LENGTH = 128M
+}
+
+REGION_ALIAS("VECTORS", SDRAM);
+REGION_ALIAS("CODE", SDRAM);
+REGION_ALIAS("DATA", SDRAM);
+REGION_ALIAS("STACKS", SDRAM);
+
+PROVIDE(_hyp_stack_size = 16K);
+PROVIDE(_und_stack_size = 16K);
+PROVIDE(_svc_stack_size = 16K);
+PROVIDE(_abt_stack_size = 16K);
+PROVIDE(_irq_stack_size = 1K);
+PROVIDE(_fiq_stack_size = 64);
+PROVIDE(_sys_stack_size = 16K);
+
+/* The psci example starts the second core */
+PROVIDE(_num_cores = 2);
@@ -0,0 +1,88 @@
+//! GICv2 example for Arm Cortex-A15 on QEMU's virt machine
+//!
+//! We send ourselves a Software Generated Interrupt, and the handler for that
+//! one sends another.
+
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use aarch32_cpu::gicv2::{Gicv2, INTID_MASK, SgiTarget};
+use aarch32_rt::{entry, irq};
+use arbitrary_int::u4;
+use critical_section::Mutex;
+use portable_atomic::{AtomicU32, Ordering};
+use semihosting::println;
+
+static GIC: Mutex<RefCell<Option<Gicv2>>> = Mutex::new(RefCell::new(None));
+
+static HANDLED: AtomicU32 = AtomicU32::new(0);
+
+const SGI_FIRST: u4 = u4::new(3);
+const SGI_SECOND: u4 = u4::new(4);
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `aarch32-rt`.
+#[entry]
+fn main() -> ! {
+    virt::init();
+    // Safety: this is the only GIC driver, and the IRQ handler only uses it
+    // once we've handed it over
+    let mut gic = unsafe { virt::gic() };
+
+    println!("Configure SGIs...");
+    for sgi in [SGI_FIRST, SGI_SECOND] {
+        gic.set_priority(sgi.value() as u32, 0x80);
+        gic.set_enabled(sgi.value() as u32, true);
+    }
+    gic.set_priority_mask(0xFF);
+    gic.enable_distributor(true);
+    gic.enable_cpu_interface(true);
+    critical_section::with(|cs| GIC.borrow_ref_mut(cs).replace(gic));
+
+    println!("Enabling interrupts...");
+    // Safety: Not in a critical-section
+    unsafe {
+        aarch32_cpu::interrupt::enable();
+    }
+
+    println!("Send SGI {}", SGI_FIRST);
+    critical_section::with(|cs| {
+        let mut gic = GIC.borrow_ref_mut(cs);
+        gic.as_mut()
+            .unwrap()
+            .send_sgi(SGI_FIRST, SgiTarget::Current);
+    });
+
+    for _ in 0..1_000_000 {
+        if HANDLED.load(Ordering::Relaxed) == 2 {
+            println!("IRQ test completed OK");
+            virt::exit(0);
+        }
+    }
+
+    println!("Only handled {} SGIs!?", HANDLED.load(Ordering::Relaxed));
+    virt::exit(1);
+}
+
+#[irq]
+fn irq_handler() {
+    println!("> IRQ");
+    critical_section::with(|cs| {
+        let mut gic = GIC.borrow_ref_mut(cs);
+        let gic = gic.as_mut().unwrap();
+        while let Some(id) = gic.acknowledge() {
+            let sgi = id & INTID_MASK;
+            println!("- IRQ handling SGI {}", sgi);
+            if sgi == SGI_FIRST.value() as u32 {
+                println!("- IRQ sending SGI {}", SGI_SECOND);
+                gic.send_sgi(SGI_SECOND, SgiTarget::Current);
+            }
+            HANDLED.fetch_add(1, Ordering::Relaxed);
+            gic.end_of_interrupt(id);
+        }
+    });
+    println!("< IRQ");
+}
@@ -0,0 +1,96 @@
+//! PSCI example for Arm Cortex-A15 on QEMU's virt machine
+//!
+//! QEMU emulates the PSCI firmware. We turn on the second core, which turns
+//! itself off again, and then we turn off the whole system.
+
+#![no_std]
+#![no_main]
+
+use aarch32_cpu::psci::{AffinityState, Psci};
+use aarch32_cpu::register::Cpsr;
+use aarch32_rt::entry;
+use portable_atomic::{AtomicBool, Ordering};
+use semihosting::println;
+
+/// The MPIDR affinity of the second core
+const CORE1: u32 = 1;
+
+/// What we pass to the second core in `r0`
+const CONTEXT_ID: u32 = 0xC0DE;
+
+/// Set by the second core once it has said hello
+static CORE1_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// The entry-point to the Rust application.
+///
+/// It is called by the start-up code in `aarch32-rt`.
+#[entry]
+fn main() -> ! {
+    let psci = Psci::detect().expect("no PSCI");
+    println!("Found PSCI, using the {:?} conduit", psci.conduit());
+    println!("Core 1 is {:?}", psci.affinity_info(CORE1, 0).unwrap());
+
+    println!("Starting core 1");
+    // Safety: core1_start sets up its own stacks
+    unsafe {
+        psci.cpu_on(CORE1, core1_start as *const () as usize, CONTEXT_ID)
+            .unwrap();
+    }
+    while !CORE1_RUNNING.load(Ordering::Acquire) {
+        core::hint::spin_loop();
+    }
+
+    while psci.affinity_info(CORE1, 0).unwrap() != AffinityState::Off {
+        core::hint::spin_loop();
+    }
+    println!("Core 1 is {:?}", AffinityState::Off);
+
+    println!("Turning the system off");
+    let error = psci.system_off();
+    println!("SYSTEM_OFF failed: {:?}", error);
+    virt::exit(1);
+}
+
+/// Where the second core starts, in SVC mode with the MMU off
+///
+/// We give it the second set of stacks, and call `core1_main` in SYS mode.
+#[unsafe(naked)]
+#[instruction_set(arm::a32)]
+extern "C" fn core1_start() -> ! {
+    core::arch::naked_asm!(
+        r#"
+        // Work around https://github.com/rust-lang/rust/issues/127269
+        .fpu vfp3
+        // keep the context ID
+        mov     r4, r0
+        // set up our stacks - also switches to SYS mode
+        mov     r0, #1
+        bl      _stack_setup_preallocated
+        // Allow VFP coprocessor access
+        mrc     p15, 0, r0, c1, c0, 2
+        orr     r0, r0, #0xF00000
+        mcr     p15, 0, r0, c1, c0, 2
+        isb
+        // Enable VFP
+        mov     r0, #0x40000000
+        vmsr    fpexc, r0
+        mov     r0, r4
+        bl      {main}
+        "#,
+        main = sym core1_main,
+    )
+}
+
+/// The Rust code for the second core
+extern "C" fn core1_main(context_id: u32) -> ! {
+    let psci = Psci::detect().expect("no PSCI on core 1");
+    println!(
+        "Core 1 running in {:?} mode, with context {:#06x}",
+        Cpsr::read().mode().unwrap(),
+        context_id
+    );
+    println!("Core 1 turning itself off");
+    CORE1_RUNNING.store(true, Ordering::Release);
+    let error = psci.cpu_off();
+    panic!("CPU_OFF failed: {:?}", error);
+}
@@ -0,0 +1,465 @@
+//! The register catalogue, and how we build it from the source code
+
+use serde::Serialize;
+
+use crate::cfg::{Cfg, Target};
+use crate::source::{self, FieldType, Item, Source};
+use crate::Error;
+
+/// Every system register defined by `aarch32-cpu`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Catalogue {
+    /// The architectures we looked at, like `v7-r`
+    pub architectures: Vec<String>,
+    /// The registers, sorted by name
+    pub registers: Vec<Register>,
+}
+
+/// A system register
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Register {
+    /// The Rust type name, like `Sctlr`
+    pub name: String,
+    /// The Rust module the type is defined in, like `register::sctlr`
+    pub module: String,
+    /// The doc comment on the Rust type
+    pub description: String,
+    /// Which architectures this register definition is available on
+    pub architectures: Vec<String>,
+    /// How the register is encoded in MRC/MCR or MRRC/MCRR instructions
+    pub encoding: Encoding,
+    /// 32 or 64
+    pub width: u32,
+    /// `r`, `w` or `rw`
+    pub access: String,
+    /// The Rust type used for the register's value, if the register uses
+    /// another register's bitfield type (e.g. `Prbar3` uses `Prbar`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    /// The fields within the register, if it has any
+    pub fields: Vec<Field>,
+}
+
+/// How a system register is encoded
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum Encoding {
+    /// A 32-bit register, accessed with MRC/MCR
+    Reg32 {
+        /// Co-processor number
+        cp: u32,
+        /// CRn argument
+        crn: u32,
+        /// Op1 argument
+        op1: u32,
+        /// CRm argument
+        crm: u32,
+        /// Op2 argument
+        op2: u32,
+    },
+    /// A 64-bit register, accessed with MRRC/MCRR
+    Reg64 {
+        /// Co-processor number
+        cp: u32,
+        /// Op1 argument
+        op1: u32,
+        /// CRm argument
+        crm: u32,
+    },
+}
+
+/// A field within a system register
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Field {
+    /// The Rust field name
+    pub name: String,
+    /// The doc comment on the field
+    pub description: String,
+    /// The bits that hold the field, least significant first
+    ///
+    /// For an array, this is the first element.
+    pub bits: Vec<BitRange>,
+    /// `r`, `w` or `rw`
+    pub access: String,
+    /// `bool`, `uint` or `enum`
+    #[serde(rename = "type")]
+    pub ty: String,
+    /// Set if this field is an array of equally spaced elements
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub array: Option<Array>,
+    /// The possible values, if this field is an enum
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub enumeration: Option<Enum>,
+}
+
+/// A contiguous range of bits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BitRange {
+    /// The least significant bit
+    pub lsb: u32,
+    /// The most significant bit
+    pub msb: u32,
+}
+
+/// Describes an array field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Array {
+    /// The number of elements
+    pub count: u32,
+    /// How many bits apart each element is
+    pub stride: u32,
+}
+
+/// The possible values for an enum field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Enum {
+    /// The Rust type name
+    pub name: String,
+    /// Does every possible bit pattern have a value?
+    pub exhaustive: bool,
+    /// The values
+    pub values: Vec<EnumValue>,
+}
+
+/// A named value for an enum field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EnumValue {
+    /// The Rust variant name
+    pub name: String,
+    /// The doc comment on the variant
+    pub description: String,
+    /// The value in the field
+    pub value: u64,
+}
+
+impl Catalogue {
+    /// Build the catalogue from the `aarch32-cpu` source code
+    pub fn build(source: &Source) -> Result<Catalogue, Error> {
+        let mut registers: Vec<Register> = Vec::new();
+        for target in &Target::ALL {
+            for sysreg in visible(&source.sysregs, target)? {
+                let register = describe(source, sysreg, target)?;
+                // Merge definitions that are identical across architectures
+                match registers.iter_mut().find(|r| {
+                    Register {
+                        architectures: r.architectures.clone(),
+                        ..register.clone()
+                    } == **r
+                }) {
+                    Some(existing) => existing.architectures.push(target.to_string()),
+                    None => registers.push(register),
+                }
+            }
+        }
+        registers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Catalogue {
+            architectures: Target::ALL.iter().map(|t| t.to_string()).collect(),
+            registers,
+        })
+    }
+}
+
+/// Describe one register, as it is on one particular architecture
+fn describe(
+    source: &Source,
+    sysreg: &Item<source::SysRegImpl>,
+    target: &Target,
+) -> Result<Register, Error> {
+    let name = &sysreg.value.name;
+    let same_type =
+        |module: &str, ty: &str| module == sysreg.module && ty == sysreg.value.name.as_str();
+
+    let mut readable = false;
+    let mut writable = false;
+    for access in visible(&source.access, target)? {
+        if same_type(&access.module, &access.value.name) {
+            if access.value.is_read {
+                readable = true;
+            } else {
+                writable = true;
+            }
+        }
+    }
+    let access = match (readable, writable) {
+        (true, true) => "rw",
+        (true, false) => "r",
+        (false, true) => "w",
+        (false, false) => {
+            return Err(Error::new(format!(
+                "{name} is neither readable nor writable"
+            )))
+        }
+    };
+
+    // Either the register is a bitfield itself, or it is read and written
+    // using another register's bitfield type
+    let mut layout = None;
+    let mut bitfield = visible(&source.bitfields, target)?
+        .into_iter()
+        .find(|b| same_type(&b.module, &b.value.name));
+    if bitfield.is_none() {
+        if let Some(hint) = visible(&source.layouts, target)?
+            .into_iter()
+            .find(|h| same_type(&h.module, &h.value.name))
+        {
+            let layout_name = &hint.value.layout;
+            // Plain types like `Dbgbvr(pub u32)` have no fields to report
+            let is_plain = source
+                .structs
+                .iter()
+                .any(|(_, name, _)| name == layout_name)
+                && !source
+                    .bitfields
+                    .iter()
+                    .any(|b| b.value.name == *layout_name);
+            if !is_plain {
+                bitfield = Some(resolve(
+                    source,
+                    &source.bitfields,
+                    &sysreg.module,
+                    layout_name,
+                    target,
+                    |b| &b.name,
+                )?);
+            }
+            layout = Some(layout_name.clone());
+        }
+    }
+
+    let mut fields = Vec::new();
+    if let Some(bitfield) = bitfield {
+        for field in &bitfield.value.fields {
+            let (ty, enumeration) = match &field.ty {
+                FieldType::Bool => ("bool", None),
+                FieldType::Uint => ("uint", None),
+                FieldType::Enum { name } => {
+                    let bitenum = resolve(
+                        source,
+                        &source.bitenums,
+                        &bitfield.module,
+                        name,
+                        target,
+                        |e| &e.name,
+                    )?;
+                    let values = bitenum
+                        .value
+                        .variants
+                        .iter()
+                        .map(|(name, value, doc)| EnumValue {
+                            name: name.clone(),
+                            description: doc.clone(),
+                            value: *value,
+                        })
+                        .collect();
+                    let enumeration = Enum {
+                        name: name.clone(),
+                        exhaustive: bitenum.value.exhaustive,
+                        values,
+                    };
+                    ("enum", Some(enumeration))
+                }
+            };
+            fields.push(Field {
+                name: field.name.clone(),
+                description: field.doc.clone(),
+                bits: field
+                    .bits
+                    .iter()
+                    .map(|&(lsb, msb)| BitRange { lsb, msb })
+                    .collect(),
+                access: field.access.clone(),
+                ty: ty.to_owned(),
+                array: field.array.map(|(count, stride)| Array { count, stride }),
+                enumeration,
+            });
+        }
+    }
+
+    let [cp, crn, op1, crm, op2] = sysreg.value.encoding;
+    let (encoding, width) = if sysreg.value.is_64 {
+        (Encoding::Reg64 { cp, op1, crm }, 64)
+    } else {
+        (
+            Encoding::Reg32 {
+                cp,
+                crn,
+                op1,
+                crm,
+                op2,
+            },
+            32,
+        )
+    };
+
+    Ok(Register {
+        name: name.clone(),
+        module: sysreg.module.clone(),
+        description: sysreg.value.doc.clone(),
+        architectures: vec![target.to_string()],
+        encoding,
+        width,
+        access: access.to_owned(),
+        layout,
+        fields,
+    })
+}
+
+/// Find the items whose `cfg`s are all true for this target
+fn visible<'a, T>(items: &'a [Item<T>], target: &Target) -> Result<Vec<&'a Item<T>>, Error> {
+    let mut result = Vec::new();
+    for item in items {
+        if all_true(&item.cfgs, target)? {
+            result.push(item);
+        }
+    }
+    Ok(result)
+}
+
+fn all_true(cfgs: &[Cfg], target: &Target) -> Result<bool, Error> {
+    for cfg in cfgs {
+        if !cfg.eval(target)? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Work out which type a name in some module refers to
+///
+/// We look in the same module first, then check where the module imported
+/// the name from, and finally accept any unique type with that name.
+fn resolve<'a, T>(
+    source: &Source,
+    items: &'a [Item<T>],
+    module: &str,
+    name: &str,
+    target: &Target,
+    name_of: impl Fn(&T) -> &String,
+) -> Result<&'a Item<T>, Error> {
+    let candidates: Vec<&Item<T>> = visible(items, target)?
+        .into_iter()
+        .filter(|item| name_of(&item.value) == name)
+        .collect();
+    if let Some(item) = candidates.iter().find(|item| item.module == module) {
+        return Ok(item);
+    }
+    let imported: Vec<&Item<T>> = source
+        .uses
+        .iter()
+        .filter(|(use_module, use_name, _)| use_module == module && use_name == name)
+        .flat_map(|(_, _, path)| {
+            candidates.iter().copied().filter(move |item| {
+                let item_path = format!("{}::{name}", item.module);
+                item_path.ends_with(path.trim_start_matches("crate::"))
+                    || path.starts_with("super::")
+                        && item_path.ends_with(path.trim_start_matches("super::"))
+            })
+        })
+        .collect();
+    match (imported.as_slice(), candidates.as_slice()) {
+        ([item], _) | ([], [item]) => Ok(item),
+        ([], []) => Err(Error::new(format!(
+            "cannot find type {name} used in {module} on {target}"
+        ))),
+        _ => Err(Error::new(format!(
+            "type {name} used in {module} is ambiguous on {target}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn catalogue() -> Catalogue {
+        let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../aarch32-cpu");
+        Catalogue::build(&Source::load(&crate_dir).unwrap()).unwrap()
+    }
+
+    fn find<'a>(catalogue: &'a Catalogue, name: &str) -> Vec<&'a Register> {
+        catalogue
+            .registers
+            .iter()
+            .filter(|r| r.name == name)
+            .collect()
+    }
+
+    #[test]
+    fn every_register() {
+        // Every `impl SysReg` in the source code must turn up, so the
+        // catalogue can't fall out of sync with the Rust definitions
+        let crate_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../aarch32-cpu");
+        let source = Source::load(&crate_dir).unwrap();
+        let catalogue = Catalogue::build(&source).unwrap();
+        for sysreg in &source.sysregs {
+            assert!(
+                !find(&catalogue, &sysreg.value.name).is_empty(),
+                "{} is missing",
+                sysreg.value.name
+            );
+        }
+    }
+
+    #[test]
+    fn sctlr() {
+        let catalogue = catalogue();
+        let sctlr = find(&catalogue, "Sctlr");
+        assert_eq!(sctlr.len(), 1);
+        let sctlr = sctlr[0];
+        assert_eq!(
+            sctlr.encoding,
+            Encoding::Reg32 {
+                cp: 15,
+                crn: 1,
+                op1: 0,
+                crm: 0,
+                op2: 0
+            }
+        );
+        assert_eq!(sctlr.access, "rw");
+        assert_eq!(sctlr.architectures, catalogue.architectures);
+        let m = sctlr.fields.iter().find(|f| f.name == "m").unwrap();
+        assert_eq!(m.bits, [BitRange { lsb: 0, msb: 0 }]);
+        assert_eq!(m.ty, "bool");
+    }
+
+    #[test]
+    fn shared_layouts_and_enums() {
+        let catalogue = catalogue();
+        let prbar3 = find(&catalogue, "Prbar3")[0];
+        assert_eq!(prbar3.architectures, ["v8-r"]);
+        assert_eq!(prbar3.layout.as_deref(), Some("Prbar"));
+        let ap = prbar3
+            .fields
+            .iter()
+            .find(|f| f.name == "access_perms")
+            .unwrap();
+        let ap = ap.enumeration.as_ref().unwrap();
+        assert!(ap.exhaustive);
+        assert_eq!(ap.values.len(), 4);
+
+        let clidr = find(&catalogue, "Clidr")[0];
+        let ctype = clidr.fields.iter().find(|f| f.name == "ctype").unwrap();
+        assert_eq!(
+            ctype.array,
+            Some(Array {
+                count: 7,
+                stride: 3
+            })
+        );
+        assert!(!ctype.enumeration.as_ref().unwrap().exhaustive);
+    }
+
+    #[test]
+    fn per_architecture_layouts() {
+        let catalogue = catalogue();
+        // IFSR has a different layout on each architecture from Armv5TE
+        let ifsr = find(&catalogue, "Ifsr");
+        let archs: Vec<&str> = ifsr
+            .iter()
+            .flat_map(|r| r.architectures.iter().map(|a| a.as_str()))
+            .collect();
+        assert_eq!(archs, ["v5te", "v6", "v7-r", "v7-a", "v8-r"]);
+        assert!(ifsr.len() > 1);
+    }
+}
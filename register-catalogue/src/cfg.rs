@@ -0,0 +1,224 @@
+//! Evaluating `#[cfg(...)]` predicates for a particular Arm architecture
+
+use arm_targets::Arch;
+use syn::punctuated::Punctuated;
+
+use crate::Error;
+
+/// A parsed `cfg` predicate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Cfg {
+    /// `any(...)`
+    Any(Vec<Cfg>),
+    /// `all(...)`
+    All(Vec<Cfg>),
+    /// `not(...)`
+    Not(Box<Cfg>),
+    /// A bare flag, like `test` or `armv7_or_higher`
+    Flag(String),
+    /// A key-value pair, like `arm_architecture = "v7-r"`
+    KeyValue(String, String),
+}
+
+impl Cfg {
+    /// Parse the contents of a `#[cfg(...)]` attribute
+    pub fn parse(meta: &syn::Meta) -> Result<Cfg, Error> {
+        match meta {
+            syn::Meta::Path(path) => Ok(Cfg::Flag(path_to_string(path)?)),
+            syn::Meta::NameValue(nv) => {
+                let key = path_to_string(&nv.path)?;
+                match &nv.value {
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(s),
+                        ..
+                    }) => Ok(Cfg::KeyValue(key, s.value())),
+                    _ => Err(Error::new(format!("cfg value for {key} is not a string"))),
+                }
+            }
+            syn::Meta::List(list) => {
+                let name = path_to_string(&list.path)?;
+                let args = list
+                    .parse_args_with(Punctuated::<syn::Meta, syn::Token![,]>::parse_terminated)
+                    .map_err(|e| Error::new(format!("bad cfg({name}(...)): {e}")))?;
+                let mut args = args
+                    .iter()
+                    .map(Cfg::parse)
+                    .collect::<Result<Vec<Cfg>, Error>>()?;
+                match name.as_str() {
+                    "any" => Ok(Cfg::Any(args)),
+                    "all" => Ok(Cfg::All(args)),
+                    "not" if args.len() == 1 => Ok(Cfg::Not(Box::new(args.remove(0)))),
+                    _ => Err(Error::new(format!("unsupported cfg predicate {name}(...)"))),
+                }
+            }
+        }
+    }
+
+    /// Collect the `#[cfg(...)]` attributes from a list of attributes
+    pub fn from_attrs(attrs: &[syn::Attribute]) -> Result<Vec<Cfg>, Error> {
+        attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("cfg"))
+            .map(|attr| {
+                let meta = attr
+                    .parse_args::<syn::Meta>()
+                    .map_err(|e| Error::new(format!("bad cfg attribute: {e}")))?;
+                Cfg::parse(&meta)
+            })
+            .collect()
+    }
+
+    /// Is this predicate true when building for the given target?
+    ///
+    /// We assume a normal build of the library, so `test`, `doc` and all
+    /// Cargo features are false.
+    pub fn eval(&self, target: &Target) -> Result<bool, Error> {
+        match self {
+            Cfg::Any(args) => {
+                for arg in args {
+                    if arg.eval(target)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Cfg::All(args) => {
+                for arg in args {
+                    if !arg.eval(target)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Cfg::Not(arg) => Ok(!arg.eval(target)?),
+            Cfg::Flag(flag) => match flag.as_str() {
+                "test" | "doc" => Ok(false),
+                other => {
+                    if let Some(version) = other.strip_suffix("_or_higher") {
+                        Ok(target.rank() >= version_rank(version)?)
+                    } else if let Some(version) = other.strip_suffix("_or_lower") {
+                        Ok(target.rank() <= version_rank(version)?)
+                    } else {
+                        Err(Error::new(format!("unsupported cfg flag {other}")))
+                    }
+                }
+            },
+            Cfg::KeyValue(key, value) => match key.as_str() {
+                "arm_architecture" => Ok(target.arch.to_string() == *value),
+                "arm_profile" => Ok(target.arch.profile().to_string() == *value),
+                "target_arch" => Ok(value == "arm"),
+                "feature" => Ok(false),
+                _ => Err(Error::new(format!("unsupported cfg key {key}"))),
+            },
+        }
+    }
+}
+
+/// An Arm architecture we can build `aarch32-cpu` for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target {
+    /// The architecture
+    pub arch: Arch,
+}
+
+impl Target {
+    /// Every A-profile, R-profile and legacy architecture `aarch32-cpu` supports
+    pub const ALL: [Target; 6] = [
+        Target { arch: Arch::Armv4T },
+        Target {
+            arch: Arch::Armv5TE,
+        },
+        Target { arch: Arch::Armv6 },
+        Target { arch: Arch::Armv7R },
+        Target { arch: Arch::Armv7A },
+        Target { arch: Arch::Armv8R },
+    ];
+
+    /// Where this target sits in the `armvX_or_higher` ordering
+    fn rank(&self) -> u32 {
+        match self.arch {
+            Arch::Armv4T => 0,
+            Arch::Armv5TE => 1,
+            Arch::Armv6 | Arch::Armv6M => 2,
+            Arch::Armv7R | Arch::Armv7A | Arch::Armv7M | Arch::Armv7EM => 3,
+            Arch::Armv8R | Arch::Armv8A | Arch::Armv8MBase | Arch::Armv8MMain => 4,
+        }
+    }
+}
+
+impl core::fmt::Display for Target {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.arch)
+    }
+}
+
+/// Convert the `X` in `armvX_or_higher` into a rank
+fn version_rank(version: &str) -> Result<u32, Error> {
+    match version {
+        "armv4t" => Ok(0),
+        "armv5te" => Ok(1),
+        "armv6" => Ok(2),
+        "armv7" => Ok(3),
+        "armv8" => Ok(4),
+        _ => Err(Error::new(format!("unsupported cfg flag {version}_or_*"))),
+    }
+}
+
+fn path_to_string(path: &syn::Path) -> Result<String, Error> {
+    path.get_ident()
+        .map(|ident| ident.to_string())
+        .ok_or_else(|| Error::new("cfg predicates must be plain identifiers"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cfg(text: &str) -> Cfg {
+        Cfg::parse(&syn::parse_str::<syn::Meta>(text).unwrap()).unwrap()
+    }
+
+    fn archs(cfg: &Cfg) -> Vec<String> {
+        Target::ALL
+            .iter()
+            .filter(|target| cfg.eval(target).unwrap())
+            .map(|target| target.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn architectures() {
+        assert_eq!(
+            archs(&cfg("any(test, doc, armv7_or_higher)")),
+            ["v7-r", "v7-a", "v8-r"]
+        );
+        assert_eq!(
+            archs(&cfg(
+                r#"any(test, doc, arm_architecture = "v7-a", arm_architecture = "v8-r")"#
+            )),
+            ["v7-a", "v8-r"]
+        );
+        assert_eq!(
+            archs(&cfg("armv5te_or_higher")),
+            ["v5te", "v6", "v7-r", "v7-a", "v8-r"]
+        );
+        assert_eq!(
+            archs(&cfg(
+                r#"all(not(target_arch = "arm"), any(test, feature = "sim"))"#
+            )),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            archs(&cfg(r#"any(arm_profile = "legacy", armv6_or_lower)"#)),
+            ["v4t", "v5te", "v6"]
+        );
+    }
+
+    #[test]
+    fn unknown_predicates() {
+        assert!(cfg("unknown_flag").eval(&Target::ALL[0]).is_err());
+        assert!(cfg(r#"target_feature = "vfp2""#)
+            .eval(&Target::ALL[0])
+            .is_err());
+    }
+}
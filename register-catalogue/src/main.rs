@@ -0,0 +1,68 @@
+//! Exports a machine-readable catalogue of the `aarch32-cpu` system registers
+//!
+//! The catalogue is built by reading the Rust source code for the `register`
+//! module, so it always matches the Rust definitions. For each register it
+//! gives the encoding, width, access, the architectures where the register is
+//! available, and the name, bit range and possible values of each field.
+//!
+//! Usage:
+//!
+//! ```console
+//! $ cargo run -- [--output <FILE>] [<PATH TO aarch32-cpu>]
+//! ```
+//!
+//! The JSON is written to standard out unless `--output` is given.
+
+mod catalogue;
+mod cfg;
+mod source;
+
+use std::path::PathBuf;
+
+use catalogue::Catalogue;
+use source::Source;
+
+/// Something went wrong reading the source code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl Error {
+    fn new(message: impl Into<String>) -> Error {
+        Error(message.into())
+    }
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut output = None;
+    let mut crate_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../aarch32-cpu");
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                output = Some(args.next().ok_or("--output needs a file name")?);
+            }
+            "-h" | "--help" => {
+                println!("Usage: register-catalogue [--output <FILE>] [<PATH TO aarch32-cpu>]");
+                return Ok(());
+            }
+            _ => crate_dir = PathBuf::from(arg),
+        }
+    }
+
+    let source = Source::load(&crate_dir)?;
+    let catalogue = Catalogue::build(&source)?;
+    let json = serde_json::to_string_pretty(&catalogue)?;
+    match output {
+        Some(path) => std::fs::write(path, json + "\n")?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,551 @@
+//! Reading the register definitions out of the `aarch32-cpu` source code
+
+use std::path::{Path, PathBuf};
+
+use syn::punctuated::Punctuated;
+
+use crate::cfg::Cfg;
+use crate::Error;
+
+/// Something defined in the source code, along with where it was defined
+#[derive(Debug, Clone)]
+pub struct Item<T> {
+    /// The module the item is in, like `register::armv8r::prbar3`
+    pub module: String,
+    /// Every `cfg` that must be true for the item to exist
+    pub cfgs: Vec<Cfg>,
+    /// The item itself
+    pub value: T,
+}
+
+/// A `#[bitbybit::bitfield]` struct
+#[derive(Debug, Clone)]
+pub struct Bitfield {
+    /// The type name
+    pub name: String,
+    /// The fields, in the order they were declared
+    pub fields: Vec<Field>,
+}
+
+/// A field within a `#[bitbybit::bitfield]` struct
+#[derive(Debug, Clone)]
+pub struct Field {
+    /// The field name
+    pub name: String,
+    /// The doc comment on the field
+    pub doc: String,
+    /// Which bits hold the field, as `(lsb, msb)` pairs, least significant
+    /// first
+    pub bits: Vec<(u32, u32)>,
+    /// Either `r`, `w` or `rw`
+    pub access: String,
+    /// The field's type
+    pub ty: FieldType,
+    /// If this is an array, how many elements, and how far apart they are
+    pub array: Option<(u32, u32)>,
+}
+
+/// The type of a bitfield field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    /// A `bool`
+    Bool,
+    /// A `u8`, `u16`, `u32`, `u64` or `arbitrary_int` integer
+    Uint,
+    /// A `#[bitbybit::bitenum]`, optionally wrapped in `Option`
+    Enum {
+        /// The enum's name
+        name: String,
+    },
+}
+
+/// A `#[bitbybit::bitenum]` enum
+#[derive(Debug, Clone)]
+pub struct Bitenum {
+    /// The type name
+    pub name: String,
+    /// Does every bit pattern have a variant?
+    pub exhaustive: bool,
+    /// `(name, value, doc)` for each variant
+    pub variants: Vec<(String, u64, String)>,
+}
+
+/// An `impl SysReg for X` or `impl SysReg64 for X` block
+#[derive(Debug, Clone)]
+pub struct SysRegImpl {
+    /// The type name
+    pub name: String,
+    /// The doc comment on the type
+    pub doc: String,
+    /// Is this a `SysReg64`?
+    pub is_64: bool,
+    /// `CP`, `CRN`, `OP1`, `CRM` and `OP2` (`CRN` and `OP2` are zero for
+    /// 64-bit registers)
+    pub encoding: [u32; 5],
+}
+
+/// Which accessor traits a type implements
+#[derive(Debug, Clone)]
+pub struct AccessImpl {
+    /// The type name
+    pub name: String,
+    /// Is this `SysRegRead` (or `SysRegRead64`), as opposed to a write trait?
+    pub is_read: bool,
+}
+
+/// A type whose `read` or `write` function uses a different bitfield type
+///
+/// For example, `Prbar3::read()` returns a `Prbar`.
+#[derive(Debug, Clone)]
+pub struct LayoutHint {
+    /// The register type
+    pub name: String,
+    /// The type it is read as or written as
+    pub layout: String,
+}
+
+/// Everything we found in the source code
+#[derive(Debug, Default)]
+pub struct Source {
+    /// Bitfield structs
+    pub bitfields: Vec<Item<Bitfield>>,
+    /// Bitenum enums
+    pub bitenums: Vec<Item<Bitenum>>,
+    /// System register definitions
+    pub sysregs: Vec<Item<SysRegImpl>>,
+    /// System register accessor trait impls
+    pub access: Vec<Item<AccessImpl>>,
+    /// Types read or written using some other bitfield type
+    pub layouts: Vec<Item<LayoutHint>>,
+    /// Every struct, as `(module, name, doc comment)`
+    pub structs: Vec<(String, String, String)>,
+    /// Every `use` statement, as `(module, imported name, full path)`
+    pub uses: Vec<(String, String, String)>,
+}
+
+impl Source {
+    /// Parse the `register` module of the `aarch32-cpu` crate at the given path
+    pub fn load(crate_dir: &Path) -> Result<Source, Error> {
+        let mut source = Source::default();
+        let file = crate_dir.join("src").join("register").join("mod.rs");
+        source.load_file(&file, "register", &[])?;
+        // The doc comment for a register is on the struct, not the `impl`
+        for sysreg in source.sysregs.iter_mut() {
+            if let Some((_, _, doc)) = source
+                .structs
+                .iter()
+                .find(|(module, name, _)| *module == sysreg.module && *name == sysreg.value.name)
+            {
+                sysreg.value.doc = doc.clone();
+            }
+        }
+        Ok(source)
+    }
+
+    fn load_file(&mut self, path: &Path, module: &str, cfgs: &[Cfg]) -> Result<(), Error> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::new(format!("reading {}: {e}", path.display())))?;
+        let file = syn::parse_file(&text)
+            .map_err(|e| Error::new(format!("parsing {}: {e}", path.display())))?;
+        // `foo/mod.rs` and `foo.rs` both keep their child modules in `foo/`
+        let child_dir = if path.file_name().is_some_and(|name| name == "mod.rs") {
+            path.parent().unwrap().to_owned()
+        } else {
+            path.with_extension("")
+        };
+        self.load_items(&file.items, &child_dir, module, cfgs)
+            .map_err(|e| Error::new(format!("{}: {e}", path.display())))
+    }
+
+    fn load_items(
+        &mut self,
+        items: &[syn::Item],
+        dir: &Path,
+        module: &str,
+        cfgs: &[Cfg],
+    ) -> Result<(), Error> {
+        for item in items {
+            match item {
+                syn::Item::Mod(item_mod) => {
+                    let mut cfgs = cfgs.to_vec();
+                    cfgs.extend(Cfg::from_attrs(&item_mod.attrs)?);
+                    let name = item_mod.ident.to_string();
+                    let child_module = format!("{module}::{name}");
+                    if let Some((_, items)) = &item_mod.content {
+                        self.load_items(items, &dir.join(&name), &child_module, &cfgs)?;
+                    } else {
+                        self.load_file(&module_file(dir, &name)?, &child_module, &cfgs)?;
+                    }
+                }
+                syn::Item::Struct(item_struct) => {
+                    self.structs.push((
+                        module.to_owned(),
+                        item_struct.ident.to_string(),
+                        doc_string(&item_struct.attrs),
+                    ));
+                    if let Some(bitfield) = parse_bitfield(item_struct)? {
+                        self.bitfields
+                            .push(make_item(module, cfgs, &item_struct.attrs, bitfield)?);
+                    }
+                }
+                syn::Item::Enum(item_enum) => {
+                    if let Some(bitenum) = parse_bitenum(item_enum)? {
+                        self.bitenums
+                            .push(make_item(module, cfgs, &item_enum.attrs, bitenum)?);
+                    }
+                }
+                syn::Item::Impl(item_impl) => self.load_impl(item_impl, module, cfgs)?,
+                syn::Item::Use(item_use) => {
+                    collect_uses(&item_use.tree, String::new(), &mut |name, path| {
+                        self.uses.push((module.to_owned(), name, path));
+                    });
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn load_impl(
+        &mut self,
+        item_impl: &syn::ItemImpl,
+        module: &str,
+        cfgs: &[Cfg],
+    ) -> Result<(), Error> {
+        let Some(name) = type_name(&item_impl.self_ty) else {
+            return Ok(());
+        };
+        let Some((_, trait_path, _)) = &item_impl.trait_ else {
+            // An inherent impl - look for `read` and `write` using another type
+            for impl_item in &item_impl.items {
+                if let syn::ImplItem::Fn(f) = impl_item {
+                    if let Some(layout) = accessor_type(&f.sig) {
+                        if layout != name && layout != "Self" {
+                            let hint = LayoutHint {
+                                name: name.clone(),
+                                layout,
+                            };
+                            self.layouts
+                                .push(make_item(module, cfgs, &item_impl.attrs, hint)?);
+                        }
+                    }
+                }
+            }
+            return Ok(());
+        };
+        let trait_name = trait_path
+            .segments
+            .last()
+            .map(|s| s.ident.to_string())
+            .unwrap_or_default();
+        match trait_name.as_str() {
+            "SysReg" | "SysReg64" => {
+                let is_64 = trait_name == "SysReg64";
+                let mut encoding = [0u32; 5];
+                for impl_item in &item_impl.items {
+                    let syn::ImplItem::Const(c) = impl_item else {
+                        continue;
+                    };
+                    let idx = match c.ident.to_string().as_str() {
+                        "CP" => 0,
+                        "CRN" => 1,
+                        "OP1" => 2,
+                        "CRM" => 3,
+                        "OP2" => 4,
+                        other => {
+                            return Err(Error::new(format!("{name} has unknown const {other}")))
+                        }
+                    };
+                    encoding[idx] = lit_int(&c.expr).ok_or_else(|| {
+                        Error::new(format!("{name}::{} is not a literal", c.ident))
+                    })? as u32;
+                }
+                let sysreg = SysRegImpl {
+                    name,
+                    doc: String::new(),
+                    is_64,
+                    encoding,
+                };
+                self.sysregs
+                    .push(make_item(module, cfgs, &item_impl.attrs, sysreg)?);
+            }
+            "SysRegRead" | "SysRegRead64" | "SysRegWrite" | "SysRegWrite64" => {
+                let access = AccessImpl {
+                    name,
+                    is_read: trait_name.starts_with("SysRegRead"),
+                };
+                self.access
+                    .push(make_item(module, cfgs, &item_impl.attrs, access)?);
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn make_item<T>(
+    module: &str,
+    cfgs: &[Cfg],
+    attrs: &[syn::Attribute],
+    value: T,
+) -> Result<Item<T>, Error> {
+    let mut cfgs = cfgs.to_vec();
+    cfgs.extend(Cfg::from_attrs(attrs)?);
+    Ok(Item {
+        module: module.to_owned(),
+        cfgs,
+        value,
+    })
+}
+
+/// Find the file for `mod name;`
+fn module_file(dir: &Path, name: &str) -> Result<PathBuf, Error> {
+    let flat = dir.join(format!("{name}.rs"));
+    if flat.exists() {
+        return Ok(flat);
+    }
+    let nested = dir.join(name).join("mod.rs");
+    if nested.exists() {
+        return Ok(nested);
+    }
+    Err(Error::new(format!(
+        "cannot find module {name} in {}",
+        dir.display()
+    )))
+}
+
+/// Join up all the `///` comments on an item
+fn doc_string(attrs: &[syn::Attribute]) -> String {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(nv) if nv.path.is_ident("doc") => match &nv.value {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                }) => Some(s.value().trim().to_owned()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect();
+    lines.join("\n").trim().to_owned()
+}
+
+/// Find an attribute like `#[bitbybit::NAME(...)]`
+fn bitbybit_attr<'a>(attrs: &'a [syn::Attribute], name: &str) -> Option<&'a syn::Attribute> {
+    attrs.iter().find(|attr| {
+        let segments: Vec<String> = attr
+            .path()
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        segments == ["bitbybit", name]
+    })
+}
+
+fn parse_bitfield(item: &syn::ItemStruct) -> Result<Option<Bitfield>, Error> {
+    if bitbybit_attr(&item.attrs, "bitfield").is_none() {
+        return Ok(None);
+    }
+    let name = item.ident.to_string();
+    let mut fields = Vec::new();
+    for field in &item.fields {
+        let Some(field_name) = &field.ident else {
+            continue;
+        };
+        let field_name = field_name.to_string();
+        let Some(attr) = field
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("bit") || attr.path().is_ident("bits"))
+        else {
+            continue;
+        };
+        let args = attr
+            .parse_args_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+            .map_err(|e| Error::new(format!("{name}::{field_name}: {e}")))?;
+        let mut args = args.into_iter();
+        let position = args
+            .next()
+            .ok_or_else(|| Error::new(format!("{name}::{field_name} has no bit position")))?;
+        let bits = parse_bit_position(&position)
+            .ok_or_else(|| Error::new(format!("{name}::{field_name} has a bad bit position")))?;
+        let mut access = None;
+        let mut stride = None;
+        for arg in args {
+            match &arg {
+                syn::Expr::Path(p) if p.path.get_ident().is_some() => {
+                    access = Some(p.path.get_ident().unwrap().to_string());
+                }
+                syn::Expr::Assign(assign) if is_ident(&assign.left, "stride") => {
+                    stride = lit_int(&assign.right).map(|v| v as u32);
+                }
+                _ => {
+                    return Err(Error::new(format!(
+                        "{name}::{field_name} has an unknown bit argument"
+                    )))
+                }
+            }
+        }
+        let access =
+            access.ok_or_else(|| Error::new(format!("{name}::{field_name} has no access")))?;
+        let (ty, array) = match &field.ty {
+            syn::Type::Array(array) => {
+                let count = lit_int(&array.len).ok_or_else(|| {
+                    Error::new(format!("{name}::{field_name} has a bad array length"))
+                })? as u32;
+                let (lsb, msb) = bits[0];
+                let stride = stride.unwrap_or(msb - lsb + 1);
+                (field_type(&array.elem), Some((count, stride)))
+            }
+            ty => (field_type(ty), None),
+        };
+        let ty = ty.ok_or_else(|| Error::new(format!("{name}::{field_name} has a bad type")))?;
+        fields.push(Field {
+            name: field_name,
+            doc: doc_string(&field.attrs),
+            bits,
+            access,
+            ty,
+            array,
+        });
+    }
+    Ok(Some(Bitfield { name, fields }))
+}
+
+/// Parse `3`, `0..=7` or `[0..=3, 10]` into `(lsb, msb)` pairs
+fn parse_bit_position(expr: &syn::Expr) -> Option<Vec<(u32, u32)>> {
+    match expr {
+        syn::Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| parse_bit_position(elem).map(|v| v[0]))
+            .collect(),
+        syn::Expr::Range(range) if matches!(range.limits, syn::RangeLimits::Closed(_)) => {
+            let lsb = lit_int(range.start.as_ref()?)? as u32;
+            let msb = lit_int(range.end.as_ref()?)? as u32;
+            Some(vec![(lsb, msb)])
+        }
+        expr => {
+            let bit = lit_int(expr)? as u32;
+            Some(vec![(bit, bit)])
+        }
+    }
+}
+
+fn field_type(ty: &syn::Type) -> Option<FieldType> {
+    let syn::Type::Path(type_path) = ty else {
+        return None;
+    };
+    let last = type_path.path.segments.last()?;
+    let name = last.ident.to_string();
+    if name == "Option" {
+        // An `Option<Enum>` for a non-exhaustive enum
+        let syn::PathArguments::AngleBracketed(args) = &last.arguments else {
+            return None;
+        };
+        let Some(syn::GenericArgument::Type(inner)) = args.args.first() else {
+            return None;
+        };
+        return field_type(inner);
+    }
+    if name == "bool" {
+        Some(FieldType::Bool)
+    } else if name.starts_with('u') && name[1..].parse::<u32>().is_ok() {
+        Some(FieldType::Uint)
+    } else {
+        Some(FieldType::Enum { name })
+    }
+}
+
+fn parse_bitenum(item: &syn::ItemEnum) -> Result<Option<Bitenum>, Error> {
+    let Some(attr) = bitbybit_attr(&item.attrs, "bitenum") else {
+        return Ok(None);
+    };
+    let name = item.ident.to_string();
+    let args = attr
+        .parse_args_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+        .map_err(|e| Error::new(format!("{name}: {e}")))?;
+    let mut exhaustive = false;
+    for arg in &args {
+        if let syn::Expr::Assign(assign) = arg {
+            if is_ident(&assign.left, "exhaustive") {
+                exhaustive = matches!(
+                    &*assign.right,
+                    syn::Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Bool(b),
+                        ..
+                    }) if b.value
+                );
+            }
+        }
+    }
+    let mut variants = Vec::new();
+    for variant in &item.variants {
+        let value = variant
+            .discriminant
+            .as_ref()
+            .and_then(|(_, expr)| lit_int(expr))
+            .ok_or_else(|| Error::new(format!("{name}::{} has no literal value", variant.ident)))?;
+        variants.push((variant.ident.to_string(), value, doc_string(&variant.attrs)));
+    }
+    Ok(Some(Bitenum {
+        name,
+        exhaustive,
+        variants,
+    }))
+}
+
+/// Get the type used by `fn read() -> T` or `fn write(value: T)`
+fn accessor_type(sig: &syn::Signature) -> Option<String> {
+    match sig.ident.to_string().as_str() {
+        "read" if sig.inputs.is_empty() => match &sig.output {
+            syn::ReturnType::Type(_, ty) => type_name(ty),
+            syn::ReturnType::Default => None,
+        },
+        "write" if sig.inputs.len() == 1 => match sig.inputs.first()? {
+            syn::FnArg::Typed(arg) => type_name(&arg.ty),
+            syn::FnArg::Receiver(_) => None,
+        },
+        _ => None,
+    }
+}
+
+/// Get the last identifier in a type path like `crate::register::Prbar`
+fn type_name(ty: &syn::Type) -> Option<String> {
+    match ty {
+        syn::Type::Path(p) => p.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn is_ident(expr: &syn::Expr, name: &str) -> bool {
+    matches!(expr, syn::Expr::Path(p) if p.path.is_ident(name))
+}
+
+fn lit_int(expr: &syn::Expr) -> Option<u64> {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(i),
+            ..
+        }) => i.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+/// Flatten a `use` tree into `(name, path)` pairs
+fn collect_uses(tree: &syn::UseTree, prefix: String, f: &mut dyn FnMut(String, String)) {
+    match tree {
+        syn::UseTree::Path(p) => collect_uses(&p.tree, format!("{prefix}{}::", p.ident), f),
+        syn::UseTree::Name(n) => f(n.ident.to_string(), format!("{prefix}{}", n.ident)),
+        syn::UseTree::Rename(r) => f(r.rename.to_string(), format!("{prefix}{}", r.ident)),
+        syn::UseTree::Group(g) => {
+            for tree in &g.items {
+                collect_uses(tree, prefix.clone(), f);
+            }
+        }
+        syn::UseTree::Glob(_) => {}
+    }
+}